@@ -0,0 +1,51 @@
+//SPDX-FileCopyrightText: 2024 Claire Bts <claxxx.bts@gmail.com>
+//SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Help generation of large command trees, run with `cargo bench`
+//!
+//! Uses the standard library only: each case is rendered a number of times and the mean duration
+//! is printed.
+
+use clipv::builder::CommandBuilder;
+use clipv::describe::command::Command;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// Command with `arguments` positional arguments, as many options and `subcommands` subcommands
+/// built the same way
+fn tree(arguments: usize, subcommands: usize) -> Command {
+    fn builder(name: String, arguments: usize, subcommands: usize) -> CommandBuilder {
+        let mut command = CommandBuilder::new(name, Some("a generated command"));
+        for index in 0..arguments {
+            command = command
+                .arg(format!("arg{index}"), Some("a generated argument"))
+                .option(format!("--option{index}"), Some("a generated option"));
+        }
+        for index in 0..subcommands {
+            command = command.subcommand(builder(format!("sub{index}"), arguments, 0));
+        }
+        command
+    }
+    builder(String::from("app"), arguments, subcommands).build()
+}
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    f();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    println!("{name:<32} {:>10.1?} per iteration", start.elapsed() / iterations);
+}
+
+fn main() {
+    for (arguments, subcommands) in [(10, 10), (100, 10), (500, 100)] {
+        let command = tree(arguments, subcommands);
+        bench(&format!("help {arguments} args {subcommands} subcommands"), 50, || {
+            black_box(command.help());
+        });
+        bench(&format!("markdown {arguments} args {subcommands} subcommands"), 10, || {
+            black_box(command.to_markdown());
+        });
+    }
+}
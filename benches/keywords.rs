@@ -0,0 +1,59 @@
+//SPDX-FileCopyrightText: 2024 Claire Bts <claxxx.bts@gmail.com>
+//SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Keyword matching of an enum with many variants, run with `cargo bench --features derive`
+//!
+//! Compares the default matching, ignoring the ASCII case without allocating, with
+//! `#[clip(unicode_case)]`, which lowercases each keyword first, and with `#[clip(fast_match)]`,
+//! which dispatches the keywords on their length first.
+
+use clipv::parser::TryParse;
+use clipv::TryParse;
+use std::hint::black_box;
+use std::time::Instant;
+
+macro_rules! keywords {
+    ($($name:ident $(#[$attr:meta])*: $($variant:ident)*;)*) => {
+        $(
+            #[allow(dead_code)]
+            #[derive(TryParse)]
+            $(#[$attr])*
+            enum $name { $($variant),* }
+        )*
+    };
+}
+
+keywords! {
+    Ascii: Alpha Bravo Charlie Delta Echo Foxtrot Golf Hotel India Juliett Kilo Lima Mike
+        November Oscar Papa Quebec Romeo Sierra Tango Uniform Victor Whiskey Xray Yankee Zulu;
+    Unicode #[clip(unicode_case)]: Alpha Bravo Charlie Delta Echo Foxtrot Golf Hotel India Juliett
+        Kilo Lima Mike November Oscar Papa Quebec Romeo Sierra Tango Uniform Victor Whiskey Xray
+        Yankee Zulu;
+    Fast #[clip(fast_match)]: Alpha Bravo Charlie Delta Echo Foxtrot Golf Hotel India Juliett Kilo
+        Lima Mike November Oscar Papa Quebec Romeo Sierra Tango Uniform Victor Whiskey Xray Yankee
+        Zulu;
+}
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    f();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    println!("{name:<32} {:>10.1?} per iteration", start.elapsed() / iterations);
+}
+
+fn main() {
+    for keyword in ["alpha", "Mike", "ZULU"] {
+        let arguments = [keyword];
+        bench(&format!("ascii case {keyword}"), 100_000, || {
+            let _ = black_box(Ascii::try_parse(black_box(arguments.iter())));
+        });
+        bench(&format!("unicode case {keyword}"), 100_000, || {
+            let _ = black_box(Unicode::try_parse(black_box(arguments.iter())));
+        });
+        bench(&format!("fast match {keyword}"), 100_000, || {
+            let _ = black_box(Fast::try_parse(black_box(arguments.iter())));
+        });
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Expansion of argument files
+//!
+//! An argument starting with `@` names a file whose content is split with `shlex::split` and
+//! spliced in its place, for argument lists longer than what the system accepts. Arguments
+//! spanning several lines are simply written one per line, quoted when they hold spaces:
+//!
+//! ```text
+//! --output 'build dir'
+//! src/main.rs
+//! ```
+//!
+//! `@@name` stands for the argument `@name` itself. Files are not expanded recursively.
+
+use crate::shlex::{split, SplitError};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum ArgFileError {
+    /// the argument file could not be read
+    Io(PathBuf, std::io::Error),
+    /// the content of the argument file could not be split
+    Split(PathBuf, SplitError),
+}
+
+impl std::fmt::Display for ArgFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgFileError::Io(path, err) => write!(f, "cannot read '{}': {err}", path.display()),
+            ArgFileError::Split(path, err) => write!(f, "cannot split '{}': {err}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ArgFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArgFileError::Io(_, err) => Some(err),
+            ArgFileError::Split(_, err) => Some(err),
+        }
+    }
+}
+
+/// Arguments with the `@file` ones replaced by the content of the file
+pub fn expand<S: AsRef<str>>(args: &[S]) -> Result<Vec<String>, ArgFileError> {
+    let mut expanded = Vec::with_capacity(args.len());
+    for arg in args {
+        let arg = arg.as_ref();
+        match arg.strip_prefix('@') {
+            Some(escaped) if escaped.starts_with('@') => expanded.push(escaped.to_string()),
+            Some(path) if !path.is_empty() => {
+                let path = PathBuf::from(path);
+                let content = std::fs::read_to_string(&path).map_err(|err| ArgFileError::Io(path.clone(), err))?;
+                expanded.extend(split(&content).map_err(|err| ArgFileError::Split(path, err))?);
+            }
+            _ => expanded.push(arg.to_string()),
+        }
+    }
+    Ok(expanded)
+}
+
+/// Same as `expand` with the arguments of the operating system, the ones which are not unicode
+/// being kept as they are
+pub fn expand_os(args: &[OsString]) -> Result<Vec<OsString>, ArgFileError> {
+    let mut expanded = Vec::with_capacity(args.len());
+    for arg in args {
+        match arg.to_str() {
+            Some(text) => expanded.extend(expand(&[text])?.into_iter().map(OsString::from)),
+            None => expanded.push(arg.clone()),
+        }
+    }
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arg_file(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("clip-argfile-{}-{name}", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn it_should_splice_the_file_content() {
+        let path = arg_file("splice", "--output 'build dir'\nsrc/main.rs\n");
+        let args = ["build", &format!("@{path}"), "--release"];
+        assert_eq!(expand(&args).unwrap(), ["build", "--output", "build dir", "src/main.rs", "--release"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn it_should_keep_the_arguments_which_are_not_unicode() {
+        use std::os::unix::ffi::OsStringExt;
+        let raw = OsString::from_vec(b"caf\xe9".to_vec());
+        let path = arg_file("os", "--release\n");
+        let args = [raw.clone(), OsString::from(format!("@{path}"))];
+        assert_eq!(expand_os(&args).unwrap(), [raw, OsString::from("--release")]);
+    }
+
+    #[test]
+    fn it_should_keep_escaped_and_lone_at_signs() {
+        assert_eq!(expand(&["@@user", "@", "a@b"]).unwrap(), ["@user", "@", "a@b"]);
+    }
+
+    #[test]
+    fn it_should_report_unreadable_files() {
+        assert!(matches!(expand(&["@/nonexistent/clip/args"]), Err(ArgFileError::Io(..))));
+        let path = arg_file("unterminated", "'open");
+        assert!(matches!(expand(&[format!("@{path}")]), Err(ArgFileError::Split(..))));
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Programmatic definition of commands, for the ones which cannot be derived
+//!
+//! A `CommandBuilder` describes positional arguments, named options and subcommands at runtime.
+//! It builds the same `Command` tree as the AsCommand derive, so help and linting work the same
+//! way, and matches an argument line into the untyped `Matches` of the `matches` module.
+//!
+//! ```
+//! use clip_core::builder::CommandBuilder;
+//!
+//! let app = CommandBuilder::new("app", Some("an application"))
+//!     .flag("--verbose", Some("prints more"))
+//!     .subcommand(CommandBuilder::new("run", Some("runs a script")).arg("script", None));
+//! let matches = app.get_matches(&["run", "--verbose", "build.sh"]).unwrap();
+//! assert!(matches.contains("--verbose"));
+//! assert_eq!(matches.subcommands(), ["run"]);
+//! assert_eq!(matches.value("script"), Some("build.sh"));
+//! ```
+
+use crate::describe::arg::Arg;
+use crate::describe::command::Command;
+use crate::describe::value::IntoDescription;
+use crate::matches::{matches, Matches};
+use crate::parser::ParsingError;
+use std::borrow::Cow;
+
+/// Named option, written with its leading dashes
+struct OptionDef {
+    name: Cow<'static, str>,
+    description: Option<Cow<'static, str>>,
+    /// false for a flag
+    takes_value: bool,
+}
+
+/// Runtime definition of a command
+pub struct CommandBuilder {
+    name: Cow<'static, str>,
+    description: Option<Cow<'static, str>>,
+    arguments: Vec<(Cow<'static, str>, Option<Cow<'static, str>>)>,
+    options: Vec<OptionDef>,
+    subcommands: Vec<CommandBuilder>,
+}
+
+impl CommandBuilder {
+    pub fn new(name: impl Into<Cow<'static, str>>, description: impl IntoDescription) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into_description(),
+            arguments: Vec::new(),
+            options: Vec::new(),
+            subcommands: Vec::new(),
+        }
+    }
+
+    /// Adds a required positional argument, matched in declaration order
+    pub fn arg(mut self, name: impl Into<Cow<'static, str>>, description: impl IntoDescription) -> Self {
+        self.arguments.push((name.into(), description.into_description()));
+        self
+    }
+
+    /// Adds an option without value, e.g. `--verbose`
+    ///
+    /// Like the `#[global]` fields of the derive, options are accepted anywhere after the command,
+    /// including after one of its subcommands.
+    pub fn flag(mut self, name: impl Into<Cow<'static, str>>, description: impl IntoDescription) -> Self {
+        self.options.push(OptionDef {
+            name: name.into(),
+            description: description.into_description(),
+            takes_value: false,
+        });
+        self
+    }
+
+    /// Adds an option taking a value, either as `--name value` or `--name=value`
+    pub fn option(mut self, name: impl Into<Cow<'static, str>>, description: impl IntoDescription) -> Self {
+        self.options.push(OptionDef {
+            name: name.into(),
+            description: description.into_description(),
+            takes_value: true,
+        });
+        self
+    }
+
+    /// Adds a subcommand, selected after all the positional arguments
+    pub fn subcommand(mut self, subcommand: CommandBuilder) -> Self {
+        self.subcommands.push(subcommand);
+        self
+    }
+
+    /// Describes the command, for help messages and linting
+    pub fn build(&self) -> Command {
+        let mut command = Command::new(self.name.clone(), None);
+        command.value.description = self.description.clone();
+        command.set_arguments(
+            self.arguments
+                .iter()
+                .map(|(name, description)| described(Arg::new(name.clone(), None), description))
+                .collect(),
+        );
+        command.set_global_options(
+            self.options
+                .iter()
+                .map(|option| {
+                    let arg = described(Arg::new(option.name.clone(), None), &option.description);
+                    if option.takes_value {
+                        arg.with_value_name(option.name.trim_start_matches('-').to_uppercase())
+                    } else {
+                        arg
+                    }
+                })
+                .collect(),
+        );
+        if !self.subcommands.is_empty() {
+            command.set_subcommands(self.subcommands.iter().map(CommandBuilder::build).collect());
+            command.add_help_subcommand();
+        }
+        command
+    }
+
+    /// Matches the arguments (without the program name) against the definition
+    ///
+    /// Every positional argument of the selected commands is required, as well as a subcommand
+    /// when the last command selected has some.
+    pub fn get_matches<S: AsRef<str>>(&self, args: &[S]) -> Result<Matches, ParsingError> {
+        let matches = matches(&self.build(), args)?;
+        let mut builder = self;
+        for name in matches.subcommands() {
+            if builder.arguments.iter().any(|(argument, _)| !matches.contains(argument)) {
+                return Err(ParsingError::TooFewArguments);
+            }
+            builder = builder
+                .subcommands
+                .iter()
+                .find(|subcommand| subcommand.name == name)
+                .expect("the subcommands matched are the ones built");
+        }
+        if !builder.subcommands.is_empty() || builder.arguments.iter().any(|(argument, _)| !matches.contains(argument)) {
+            return Err(ParsingError::TooFewArguments);
+        }
+        Ok(matches)
+    }
+}
+
+fn described(arg: Arg, description: &Option<Cow<'static, str>>) -> Arg {
+    match description {
+        Some(description) => arg.with_description(description.clone()),
+        None => arg,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote() -> CommandBuilder {
+        CommandBuilder::new("app", Some("an application"))
+            .flag("--verbose", Some("prints more"))
+            .option("--config", Some("configuration file"))
+            .subcommand(
+                CommandBuilder::new("remote", Some("manages remotes"))
+                    .subcommand(CommandBuilder::new("add", Some("adds a remote")).arg("name", Some("remote name")))
+                    .subcommand(CommandBuilder::new("remove", Some("removes a remote")).arg("name", None)),
+            )
+    }
+
+    #[test]
+    fn it_should_build_the_command_tree() {
+        let command = remote().build();
+        assert_eq!(command.summarize(), "app [OPTIONS] [COMMAND] ..");
+        assert_eq!(
+            command.help_for_path(&["remote", "add"]),
+            Some(String::from("adds a remote\n\nUsage: app remote add <name>\n\nArguments:\n  name    remote name\n"))
+        );
+    }
+
+    #[test]
+    fn it_should_match_nested_subcommands_and_options() {
+        let matches = remote()
+            .get_matches(&["--config=app.toml", "remote", "ADD", "origin", "--verbose", "--verbose"])
+            .unwrap();
+        assert_eq!(matches.subcommands(), ["remote", "add"]);
+        assert_eq!(matches.value("--config"), Some("app.toml"));
+        assert_eq!(matches.occurrences("--verbose"), 2);
+        assert_eq!(matches.value("name"), Some("origin"));
+    }
+
+    #[test]
+    fn it_should_match_the_arguments_before_the_subcommand() {
+        let app = CommandBuilder::new("app", None)
+            .arg("profile", None)
+            .subcommand(CommandBuilder::new("run", None));
+        let matches = app.get_matches(&["release", "run"]).unwrap();
+        assert_eq!((matches.value("profile"), matches.subcommands()), (Some("release"), vec!["run"]));
+        assert_eq!(app.get_matches(&["release"]), Err(ParsingError::TooFewArguments));
+    }
+
+    #[test]
+    fn it_should_report_invalid_lines() {
+        assert_eq!(remote().get_matches(&["remote"]), Err(ParsingError::TooFewArguments));
+        assert_eq!(remote().get_matches(&["remote", "add"]), Err(ParsingError::TooFewArguments));
+        assert_eq!(remote().get_matches(&["remote", "rename"]), Err(ParsingError::VariantNotFound));
+        assert_eq!(remote().get_matches(&["remote", "add", "a", "b"]), Err(ParsingError::TooManyArguments));
+        assert_eq!(remote().get_matches(&["--config"]), Err(ParsingError::TooFewArguments));
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fish completion, one `complete -c` line per option, subcommand and choice
+
+use super::positionals;
+use crate::describe::arg::{GetArgs, ValueHint};
+use crate::describe::command::Command;
+use crate::describe::value::Value;
+
+/// Quotes a string for fish
+fn quote(string: &str) -> String {
+    format!("'{}'", string.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// `complete` line for `bin_name`, optionally under a condition
+fn complete(bin_name: &str, condition: Option<&str>, spec: String, description: Option<&str>) -> String {
+    let mut line = format!("complete -c {bin_name}");
+    if let Some(condition) = condition {
+        line.push_str(&format!(" -n {}", quote(condition)));
+    }
+    line.push_str(&format!(" {spec}"));
+    if let Some(description) = description {
+        line.push_str(&format!(" -d {}", quote(description)));
+    }
+    line.push('\n');
+    line
+}
+
+fn option_spec(name: &str) -> String {
+    match (name.strip_prefix("--"), name.strip_prefix('-')) {
+        (Some(long), _) => format!("-l {}", quote(long)),
+        (None, Some(short)) => format!("-s {}", quote(short)),
+        (None, None) => format!("-o {}", quote(name)),
+    }
+}
+
+/// Arguments of `complete` completing a hinted value, None if there is nothing to complete
+fn hint_spec(hint: ValueHint) -> Option<&'static str> {
+    match hint {
+        ValueHint::Unknown => None,
+        ValueHint::FilePath => Some("-F"),
+        ValueHint::DirPath => Some("-f -a '(__fish_complete_directories)'"),
+        ValueHint::Hostname => Some("-f -a '(__fish_print_hostnames)'"),
+        ValueHint::Username => Some("-f -a '(__fish_complete_users)'"),
+        ValueHint::CommandName => Some("-f -a '(__fish_complete_command)'"),
+    }
+}
+
+fn candidate(bin_name: &str, condition: &str, value: &Value) -> String {
+    complete(bin_name, Some(condition), format!("-f -a {}", quote(value.name())), value.description())
+}
+
+/// Writes the lines completing `command`, reached after the `parent` keyword (None for the root)
+fn lines(command: &Command, parent: Option<&str>, bin_name: &str, script: &mut String) {
+    // the root has no keyword of its own: it is active as long as no subcommand has been given
+    let active = match parent {
+        Some(parent) => format!("__fish_seen_subcommand_from {parent}"),
+        None => String::from("__fish_use_subcommand"),
+    };
+    for option in command.global_options.get_args() {
+        let condition = parent.map(|_| active.as_str());
+        let mut spec = option_spec(option.value.name());
+        // an option with a value name takes a value, completed with its possible values or hint
+        if option.value.value_name.is_some() {
+            spec.push_str(" -r");
+            if !option.possible.is_empty() {
+                let names: Vec<&str> = option.possible.iter().map(|value| value.name()).collect();
+                spec.push_str(&format!(" -f -a {}", quote(&names.join(" "))));
+            } else if let Some(hint) = hint_spec(option.value_hint) {
+                spec.push_str(&format!(" {hint}"));
+            }
+        }
+        script.push_str(&complete(bin_name, condition, spec, option.value.description()));
+    }
+    let subcommands = command.subcommands.as_deref().unwrap_or_default();
+    // candidates of this command only, not once one of its own subcommands has been given
+    let here = if subcommands.is_empty() || parent.is_none() {
+        active.clone()
+    } else {
+        let names: Vec<&str> = subcommands.iter().map(|cmd| cmd.value.name()).collect();
+        format!("{active}; and not __fish_seen_subcommand_from {}", names.join(" "))
+    };
+    for positional in positionals(command) {
+        if let Some(hint) = hint_spec(positional.hint).filter(|_| positional.choices.is_empty()) {
+            script.push_str(&complete(bin_name, Some(&here), hint.to_string(), positional.value.description()));
+        }
+        for choice in positional.choices {
+            script.push_str(&candidate(bin_name, &here, choice));
+        }
+    }
+    for subcommand in subcommands {
+        script.push_str(&candidate(bin_name, &here, &subcommand.value));
+    }
+    for subcommand in subcommands {
+        lines(subcommand, Some(subcommand.value.name()), bin_name, script);
+    }
+}
+
+pub(super) fn generate(command: &Command, bin_name: &str) -> String {
+    let mut script = String::new();
+    lines(command, None, bin_name, &mut script);
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::arg::{Arg, ArgType, Choices};
+
+    #[test]
+    fn it_should_complete_hinted_values() {
+        static WHEN: [Value<'static>; 2] = [Value::keyword("auto", None), Value::keyword("never", None)];
+        let mut app = Command::new("App", None);
+        app.set_arguments(vec![Arg::new("directory", Some("where to run")).with_hint(ValueHint::DirPath)]);
+        app.set_global_options(vec![
+            Arg::new("--config", None).with_value_name("FILE").with_hint(ValueHint::FilePath),
+            Arg::new("--jobs", None).with_value_name("N"),
+            Arg::new("--color", None).with_value_name("WHEN").with_possible_values(&WHEN),
+        ]);
+        assert_eq!(
+            generate(&app, "app"),
+            "complete -c app -l 'config' -r -F
+complete -c app -l 'jobs' -r
+complete -c app -l 'color' -r -f -a 'auto never'
+complete -c app -n '__fish_use_subcommand' -f -a '(__fish_complete_directories)' -d 'where to run'
+"
+        );
+    }
+
+    #[test]
+    fn it_should_generate_conditional_lines() {
+        let mut add = Command::new("add", Some("adds a remote"));
+        add.set_arguments(vec![Arg::with_type(
+            "mode",
+            None,
+            ArgType::Choices(Choices(vec![Arg::new("fast", Some("no checks")), Arg::new("slow", None)])),
+        )]);
+        add.set_global_options(vec![Arg::new("--force", Some("overwrites"))]);
+        let mut remote = Command::new("remote", Some("manages remotes"));
+        remote.set_subcommands(vec![add, Command::new("remove", None)]);
+        let mut app = Command::new("App", None);
+        app.set_global_options(vec![Arg::new("--verbose", Some("it's verbose"))]);
+        app.set_subcommands(vec![remote]);
+        assert_eq!(
+            generate(&app, "app"),
+            "complete -c app -l 'verbose' -d 'it\\'s verbose'
+complete -c app -n '__fish_use_subcommand' -f -a 'remote' -d 'manages remotes'
+complete -c app -n '__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add remove' -f -a 'add' -d 'adds a remote'
+complete -c app -n '__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add remove' -f -a 'remove'
+complete -c app -n '__fish_seen_subcommand_from add' -l 'force' -d 'overwrites'
+complete -c app -n '__fish_seen_subcommand_from add' -f -a 'fast' -d 'no checks'
+complete -c app -n '__fish_seen_subcommand_from add' -f -a 'slow'
+"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Shell completion scripts generated from a command tree
+//!
+//! Every generator walks the same `Command` tree as the help message: subcommands, options and
+//! positional arguments are completed with their description as hint.
+//!
+//! Static scripts only know the tree. With the dynamic protocol the shell calls back into the
+//! program with the hidden `__complete` keyword followed by the words of the current line, and
+//! the program prints the candidates of the last word, one per line.
+
+mod fish;
+mod powershell;
+mod zsh;
+
+use crate::describe::arg::{Arg, ArgType, Choices, Commands, GetArgs, ValueHint};
+use crate::describe::command::Command;
+use crate::describe::value::Value;
+use std::borrow::Cow;
+
+/// Hidden keyword asking for the candidates of the words following it
+pub const COMPLETE_KEYWORD: &str = "__complete";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Zsh,
+    Fish,
+    PowerShell,
+}
+
+impl Shell {
+    /// Completion script of the `bin_name` program described by `command`
+    pub fn generate(self, command: &Command, bin_name: &str) -> String {
+        match self {
+            Shell::Zsh => zsh::generate(command, bin_name),
+            Shell::Fish => fish::generate(command, bin_name),
+            Shell::PowerShell => powershell::generate(command, bin_name),
+        }
+    }
+}
+
+/// Positional argument as seen by a shell: a name and the candidates if any
+struct Positional<'a> {
+    value: &'a Value<'static>,
+    choices: Vec<&'a Value<'static>>,
+    hint: ValueHint,
+}
+
+/// Leaf positional arguments in the order they are expected, groups being walked through
+fn positionals(command: &Command) -> Vec<Positional<'_>> {
+    fn walk<'a>(args: &'a [Arg], result: &mut Vec<Positional<'a>>) {
+        for arg in args {
+            match &arg.r#type {
+                ArgType::Value => result.push(Positional {
+                    value: &arg.value,
+                    choices: arg.possible.iter().collect(),
+                    hint: arg.value_hint,
+                }),
+                ArgType::Choices(Choices(choices)) | ArgType::Commands(Commands(choices)) => result.push(Positional {
+                    value: &arg.value,
+                    choices: choices.iter().map(|choice| &choice.value).collect(),
+                    hint: arg.value_hint,
+                }),
+                ArgType::Group(group) => walk(group.get_args(), result),
+            }
+        }
+    }
+    let mut result = Vec::new();
+    walk(command.arguments.get_args(), &mut result);
+    result
+}
+
+/// Candidates completing the last of `words`, the arguments typed so far (empty when the cursor
+/// starts a new word)
+///
+/// Previous words are walked through the tree to find the active command and positional argument,
+/// the word following an option with a value name being its value. A value is completed with its
+/// possible values or, for a path, with the entries of the directory being typed.
+pub fn complete<'a>(command: &'a Command, words: &[&str]) -> Vec<Cow<'a, str>> {
+    let (current, previous) = words.split_last().unwrap_or((&"", &[]));
+    let mut active = command;
+    let mut options: Vec<&Arg> = active.global_options.get_args().iter().collect();
+    let mut index = 0;
+    // option whose value is the word being walked through
+    let mut pending: Option<&Arg> = None;
+    for word in previous {
+        if pending.take().is_some() {
+            continue;
+        }
+        if word.starts_with('-') {
+            pending = options.iter().copied().find(|option| takes_value(option, word));
+        } else if index < positionals(active).len() {
+            index += 1;
+        } else if let Some(subcommand) = active.find(&[word]) {
+            active = subcommand;
+            options.extend(active.global_options.get_args());
+            index = 0;
+        }
+    }
+    if let Some(option) = pending {
+        return values(option.possible.iter().collect(), option.value_hint, current);
+    }
+    let candidates: Vec<&str> = if current.starts_with('-') {
+        options.iter().map(|option| option.value.name()).collect()
+    } else if let Some(positional) = positionals(active).get(index) {
+        return values(positional.choices.clone(), positional.hint, current);
+    } else {
+        active
+            .subcommands
+            .iter()
+            .flatten()
+            .map(|subcommand| subcommand.value.name())
+            .collect()
+    };
+    starting_with(candidates, current)
+}
+
+/// Whether `word` is the name of `option`, given without its value
+fn takes_value(option: &Arg, word: &str) -> bool {
+    option.value.value_name.is_some()
+        && (option.value.name() == word || option.short.is_some_and(|short| word == format!("-{short}")))
+}
+
+/// Candidates of a value: its possible values, or the paths when hinted as such
+fn values<'a>(choices: Vec<&'a Value<'static>>, hint: ValueHint, current: &str) -> Vec<Cow<'a, str>> {
+    match hint {
+        _ if !choices.is_empty() => starting_with(choices.into_iter().map(Value::name).collect(), current),
+        ValueHint::FilePath => paths(current, false).into_iter().map(Cow::Owned).collect(),
+        ValueHint::DirPath => paths(current, true).into_iter().map(Cow::Owned).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Candidates starting with `current`, ignoring the case
+fn starting_with<'a>(candidates: Vec<&'a str>, current: &str) -> Vec<Cow<'a, str>> {
+    let current = current.to_lowercase();
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.to_lowercase().starts_with(&current))
+        .map(Cow::Borrowed)
+        .collect()
+}
+
+/// Sorted paths of the directory being typed starting with `current`, the directories ending
+/// with a `/` so that their entries can be completed next
+fn paths(current: &str, dirs_only: bool) -> Vec<String> {
+    let (dir, prefix) = current.rsplit_once('/').map_or(("", current), |(dir, prefix)| (&current[..=dir.len()], prefix));
+    let Ok(entries) = std::fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let is_dir = entry.path().is_dir();
+            // hidden entries are only offered once their dot is typed
+            let offered = name.starts_with(prefix) && (!name.starts_with('.') || prefix.starts_with('.'));
+            (offered && (is_dir || !dirs_only)).then(|| format!("{dir}{name}{}", if is_dir { "/" } else { "" }))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> Command {
+        let mut add = Command::new("add", None);
+        add.set_arguments(vec![
+            Arg::new("name", None),
+            Arg::with_type(
+                "mode",
+                None,
+                ArgType::Choices(Choices(vec![Arg::new("fast", None), Arg::new("slow", None)])),
+            ),
+        ]);
+        add.set_global_options(vec![Arg::new("--force", None)]);
+        let mut remote = Command::new("remote", None);
+        remote.set_subcommands(vec![add, Command::new("remove", None)]);
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![Arg::new("--verbose", None)]);
+        app.set_subcommands(vec![remote, Command::new("run", None)]);
+        app
+    }
+
+    #[test]
+    fn it_should_complete_subcommands() {
+        assert_eq!(complete(&app(), &[]), vec!["remote", "run"]);
+        assert_eq!(complete(&app(), &["r"]), vec!["remote", "run"]);
+        assert_eq!(complete(&app(), &["--verbose", "Remote", "re"]), vec!["remove"]);
+    }
+
+    #[test]
+    fn it_should_complete_the_active_argument() {
+        assert_eq!(complete(&app(), &["remote", "add", "origin", ""]), vec!["fast", "slow"]);
+        assert_eq!(complete(&app(), &["remote", "add", "origin", "s"]), vec!["slow"]);
+        assert_eq!(complete(&app(), &["remote", "add", ""]), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn it_should_complete_the_value_of_an_option() {
+        static LEVELS: [Value<'static>; 2] = [Value::keyword("low", None), Value::keyword("high", None)];
+        let mut app = app();
+        let dir = std::env::temp_dir().join(format!("clip-complete-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("settings.toml"), "").unwrap();
+        app.set_global_options(vec![
+            Arg::new("--verbose", None),
+            Arg::new("--level", None).with_short('l').with_value_name("LEVEL").with_possible_values(&LEVELS),
+            Arg::new("--config", None).with_value_name("FILE").with_hint(ValueHint::FilePath),
+            Arg::new("--output", None).with_value_name("DIR").with_hint(ValueHint::DirPath),
+            Arg::new("--name", None).with_value_name("NAME"),
+        ]);
+        assert_eq!(complete(&app, &["--level", "h"]), vec!["high"]);
+        assert_eq!(complete(&app, &["-l", ""]), vec!["low", "high"]);
+        assert_eq!(complete(&app, &["--name", ""]), Vec::<&str>::new());
+        // the value is not taken for a subcommand nor a positional argument
+        assert_eq!(complete(&app, &["--name", "remote", "r"]), vec!["remote", "run"]);
+        assert_eq!(complete(&app, &["--verbose", "remote", "re"]), vec!["remove"]);
+        let prefix = format!("{}/s", dir.display());
+        let path = |name: &str| format!("{}/{name}", dir.display());
+        assert_eq!(complete(&app, &["--config", &prefix]), vec![path("settings.toml"), path("src/")]);
+        assert_eq!(complete(&app, &["--output", &prefix]), vec![path("src/")]);
+    }
+
+    #[test]
+    fn it_should_complete_inherited_options() {
+        assert_eq!(complete(&app(), &["remote", "add", "--"]), vec!["--verbose", "--force"]);
+        assert_eq!(complete(&app(), &["--v"]), vec!["--verbose"]);
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! PowerShell completion, a `Register-ArgumentCompleter` switching on the subcommand path
+
+use super::positionals;
+use crate::describe::arg::{Arg, GetArgs};
+use crate::describe::command::Command;
+use crate::describe::value::Value;
+
+/// Quotes a string for PowerShell
+fn quote(string: &str) -> String {
+    format!("'{}'", string.replace('\'', "''"))
+}
+
+fn result(value: &Value, list_item: &str, kind: &str) -> String {
+    format!(
+        "            [CompletionResult]::new({}, {}, [CompletionResultType]::{kind}, {})\n",
+        quote(value.name()),
+        quote(list_item),
+        // an empty tooltip is rejected
+        quote(value.description().unwrap_or(value.name()))
+    )
+}
+
+/// Writes the switch case of `command`, then the ones of its subcommands
+fn cases<'a>(command: &'a Command, path: &mut Vec<&'a str>, inherited: &mut Vec<&'a Arg>, script: &mut String) {
+    inherited.extend(command.global_options.get_args());
+    script.push_str(&format!("        {} {{\n", quote(&path.join(";"))));
+    for option in inherited.iter() {
+        let list_item = option.value.name().trim_start_matches('-');
+        script.push_str(&result(&option.value, list_item, "ParameterName"));
+    }
+    for positional in positionals(command) {
+        for choice in positional.choices {
+            script.push_str(&result(choice, choice.name(), "ParameterValue"));
+        }
+    }
+    let subcommands = command.subcommands.as_deref().unwrap_or_default();
+    for subcommand in subcommands {
+        script.push_str(&result(&subcommand.value, subcommand.value.name(), "ParameterValue"));
+    }
+    script.push_str("            break\n        }\n");
+    let depth = inherited.len();
+    for subcommand in subcommands {
+        path.push(subcommand.value.name());
+        cases(subcommand, path, inherited, script);
+        path.pop();
+        inherited.truncate(depth);
+    }
+}
+
+pub(super) fn generate(command: &Command, bin_name: &str) -> String {
+    let mut script = format!(
+        r#"using namespace System.Management.Automation
+using namespace System.Management.Automation.Language
+
+Register-ArgumentCompleter -Native -CommandName {name} -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $commandElements = $commandAst.CommandElements
+    $command = @(
+        {name}
+        for ($i = 1; $i -lt $commandElements.Count; $i++) {{
+            $element = $commandElements[$i]
+            if ($element -isnot [StringConstantExpressionAst] -or
+                $element.StringConstantType -ne [StringConstantType]::BareWord -or
+                $element.Value.StartsWith('-') -or
+                $element.Value -eq $wordToComplete) {{
+                break
+            }}
+            $element.Value
+        }}) -join ';'
+
+    $completions = @(switch ($command) {{
+"#,
+        name = quote(bin_name)
+    );
+    cases(command, &mut vec![bin_name], &mut Vec::new(), &mut script);
+    script.push_str(
+        r#"    })
+
+    $completions.Where{ $_.CompletionText -like "$wordToComplete*" } |
+        Sort-Object -Property ListItemText
+}
+"#,
+    );
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_switch_on_the_subcommand_path() {
+        let mut remote = Command::new("remote", Some("manages remotes"));
+        remote.set_subcommands(vec![Command::new("add", Some("adds a remote"))]);
+        let mut app = Command::new("App", None);
+        app.set_global_options(vec![Arg::new("--verbose", Some("it's verbose"))]);
+        app.set_subcommands(vec![remote]);
+        let script = generate(&app, "app");
+        assert!(script.contains("Register-ArgumentCompleter -Native -CommandName 'app' -ScriptBlock {"));
+        assert!(script.ends_with(
+            r#"    $completions = @(switch ($command) {
+        'app' {
+            [CompletionResult]::new('--verbose', 'verbose', [CompletionResultType]::ParameterName, 'it''s verbose')
+            [CompletionResult]::new('remote', 'remote', [CompletionResultType]::ParameterValue, 'manages remotes')
+            break
+        }
+        'app;remote' {
+            [CompletionResult]::new('--verbose', 'verbose', [CompletionResultType]::ParameterName, 'it''s verbose')
+            [CompletionResult]::new('add', 'add', [CompletionResultType]::ParameterValue, 'adds a remote')
+            break
+        }
+        'app;remote;add' {
+            [CompletionResult]::new('--verbose', 'verbose', [CompletionResultType]::ParameterName, 'it''s verbose')
+            break
+        }
+    })
+
+    $completions.Where{ $_.CompletionText -like "$wordToComplete*" } |
+        Sort-Object -Property ListItemText
+}
+"#
+        ));
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Zsh completion, one `_arguments` based function per command
+
+use super::positionals;
+use crate::describe::arg::{Arg, GetArgs, ValueHint};
+use crate::describe::command::Command;
+
+/// Escapes a string to be put between single quotes
+fn quote(string: &str) -> String {
+    string.replace('\'', "'\\''")
+}
+
+/// Escapes the characters having a meaning inside a quoted `_arguments` spec
+fn escape(string: &str) -> String {
+    quote(&string.replace('\\', "\\\\"))
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn function_name(path: &[&str]) -> String {
+    path.iter()
+        .map(|name| name.replace(|c: char| !c.is_ascii_alphanumeric(), "_"))
+        .fold(String::new(), |name, part| format!("{name}_{part}"))
+}
+
+/// Completion function of a hinted value, None if there is nothing to complete
+fn hint_action(hint: ValueHint) -> Option<&'static str> {
+    match hint {
+        ValueHint::Unknown => None,
+        ValueHint::FilePath => Some("_files"),
+        ValueHint::DirPath => Some("_files -/"),
+        ValueHint::Hostname => Some("_hosts"),
+        ValueHint::Username => Some("_users"),
+        ValueHint::CommandName => Some("_command_names -e"),
+    }
+}
+
+fn option_spec(option: &Arg) -> String {
+    let mut spec = escape(option.value.name());
+    if let Some(description) = option.value.description() {
+        spec.push_str(&format!("[{}]", escape(description)));
+    }
+    // an option with a value name takes a value, completed as the shell does by default unless
+    // its possible values or its hint are known
+    if let Some(value_name) = &option.value.value_name {
+        let action = if option.possible.is_empty() {
+            hint_action(option.value_hint).unwrap_or("_default").to_string()
+        } else {
+            let names: Vec<String> = option.possible.iter().map(|value| escape(value.name())).collect();
+            format!("({})", names.join(" "))
+        };
+        spec.push_str(&format!(":{}:{action}", escape(value_name)));
+    }
+    format!("'{spec}'")
+}
+
+/// Writes the function completing `command`, then the ones of its subcommands
+fn function<'a>(command: &'a Command, path: &mut Vec<&'a str>, inherited: &mut Vec<&'a Arg>, script: &mut String) {
+    inherited.extend(command.global_options.get_args());
+    let mut specs: Vec<String> = inherited.iter().map(|option| option_spec(option)).collect();
+    let positionals = positionals(command);
+    for (index, positional) in positionals.iter().enumerate() {
+        let action = if positional.choices.is_empty() {
+            hint_action(positional.hint).unwrap_or(" ").to_string()
+        } else {
+            let names: Vec<String> = positional.choices.iter().map(|choice| escape(choice.name())).collect();
+            format!("({})", names.join(" "))
+        };
+        specs.push(format!("'{}:{}:{action}'", index + 1, escape(positional.value.name())));
+    }
+    let subcommands = command.subcommands.as_deref().unwrap_or_default();
+    if !subcommands.is_empty() {
+        specs.push(format!("'{}: :->commands'", positionals.len() + 1));
+        specs.push(String::from("'*:: :->arguments'"));
+    }
+    script.push_str(&format!("{}() {{\n", function_name(path)));
+    script.push_str("  local context state state_descr line\n  typeset -A opt_args\n  _arguments -C");
+    for spec in specs {
+        script.push_str(&format!(" \\\n    {spec}"));
+    }
+    script.push('\n');
+    if !subcommands.is_empty() {
+        script.push_str("  case $state in\n    commands)\n      local -a commands\n      commands=(\n");
+        for subcommand in subcommands {
+            let entry = match subcommand.value.description() {
+                Some(description) => format!("{}:{}", subcommand.value.name(), description),
+                None => subcommand.value.name().to_string(),
+            };
+            script.push_str(&format!("        '{}'\n", escape_entry(&entry)));
+        }
+        script.push_str("      )\n      _describe -t commands 'command' commands\n      ;;\n");
+        script.push_str(&format!("    arguments)\n      case $line[{}] in\n", positionals.len() + 1));
+        for subcommand in subcommands {
+            path.push(subcommand.value.name());
+            script.push_str(&format!("        {}) {} ;;\n", subcommand.value.name(), function_name(path)));
+            path.pop();
+        }
+        script.push_str("      esac\n      ;;\n  esac\n");
+    }
+    script.push_str("}\n\n");
+    let depth = inherited.len();
+    for subcommand in subcommands {
+        path.push(subcommand.value.name());
+        function(subcommand, path, inherited, script);
+        path.pop();
+        inherited.truncate(depth);
+    }
+}
+
+/// Escapes a `name:description` entry of `_describe`, only the first colon being a separator
+fn escape_entry(entry: &str) -> String {
+    match entry.split_once(':') {
+        Some((name, description)) => format!("{}:{}", quote(&name.replace(':', "\\:")), quote(description)),
+        None => quote(&entry.replace(':', "\\:")),
+    }
+}
+
+pub(super) fn generate(command: &Command, bin_name: &str) -> String {
+    let mut script = format!("#compdef {bin_name}\n\n");
+    function(command, &mut vec![bin_name], &mut Vec::new(), &mut script);
+    let name = function_name(&[bin_name]);
+    script.push_str(&format!(
+        "if [ \"$funcstack[1]\" = \"{name}\" ]; then\n  {name} \"$@\"\nelse\n  compdef {name} {bin_name}\nfi\n"
+    ));
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::arg::{ArgType, Choices};
+    use crate::describe::value::Value;
+
+    #[test]
+    fn it_should_generate_nested_functions() {
+        static WHEN: [Value<'static>; 2] = [Value::keyword("auto", None), Value::keyword("never", None)];
+        let mut add = Command::new("add", Some("adds a remote"));
+        add.set_arguments(vec![
+            Arg::new("name", Some("remote name")),
+            Arg::new("host", None).with_hint(ValueHint::Hostname),
+        ]);
+        let mut remote = Command::new("remote", Some("manages: remotes"));
+        remote.set_arguments(vec![Arg::with_type(
+            "mode",
+            None,
+            ArgType::Choices(Choices(vec![Arg::new("fast", None), Arg::new("slow", None)])),
+        )]);
+        remote.set_subcommands(vec![add]);
+        let mut app = Command::new("App", None);
+        app.set_global_options(vec![
+            Arg::new("--verbose", Some("prints [more]")),
+            Arg::new("--config", None).with_value_name("FILE").with_hint(ValueHint::FilePath),
+            Arg::new("--jobs", None).with_value_name("N"),
+            Arg::new("--color", None).with_value_name("WHEN").with_possible_values(&WHEN),
+        ]);
+        app.set_subcommands(vec![remote]);
+        assert_eq!(
+            generate(&app, "my-app"),
+            r#"#compdef my-app
+
+_my_app() {
+  local context state state_descr line
+  typeset -A opt_args
+  _arguments -C \
+    '--verbose[prints \[more\]]' \
+    '--config:FILE:_files' \
+    '--jobs:N:_default' \
+    '--color:WHEN:(auto never)' \
+    '1: :->commands' \
+    '*:: :->arguments'
+  case $state in
+    commands)
+      local -a commands
+      commands=(
+        'remote:manages: remotes'
+      )
+      _describe -t commands 'command' commands
+      ;;
+    arguments)
+      case $line[1] in
+        remote) _my_app_remote ;;
+      esac
+      ;;
+  esac
+}
+
+_my_app_remote() {
+  local context state state_descr line
+  typeset -A opt_args
+  _arguments -C \
+    '--verbose[prints \[more\]]' \
+    '--config:FILE:_files' \
+    '--jobs:N:_default' \
+    '--color:WHEN:(auto never)' \
+    '1:mode:(fast slow)' \
+    '2: :->commands' \
+    '*:: :->arguments'
+  case $state in
+    commands)
+      local -a commands
+      commands=(
+        'add:adds a remote'
+      )
+      _describe -t commands 'command' commands
+      ;;
+    arguments)
+      case $line[2] in
+        add) _my_app_remote_add ;;
+      esac
+      ;;
+  esac
+}
+
+_my_app_remote_add() {
+  local context state state_descr line
+  typeset -A opt_args
+  _arguments -C \
+    '--verbose[prints \[more\]]' \
+    '--config:FILE:_files' \
+    '--jobs:N:_default' \
+    '--color:WHEN:(auto never)' \
+    '1:name: ' \
+    '2:host:_hosts'
+}
+
+if [ "$funcstack[1]" = "_my_app" ]; then
+  _my_app "$@"
+else
+  compdef _my_app my-app
+fi
+"#
+        );
+    }
+}
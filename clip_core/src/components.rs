@@ -0,0 +1,213 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Options shared by most command line interfaces, flattened into a struct deriving TryParse and
+//! AsCommand with a `#[clip(flatten)] verbosity: Verbosity` field
+//!
+//! - `Verbosity`: `-v`, `-vv`.. and `-q` setting a log `Level`
+//! - `ColorChoice`: `--color auto|always|never`, given as a `#[global]` option with
+//!   `#[clip(default = "auto")]` and applied to the whole application with `ColorChoice::apply`
+
+use crate::describe::value::{PossibleValues, Value};
+use crate::parser::{Flatten, OptionSpec, ParsingError};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Level of the messages to log, from the most to the least severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    const ALL: [Level; 6] = [Level::Off, Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+    /// Name of the level as the `log` and `tracing` filters parse it, e.g. `"debug"`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Off => "off",
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `-v`/`--verbose` raising the log level and `-q`/`--quiet` lowering it, both repeatable
+///
+/// The level is `Warn` without any of them: `-v` gives `Info`, `-vv` `Debug`, `-vvv` `Trace`,
+/// `-q` `Error` and `-qq` `Off`. `level().as_str()` is understood by the filters of `log`
+/// (`LevelFilter::from_str`) and `tracing` (`EnvFilter::new`).
+///
+/// ```
+/// use clip_core::components::{Level, Verbosity};
+/// use clip_core::parser::Flatten;
+///
+/// let verbosity = Verbosity::build(vec![("verbose", None), ("verbose", None)]).unwrap();
+/// assert_eq!(verbosity.level(), Level::Debug);
+/// assert_eq!(verbosity.level().as_str(), "debug");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Verbosity {
+    pub verbose: u8,
+    pub quiet: u8,
+}
+
+impl Verbosity {
+    pub fn level(&self) -> Level {
+        let index = (Level::Warn as i16 + i16::from(self.verbose) - i16::from(self.quiet)).clamp(0, Level::Trace as i16);
+        Level::ALL[index as usize]
+    }
+
+    /// Whether `--quiet` asked for no output at all
+    pub fn is_silent(&self) -> bool {
+        self.level() == Level::Off
+    }
+}
+
+impl Flatten for Verbosity {
+    fn options() -> &'static [OptionSpec] {
+        &[
+            OptionSpec { name: "verbose", short: Some('v'), takes_value: false, description: Some("more output, repeated for even more") },
+            OptionSpec { name: "quiet", short: Some('q'), takes_value: false, description: Some("less output, repeated for none") },
+        ]
+    }
+
+    fn build(found: Vec<(&'static str, Option<String>)>) -> Result<Self, ParsingError> {
+        let count = |option| found.iter().filter(|(name, _)| *name == option).count().min(u8::MAX as usize) as u8;
+        Ok(Verbosity { verbose: count("verbose"), quiet: count("quiet") })
+    }
+}
+
+/// Whether to color the output: `auto` when it goes to a terminal, `always` or `never`
+///
+/// The choice applied last is the one of the whole application: the colored help of
+/// `AsCommand::help` follows it, and so can the application's own output with
+/// `ColorChoice::current().enabled()`.
+///
+/// ```
+/// use clip_core::components::ColorChoice;
+///
+/// let choice: ColorChoice = "Never".parse().unwrap();
+/// assert!(!choice.enabled());
+/// choice.apply();
+/// assert_eq!(ColorChoice::current(), ColorChoice::Never);
+/// assert!("sometimes".parse::<ColorChoice>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// `ColorChoice` applied to the application, as its index in `ColorChoice::ALL`
+static APPLIED: AtomicU8 = AtomicU8::new(0);
+
+impl ColorChoice {
+    const ALL: [ColorChoice; 3] = [ColorChoice::Auto, ColorChoice::Always, ColorChoice::Never];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        }
+    }
+
+    /// Whether to color with this choice, `auto` meaning that the standard output is a terminal
+    /// and `NO_COLOR` is not set
+    pub fn enabled(&self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && std::io::stdout().is_terminal()
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+
+    /// Makes this choice the one of the whole application, see `current`
+    pub fn apply(self) {
+        APPLIED.store(self as u8, Ordering::Relaxed);
+    }
+
+    /// Choice applied last, `auto` until one is
+    pub fn current() -> Self {
+        Self::ALL[APPLIED.load(Ordering::Relaxed) as usize]
+    }
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|choice| choice.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("expected auto, always or never, got '{s}'"))
+    }
+}
+
+impl std::fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PossibleValues for ColorChoice {
+    fn values() -> &'static [Value<'static>] {
+        static VALUES: [Value<'static>; 3] = [
+            Value::keyword("auto", Some("when writing to a terminal")),
+            Value::keyword("always", None),
+            Value::keyword("never", None),
+        ];
+        &VALUES
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_count_the_verbosity_flags() {
+        let level = |found: &[&'static str]| Verbosity::build(found.iter().map(|name| (*name, None)).collect()).unwrap().level();
+        assert_eq!(level(&[]), Level::Warn);
+        assert_eq!(level(&["verbose"]), Level::Info);
+        assert_eq!(level(&["verbose"; 5]), Level::Trace);
+        assert_eq!(level(&["quiet", "quiet", "quiet"]), Level::Off);
+        assert_eq!(level(&["verbose", "quiet"]), Level::Warn);
+        assert!(Verbosity { verbose: 0, quiet: 2 }.is_silent());
+    }
+
+    #[test]
+    fn it_should_parse_a_color_choice() {
+        assert_eq!("ALWAYS".parse(), Ok(ColorChoice::Always));
+        assert_eq!("auto".parse::<ColorChoice>().map(|choice| choice.to_string()), Ok(String::from("auto")));
+        assert_eq!("yes".parse::<ColorChoice>(), Err(String::from("expected auto, always or never, got 'yes'")));
+        assert!(ColorChoice::Always.enabled() && !ColorChoice::Never.enabled());
+        assert!(ColorChoice::is_possible("never"));
+    }
+}
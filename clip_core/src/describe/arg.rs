@@ -16,13 +16,15 @@
 ///
 /// It's composed of two types of arguments' description:
 ///  - summary: displays the list of argument required (and their order). Choices parent name is
-///  used
+///    used
 ///  - details: displays each arguments with its description. Here, order doesn't matter. Choices
-///  are also details.
+///    are also details.
 ///
 ///  Both type of description are complementary to describe command line arguments
-use super::formatter::Formatter;
-use super::value::Value;
+use super::formatter::{start_with, Formatter};
+use super::locale::Localizer;
+use super::value::{IntoDescription, Value};
+use std::borrow::Cow;
 use std::vec::Vec;
 
 #[derive(Debug, PartialEq, Default)]
@@ -31,6 +33,10 @@ pub struct ArgGroup(pub Vec<Arg>);
 #[derive(Debug, PartialEq)]
 pub struct Choices(pub Vec<Arg>);
 
+/// Keywords each selecting a command, summarized as `{a|b|c}`
+#[derive(Debug, PartialEq)]
+pub struct Commands(pub Vec<Arg>);
+
 pub trait GetArgs {
     /// Required method
     fn get_args(&self) -> &Vec<Arg>;
@@ -57,6 +63,11 @@ pub trait ArgSummarize: GetArgs + SummaryFormatter {
     fn summarize(&self) -> String {
         Self::get_summary_formatter().fmt(self.get_args().iter(), |arg: &Arg| Some(arg.summarize()))
     }
+
+    /// Same as `summarize`, values being followed by their type, e.g. `<count: u8>`
+    fn summarize_typed(&self) -> String {
+        Self::get_summary_formatter().fmt(self.get_args().iter(), |arg: &Arg| Some(arg.summarize_typed()))
+    }
 }
 
 pub trait DetailsFormatter {
@@ -67,10 +78,18 @@ pub trait DetailsFormatter {
 
 pub trait ArgDetails: GetArgs + DetailsFormatter {
     fn details(&self) -> String {
-        Self::get_details_formatter().fmt(self.get_args().iter(), |arg: &Arg| Some(arg.details()))
+        self.details_indented(DEFAULT_INDENT)
+    }
+
+    /// Same as `details`, each nesting level being indented by `indent`
+    fn details_indented(&self, indent: &str) -> String {
+        Self::get_details_formatter().fmt(self.get_args().iter(), |arg: &Arg| Some(arg.details_indented(indent)))
     }
 }
 
+/// Indentation of each nesting level of the details, see `Command::set_indent`
+pub const DEFAULT_INDENT: &str = "  ";
+
 impl GetArgs for ArgGroup {
     fn get_args(&self) -> &Vec<Arg> {
         match self {
@@ -90,7 +109,25 @@ impl SummaryFormatter for ArgGroup {
         }
     }
 }
-impl ArgSummarize for ArgGroup {}
+/// Each argument is bracketed, a variadic one being followed by `...`, e.g. `<name> <files>...`
+impl ArgSummarize for ArgGroup {
+    fn summarize(&self) -> String {
+        self.bracketed(Arg::summarize)
+    }
+
+    fn summarize_typed(&self) -> String {
+        self.bracketed(Arg::summarize_typed)
+    }
+}
+
+impl ArgGroup {
+    fn bracketed(&self, summarize: fn(&Arg) -> String) -> String {
+        Formatter { start: None, end: None, ..Self::get_summary_formatter() }.fmt(self.get_args().iter(), |arg: &Arg| {
+            let dots = if arg.arity.is_some() { "..." } else { "" };
+            Some(format!("<{}>{dots}", summarize(arg)))
+        })
+    }
+}
 impl DetailsFormatter for ArgGroup {}
 impl ArgDetails for ArgGroup {}
 
@@ -114,6 +151,33 @@ impl SummaryFormatter for Choices {
     }
 }
 impl ArgSummarize for Choices {}
+
+impl Choices {
+    /// Same as `summarize` with at most `limit` choices, followed by `…` when some are left out
+    pub fn summarize_limited(&self, limit: usize) -> String {
+        self.limited(limit, Arg::summarize)
+    }
+
+    /// Same as `summarize_typed` with at most `limit` choices, see `summarize_limited`
+    pub fn summarize_typed_limited(&self, limit: usize) -> String {
+        self.limited(limit, Arg::summarize_typed)
+    }
+
+    fn limited(&self, limit: usize, summarize: fn(&Arg) -> String) -> String {
+        format!("<{}>", self.limited_list(limit, summarize))
+    }
+
+    /// `a|b|…` without the brackets, added by the group of the argument
+    fn limited_list(&self, limit: usize, summarize: fn(&Arg) -> String) -> String {
+        let truncated = self.get_args().len() > limit;
+        Formatter {
+            very_start: None,
+            very_end: truncated.then_some("|…"),
+            ..Self::get_summary_formatter()
+        }
+        .fmt(self.get_args().iter().take(limit), |arg: &Arg| Some(summarize(arg)))
+    }
+}
 impl DetailsFormatter for Choices {
     fn get_details_formatter<'a>() -> Formatter<'a> {
         Formatter {
@@ -123,7 +187,67 @@ impl DetailsFormatter for Choices {
         }
     }
 }
-impl ArgDetails for Choices {}
+/// Each choice is detailed with its description, the arguments it holds being listed below it
+impl ArgDetails for Choices {
+    fn details_indented(&self, indent: &str) -> String {
+        Self::get_details_formatter().fmt(self.get_args().iter(), |arg: &Arg| {
+            Some(match &arg.r#type {
+                ArgType::Group(group) if !group.get_args().is_empty() => {
+                    format!("{:#}\n{}", arg.value, start_with(group.details_indented(indent), indent))
+                }
+                _ => arg.details_indented(indent),
+            })
+        })
+    }
+}
+
+impl GetArgs for Commands {
+    fn get_args(&self) -> &Vec<Arg> {
+        match self {
+            Commands(args) => args,
+        }
+    }
+}
+impl ArgTree for Commands {}
+
+impl SummaryFormatter for Commands {
+    fn get_summary_formatter<'a>() -> Formatter<'a> {
+        Formatter {
+            very_start: Some("{"),
+            very_end: Some("}"),
+            middle: Some("|"),
+            ..Default::default()
+        }
+    }
+}
+
+/// Each command is summarized with its name, followed by its own arguments if any
+impl ArgSummarize for Commands {
+    fn summarize(&self) -> String {
+        Self::get_summary_formatter().fmt(self.get_args().iter(), |arg: &Arg| {
+            Some(match &arg.r#type {
+                ArgType::Value => arg.value.name.to_string(),
+                _ => format!("{} {}", arg.value.name, arg.summarize()),
+            })
+        })
+    }
+}
+impl DetailsFormatter for Commands {
+    fn get_details_formatter<'a>() -> Formatter<'a> {
+        Choices::get_details_formatter()
+    }
+}
+/// Each command is detailed with its description, its nested commands being listed below it
+impl ArgDetails for Commands {
+    fn details_indented(&self, indent: &str) -> String {
+        Self::get_details_formatter().fmt(self.get_args().iter(), |arg: &Arg| {
+            Some(match &arg.r#type {
+                ArgType::Commands(_) => arg.details_indented(indent),
+                _ => format!("{:#}\n", arg.value),
+            })
+        })
+    }
+}
 
 /// All argument type supporting a formatting
 /// Either a leaf or subtree's holder
@@ -136,7 +260,88 @@ pub enum ArgType {
     Choices(Choices),
     /// struct argument type
     Group(ArgGroup),
+    /// subcommands argument type
+    Commands(Commands),
+}
+/// Order in which the help lists arguments and subcommands
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum DisplayOrder {
+    /// as declared
+    #[default]
+    Declaration,
+    /// by name, case insensitive
+    Alphabetical,
+    /// required arguments, then the optional ones, each in declaration order
+    RequiredFirst,
+}
+
+impl DisplayOrder {
+    /// Arguments in display order, the usage line keeping the declaration order
+    pub fn sort<'a>(&self, args: &'a [Arg]) -> Vec<&'a Arg> {
+        let mut sorted: Vec<&Arg> = args.iter().collect();
+        match self {
+            DisplayOrder::Declaration => (),
+            DisplayOrder::Alphabetical => sorted.sort_by_key(|arg| arg.value.name.to_lowercase()),
+            DisplayOrder::RequiredFirst => sorted.sort_by_key(|arg| !arg.is_required()),
+        }
+        sorted
+    }
+}
+
+/// How the usage line shows an argument holding choices
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum ChoicesSummary {
+    /// the name when no choice holds arguments, the list of choices otherwise
+    #[default]
+    Auto,
+    /// always the name, e.g. `<mode>`
+    Name,
+    /// the list of choices, truncated after the given number of them, e.g. `<a|b|…>`
+    Limit(usize),
+    /// the whole list of choices even when none holds arguments, e.g. `<fast|slow>` for an enum
+    /// worth seeing in the usage line
+    Inline,
+}
+
+/// Number of values taken by a variadic argument, e.g. a `Vec` field
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Arity {
+    pub min: usize,
+    /// no limit when None
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    /// Bounds as shown by the details, e.g. `(1-4)` or `(2+)`, None when any number is accepted
+    pub fn bounds(&self) -> Option<String> {
+        match (self.min, self.max) {
+            (0, None) => None,
+            (min, None) => Some(format!("({min}+)")),
+            (min, Some(max)) if min == max => Some(format!("({min})")),
+            (min, Some(max)) => Some(format!("({min}-{max})")),
+        }
+    }
+
+    /// Whether another value may be taken once `taken` have been
+    pub fn takes_more(&self, taken: usize) -> bool {
+        self.max.is_none_or(|max| taken < max)
+    }
+}
+
+/// Kind of value expected by an argument, telling the shells what to complete
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum ValueHint {
+    /// nothing is known about the value
+    #[default]
+    Unknown,
+    FilePath,
+    DirPath,
+    Hostname,
+    Username,
+    /// name of an executable found in the PATH
+    CommandName,
 }
+
 /// Argument tree root: a single field with its associated type (if needed, recursivly display all argument)
 /// It contains the description of the argument itself and its type. It is a node of the tree. It
 /// being a leaf is determined by it type.
@@ -146,57 +351,250 @@ pub struct Arg {
     pub value: Value<'static>,
     /// type of argument determining when and what to display
     pub r#type: ArgType,
+    /// what the value is, for completion
+    pub value_hint: ValueHint,
+    /// one letter alias of a named option, e.g. `v` for `-v`
+    pub short: Option<char>,
+    /// how the usage line shows the choices, see `with_choices_summary`
+    pub choices_summary: ChoicesSummary,
+    /// value never echoed, see `secret`
+    pub secret: bool,
+    /// values accepted, offered by the completions, see `with_possible_values`; borrowed from a
+    /// `PossibleValues` type or built at runtime
+    pub possible: Cow<'static, [Value<'static>]>,
+    /// number of values of a variadic argument, None for a single value, see `with_arity`
+    pub arity: Option<Arity>,
+    /// takes every remaining value verbatim, see `trailing`
+    pub trailing: bool,
     max_depth: usize,
+    /// whether the argument may be left out, see `optional`
+    optional: bool,
 }
 
 impl Arg {
     /// creates an argument with its inner type
     pub fn with_type(
-        name: &'static str,
-        description: Option<&'static str>,
+        name: impl Into<Cow<'static, str>>,
+        description: impl IntoDescription,
         r#type: ArgType,
     ) -> Arg {
         let max_depth = match &r#type {
             ArgType::Choices(choices) => choices.max_depth() + 1,
+            ArgType::Commands(commands) => commands.max_depth() + 1,
             ArgType::Group(group) => group.max_depth(),
             ArgType::Value => 1,
         };
+        let mut value = Value { description: description.into_description(), ..Value::new(name, None) };
+        if let ArgType::Choices(choices) = &r#type {
+            // undocumented keywords read better inline than as a list
+            if choices.get_args().iter().all(|choice| choice.r#type == ArgType::Value && choice.value.description.is_none()) {
+                value.possible_values = choices.get_args().iter().map(|choice| choice.value.name.clone()).collect();
+            }
+        }
         Arg {
-            value: Value { name, description },
+            value,
             r#type,
+            value_hint: ValueHint::Unknown,
+            short: None,
+            choices_summary: ChoicesSummary::Auto,
+            secret: false,
+            possible: Cow::Borrowed(&[]),
+            arity: None,
+            trailing: false,
             max_depth,
+            optional: false,
         }
     }
 
     /// default constructor, by default we expect a single simple value as a field
-    pub fn new(name: &'static str, description: Option<&'static str>) -> Arg {
+    pub fn new(name: impl Into<Cow<'static, str>>, description: impl IntoDescription) -> Arg {
         Arg {
-            value: Value { name, description },
+            value: Value { description: description.into_description(), ..Value::new(name, None) },
             r#type: ArgType::Value,
+            value_hint: ValueHint::Unknown,
+            short: None,
+            choices_summary: ChoicesSummary::Auto,
+            secret: false,
+            possible: Cow::Borrowed(&[]),
+            arity: None,
+            trailing: false,
             max_depth: 1,
+            optional: false,
+        }
+    }
+
+    /// Sets the description, e.g. one only known at runtime
+    pub fn with_description(mut self, description: impl Into<Cow<'static, str>>) -> Arg {
+        self.value.description = Some(description.into());
+        self
+    }
+
+    /// Sets the whole documentation, the description being its first paragraph
+    pub fn with_long_description(mut self, long_description: impl Into<Cow<'static, str>>) -> Arg {
+        self.value.long_description = Some(long_description.into());
+        self
+    }
+
+    /// Marks the argument as deprecated, `note` telling what to use instead (may be empty)
+    pub fn deprecated(mut self, note: impl Into<Cow<'static, str>>) -> Arg {
+        self.value.deprecated = Some(note.into());
+        self
+    }
+
+    /// Sets the one letter alias of a named option, e.g. `v` for `-v`
+    pub fn with_short(mut self, short: char) -> Arg {
+        self.short = Some(short);
+        self
+    }
+
+    /// Marks the value as secret, e.g. a password: masked in error messages and typed without
+    /// echo when prompted for
+    pub fn secret(mut self) -> Arg {
+        self.secret = true;
+        self
+    }
+
+    /// Marks the argument as one which may be left out, e.g. an `Option` field or a flag
+    pub fn optional(mut self) -> Arg {
+        self.optional = true;
+        self
+    }
+
+    /// Whether the argument must be given: neither optional nor with a default value
+    pub fn is_required(&self) -> bool {
+        !self.optional && self.value.default.is_none()
+    }
+
+    /// Sets the value used when the argument is missing, displayed as `[default: ..]`
+    pub fn with_default(mut self, default: impl Into<Cow<'static, str>>) -> Arg {
+        self.value.default = Some(default.into());
+        self
+    }
+
+    /// Sets the placeholder displayed instead of the name, e.g. `FILE` for `<FILE>`
+    pub fn with_value_name(mut self, value_name: impl Into<Cow<'static, str>>) -> Arg {
+        self.value.value_name = Some(value_name.into());
+        self
+    }
+
+    /// Records the type of the value, see `ArgSummarize::summarize_typed`
+    pub fn with_type_name(mut self, type_name: impl Into<Cow<'static, str>>) -> Arg {
+        self.value.type_name = Some(type_name.into());
+        self
+    }
+
+    /// Sets how the usage line shows the choices, e.g. `ChoicesSummary::Limit(3)` for an enum
+    /// with dozens of variants, the details still listing all of them
+    pub fn with_choices_summary(mut self, choices_summary: ChoicesSummary) -> Arg {
+        self.choices_summary = choices_summary;
+        self
+    }
+
+    /// Sets the values accepted, listed as `[possible values: ..]` and offered by the completions,
+    /// e.g. `PossibleValues::values()` of the type of the argument
+    pub fn with_possible_values(mut self, possible: impl Into<Cow<'static, [Value<'static>]>>) -> Arg {
+        let possible = possible.into();
+        if !possible.is_empty() {
+            self.value.possible_values = possible.iter().map(|value| value.name.clone()).collect();
+            self.possible = possible;
+        }
+        self
+    }
+
+    /// Makes the argument variadic, taking from `min` to `max` values (any number when None),
+    /// shown as `<name>...` by the usage line and `<name>... (1-4)` by the details
+    pub fn with_arity(mut self, min: usize, max: Option<usize>) -> Arg {
+        self.arity = Some(Arity { min, max });
+        self.optional |= min == 0;
+        self
+    }
+
+    /// Makes the argument take every remaining value verbatim, options and `--` included, e.g. the
+    /// command line of a program run by a wrapper
+    pub fn trailing(mut self) -> Arg {
+        self.trailing = true;
+        self
+    }
+
+    /// Sets the kind of value expected, see `ValueHint`
+    pub fn with_hint(mut self, value_hint: ValueHint) -> Arg {
+        self.value_hint = value_hint;
+        self
+    }
+
+    /// Translates the descriptions of the argument and of the arguments it holds, see
+    /// `Command::localize`
+    pub(crate) fn localize(&mut self, localizer: &impl Localizer, parent: &str) {
+        let id = format!("{parent}-{}", self.value.name.trim_start_matches('-'));
+        self.value.localize(localizer, &id);
+        match &mut self.r#type {
+            ArgType::Value => (),
+            ArgType::Choices(Choices(args)) | ArgType::Group(ArgGroup(args)) | ArgType::Commands(Commands(args)) => {
+                for arg in args {
+                    arg.localize(localizer, &id);
+                }
+            }
         }
     }
 
     /// Summarize argument order and name to details afterwards
     pub fn summarize(&self) -> String {
         match &self.r#type {
-            ArgType::Value => self.value.name.to_string(),
-            // displays only the name if the commands or choices depth are not more than two.
-            // since it would mean a complicated argument description
-            ArgType::Choices(_) if self.max_depth <= 2 => self.value.name.to_string(),
-            ArgType::Choices(choices) => choices.summarize(),
+            ArgType::Value => self.value.label(),
+            ArgType::Choices(choices) => match self.choices_summary {
+                // displays only the name if the choices depth is not more than two,
+                // since it would mean a complicated argument description
+                ChoicesSummary::Auto if self.max_depth > 2 => choices.summarize(),
+                ChoicesSummary::Auto | ChoicesSummary::Name => self.value.name.to_string(),
+                ChoicesSummary::Limit(limit) => choices.limited_list(limit, Arg::summarize),
+                ChoicesSummary::Inline => choices.limited_list(usize::MAX, Arg::summarize),
+            },
+            ArgType::Commands(commands) => commands.summarize(),
             ArgType::Group(group) => group.summarize(),
         }
     }
 
+    /// Same as `summarize`, values being followed by their type when it is known
+    pub fn summarize_typed(&self) -> String {
+        match &self.r#type {
+            ArgType::Value => match &self.value.type_name {
+                Some(type_name) => format!("{}: {type_name}", self.value.label()),
+                None => self.value.label(),
+            },
+            ArgType::Choices(choices) => match self.choices_summary {
+                ChoicesSummary::Auto if self.max_depth > 2 => choices.summarize_typed(),
+                ChoicesSummary::Auto | ChoicesSummary::Name => self.value.name.to_string(),
+                ChoicesSummary::Limit(limit) => choices.limited_list(limit, Arg::summarize_typed),
+                ChoicesSummary::Inline => choices.limited_list(usize::MAX, Arg::summarize_typed),
+            },
+            ArgType::Commands(commands) => commands.summarize(),
+            ArgType::Group(group) => group.summarize_typed(),
+        }
+    }
+
     pub fn details(&self) -> String {
+        self.details_indented(DEFAULT_INDENT)
+    }
+
+    /// Same as `details`, each nesting level of choices and commands being indented by `indent`
+    pub fn details_indented(&self, indent: &str) -> String {
         match &self.r#type {
-            ArgType::Value => format!("{:#}\n", self.value),
-            ArgType::Choices(choices) if self.max_depth <= 2 => {
-                format!("{:#}\n{}", self.value, super::formatter::start_with(choices.details(), "  "))
+            ArgType::Value => match self.arity {
+                Some(arity) => {
+                    let label = format!("<{}>...", self.value.label());
+                    let label = arity.bounds().map_or(label.clone(), |bounds| format!("{label} {bounds}"));
+                    format!("{}\n", self.value.line_with_label(label, true))
+                }
+                None => format!("{:#}\n", self.value),
+            },
+            ArgType::Choices(_) if !self.value.possible_values.is_empty() => format!("{:#}\n", self.value),
+            ArgType::Choices(choices) => {
+                format!("{:#}\n{}", self.value, start_with(choices.details_indented(indent), indent))
             }
-            ArgType::Choices(choices) => choices.details(),
-            ArgType::Group(group) => group.details(),
+            ArgType::Commands(commands) => {
+                format!("{:#}\n{}", self.value, start_with(commands.details_indented(indent), indent))
+            }
+            ArgType::Group(group) => group.details_indented(indent),
         }
     }
 }
@@ -256,6 +654,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_should_summarize_nested_commands_with_braces() {
+        let remote = Arg::with_type(
+            "remote",
+            Some("manages remotes"),
+            ArgType::Commands(Commands(vec![
+                Arg::with_type("add", None, ArgType::Group(ArgGroup(vec![Arg::new("name", None)]))),
+                Arg::new("remove", None),
+            ])),
+        );
+        let commands = Commands(vec![remote, Arg::new("status", Some("shows the status"))]);
+        assert_eq!(commands.summarize(), "{remote {add <name>|remove}|status}");
+        assert_eq!(
+            commands.details(),
+            "- remote  manages remotes\n    - add\n    - remove\n- status  shows the status\n"
+        );
+    }
+
+    #[test]
+    fn it_should_truncate_long_choice_lists() {
+        let letters: Vec<Arg> = ["a", "b", "c", "d", "e"].into_iter().map(|name| Arg::new(name, None)).collect();
+        let choices = Choices(letters);
+        assert_eq!(choices.summarize_limited(2), "<a|b|…>");
+        assert_eq!(choices.summarize_limited(5), "<a|b|c|d|e>");
+        let letter = Arg::with_type("letter", None, ArgType::Choices(choices));
+        assert_eq!(letter.summarize(), "letter");
+        let letter = letter.with_choices_summary(ChoicesSummary::Limit(3));
+        assert_eq!(letter.details(), "letter  [possible values: a, b, c, d, e]\n");
+        assert_eq!(ArgGroup(vec![letter]).summarize(), "<a|b|c|…>");
+        let complexe = Arg::with_type("complexe", None, Complexe::arguments());
+        assert_eq!(complexe.summarize(), "<<titi> <tutu>|<test> <<titi> <tutu>>|Three>");
+        assert_eq!(complexe.with_choices_summary(ChoicesSummary::Name).summarize(), "complexe");
+    }
+
+    #[test]
+    fn it_should_inline_choices_on_request() {
+        let speeds = Choices(vec![Arg::new("slow", None), Arg::new("fast", None)]);
+        let speed = Arg::with_type("speed", None, ArgType::Choices(speeds)).with_choices_summary(ChoicesSummary::Inline);
+        assert_eq!(speed.summarize(), "slow|fast");
+        assert_eq!(ArgGroup(vec![speed]).summarize(), "<slow|fast>");
+    }
+
+    #[test]
+    fn it_should_describe_arguments_known_at_runtime() {
+        let profiles = ["dev", "release"].map(|name| Value::keyword(name, None)).to_vec();
+        let profile = Arg::new(String::from("profile"), format!("one of the {} profiles", profiles.len()))
+            .with_possible_values(profiles);
+        assert_eq!(profile.details(), "profile  one of the 2 profiles [possible values: dev, release]\n");
+        assert_eq!(profile.possible.len(), 2);
+    }
+
+    #[test]
+    fn it_should_show_the_arity_of_variadic_arguments() {
+        let group = ArgGroup(vec![
+            Arg::new("files", Some("files to copy")).with_arity(1, Some(4)),
+            Arg::new("tags", None).with_arity(0, None),
+        ]);
+        assert_eq!(group.summarize(), "<files>... <tags>...");
+        assert_eq!(group.details(), "<files>... (1-4)  files to copy\n<tags>...\n");
+        assert!(!group.get_args()[1].is_required());
+        assert_eq!(Arity { min: 2, max: None }.bounds().as_deref(), Some("(2+)"));
+        assert_eq!(Arity { min: 2, max: Some(2) }.bounds().as_deref(), Some("(2)"));
+    }
+
+    #[test]
+    fn it_should_sort_arguments() {
+        let args = vec![
+            Arg::new("verbose", None).optional(),
+            Arg::new("Config", None),
+            Arg::new("level", None).with_default("1"),
+            Arg::new("address", None),
+        ];
+        let names = |order: DisplayOrder| -> Vec<&str> { order.sort(&args).iter().map(|arg| arg.value.name()).collect() };
+        assert_eq!(names(DisplayOrder::Declaration), vec!["verbose", "Config", "level", "address"]);
+        assert_eq!(names(DisplayOrder::Alphabetical), vec!["address", "Config", "level", "verbose"]);
+        assert_eq!(names(DisplayOrder::RequiredFirst), vec!["Config", "address", "verbose", "level"]);
+    }
+
+    #[test]
+    fn it_should_summarize_with_types() {
+        let group = ArgGroup(vec![
+            Arg::new("count", None).with_type_name("u8"),
+            Arg::new("path", None).with_value_name("PATH").with_type_name("PathBuf"),
+            Arg::new("name", None),
+        ]);
+        assert_eq!(group.summarize_typed(), "<count: u8> <PATH: PathBuf> <name>");
+        assert_eq!(group.summarize(), "<count> <PATH> <name>");
+    }
+
     #[test]
     fn it_should_format_one_layer_arguments_alternate() {
         // group of argument
@@ -365,18 +852,21 @@ tutu    tutu is the second argument
   - One
   - Two     Second argument
   - Three
-- titi    This titi belongs to the Tata struct and is an unsigned integer
-  tutu    tutu is the second argument
-    - One
-    - Two     Second argument
-    - Three
-- test    This test belongs to the Two value of complexe
-  titi    This titi belongs to the Tata struct and is an unsigned integer
-  tutu    tutu is the second argument
-    - One
-    - Two     Second argument
-    - Three
-- Three
+complexe
+  - One
+      titi    This titi belongs to the Tata struct and is an unsigned integer
+      tutu    tutu is the second argument
+        - One
+        - Two     Second argument
+        - Three
+  - Two
+      test    This test belongs to the Two value of complexe
+      titi    This titi belongs to the Tata struct and is an unsigned integer
+      tutu    tutu is the second argument
+        - One
+        - Two     Second argument
+        - Three
+  - Three
 "#
             .to_string()
         );
@@ -0,0 +1,338 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! clap builder equivalent to a command tree
+//!
+//! With the `clap` feature, a `Command` converts into the same `clap::Command`, e.g. for
+//! clap_mangen or clap_complete. Without it, `to_clap_source` writes the Rust expression building
+//! it, to be compiled where clap is available, e.g. by a build script, or pasted while migrating a
+//! CLI from one library to the other.
+//!
+//! - an option taking a value gets its `value_name`, a repeatable one appends its values, and an
+//!   option without value is a `SetTrue` flag
+//! - positional arguments keep their order, the arguments of a group being inlined and choices
+//!   without arguments of their own becoming possible values
+//! - subcommands are nested `subcommand` calls, but the `help` one which clap adds by itself
+//!
+//! ```
+//! use clip_core::describe::arg::Arg;
+//! use clip_core::describe::clap::to_clap_source;
+//! use clip_core::describe::command::Command;
+//!
+//! let mut app = Command::new("app", Some("an application"));
+//! app.set_arguments(vec![Arg::new("file", None)]);
+//! assert_eq!(
+//!     to_clap_source(&app),
+//!     "clap::Command::new(\"app\")\n    .about(\"an application\")\n    .arg(clap::Arg::new(\"file\").required(true))"
+//! );
+//! ```
+
+use super::arg::{Arg, ArgType, GetArgs};
+use super::command::Command;
+
+/// Rust expression building the `clap::Command` equivalent to `command`
+pub fn to_clap_source(command: &Command) -> String {
+    let mut out = String::new();
+    write_command(&mut out, command, 1);
+    out
+}
+
+fn write_command(out: &mut String, command: &Command, depth: usize) {
+    let indent = "    ".repeat(depth);
+    out.push_str(&format!("clap::Command::new({:?})", command.value.name()));
+    if let Some(description) = command.value.description() {
+        out.push_str(&format!("\n{indent}.about({description:?})"));
+    }
+    if let Some(version) = &command.version {
+        out.push_str(&format!("\n{indent}.version({version:?})"));
+    }
+    if let Some(author) = &command.author {
+        out.push_str(&format!("\n{indent}.author({author:?})"));
+    }
+    for option in command.global_options.get_args() {
+        out.push_str(&format!("\n{indent}.arg({})", option_source(option)));
+    }
+    write_positionals(out, command.arguments.get_args(), depth);
+    if let Some(after_help) = &command.after_help {
+        out.push_str(&format!("\n{indent}.after_help({after_help:?})"));
+    }
+    for subcommand in subcommands(command) {
+        out.push_str(&format!("\n{indent}.subcommand(\n{indent}    "));
+        write_command(out, subcommand, depth + 2);
+        out.push_str(&format!("\n{indent})"));
+    }
+}
+
+/// Subcommands of `command`, but the `help` one which clap adds by itself
+fn subcommands(command: &Command) -> impl Iterator<Item = &Command> {
+    command
+        .subcommands
+        .iter()
+        .flatten()
+        .filter(|subcommand| !subcommand.value.name().eq_ignore_ascii_case("help"))
+}
+
+/// Positional arguments in order, the arguments of a group being inlined and commands becoming
+/// subcommands
+fn write_positionals(out: &mut String, args: &[Arg], depth: usize) {
+    let indent = "    ".repeat(depth);
+    for arg in args {
+        match &arg.r#type {
+            ArgType::Group(group) => write_positionals(out, group.get_args(), depth),
+            ArgType::Commands(commands) => {
+                out.push_str(&format!("\n{indent}.subcommand_required(true)"));
+                for command in commands.get_args() {
+                    out.push_str(&format!("\n{indent}.subcommand(\n{indent}    clap::Command::new({:?})", command.value.name()));
+                    if let Some(description) = command.value.description() {
+                        out.push_str(&format!("\n{indent}        .about({description:?})"));
+                    }
+                    if let ArgType::Group(group) = &command.r#type {
+                        write_positionals(out, group.get_args(), depth + 2);
+                    }
+                    out.push_str(&format!("\n{indent})"));
+                }
+            }
+            _ => out.push_str(&format!("\n{indent}.arg({})", positional_source(arg))),
+        }
+    }
+}
+
+fn option_source(option: &Arg) -> String {
+    let name = option.value.name();
+    let id = name.trim_start_matches('-');
+    let mut source = format!("clap::Arg::new({id:?}).long({id:?})");
+    if let Some(short) = option.short {
+        source.push_str(&format!(".short({short:?})"));
+    }
+    push_help(&mut source, option);
+    match &option.value.value_name {
+        Some(value_name) => {
+            source.push_str(&format!(".value_name({value_name:?})"));
+            if option.arity.is_some() {
+                source.push_str(".action(clap::ArgAction::Append)");
+            }
+            push_values(&mut source, option);
+            if option.is_required() {
+                source.push_str(".required(true)");
+            }
+        }
+        None => source.push_str(".action(clap::ArgAction::SetTrue)"),
+    }
+    source
+}
+
+fn positional_source(positional: &Arg) -> String {
+    let mut source = format!("clap::Arg::new({:?})", positional.value.name());
+    push_help(&mut source, positional);
+    if let Some(arity) = positional.arity {
+        match arity.max {
+            Some(max) => source.push_str(&format!(".num_args({}..={max})", arity.min)),
+            None => source.push_str(&format!(".num_args({}..)", arity.min)),
+        }
+    }
+    if positional.trailing {
+        source.push_str(".trailing_var_arg(true).allow_hyphen_values(true)");
+    }
+    push_values(&mut source, positional);
+    source.push_str(&format!(".required({})", positional.is_required()));
+    source
+}
+
+fn push_help(source: &mut String, arg: &Arg) {
+    if let Some(description) = arg.value.description() {
+        source.push_str(&format!(".help({description:?})"));
+    }
+}
+
+/// Possible values and default value
+fn push_values(source: &mut String, arg: &Arg) {
+    let possible = &arg.value.possible_values;
+    if !possible.is_empty() {
+        source.push_str(&format!(".value_parser({possible:?})"));
+    }
+    if let Some(default) = &arg.value.default {
+        source.push_str(&format!(".default_value({default:?})"));
+    }
+}
+
+#[cfg(feature = "clap")]
+impl From<&Command> for ::clap::Command {
+    fn from(command: &Command) -> Self {
+        let mut clap = ::clap::Command::new(command.value.name().to_string());
+        if let Some(description) = command.value.description() {
+            clap = clap.about(description.to_string());
+        }
+        if let Some(version) = &command.version {
+            clap = clap.version(version.to_string());
+        }
+        if let Some(author) = &command.author {
+            clap = clap.author(author.to_string());
+        }
+        for option in command.global_options.get_args() {
+            clap = clap.arg(clap_option(option));
+        }
+        clap = clap_positionals(clap, command.arguments.get_args());
+        if let Some(after_help) = &command.after_help {
+            clap = clap.after_help(after_help.to_string());
+        }
+        for subcommand in subcommands(command) {
+            clap = clap.subcommand(::clap::Command::from(subcommand));
+        }
+        clap
+    }
+}
+
+#[cfg(feature = "clap")]
+impl From<Command> for ::clap::Command {
+    fn from(command: Command) -> Self {
+        ::clap::Command::from(&command)
+    }
+}
+
+#[cfg(feature = "clap")]
+fn clap_positionals(mut clap: ::clap::Command, args: &[Arg]) -> ::clap::Command {
+    for arg in args {
+        clap = match &arg.r#type {
+            ArgType::Group(group) => clap_positionals(clap, group.get_args()),
+            ArgType::Commands(commands) => {
+                let mut clap = clap.subcommand_required(true);
+                for command in commands.get_args() {
+                    let mut subcommand = ::clap::Command::new(command.value.name().to_string());
+                    if let Some(description) = command.value.description() {
+                        subcommand = subcommand.about(description.to_string());
+                    }
+                    if let ArgType::Group(group) = &command.r#type {
+                        subcommand = clap_positionals(subcommand, group.get_args());
+                    }
+                    clap = clap.subcommand(subcommand);
+                }
+                clap
+            }
+            _ => clap.arg(clap_positional(arg)),
+        };
+    }
+    clap
+}
+
+#[cfg(feature = "clap")]
+fn clap_option(option: &Arg) -> ::clap::Arg {
+    let id = option.value.name().trim_start_matches('-').to_string();
+    let mut clap = ::clap::Arg::new(id.clone()).long(id);
+    if let Some(short) = option.short {
+        clap = clap.short(short);
+    }
+    clap = clap_values(clap_help(clap, option), option);
+    match &option.value.value_name {
+        Some(value_name) => {
+            clap = clap.value_name(value_name.to_string()).required(option.is_required());
+            if option.arity.is_some() {
+                clap = clap.action(::clap::ArgAction::Append);
+            }
+            clap
+        }
+        None => clap.action(::clap::ArgAction::SetTrue),
+    }
+}
+
+#[cfg(feature = "clap")]
+fn clap_positional(positional: &Arg) -> ::clap::Arg {
+    let mut clap = clap_help(::clap::Arg::new(positional.value.name().to_string()), positional);
+    if let Some(arity) = positional.arity {
+        clap = match arity.max {
+            Some(max) => clap.num_args(arity.min..=max),
+            None => clap.num_args(arity.min..),
+        };
+    }
+    if positional.trailing {
+        clap = clap.trailing_var_arg(true).allow_hyphen_values(true);
+    }
+    clap_values(clap, positional).required(positional.is_required())
+}
+
+#[cfg(feature = "clap")]
+fn clap_help(clap: ::clap::Arg, arg: &Arg) -> ::clap::Arg {
+    match arg.value.description() {
+        Some(description) => clap.help(description.to_string()),
+        None => clap,
+    }
+}
+
+/// Possible values and default value
+#[cfg(feature = "clap")]
+fn clap_values(mut clap: ::clap::Arg, arg: &Arg) -> ::clap::Arg {
+    let possible = &arg.value.possible_values;
+    if !possible.is_empty() {
+        let possible: Vec<String> = possible.iter().map(|value| value.to_string()).collect();
+        clap = clap.value_parser(::clap::builder::PossibleValuesParser::new(possible));
+    }
+    if let Some(default) = &arg.value.default {
+        clap = clap.default_value(default.to_string());
+    }
+    clap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_write_the_builder_of_a_command_tree() {
+        let mut add = Command::new("add", Some("adds a remote"));
+        add.set_arguments(vec![Arg::new("name", None), Arg::new("urls", None).with_arity(1, None)]);
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![
+            Arg::new("--verbose", Some("talks more")).with_short('v').optional(),
+            Arg::new("--jobs", None).with_value_name("N").with_arity(0, Some(2)),
+        ]);
+        app.set_subcommands(vec![add]);
+        app.add_help_subcommand();
+        assert_eq!(
+            to_clap_source(&app),
+            "clap::Command::new(\"app\")\n    \
+             .arg(clap::Arg::new(\"verbose\").long(\"verbose\").short('v').help(\"talks more\").action(clap::ArgAction::SetTrue))\n    \
+             .arg(clap::Arg::new(\"jobs\").long(\"jobs\").value_name(\"N\").action(clap::ArgAction::Append))\n    \
+             .subcommand(\n        \
+             clap::Command::new(\"add\")\n            \
+             .about(\"adds a remote\")\n            \
+             .arg(clap::Arg::new(\"name\").required(true))\n            \
+             .arg(clap::Arg::new(\"urls\").num_args(1..).required(true))\n    \
+             )"
+        );
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn it_should_convert_into_a_clap_command() {
+        use crate::describe::value::Value;
+
+        let mut add = Command::new("add", Some("adds a remote"));
+        add.set_arguments(vec![Arg::new("name", None), Arg::new("urls", None).with_arity(1, None)]);
+        let mut app = Command::new("app", Some("an application"));
+        app.set_global_options(vec![
+            Arg::new("--verbose", Some("talks more")).with_short('v').optional(),
+            Arg::new("--color", None)
+                .with_value_name("WHEN")
+                .with_possible_values(vec![Value::keyword("auto", None), Value::keyword("never", None)])
+                .with_default("auto"),
+        ]);
+        app.set_subcommands(vec![add]);
+        app.add_help_subcommand();
+        let clap = ::clap::Command::from(&app);
+        clap.clone().debug_assert();
+        assert_eq!(clap.get_about().map(ToString::to_string), Some(String::from("an application")));
+        let matches = clap.clone().try_get_matches_from(["app", "-v", "add", "origin", "a", "b"]).unwrap();
+        assert!(matches.get_flag("verbose"));
+        assert_eq!(matches.get_one::<String>("color").map(String::as_str), Some("auto"));
+        let (name, add) = matches.subcommand().unwrap();
+        assert_eq!(name, "add");
+        assert_eq!(add.get_many::<String>("urls").unwrap().collect::<Vec<_>>(), ["a", "b"]);
+        assert!(clap.try_get_matches_from(["app", "--color", "blue"]).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! ANSI colors of the help message
+//!
+//! A `Theme` paints the plain text help: section titles, command names, argument placeholders
+//! and descriptions each have their own style. `AsCommand::help` applies the default theme when
+//! `enabled` says the output can be colored.
+
+use super::command::Command;
+use super::render::{PlainText, Renderer};
+use crate::components::ColorChoice;
+
+/// ANSI SGR parameters, e.g. `"1;32"` for bold green; empty for no styling
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style(pub &'static str);
+
+impl Style {
+    pub fn paint(&self, text: &str) -> String {
+        if self.0.is_empty() || text.is_empty() {
+            text.to_string()
+        } else {
+            format!("\x1b[{}m{text}\x1b[0m", self.0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// `Usage:` and the section titles
+    pub usage: Style,
+    /// command and subcommand names
+    pub command: Style,
+    /// argument and option names
+    pub placeholder: Style,
+    pub description: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            usage: Style("1;32"),
+            command: Style("1;36"),
+            placeholder: Style("36"),
+            description: Style(""),
+        }
+    }
+}
+
+/// Whether colors should be used, as the `ColorChoice` applied to the application says: by
+/// default, when the standard output is a terminal and `NO_COLOR` is not set
+pub fn enabled() -> bool {
+    ColorChoice::current().enabled()
+}
+
+impl Theme {
+    /// Paints the usage line: placeholders and options in the placeholder style, names in the
+    /// command style
+    fn paint_usage(&self, usage: &str) -> String {
+        let words: Vec<String> = usage
+            .split(' ')
+            .map(|word| match word {
+                ".." => word.to_string(),
+                word if word.starts_with(['<', '[', '{']) => self.placeholder.paint(word),
+                word => self.command.paint(word),
+            })
+            .collect();
+        format!("{} {}", self.usage.paint("Usage:"), words.join(" "))
+    }
+
+    /// Paints a `  name    description` line of a section
+    fn paint_detail(&self, line: &str, name_style: Style) -> String {
+        let indent = line.len() - line.trim_start().len();
+        let (name, rest) = line[indent..].split_once(' ').unwrap_or((&line[indent..], ""));
+        let description = rest.trim_start();
+        let gap = if rest.is_empty() { "" } else { &line[indent + name.len()..line.len() - description.len()] };
+        format!("{}{}{gap}{}", &line[..indent], name_style.paint(name), self.description.paint(description))
+    }
+
+    /// Colors a plain text help message, line by line
+    pub fn paint(&self, help: &str) -> String {
+        let mut result = String::new();
+        let mut section: Option<&str> = None;
+        for line in help.lines() {
+            let painted = if let Some(usage) = line.strip_prefix("Usage: ") {
+                self.paint_usage(usage)
+            } else if line.starts_with(' ') && section.is_some() {
+                // any other section lists commands, e.g. the categories of subcommands
+                let name_style = match section {
+                    Some("Arguments:" | "Options:" | "Examples:" | "Notes:") => self.placeholder,
+                    _ => self.command,
+                };
+                self.paint_detail(line, name_style)
+            } else if !line.starts_with(' ') && line.ends_with(':') {
+                section = Some(line);
+                self.usage.paint(line)
+            } else {
+                line.to_string()
+            };
+            result.push_str(&painted);
+            result.push('\n');
+        }
+        result
+    }
+}
+
+impl Renderer for Theme {
+    fn render(&self, command: &Command, ancestors: &[&str]) -> String {
+        self.paint(&PlainText::default().render(command, ancestors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::arg::Arg;
+
+    #[test]
+    fn it_should_paint_each_part() {
+        let mut app = Command::new("app", Some("an application"));
+        app.set_arguments(vec![Arg::new("file", Some("file to read"))]);
+        app.set_subcommands(vec![Command::new("run", None)]);
+        let theme = Theme {
+            description: Style("2"),
+            ..Theme::default()
+        };
+        assert_eq!(
+            app.render(&theme),
+            "an application
+
+\x1b[1;32mUsage:\x1b[0m \x1b[1;36mapp\x1b[0m \x1b[36m<file>\x1b[0m \x1b[36m[COMMAND]\x1b[0m ..
+
+\x1b[1;32mArguments:\x1b[0m
+  \x1b[36mfile\x1b[0m    \x1b[2mfile to read\x1b[0m
+
+\x1b[1;32mCommands:\x1b[0m
+  \x1b[1;36mrun\x1b[0m
+"
+        );
+    }
+
+    #[test]
+    fn it_should_not_style_with_an_empty_style() {
+        assert_eq!(Style("").paint("text"), "text");
+    }
+}
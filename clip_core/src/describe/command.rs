@@ -16,28 +16,71 @@
 //You should have received a copy of the GNU General Public License along with this program. If
 //not, see <https://www.gnu.org/licenses/>.
 
-use super::arg::{Arg, ArgDetails, ArgGroup, ArgSummarize, DetailsFormatter, GetArgs};
-use super::value::Value;
-use super::formatter::start_with;
+use super::arg::{Arg, ArgGroup, ArgSummarize, ArgType, Commands, DetailsFormatter, DisplayOrder, GetArgs};
+use super::formatter::wrap;
+use super::render::{Markdown, PlainText, Renderer};
+use super::locale::{Localizer, Titles};
+use super::value::{IntoDescription, Value};
+use std::borrow::Cow;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use crate::parser::ParsingError;
 
 #[derive(Debug, PartialEq)]
 pub struct Command {
     pub value: Value<'static>,
     pub subcommands: Option<Vec<Command>>,
     pub arguments: ArgGroup,
+    /// named options accepted anywhere on the line, before or after the subcommand
+    pub global_options: ArgGroup,
+    /// title of the group the command is listed in by the help of its parent
+    pub category: Option<Cow<'static, str>>,
+    /// version displayed by the help message and `--version`
+    pub version: Option<Cow<'static, str>>,
+    /// author(s) displayed by the help message
+    pub author: Option<Cow<'static, str>>,
+    /// layout of the help message, see `set_template`
+    pub template: Option<Cow<'static, str>>,
+    /// text displayed before anything else in the help message
+    pub before_help: Option<Cow<'static, str>>,
+    /// notes displayed at the end of the help message, e.g. a link to the documentation
+    pub after_help: Option<Cow<'static, str>>,
+    /// example invocations
+    pub examples: Vec<Cow<'static, str>>,
+    /// whether the usage line shows the type of each value, see `set_typed_usage`
+    pub typed_usage: bool,
+    /// whether the usage line lists the subcommand names, see `set_inline_subcommands`
+    pub inline_subcommands: bool,
+    /// order of the arguments, options and subcommands listed by the help
+    pub display_order: DisplayOrder,
+    /// number of spaces indenting the sections and each nesting level of the help
+    pub indent: usize,
+    /// titles of the help sections, see `localize`
+    pub titles: Titles,
 }
 
 impl DetailsFormatter for Command {}
 
 impl Command {
-    pub fn new(name: &'static str, description: Option<&'static str>) -> Self {
+    pub fn new(name: impl Into<Cow<'static, str>>, description: impl IntoDescription) -> Self {
         Self {
-            value: Value {
-                name,
-                description,
-            },
+            value: Value { description: description.into_description(), ..Value::new(name, None) },
             subcommands: None,
             arguments: ArgGroup(Vec::new()),
+            global_options: ArgGroup(Vec::new()),
+            category: None,
+            version: None,
+            author: None,
+            template: None,
+            before_help: None,
+            after_help: None,
+            examples: Vec::new(),
+            typed_usage: false,
+            inline_subcommands: false,
+            display_order: DisplayOrder::Declaration,
+            indent: 2,
+            titles: Titles::default(),
         }
     }
     pub fn set_subcommands(&mut self, subcommands: Vec<Command>) {
@@ -46,42 +89,312 @@ impl Command {
     pub fn set_arguments(&mut self, arguments: Vec<Arg>) {
         match &mut self.arguments { ArgGroup(args) => args }.extend(arguments);
     }
+    pub fn set_global_options(&mut self, options: Vec<Arg>) {
+        match &mut self.global_options { ArgGroup(args) => args }.extend(options);
+    }
+    /// Overrides the layout of the help message
+    ///
+    /// `{version}`, `{author}`, `{before_help}`, `{about}`, `{usage}`, `{arguments}`, `{options}` (or
+    /// `{global_options}`), `{commands}`,
+    /// `{examples}` and `{after_help}` are replaced by the corresponding part of the default help,
+    /// empty when the command has no such part, e.g.
+    /// `"{about}\n\nUsage: {usage}\n\n{arguments}\n{commands}"`.
+    pub fn set_template(&mut self, template: impl Into<Cow<'static, str>>) {
+        self.template = Some(template.into());
+    }
+    /// Sets the whole documentation displayed by `long_help`, the description being its first
+    /// paragraph
+    pub fn set_long_description(&mut self, long_description: impl Into<Cow<'static, str>>) {
+        self.value.long_description = Some(long_description.into());
+    }
+    /// Sets the description, e.g. one only known at runtime
+    pub fn set_description(&mut self, description: impl Into<Cow<'static, str>>) {
+        self.value.description = Some(description.into());
+    }
+    /// Marks the command as deprecated, `note` telling what to use instead (may be empty)
+    pub fn set_deprecated(&mut self, note: impl Into<Cow<'static, str>>) {
+        self.value.deprecated = Some(note.into());
+    }
+    /// Lists the command under its own `category:` title in the help of its parent
+    pub fn set_category(&mut self, category: impl Into<Cow<'static, str>>) {
+        self.category = Some(category.into());
+    }
+    pub fn set_version(&mut self, version: impl Into<Cow<'static, str>>) {
+        self.version = Some(version.into());
+    }
+    pub fn set_author(&mut self, author: impl Into<Cow<'static, str>>) {
+        self.author = Some(author.into());
+    }
+    pub fn set_before_help(&mut self, before_help: impl Into<Cow<'static, str>>) {
+        self.before_help = Some(before_help.into());
+    }
+    pub fn set_after_help(&mut self, after_help: impl Into<Cow<'static, str>>) {
+        self.after_help = Some(after_help.into());
+    }
+    /// Shows the type of each value in the usage line, e.g. `<count: u8>`
+    pub fn set_typed_usage(&mut self) {
+        self.typed_usage = true;
+    }
+    /// Lists the subcommands in the usage line, e.g. `{add|remove}`, instead of `[COMMAND] ..`
+    pub fn set_inline_subcommands(&mut self) {
+        self.inline_subcommands = true;
+    }
+    /// Sorts the listings of the help, the usage line keeping the declaration order
+    pub fn set_display_order(&mut self, display_order: DisplayOrder) {
+        self.display_order = display_order;
+    }
+    /// Sets the number of spaces indenting the sections of the help and each nesting level of
+    /// choices and commands, 2 by default
+    pub fn set_indent(&mut self, indent: usize) {
+        self.indent = indent;
+    }
+    /// Translates the section titles and the descriptions of the whole tree, see
+    /// `describe::locale` for the message identifiers
+    pub fn localize(&mut self, localizer: &impl Localizer) {
+        self.localize_with_parent(localizer, None);
+    }
+    fn localize_with_parent(&mut self, localizer: &impl Localizer, parent: Option<&str>) {
+        let id = match parent {
+            Some(parent) => format!("{parent}-{}", self.value.name),
+            None => self.value.name.to_string(),
+        };
+        self.value.localize(localizer, &id);
+        self.titles = Titles::localized(localizer);
+        for arg in self.arguments.0.iter_mut().chain(self.global_options.0.iter_mut()) {
+            arg.localize(localizer, &id);
+        }
+        for subcommand in self.subcommands.iter_mut().flatten() {
+            subcommand.localize_with_parent(localizer, Some(&id));
+        }
+    }
+    /// Lists a `help [COMMAND]..` subcommand after the subcommands, unless one is already named
+    /// `help`, so that it shows in the help message, the usage line and the completions
+    ///
+    /// Does nothing on a command without subcommands. `help_subcommand` answers it.
+    pub fn add_help_subcommand(&mut self) {
+        let Some(subcommands) = &mut self.subcommands else {
+            return;
+        };
+        if subcommands.iter().any(|subcommand| subcommand.value.name().eq_ignore_ascii_case("help")) {
+            return;
+        }
+        let mut help = Command::new("help", "prints this message or the help of the given subcommand");
+        help.set_arguments(vec![Arg::new("command", None)
+            .with_value_name("COMMAND")
+            .with_arity(0, None)
+            .optional()]);
+        subcommands.push(help);
+    }
+    pub fn add_example(&mut self, example: impl Into<Cow<'static, str>>) {
+        self.examples.push(example.into());
+    }
 
-    fn summarize(&self) -> String {
+    /// One line usage: name, options, arguments and subcommands
+    pub fn summarize(&self) -> String {
         let mut result = format!("{}", self.value);
+        if !self.global_options.get_args().is_empty() {
+            result.push_str(" [OPTIONS]");
+        }
         if !self.arguments.get_args().is_empty() {
-            result.push_str(format!(" {}", self.arguments.summarize()).as_str());
+            let arguments = if self.typed_usage {
+                self.arguments.summarize_typed()
+            } else {
+                self.arguments.summarize()
+            };
+            result.push_str(format!(" {arguments}").as_str());
         }
-        if self.subcommands.is_some() {
-            result.push_str(" [COMMAND] ..");
+        match self.subcommands_summary() {
+            Some(commands) if self.inline_subcommands => result.push_str(&format!(" {}", commands.summarize())),
+            Some(_) => result.push_str(" [COMMAND] .."),
+            None => (),
         }
         result
     }
 
-    fn arguments_details(&self) -> String {
-        format!("Arguments:\n{}", start_with(self.arguments.details(), "  "))
+    /// Subcommand names as a `Commands` node, nested subcommands included
+    pub fn subcommands_summary(&self) -> Option<Commands> {
+        let subcommands = self.subcommands.as_ref()?;
+        Some(Commands(
+            subcommands
+                .iter()
+                .map(|cmd| {
+                    let mut arg = match cmd.subcommands_summary() {
+                        Some(commands) => Arg::with_type(cmd.value.name.clone(), None, ArgType::Commands(commands)),
+                        None => Arg::new(cmd.value.name.clone(), None),
+                    };
+                    arg.value.description = cmd.value.description.clone();
+                    arg
+                })
+                .collect(),
+        ))
     }
 
-    fn command_details(&self, command: &[Command]) -> String {
-        format!("Commands:\n{}", start_with(
-            Self::get_details_formatter().fmt(command.iter(), |cmd| Some(format!("{:#}\n", cmd.value))), "  "
-        ))
+    /// Looks for a nested subcommand following the given names (case insensitive)
+    ///
+    /// An empty path returns the command itself.
+    pub fn find(&self, path: &[&str]) -> Option<&Command> {
+        match path.split_first() {
+            None => Some(self),
+            Some((name, rest)) => self
+                .subcommands
+                .as_ref()?
+                .iter()
+                .find(|command| command.value.name().eq_ignore_ascii_case(name))?
+                .find(rest),
+        }
     }
-    fn details(&self) -> String {
-        let mut result = String::new();
-        if !self.arguments.get_args().is_empty() {
-            result.push_str(
-                self.arguments_details().as_str(),
-            );
-            if self.subcommands.is_some() { result.push('\n'); }
+
+    /// Help message of the command, the usage line being prefixed by the ancestors names
+    pub(crate) fn help_with_ancestors(&self, ancestors: &[&str]) -> String {
+        PlainText::default().render(self, ancestors)
+    }
+
+    /// Renders the command with the given renderer, e.g. `PlainText` for the help message
+    pub fn render(&self, renderer: &impl Renderer) -> String {
+        renderer.render(self, &[])
+    }
+
+    /// Documentation page of the command and all its subcommands
+    pub fn to_markdown(&self) -> String {
+        self.render(&Markdown)
+    }
+
+    /// Help message with the short description, for `-h`
+    pub fn help(&self) -> String {
+        self.help_with_ancestors(&[])
+    }
+
+    /// Writes the help message to `out` instead of returning it, e.g. into a pager buffer
+    pub fn write_help(&self, out: &mut impl std::fmt::Write) -> std::fmt::Result {
+        PlainText::default().write(out, self, &[])
+    }
+
+    /// Same as `write_help` for a byte stream, e.g. `std::io::stderr()` or a file
+    pub fn write_help_io(&self, out: &mut impl std::io::Write) -> std::io::Result<()> {
+        let mut adapter = IoAdapter { inner: out, error: None };
+        match self.write_help(&mut adapter) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adapter.error.unwrap_or_else(|| std::io::Error::other("formatting the help failed"))),
         }
-        if let Some(commands) = &self.subcommands {
-            result.push_str(
-                self.command_details(commands).as_str(),
-            );
+    }
+
+    /// Help message with the whole documentation, for `--help`
+    pub fn long_help(&self) -> String {
+        self.render(&PlainText { long: true })
+    }
+
+    /// Help message wrapped to `width` characters, see `formatter::wrap`
+    pub fn help_with_width(&self, width: usize) -> String {
+        wrap(&self.help(), width)
+    }
+
+    /// Help message of the nested subcommand found with `find`
+    ///
+    /// The usage line starts with the names of all its ancestors, e.g. `app remote add <name>`
+    pub fn help_for_path(&self, path: &[&str]) -> Option<String> {
+        self.render_for_path(path, &PlainText::default())
+    }
+
+    /// Same as `help_for_path` with the whole documentation
+    pub fn long_help_for_path(&self, path: &[&str]) -> Option<String> {
+        self.render_for_path(path, &PlainText { long: true })
+    }
+
+    fn render_for_path(&self, path: &[&str], renderer: &impl Renderer) -> Option<String> {
+        let mut ancestors = Vec::new();
+        let mut command = self;
+        for name in path {
+            ancestors.push(command.value.name());
+            command = command.find(&[name])?;
         }
-        result
+        Some(renderer.render(command, &ancestors))
+    }
+
+    /// Finds a `-h` or `--help` flag, like `git remote add -h`, returning the path of the deepest
+    /// subcommand named before it and whether the long help (`--help`) is asked
+    ///
+    /// The arguments after `--`, the values of the options and the trailing arguments of a command
+    /// are never help flags, e.g. `run -- cat -h` or `exec ls -h`.
+    pub fn help_flag<'a, S: AsRef<str>>(&self, args: &'a [S]) -> Option<(Vec<&'a str>, bool)> {
+        let mut path = Vec::new();
+        let mut command = self;
+        let mut globals: Vec<&Arg> = self.global_options.get_args().iter().collect();
+        let mut args = args.iter().map(AsRef::as_ref);
+        while let Some(arg) = args.next() {
+            match arg {
+                "--" => return None,
+                "-h" | "--help" => return Some((path, arg == "--help")),
+                option if option.starts_with('-') => {
+                    let takes_value = globals.iter().copied().chain(command.arguments.get_args()).any(|known| {
+                        known.value.value_name.is_some()
+                            && (known.value.name() == option || known.short.is_some_and(|short| option == format!("-{short}")))
+                    });
+                    if takes_value {
+                        args.next();
+                    }
+                }
+                name => match command.find(&[name]) {
+                    Some(subcommand) => {
+                        path.push(name);
+                        command = subcommand;
+                        globals.extend(subcommand.global_options.get_args());
+                    }
+                    // the trailing argument takes every remaining value verbatim
+                    None if command.arguments.get_args().iter().any(|argument| argument.trailing) => return None,
+                    None => {}
+                },
+            }
+        }
+        None
     }
+
+    /// Answers a `help [COMMAND]..` invocation, like `git help remote add`, or one of a subcommand
+    /// having subcommands, like `git remote help add`
+    ///
+    /// Returns None if the arguments do not start with `help` (case insensitive) after the names of
+    /// subcommands, and `ParsingError::VariantNotFound` if the named subcommand does not exist. A
+    /// command without subcommands takes `help` as one of its values.
+    pub fn help_subcommand<S: AsRef<str>>(&self, args: &[S]) -> Option<Result<String, ParsingError>> {
+        let mut path: Vec<&str> = Vec::new();
+        let mut command = self;
+        for (index, arg) in args.iter().enumerate() {
+            let arg = arg.as_ref();
+            if arg.eq_ignore_ascii_case("help") && command.subcommands.is_some() {
+                path.extend(args[index + 1..].iter().map(AsRef::as_ref));
+                return Some(self.help_for_path(&path).ok_or(ParsingError::VariantNotFound));
+            }
+            command = command.find(&[arg])?;
+            path.push(arg);
+        }
+        None
+    }
+}
+
+/// `fmt::Write` over an `io::Write`, keeping the io error which `fmt::Error` cannot carry
+struct IoAdapter<'a, W: std::io::Write> {
+    inner: &'a mut W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            std::fmt::Error
+        })
+    }
+}
+
+/// Help message built on the first call only, as derived with `#[clip(static_help)]`
+///
+/// It is painted as by `AsCommand::help` when colors are enabled.
+pub fn cached_help(cell: &OnceLock<String>, build: impl FnOnce() -> String) -> String {
+    let help = cell.get_or_init(build);
+    #[cfg(feature = "color")]
+    if super::color::enabled() {
+        return super::color::Theme::default().paint(help);
+    }
+    help.clone()
 }
 
 /// provides helper functions to describe a command
@@ -89,18 +402,53 @@ pub trait AsCommand {
     /// Required methods
     fn command() -> Command;
     /// Optional methods
-    fn help() -> String {
+    /// Command tree built on the first call only, the later ones sharing it
+    fn command_ref() -> &'static Command
+    where
+        Self: 'static,
+    {
+        type Commands = Mutex<HashMap<TypeId, &'static Command>>;
+        static COMMANDS: OnceLock<Commands> = OnceLock::new();
+        let commands = COMMANDS.get_or_init(Default::default);
+        let lock = || commands.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(command) = lock().get(&TypeId::of::<Self>()) {
+            return command;
+        }
+        // built unlocked, as the tree of a command may be made of the ones of other commands
         let command = Self::command();
-        format!(
-            "{}Usage: {}\n\n{}",
-            if let Some(description) = command.value.description {
-                format!("{}\n\n", description)
-            } else {
-                String::new()
-            },
-            command.summarize(),
-            command.details()
-        )
+        lock().entry(TypeId::of::<Self>()).or_insert_with(|| Box::leak(Box::new(command)))
+    }
+    /// Help message, colored with the default theme if the `color` feature is enabled and the
+    /// standard output is a terminal
+    fn help() -> String
+    where
+        Self: 'static,
+    {
+        #[cfg(feature = "color")]
+        if super::color::enabled() {
+            return super::color::Theme::default().paint(&Self::command_ref().help());
+        }
+        Self::command_ref().help()
+    }
+    /// Same as `help` with the whole documentation
+    fn long_help() -> String
+    where
+        Self: 'static,
+    {
+        #[cfg(feature = "color")]
+        if super::color::enabled() {
+            return super::color::Theme::default().paint(&Self::command_ref().long_help());
+        }
+        Self::command_ref().long_help()
+    }
+    /// Help message asked by a `help [COMMAND]..` invocation, None for any other invocation
+    ///
+    /// Meant to be checked before parsing so that every command accepts a `help` subcommand
+    fn help_subcommand<S: AsRef<str>>(args: &[S]) -> Option<Result<String, ParsingError>>
+    where
+        Self: 'static,
+    {
+        Self::command_ref().help_subcommand(args)
     }
 }
 
@@ -119,16 +467,12 @@ mod tests {
     impl AsCommand for Number {
         fn command() -> Command {
             Command {
-                value: Value {
-                    name: "cli",
-                    description: None,
-                },
                 subcommands: Some(vec![
                     Command::new("One", None),
                     Command::new("Two", Some("Second command")),
                     Command::new("Three", None),
                 ]),
-                arguments: ArgGroup::default(),
+                ..Command::new("cli", None)
             }
         }
     }
@@ -141,7 +485,7 @@ mod tests {
         );
 
         assert_eq!(
-            Number::command().details(),
+            PlainText::default().details(&Number::command()),
             format!(
                 "Commands:\n  One\n  Two{ws:5}Second command\n  Three\n",
                 ws = ' '
@@ -149,6 +493,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_should_inline_nested_subcommands() {
+        let mut remote = Command::new("remote", None);
+        remote.set_subcommands(vec![Command::new("add", None), Command::new("remove", None)]);
+        let mut git = Command::new("git", None);
+        git.set_subcommands(vec![remote, Command::new("status", None)]);
+        assert_eq!(git.summarize(), "git [COMMAND] ..");
+        git.set_inline_subcommands();
+        assert_eq!(git.summarize(), "git {remote {add|remove}|status}");
+    }
+
     #[test]
     fn it_should_display_description() {
         assert_eq!(
@@ -173,10 +528,6 @@ Commands:
     impl AsCommand for Complexe {
         fn command() -> Command {
             Command {
-                value: Value {
-                    name: "complexe",
-                    description: Some("Complexified cli test"),
-                },
                 subcommands: Some(vec![
                     Command::new("One", None),
                     Command::new("Two", Some("Second command")),
@@ -186,6 +537,7 @@ Commands:
                     Arg::new("arg1", None),
                     Arg::new("arg2", Some("Second argument")),
                 ]),
+                ..Command::new("complexe", Some("Complexified cli test"))
             }
         }
     }
@@ -201,7 +553,7 @@ Commands:
     #[test]
     fn arg_and_command_details() {
         assert_eq!(
-            Complexe::command().details(),
+            PlainText::default().details(&Complexe::command()),
             r#"Arguments:
   arg1
   arg2    Second argument
@@ -233,4 +585,164 @@ Commands:
 "#
         );
     }
+
+    #[derive(Debug)]
+    struct Global {
+        // file: Option<String>,
+        // subcommand: Number,
+    }
+
+    impl AsCommand for Global {
+        fn command() -> Command {
+            let mut command = Command::new("global", None);
+            command.set_global_options(vec![Arg::new("--file", Some("configuration file"))]);
+            command.set_subcommands(vec![Command::new("One", None)]);
+            command
+        }
+    }
+
+    #[test]
+    fn global_options_help() {
+        assert_eq!(
+            Global::help(),
+            r#"Usage: global [OPTIONS] [COMMAND] ..
+
+Options:
+  --file  configuration file
+
+Commands:
+  One
+"#
+        );
+    }
+
+    fn remote() -> Command {
+        let mut add = Command::new("add", Some("Adds a remote"));
+        add.set_arguments(vec![Arg::new("name", None)]);
+        let mut remote = Command::new("remote", None);
+        remote.set_subcommands(vec![add, Command::new("remove", None)]);
+        let mut app = Command::new("app", None);
+        app.set_subcommands(vec![remote]);
+        app
+    }
+
+    #[test]
+    fn it_should_find_nested_commands() {
+        let app = remote();
+        assert_eq!(app.find(&[]), Some(&app));
+        assert_eq!(app.find(&["Remote", "add"]).map(|cmd| cmd.value.name()), Some("add"));
+        assert_eq!(app.find(&["remote", "rename"]), None);
+        assert_eq!(app.find(&["add"]), None);
+    }
+
+    #[test]
+    fn it_should_display_help_for_path() {
+        assert_eq!(
+            remote().help_for_path(&["remote", "add"]).unwrap(),
+            r#"Adds a remote
+
+Usage: app remote add <name>
+
+Arguments:
+  name
+"#
+        );
+        assert_eq!(remote().help_for_path(&["unknown"]), None);
+    }
+
+    #[test]
+    fn it_should_answer_help_subcommand() {
+        let app = remote();
+        assert_eq!(app.help_subcommand(&["remote", "add"]), None);
+        assert_eq!(app.help_subcommand::<&str>(&[]), None);
+        assert_eq!(app.help_subcommand(&["help"]), Some(Ok(app.help())));
+        assert_eq!(
+            app.help_subcommand(&["HELP", "remote", "add"]),
+            app.help_for_path(&["remote", "add"]).map(Ok)
+        );
+        assert_eq!(
+            app.help_subcommand(&["help", "rename"]),
+            Some(Err(ParsingError::VariantNotFound))
+        );
+        assert_eq!(app.help_subcommand(&["remote", "help", "add"]), app.help_for_path(&["remote", "add"]).map(Ok));
+        assert_eq!(app.help_subcommand(&["remote", "add", "help"]), None);
+        assert_eq!(Command::new("greet", None).help_subcommand(&["help"]), None);
+    }
+
+    #[test]
+    fn it_should_list_the_help_subcommand() {
+        let mut app = remote();
+        app.add_help_subcommand();
+        app.add_help_subcommand();
+        assert_eq!(app.subcommands.as_ref().unwrap().last().unwrap().summarize(), "help <COMMAND>...");
+        assert_eq!(app.subcommands.as_ref().unwrap().iter().filter(|command| command.value.name() == "help").count(), 1);
+        assert!(app.help().contains("\n  help    prints this message or the help of the given subcommand\n"));
+        let mut add = Command::new("add", None);
+        add.add_help_subcommand();
+        assert_eq!(add.subcommands, None);
+    }
+
+    #[test]
+    fn it_should_find_the_help_flag_of_the_deepest_subcommand() {
+        let mut app = remote();
+        let mut exec = Command::new("exec", None);
+        exec.set_arguments(vec![Arg::new("program", None).trailing()]);
+        app.subcommands.as_mut().unwrap().push(exec);
+        app.set_global_options(vec![Arg::new("--name", None).with_short('n').with_value_name("NAME")]);
+        assert_eq!(app.help_flag(&["--help"]), Some((vec![], true)));
+        assert_eq!(app.help_flag(&["remote", "add", "origin", "-h"]), Some((vec!["remote", "add"], false)));
+        assert_eq!(app.help_flag(&["remote", "--help", "add"]), Some((vec!["remote"], true)));
+        assert_eq!(app.help_flag(&["remote", "add", "--", "-h"]), None);
+        assert_eq!(app.help_flag(&["remote", "-n", "-h"]), None);
+        assert_eq!(app.help_flag(&["exec", "ls", "-h"]), None);
+        assert_eq!(app.help_flag(&["exec", "-h"]), Some((vec!["exec"], false)));
+        assert_eq!(app.help_flag(&["remote", "add"]), None);
+        assert!(app.long_help_for_path(&["remote", "add"]).unwrap().starts_with("Adds a remote\n\nUsage: app remote add"));
+    }
+
+    #[test]
+    fn it_should_write_the_help_to_a_writer() {
+        let command = Number::command();
+        let mut text = String::new();
+        command.write_help(&mut text).unwrap();
+        assert_eq!(text, command.help());
+        let mut bytes = Vec::new();
+        command.write_help_io(&mut bytes).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), command.help());
+    }
+
+    #[test]
+    fn it_should_build_the_command_tree_once() {
+        let number = Number::command_ref();
+        assert!(std::ptr::eq(number, Number::command_ref()));
+        assert_eq!(number.help(), Number::command().help());
+        assert!(!std::ptr::eq(number, Complexe::command_ref()));
+        assert_eq!(Complexe::command_ref().value.name(), Complexe::command().value.name());
+    }
+
+    #[test]
+    fn it_should_return_the_io_error() {
+        let mut full = [0u8; 4];
+        let err = Number::command().write_help_io(&mut &mut full[..]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn it_should_describe_runtime_strings() {
+        let config = String::from("deploy=deploys the application\ntarget=environment to deploy to");
+        let mut entries = config.lines().filter_map(|line| line.split_once('='));
+        let (name, description) = entries.next().unwrap();
+        let mut command = Command::new(name.to_string(), None);
+        command.set_description(description.to_string());
+        command.set_arguments(
+            entries
+                .map(|(name, description)| Arg::new(name.to_string(), None).with_description(description.to_string()))
+                .collect(),
+        );
+        drop(config);
+        assert_eq!(
+            command.help(),
+            "deploys the application\n\nUsage: deploy <target>\n\nArguments:\n  target  environment to deploy to\n"
+        );
+    }
 }
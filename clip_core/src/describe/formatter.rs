@@ -24,64 +24,452 @@ pub struct Formatter<'a> {
     pub end: Option<&'a str>,
     pub middle: Option<&'a str>,
     pub new_line_chars: Option<&'a str>,
+    /// layout of each item with `{name}` placeholders, filled by `fmt_fields`, e.g.
+    /// `"{name}\t{description}"`
+    pub template: Option<&'a str>,
+}
+
+/// Text written into `result` by `write`
+///
+/// Writing to a String never fails: an error could only come from a `Display` implementation, the
+/// text written until then being kept rather than panicking.
+pub(crate) fn written(mut result: String, write: impl FnOnce(&mut String) -> std::fmt::Result) -> String {
+    let _ = write(&mut result);
+    result
 }
 
 /// Adds characters to each line of a string
 pub fn start_with(string: String, chars: &str) -> String {
-    let mut result = String::new();
-    for line in string.lines() {
-        result.push_str(format!("{chars}{line}\n").as_str());
+    let result = String::with_capacity(string.len() + chars.len() * string.lines().count());
+    written(result, |result| push_lines(result, &string, chars, chars))
+}
+
+pub fn start_other_lines_with(string: String, chars: &str) -> String {
+    let result = String::with_capacity(string.len() + chars.len() * string.lines().count());
+    written(result, |result| push_lines(result, &string, "", chars))
+}
+
+/// Writes each line of `string` followed by a new line, the first one prefixed by `first` and the
+/// others by `others`
+fn push_lines(out: &mut impl std::fmt::Write, string: &str, first: &str, others: &str) -> std::fmt::Result {
+    for (index, line) in string.lines().enumerate() {
+        out.write_str(if index == 0 { first } else { others })?;
+        out.write_str(line)?;
+        out.write_char('\n')?;
     }
+    Ok(())
+}
+
+/// Replaces the `{name}` placeholders of a template by their value
+///
+/// Unknown placeholders are kept as they are.
+pub fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let (_, value) = values.iter().find(|(name, _)| *name == &rest[1..end])?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                result.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
     result
 }
 
-pub fn start_other_lines_with(string: String, chars: &str) -> String {
-    let mut iterator = string.lines();
-    let mut result = String::new();
-    if let Some(first_line) = iterator.next() {
-        result.push_str(format!("{first_line}\n").as_str());
+/// Calls `f` with each displayed character and its byte index, skipping ANSI escape sequences
+fn for_each_visible(string: &str, mut f: impl FnMut(usize, char) -> bool) {
+    let mut escaped = false;
+    for (index, c) in string.char_indices() {
+        match c {
+            '\x1b' => escaped = true,
+            'm' if escaped => escaped = false,
+            _ if escaped => (),
+            c => {
+                if !f(index, c) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Number of terminal columns taken by a character
+///
+/// With the `unicode` feature, East Asian wide characters and emoji take two columns and
+/// combining marks none, otherwise every character takes one.
+#[cfg(feature = "unicode")]
+pub fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036F
+        | 0x0483..=0x0489
+        | 0x0591..=0x05BD
+        | 0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200B..=0x200F
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F680..=0x1F6FF
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x2FFFD
+        | 0x30000..=0x3FFFD => 2,
+        _ => 1,
     }
-    for line in iterator {
-        result.push_str(format!("{chars}{line}\n").as_str());
+}
+
+#[cfg(not(feature = "unicode"))]
+pub fn char_width(_: char) -> usize {
+    1
+}
+
+/// Number of columns displayed, ANSI escape sequences (colors) being ignored, see `char_width`
+pub fn visible_width(string: &str) -> usize {
+    let mut width = 0;
+    for_each_visible(string, |_, c| {
+        width += char_width(c);
+        true
+    });
+    width
+}
+
+/// Pads a string with spaces up to `width` displayed columns, e.g. to align descriptions
+pub fn pad(string: &str, width: usize) -> String {
+    let mut result = String::from(string);
+    result.extend(std::iter::repeat_n(' ', width.saturating_sub(visible_width(string))));
+    result
+}
+
+/// Splits a string after `column` displayed columns
+fn split_at_column(string: &str, column: usize) -> (&str, &str) {
+    let mut split = string.len();
+    let mut width = 0;
+    for_each_visible(string, |index, c| {
+        if width >= column {
+            split = index;
+            return false;
+        }
+        width += char_width(c);
+        true
+    });
+    string.split_at(split)
+}
+
+/// Column where the wrapped lines of a line continue: the description column of a
+/// `name    description` line (found after two spaces), otherwise the line indentation
+fn hanging_indent(line: &str) -> usize {
+    let mut content = Vec::new();
+    for_each_visible(line, |_, c| {
+        content.push(c);
+        true
+    });
+    let column = |end: usize| content[..end].iter().map(|c| char_width(*c)).sum();
+    let indent = content.iter().take_while(|c| **c == ' ').count();
+    content[indent..]
+        .windows(3)
+        .position(|chars| chars[0] == ' ' && chars[1] == ' ' && chars[2] != ' ')
+        .map_or(column(indent), |gap| column(indent + gap + 2))
+}
+
+/// Wraps the lines longer than `width` characters at spaces, continuation lines being indented
+/// to the description column (hanging indentation)
+///
+/// A word longer than the available width is kept whole on its own line. Colors are not counted
+/// in the width.
+pub fn wrap(text: &str, width: usize) -> String {
+    let mut result = String::new();
+    for line in text.lines() {
+        let hang = hanging_indent(line);
+        if visible_width(line) <= width || hang >= width {
+            result.push_str(line);
+            result.push('\n');
+            continue;
+        }
+        let (prefix, rest) = split_at_column(line, hang);
+        let mut current = prefix.to_string();
+        let mut length = hang;
+        let mut empty = true;
+        for word in rest.split_whitespace() {
+            let word_length = visible_width(word);
+            if !empty && length + 1 + word_length > width {
+                result.push_str(&current);
+                result.push('\n');
+                current = " ".repeat(hang);
+                length = hang;
+                empty = true;
+            }
+            if !empty {
+                current.push(' ');
+                length += 1;
+            }
+            current.push_str(word);
+            length += word_length;
+            empty = false;
+        }
+        result.push_str(&current);
+        result.push('\n');
     }
     result
 }
 
+/// Width of the terminal: the `COLUMNS` variable if set, otherwise (with the `terminal` feature)
+/// the size of the terminal attached to the standard output
+pub fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|columns| *columns > 0)
+        .or_else(|| tty_size().map(|(columns, _)| columns))
+}
+
+/// Height of the terminal: the `LINES` variable if set, otherwise (with the `terminal` feature)
+/// the size of the terminal attached to the standard output
+pub fn terminal_height() -> Option<usize> {
+    std::env::var("LINES")
+        .ok()
+        .and_then(|lines| lines.parse().ok())
+        .filter(|lines| *lines > 0)
+        .or_else(|| tty_size().map(|(_, rows)| rows))
+}
+
+/// Columns and rows of the terminal attached to the standard output
+#[cfg(all(feature = "terminal", any(target_os = "linux", target_os = "macos")))]
+fn tty_size() -> Option<(usize, usize)> {
+    #[repr(C)]
+    struct WinSize {
+        rows: u16,
+        columns: u16,
+        x_pixels: u16,
+        y_pixels: u16,
+    }
+    // the architectures inheriting their ioctl numbers from other Unix systems share the value of
+    // macOS
+    #[cfg(all(
+        target_os = "linux",
+        not(any(
+            target_arch = "mips",
+            target_arch = "mips64",
+            target_arch = "powerpc",
+            target_arch = "powerpc64",
+            target_arch = "sparc",
+            target_arch = "sparc64"
+        ))
+    ))]
+    const TIOCGWINSZ: std::ffi::c_ulong = 0x5413;
+    #[cfg(any(
+        target_os = "macos",
+        all(
+            target_os = "linux",
+            any(
+                target_arch = "mips",
+                target_arch = "mips64",
+                target_arch = "powerpc",
+                target_arch = "powerpc64",
+                target_arch = "sparc",
+                target_arch = "sparc64"
+            )
+        )
+    ))]
+    const TIOCGWINSZ: std::ffi::c_ulong = 0x40087468;
+    extern "C" {
+        fn ioctl(fd: std::ffi::c_int, request: std::ffi::c_ulong, ...) -> std::ffi::c_int;
+    }
+    let mut size = WinSize {
+        rows: 0,
+        columns: 0,
+        x_pixels: 0,
+        y_pixels: 0,
+    };
+    // SAFETY: TIOCGWINSZ only writes a winsize structure at the given address
+    let result = unsafe { ioctl(1, TIOCGWINSZ, &mut size as *mut WinSize) };
+    (result == 0 && size.columns > 0 && size.rows > 0).then_some((size.columns as usize, size.rows as usize))
+}
+
+#[cfg(not(all(feature = "terminal", any(target_os = "linux", target_os = "macos"))))]
+fn tty_size() -> Option<(usize, usize)> {
+    None
+}
+
 impl<'a> Formatter<'a> {
     pub fn fmt<'b, Item: 'b, I: Iterator<Item = &'b Item>, F: FnMut(I::Item) -> Option<String>>(
         &self,
         args: I,
         format_function: F,
     ) -> String {
-        format!(
-            "{very_start}{description}{very_end}",
-            very_start = self.very_start.unwrap_or(""),
-            very_end = self.very_end.unwrap_or(""),
-            description = args.filter_map(format_function).fold(
-                "".to_string(),
-                |string: String, item: String| {
-                    format!(
-                        "{string}{middle}{start}{content}{end}",
-                        start = self.start.unwrap_or(""),
-                        content = if let Some(chars) = self.new_line_chars {
-                            start_other_lines_with(item, chars)
-                        } else { item },
-                        middle = if string.is_empty() {
-                            ""
-                        } else {
-                            self.middle.unwrap_or("")
-                        },
-                        end = self.end.unwrap_or("")
-                    )
+        written(String::new(), |result| self.write(result, args, format_function))
+    }
+
+    /// Same as `fmt`, every piece being pushed into `out` as it is formatted
+    pub fn write<'b, Item: 'b, I: Iterator<Item = &'b Item>, F: FnMut(I::Item) -> Option<String>>(
+        &self,
+        out: &mut impl std::fmt::Write,
+        args: I,
+        format_function: F,
+    ) -> std::fmt::Result {
+        out.write_str(self.very_start.unwrap_or(""))?;
+        // the separator is only written once something has been
+        let mut empty = true;
+        for item in args.filter_map(format_function) {
+            if !empty {
+                out.write_str(self.middle.unwrap_or(""))?;
+            }
+            let start = self.start.unwrap_or("");
+            let end = self.end.unwrap_or("");
+            match self.new_line_chars {
+                Some(chars) => {
+                    out.write_str(start)?;
+                    push_lines(out, &item, "", chars)?;
+                    empty &= start.is_empty() && item.lines().next().is_none();
                 }
-            )
-        )
+                None => {
+                    out.write_str(start)?;
+                    out.write_str(&item)?;
+                    empty &= start.is_empty() && item.is_empty();
+                }
+            }
+            out.write_str(end)?;
+            empty &= end.is_empty();
+        }
+        out.write_str(self.very_end.unwrap_or(""))
+    }
+
+    /// Same as `fmt`, each item being given as named fields which fill the `template`
+    ///
+    /// Without a template, the values of the fields are concatenated.
+    pub fn fmt_fields<'b, 'c, Item: 'b, I, F>(&self, args: I, format_function: F) -> String
+    where
+        I: Iterator<Item = &'b Item>,
+        F: FnMut(I::Item) -> Option<Vec<(&'c str, String)>>,
+    {
+        written(String::new(), |result| self.write_fields(result, args, format_function))
+    }
+
+    /// Same as `fmt_fields`, every piece being pushed into `out` as it is formatted
+    pub fn write_fields<'b, 'c, Item: 'b, I, F>(
+        &self,
+        out: &mut impl std::fmt::Write,
+        args: I,
+        mut format_function: F,
+    ) -> std::fmt::Result
+    where
+        I: Iterator<Item = &'b Item>,
+        F: FnMut(I::Item) -> Option<Vec<(&'c str, String)>>,
+    {
+        self.write(out, args, |item| {
+            let fields = format_function(item)?;
+            Some(match self.template {
+                Some(template) => {
+                    let values: Vec<(&str, &str)> = fields.iter().map(|(name, value)| (*name, value.as_str())).collect();
+                    fill(template, &values)
+                }
+                None => fields.into_iter().map(|(_, value)| value).collect(),
+            })
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn it_should_fill_known_placeholders() {
+        assert_eq!(
+            fill("{name}: {description} {other} {", &[("name", "file"), ("description", "to read")]),
+            "file: to read {other} {"
+        );
+    }
+
+    #[test]
+    fn it_should_wrap_with_hanging_indentation() {
+        assert_eq!(
+            wrap("  file    the file to read, relative to the current directory\nshort\n", 30),
+            "  file    the file to read,\n          relative to the\n          current directory\nshort\n"
+        );
+    }
+
+    #[test]
+    fn it_should_not_count_colors() {
+        assert_eq!(visible_width("\x1b[1;32mfile\x1b[0m"), 4);
+        assert_eq!(
+            wrap("  \x1b[32mfile\x1b[0m    \x1b[2mthe file to read\x1b[0m", 20),
+            "  \x1b[32mfile\x1b[0m    \x1b[2mthe file\n          to read\x1b[0m\n"
+        );
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn it_should_count_wide_characters_twice() {
+        assert_eq!(visible_width("日本語"), 6);
+        assert_eq!(visible_width("e\u{301}"), 1);
+        assert_eq!(pad("日本", 6), "日本  ");
+        assert_eq!(wrap("  日本    ファイルを読む 設定", 20), "  日本    ファイルを読む\n          設定\n");
+    }
+
+    #[test]
+    fn it_should_keep_long_words_whole() {
+        assert_eq!(
+            wrap("a description with averyveryverylongword", 12),
+            "a\ndescription\nwith\naveryveryverylongword\n"
+        );
+    }
+
+    #[test]
+    fn it_should_write_into_a_sink() {
+        let mut result = String::from("> ");
+        Formatter {
+            middle: Some(", "),
+            new_line_chars: Some("  "),
+            ..Default::default()
+        }
+        .write(&mut result, ["a\nb", "", "c"].iter(), |item| Some(item.to_string()))
+        .unwrap();
+        assert_eq!(result, "> a\n  b\n, , c\n");
+    }
+
+    #[test]
+    fn it_should_fill_the_item_template() {
+        let formatter = Formatter {
+            very_start: Some("| Name | Type |\n"),
+            template: Some("| {name} | {type} |\n"),
+            ..Default::default()
+        };
+        assert_eq!(
+            formatter.fmt_fields([("count", "u8"), ("name", "String")].iter(), |(name, r#type)| {
+                Some(vec![("name", name.to_string()), ("type", r#type.to_string())])
+            }),
+            "| Name | Type |\n| count | u8 |\n| name | String |\n"
+        );
+        assert_eq!(
+            Formatter::default().fmt_fields([1, 2].iter(), |item| Some(vec![("a", item.to_string()), ("b", "!".to_string())])),
+            "1!2!"
+        );
+    }
+
     #[test]
     fn default_formatter() {
         assert_eq!(
@@ -125,6 +513,7 @@ mod tests {
                 very_start: Some("Result: "),
                 very_end: Some("."),
                 new_line_chars: None,
+                template: None,
             }
             .fmt([1, 2, 3].iter(), |item| Some(item.to_string())),
             "Result: <1> <2> <3>."
@@ -0,0 +1,342 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! JSON export of the command tree, for tools introspecting a CLI (GUIs, docs generators,
+//! completion daemons)
+//!
+//! The output is written by hand so that no serialization framework is needed:
+//! - a value is `{"name": .., "description": ..}`, the description being null when missing
+//! - an argument adds its `short` alias, `value_name`, `default`, `deprecated` note (null when
+//!   missing), `possible_values`, `arity` (`{"min": 1, "max": null}`, null for a single value),
+//!   whether it is `secret`, `trailing` and `required`, its `value_hint` and `type`, the latter
+//!   being `{"kind": "value"}`, `{"kind": "choices", "args": [..]}`,
+//!   `{"kind": "commands", "args": [..]}` or `{"kind": "group", "args": [..]}`
+//! - a command adds its `arguments`, `global_options`, `subcommands` (null when it has none),
+//!   `category`, `version`, `author`, `before_help`, `after_help` (null when missing) and
+//!   `examples`
+//!
+//! With the `serde` feature, the same tree implements `serde::Serialize`, e.g. for
+//! `serde_json::to_string` or any other format.
+
+use super::arg::{Arg, ArgType, Arity, GetArgs};
+use super::command::Command;
+use super::value::Value;
+
+pub trait ToJson {
+    /// Required method
+    fn write_json(&self, out: &mut String);
+
+    fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+}
+
+fn write_string(out: &mut String, string: &str) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_array<T: ToJson>(out: &mut String, items: &[T]) {
+    out.push('[');
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        item.write_json(out);
+    }
+    out.push(']');
+}
+
+/// Writes a `"name":` member whose text may be missing, preceded by a comma
+fn write_optional(out: &mut String, name: &str, text: Option<&str>) {
+    out.push_str(&format!(",\"{name}\":"));
+    match text {
+        Some(text) => write_string(out, text),
+        None => out.push_str("null"),
+    }
+}
+
+/// Writes the name and description members, without the braces
+fn write_value_members(out: &mut String, value: &Value) {
+    out.push_str("\"name\":");
+    write_string(out, value.name());
+    out.push_str(",\"description\":");
+    match value.description() {
+        Some(description) => write_string(out, description),
+        None => out.push_str("null"),
+    }
+}
+
+impl ToJson for Value<'_> {
+    fn write_json(&self, out: &mut String) {
+        out.push('{');
+        write_value_members(out, self);
+        out.push('}');
+    }
+}
+
+impl ToJson for ArgType {
+    fn write_json(&self, out: &mut String) {
+        match self {
+            ArgType::Value => out.push_str("{\"kind\":\"value\"}"),
+            ArgType::Choices(choices) => {
+                out.push_str("{\"kind\":\"choices\",\"args\":");
+                write_array(out, choices.get_args());
+                out.push('}');
+            }
+            ArgType::Commands(commands) => {
+                out.push_str("{\"kind\":\"commands\",\"args\":");
+                write_array(out, commands.get_args());
+                out.push('}');
+            }
+            ArgType::Group(group) => {
+                out.push_str("{\"kind\":\"group\",\"args\":");
+                write_array(out, group.get_args());
+                out.push('}');
+            }
+        }
+    }
+}
+
+impl ToJson for Arg {
+    fn write_json(&self, out: &mut String) {
+        out.push('{');
+        write_value_members(out, &self.value);
+        write_optional(out, "short", self.short.map(String::from).as_deref());
+        write_optional(out, "value_name", self.value.value_name.as_deref());
+        write_optional(out, "default", self.value.default.as_deref());
+        write_optional(out, "deprecated", self.value.deprecated.as_deref());
+        out.push_str(",\"possible_values\":[");
+        for (index, value) in self.value.possible_values.iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+            write_string(out, value);
+        }
+        out.push_str("],\"arity\":");
+        match self.arity {
+            Some(Arity { min, max: Some(max) }) => out.push_str(&format!("{{\"min\":{min},\"max\":{max}}}")),
+            Some(Arity { min, max: None }) => out.push_str(&format!("{{\"min\":{min},\"max\":null}}")),
+            None => out.push_str("null"),
+        }
+        out.push_str(&format!(
+            ",\"secret\":{},\"trailing\":{},\"required\":{}",
+            self.secret,
+            self.trailing,
+            self.is_required()
+        ));
+        out.push_str(",\"value_hint\":");
+        write_string(out, &format!("{:?}", self.value_hint));
+        out.push_str(",\"type\":");
+        self.r#type.write_json(out);
+        out.push('}');
+    }
+}
+
+impl ToJson for Command {
+    fn write_json(&self, out: &mut String) {
+        out.push('{');
+        write_value_members(out, &self.value);
+        out.push_str(",\"arguments\":");
+        write_array(out, self.arguments.get_args());
+        out.push_str(",\"global_options\":");
+        write_array(out, self.global_options.get_args());
+        out.push_str(",\"subcommands\":");
+        match &self.subcommands {
+            Some(subcommands) => write_array(out, subcommands),
+            None => out.push_str("null"),
+        }
+        for (name, text) in [
+            ("category", &self.category),
+            ("version", &self.version),
+            ("author", &self.author),
+            ("before_help", &self.before_help),
+            ("after_help", &self.after_help),
+        ] {
+            write_optional(out, name, text.as_deref());
+        }
+        out.push_str(",\"examples\":[");
+        for (index, example) in self.examples.iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+            write_string(out, example);
+        }
+        out.push_str("]}");
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serialize {
+    use super::*;
+    use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+    impl Serialize for Value<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut value = serializer.serialize_struct("Value", 2)?;
+            value.serialize_field("name", self.name())?;
+            value.serialize_field("description", &self.description())?;
+            value.end()
+        }
+    }
+
+    impl Serialize for Arity {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut arity = serializer.serialize_struct("Arity", 2)?;
+            arity.serialize_field("min", &self.min)?;
+            arity.serialize_field("max", &self.max)?;
+            arity.end()
+        }
+    }
+
+    impl Serialize for ArgType {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let (kind, args) = match self {
+                ArgType::Value => ("value", None),
+                ArgType::Choices(choices) => ("choices", Some(choices.get_args())),
+                ArgType::Commands(commands) => ("commands", Some(commands.get_args())),
+                ArgType::Group(group) => ("group", Some(group.get_args())),
+            };
+            let mut r#type = serializer.serialize_struct("ArgType", 1 + usize::from(args.is_some()))?;
+            r#type.serialize_field("kind", kind)?;
+            if let Some(args) = args {
+                r#type.serialize_field("args", args)?;
+            }
+            r#type.end()
+        }
+    }
+
+    impl Serialize for Arg {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut arg = serializer.serialize_struct("Arg", 13)?;
+            arg.serialize_field("name", self.value.name())?;
+            arg.serialize_field("description", &self.value.description())?;
+            arg.serialize_field("short", &self.short)?;
+            arg.serialize_field("value_name", &self.value.value_name)?;
+            arg.serialize_field("default", &self.value.default)?;
+            arg.serialize_field("deprecated", &self.value.deprecated)?;
+            arg.serialize_field("possible_values", &self.value.possible_values)?;
+            arg.serialize_field("arity", &self.arity)?;
+            arg.serialize_field("secret", &self.secret)?;
+            arg.serialize_field("trailing", &self.trailing)?;
+            arg.serialize_field("required", &self.is_required())?;
+            arg.serialize_field("value_hint", &format!("{:?}", self.value_hint))?;
+            arg.serialize_field("type", &self.r#type)?;
+            arg.end()
+        }
+    }
+
+    impl Serialize for Command {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut command = serializer.serialize_struct("Command", 11)?;
+            command.serialize_field("name", self.value.name())?;
+            command.serialize_field("description", &self.value.description())?;
+            command.serialize_field("arguments", self.arguments.get_args())?;
+            command.serialize_field("global_options", self.global_options.get_args())?;
+            command.serialize_field("subcommands", &self.subcommands)?;
+            command.serialize_field("category", &self.category)?;
+            command.serialize_field("version", &self.version)?;
+            command.serialize_field("author", &self.author)?;
+            command.serialize_field("before_help", &self.before_help)?;
+            command.serialize_field("after_help", &self.after_help)?;
+            command.serialize_field("examples", &self.examples)?;
+            command.end()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::arg::{Choices, ValueHint};
+
+    #[test]
+    fn it_should_export_the_whole_tree() {
+        let mut run = Command::new("run", Some("runs \"quoted\"\nscripts"));
+        run.set_arguments(vec![
+            Arg::new("script", None).with_hint(ValueHint::FilePath),
+            Arg::with_type("mode", None, ArgType::Choices(Choices(vec![Arg::new("fast", None)]))),
+        ]);
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![Arg::new("--verbose", Some("prints more"))]);
+        app.set_subcommands(vec![run]);
+        app.set_version("1.0.0");
+        app.set_after_help("see the docs");
+        app.add_example("app run x.sh");
+        // members of an argument left as built by `Arg::new`
+        let plain = r#""short":null,"value_name":null,"default":null,"deprecated":null,"possible_values":[],"arity":null,"secret":false,"trailing":false,"required":true"#;
+        let choice = plain.replace(r#""possible_values":[]"#, r#""possible_values":["fast"]"#);
+        assert_eq!(
+            app.to_json(),
+            [
+                r#"{"name":"app","description":null,"arguments":[],"global_options":["#,
+                r#"{"name":"--verbose","description":"prints more","#, plain, r#","value_hint":"Unknown","type":{"kind":"value"}}],"#,
+                r#""subcommands":[{"name":"run","description":"runs \"quoted\"\nscripts","arguments":["#,
+                r#"{"name":"script","description":null,"#, plain, r#","value_hint":"FilePath","type":{"kind":"value"}},"#,
+                r#"{"name":"mode","description":null,"#, &choice, r#","value_hint":"Unknown","type":{"kind":"choices","args":["#,
+                r#"{"name":"fast","description":null,"#, plain, r#","value_hint":"Unknown","type":{"kind":"value"}}]}}],"#,
+                r#""global_options":[],"subcommands":null,"category":null,"version":null,"author":null,"#,
+                r#""before_help":null,"after_help":null,"examples":[]}],"#,
+                r#""category":null,"version":"1.0.0","author":null,"before_help":null,"after_help":"see the docs","examples":["app run x.sh"]}"#
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn it_should_export_the_settings_of_an_argument() {
+        let option = Arg::new("--include", Some("directories searched"))
+            .with_short('I')
+            .with_value_name("DIR")
+            .with_default("src")
+            .with_arity(1, None)
+            .deprecated("use --path");
+        assert_eq!(
+            option.to_json(),
+            concat!(
+                r#"{"name":"--include","description":"directories searched","short":"I","value_name":"DIR","default":"src","#,
+                r#""deprecated":"use --path","possible_values":[],"arity":{"min":1,"max":null},"secret":false,"trailing":false,"#,
+                r#""required":false,"value_hint":"Unknown","type":{"kind":"value"}}"#
+            )
+        );
+        let token = Arg::new("token", None).secret().with_arity(0, Some(2));
+        assert!(token.to_json().contains(r#""arity":{"min":0,"max":2},"secret":true,"trailing":false,"required":false"#));
+        assert!(Arg::new("command", None).trailing().to_json().contains(r#""trailing":true"#));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_should_serialize_as_exported() {
+        let mut add = Command::new("add", Some("adds \"a\" remote"));
+        add.set_arguments(vec![
+            Arg::new("name", None).with_arity(1, Some(2)),
+            Arg::with_type("mode", None, ArgType::Choices(Choices(vec![Arg::new("fast", None)]))).with_default("fast"),
+        ]);
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![Arg::new("--jobs", None).with_short('j').with_value_name("N").deprecated("")]);
+        app.set_subcommands(vec![add]);
+        app.set_author("me");
+        app.add_example("app add origin");
+        assert_eq!(serde_json::to_string(&app).unwrap(), app.to_json());
+    }
+}
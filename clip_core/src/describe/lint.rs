@@ -0,0 +1,221 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Quality checks of a whole command tree
+//!
+//! Meant to be run from a test suite so that a CLI cannot regress: `assert!(lint(&cmd).is_empty())`
+//! or the `#[clip(lint)]` option of the AsCommand derive which generates such a test.
+
+use super::arg::{Arg, ArgGroup, ArgType, Choices, Commands, GetArgs};
+use super::command::Command;
+use super::formatter::visible_width;
+
+#[derive(Debug, PartialEq)]
+pub enum Lint<'a> {
+    /// two siblings have the exact same name
+    DuplicateName {
+        path: Vec<&'a str>,
+        name: &'a str,
+    },
+    /// names only differing by case: keywords are case insensitive so only the first one matches
+    UnreachableVariant {
+        path: Vec<&'a str>,
+        name: &'a str,
+    },
+    /// command or argument without any description
+    MissingDescription { path: Vec<&'a str> },
+    /// line of a help message longer than the configured width
+    LineTooLong {
+        path: Vec<&'a str>,
+        line: String,
+    },
+}
+
+impl std::fmt::Display for Lint<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Lint::DuplicateName { path, name } => {
+                write!(f, "{}: duplicated name '{name}'", path.join(" "))
+            }
+            Lint::UnreachableVariant { path, name } => write!(
+                f,
+                "{}: '{name}' is unreachable, a previous name only differs by case",
+                path.join(" ")
+            ),
+            Lint::MissingDescription { path } => write!(f, "{}: missing description", path.join(" ")),
+            Lint::LineTooLong { path, line } => write!(
+                f,
+                "{}: help line is {} characters long: {line}",
+                path.join(" "),
+                visible_width(line)
+            ),
+        }
+    }
+}
+
+pub struct Linter {
+    /// maximum number of characters of a help line
+    pub max_line_width: usize,
+    /// whether commands and arguments must be documented
+    pub require_descriptions: bool,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self {
+            max_line_width: 100,
+            require_descriptions: true,
+        }
+    }
+}
+
+impl Linter {
+    /// Checks the whole tree and returns every problem found
+    pub fn lint<'a>(&self, command: &'a Command) -> Vec<Lint<'a>> {
+        let mut lints = Vec::new();
+        self.lint_command(command, &mut Vec::new(), &mut lints);
+        lints
+    }
+
+    fn lint_command<'a>(&self, command: &'a Command, path: &mut Vec<&'a str>, lints: &mut Vec<Lint<'a>>) {
+        path.push(command.value.name());
+        if self.require_descriptions && command.value.description().is_none() {
+            lints.push(Lint::MissingDescription { path: path.clone() });
+        }
+        for line in command.help_with_ancestors(&path[..path.len() - 1]).lines() {
+            if visible_width(line) > self.max_line_width {
+                lints.push(Lint::LineTooLong {
+                    path: path.clone(),
+                    line: line.to_string(),
+                });
+            }
+        }
+        self.lint_group(&command.arguments, path, lints);
+        self.lint_group(&command.global_options, path, lints);
+        if let Some(subcommands) = &command.subcommands {
+            lint_names(subcommands.iter().map(|cmd| cmd.value.name()), path, lints);
+            for subcommand in subcommands {
+                self.lint_command(subcommand, path, lints);
+            }
+        }
+        path.pop();
+    }
+
+    fn lint_group<'a>(&self, group: &'a ArgGroup, path: &mut Vec<&'a str>, lints: &mut Vec<Lint<'a>>) {
+        for arg in group.get_args() {
+            self.lint_arg(arg, path, lints);
+        }
+    }
+
+    fn lint_arg<'a>(&self, arg: &'a Arg, path: &mut Vec<&'a str>, lints: &mut Vec<Lint<'a>>) {
+        path.push(arg.value.name());
+        match &arg.r#type {
+            // a group is only a container, its own arguments are the ones documented
+            ArgType::Group(group) => self.lint_group(group, path, lints),
+            r#type => {
+                if self.require_descriptions && arg.value.description().is_none() {
+                    lints.push(Lint::MissingDescription { path: path.clone() });
+                }
+                if let ArgType::Choices(Choices(choices)) | ArgType::Commands(Commands(choices)) = r#type {
+                    lint_names(choices.iter().map(|arg| arg.value.name()), path, lints);
+                    for choice in choices {
+                        self.lint_arg(choice, path, lints);
+                    }
+                }
+            }
+        }
+        path.pop();
+    }
+}
+
+/// Looks for duplicated and unreachable names among siblings
+fn lint_names<'a>(
+    names: impl Iterator<Item = &'a str>,
+    path: &[&'a str],
+    lints: &mut Vec<Lint<'a>>,
+) {
+    let mut previous: Vec<&'a str> = Vec::new();
+    for name in names {
+        if previous.contains(&name) {
+            lints.push(Lint::DuplicateName {
+                path: path.to_vec(),
+                name,
+            });
+        } else if previous.iter().any(|other| other.eq_ignore_ascii_case(name)) {
+            lints.push(Lint::UnreachableVariant {
+                path: path.to_vec(),
+                name,
+            });
+        }
+        previous.push(name);
+    }
+}
+
+/// Checks the whole tree with the default configuration
+pub fn lint(command: &Command) -> Vec<Lint<'_>> {
+    Linter::default().lint(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn documented(name: &'static str) -> Arg {
+        Arg::new(name, Some("documented"))
+    }
+
+    #[test]
+    fn it_should_accept_a_clean_tree() {
+        let mut command = Command::new("app", Some("an application"));
+        command.set_arguments(vec![documented("file")]);
+        command.set_subcommands(vec![Command::new("run", Some("runs"))]);
+        assert_eq!(lint(&command), vec![]);
+    }
+
+    #[test]
+    fn it_should_report_every_problem() {
+        let mut command = Command::new("app", Some("an application"));
+        command.set_arguments(vec![Arg::with_type(
+            "mode",
+            Some("mode"),
+            ArgType::Choices(Choices(vec![documented("fast"), documented("Fast"), Arg::new("slow", None)])),
+        )]);
+        command.set_subcommands(vec![
+            Command::new("run", Some("runs")),
+            Command::new("run", "x".repeat(120)),
+        ]);
+        let lints = lint(&command);
+        assert_eq!(
+            lints,
+            vec![
+                Lint::LineTooLong {
+                    path: vec!["app"],
+                    line: format!("  run     {}", "x".repeat(120))
+                },
+                Lint::UnreachableVariant {
+                    path: vec!["app", "mode"],
+                    name: "Fast"
+                },
+                Lint::MissingDescription {
+                    path: vec!["app", "mode", "slow"]
+                },
+                Lint::DuplicateName {
+                    path: vec!["app"],
+                    name: "run"
+                },
+                Lint::LineTooLong {
+                    path: vec!["app", "run"],
+                    line: "x".repeat(120)
+                },
+            ]
+        );
+        assert_eq!(lints[1].to_string(), "app mode: 'Fast' is unreachable, a previous name only differs by case");
+    }
+}
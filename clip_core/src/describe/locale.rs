@@ -0,0 +1,190 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Localization of help messages
+//!
+//! `Command::localize` resolves the texts of a whole command tree through a `Localizer`, the
+//! English text being kept for every message it does not know. Messages are identified by:
+//! - `usage`, `arguments`, `options`, `commands`, `examples` and `notes` for the section titles
+//! - the names of the commands and arguments leading to it, joined by `-`, for a description,
+//!   e.g. `app-remote-add` for the `add` subcommand and `app-remote-add-name` for its argument
+//!   (options without their leading dashes)
+//! - the same identifier followed by `-long` for a whole documentation
+//!
+//! `Catalog` reads such messages from `id = text` lines, the syntax of simple Fluent messages.
+//! With the `fluent` feature, a `fluent_bundle::FluentBundle` is a `Localizer` too, its messages
+//! using the whole Fluent syntax: terms, multiline texts, selectors on the locale..
+//!
+//! ```
+//! use clip_core::describe::command::Command;
+//! use clip_core::describe::locale::Catalog;
+//!
+//! let mut app = Command::new("app", Some("an application"));
+//! app.localize(&Catalog::parse("app = une application\nusage = Utilisation"));
+//! assert_eq!(app.help(), "une application\n\nUtilisation: app\n\n");
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+pub trait Localizer {
+    /// Required method
+    /// Translation of the message `id`, None keeping the English text
+    fn message(&self, id: &str) -> Option<String>;
+}
+
+/// Titles of the help sections, English by default
+#[derive(Debug, Clone, PartialEq)]
+pub struct Titles {
+    pub usage: Cow<'static, str>,
+    pub arguments: Cow<'static, str>,
+    pub options: Cow<'static, str>,
+    pub commands: Cow<'static, str>,
+    pub examples: Cow<'static, str>,
+    pub notes: Cow<'static, str>,
+}
+
+impl Default for Titles {
+    fn default() -> Self {
+        Self {
+            usage: Cow::Borrowed("Usage"),
+            arguments: Cow::Borrowed("Arguments"),
+            options: Cow::Borrowed("Options"),
+            commands: Cow::Borrowed("Commands"),
+            examples: Cow::Borrowed("Examples"),
+            notes: Cow::Borrowed("Notes"),
+        }
+    }
+}
+
+impl Titles {
+    /// Titles translated by `localizer`, English for the ones it does not know
+    pub fn localized(localizer: &impl Localizer) -> Self {
+        let mut titles = Self::default();
+        for (id, title) in [
+            ("usage", &mut titles.usage),
+            ("arguments", &mut titles.arguments),
+            ("options", &mut titles.options),
+            ("commands", &mut titles.commands),
+            ("examples", &mut titles.examples),
+            ("notes", &mut titles.notes),
+        ] {
+            if let Some(message) = localizer.message(id) {
+                *title = Cow::Owned(message);
+            }
+        }
+        titles
+    }
+}
+
+/// Messages read from `id = text` lines
+///
+/// Empty lines and comments starting with `#` are skipped, as are lines without `=`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Catalog(HashMap<String, String>);
+
+impl Catalog {
+    pub fn parse(source: &str) -> Self {
+        Self(
+            source
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with('#'))
+                .filter_map(|line| line.split_once('='))
+                .map(|(id, text)| (id.trim().to_string(), text.trim().to_string()))
+                .collect(),
+        )
+    }
+}
+
+impl Localizer for Catalog {
+    fn message(&self, id: &str) -> Option<String> {
+        self.0.get(id).cloned()
+    }
+}
+
+impl Localizer for HashMap<String, String> {
+    fn message(&self, id: &str) -> Option<String> {
+        self.get(id).cloned()
+    }
+}
+
+/// Values of the messages of the bundle, None for the ones which cannot be formatted
+///
+/// The messages of help texts take no arguments: `set_use_isolating(false)` keeps the Unicode
+/// isolation marks out of the terminal.
+#[cfg(feature = "fluent")]
+impl<R, M> Localizer for fluent_bundle::bundle::FluentBundle<R, M>
+where
+    R: std::borrow::Borrow<fluent_bundle::FluentResource>,
+    M: fluent_bundle::memoizer::MemoizerKind,
+{
+    fn message(&self, id: &str) -> Option<String> {
+        let pattern = self.get_message(id)?.value()?;
+        let mut errors = Vec::new();
+        let text = self.format_pattern(pattern, None, &mut errors);
+        errors.is_empty().then(|| text.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::arg::Arg;
+    use crate::describe::command::Command;
+
+    #[test]
+    fn it_should_parse_a_catalog() {
+        let catalog = Catalog::parse("# titles\nusage = Utilisation\n\ninvalid line\napp-file = fichier à lire\n");
+        assert_eq!(catalog.message("usage").as_deref(), Some("Utilisation"));
+        assert_eq!(catalog.message("app-file").as_deref(), Some("fichier à lire"));
+        assert_eq!(catalog.message("invalid line"), None);
+    }
+
+    #[cfg(feature = "fluent")]
+    #[test]
+    fn it_should_localize_with_a_fluent_bundle() {
+        use fluent_bundle::{FluentBundle, FluentResource};
+
+        let resource = FluentResource::try_new(String::from(
+            "-brand = Outil\nusage = Utilisation\napp = { -brand } de démonstration\n    sur plusieurs lignes\napp-file = { missing }",
+        ))
+        .unwrap();
+        let mut bundle = FluentBundle::default();
+        bundle.set_use_isolating(false);
+        bundle.add_resource(resource).unwrap();
+        assert_eq!(bundle.message("app").as_deref(), Some("Outil de démonstration\nsur plusieurs lignes"));
+        assert_eq!((bundle.message("-brand"), bundle.message("app-file"), bundle.message("notes")), (None, None, None));
+        let mut app = Command::new("app", None);
+        app.localize(&bundle);
+        assert_eq!(app.help(), "Outil de démonstration\nsur plusieurs lignes\n\nUtilisation: app\n\n");
+    }
+
+    #[test]
+    fn it_should_localize_a_command_tree() {
+        let mut add = Command::new("add", Some("adds a remote"));
+        add.set_arguments(vec![Arg::new("name", Some("remote name"))]);
+        let mut app = Command::new("app", Some("an application"));
+        app.set_global_options(vec![Arg::new("--verbose", Some("log more"))]);
+        app.set_subcommands(vec![add]);
+        app.localize(&Catalog::parse(
+            "usage = Utilisation\noptions = Options globales\ncommands = Commandes\n\
+             app-verbose = affiche plus\napp-add = ajoute un dépôt\napp-add-name = nom du dépôt",
+        ));
+        assert_eq!(
+            app.help(),
+            "an application\n\nUtilisation: app [OPTIONS] [COMMAND] ..\n\nOptions globales:\n  --verbose  affiche plus\n\nCommandes:\n  add     ajoute un dépôt\n"
+        );
+        assert_eq!(
+            app.help_for_path(&["add"]).unwrap(),
+            "ajoute un dépôt\n\nUtilisation: app add <name>\n\nArguments:\n  name    nom du dépôt\n"
+        );
+    }
+}
@@ -1,4 +1,11 @@
 pub mod arg;
+#[cfg(feature = "color")]
+pub mod color;
+pub mod clap;
 pub mod command;
 pub mod formatter;
-mod value;
+pub mod json;
+pub mod lint;
+pub mod locale;
+pub mod render;
+pub mod value;
@@ -0,0 +1,477 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Renderers turning a command tree into a document
+//!
+//! `PlainText` is the terminal help message, `Markdown` a page for a documentation site.
+
+use super::arg::{Arg, ArgGroup, ArgType, Choices, Commands, DetailsFormatter, DisplayOrder, GetArgs};
+use super::command::Command;
+use super::formatter::{fill, pad, start_with, visible_width, written, Formatter};
+
+pub trait Renderer {
+    /// Required method
+    /// Document describing `command`, reached through the `ancestors` commands
+    fn render(&self, command: &Command, ancestors: &[&str]) -> String;
+}
+
+/// Help message displayed in a terminal
+#[derive(Default)]
+pub struct PlainText {
+    /// whole documentation of the command instead of its first paragraph
+    pub long: bool,
+}
+
+impl PlainText {
+    /// Indentation of the sections and of each nesting level, see `Command::set_indent`
+    fn indent(command: &Command) -> String {
+        " ".repeat(command.indent)
+    }
+
+    /// Details of a group in the display order of the command
+    fn group_details(&self, command: &Command, group: &ArgGroup) -> String {
+        ArgGroup::get_details_formatter().fmt(command.display_order.sort(group.get_args()).into_iter(), |arg| {
+            Some(arg.details_indented(&Self::indent(command)))
+        })
+    }
+
+    fn arguments_details(&self, command: &Command) -> Option<String> {
+        (!command.arguments.get_args().is_empty())
+            .then(|| format!("{}:\n{}", command.titles.arguments, start_with(self.group_details(command, &command.arguments), &Self::indent(command))))
+    }
+
+    /// `Options:` section: `-s, --long <VALUE>` columns aligned, followed by the descriptions
+    fn global_options_details(&self, command: &Command) -> Option<String> {
+        let options = command.display_order.sort(command.global_options.get_args());
+        if options.is_empty() {
+            return None;
+        }
+        let any_short = options.iter().any(|option| option.short.is_some());
+        // a repeatable option is followed by `...` and its bounds if any
+        let label = |option: &Arg| match option.arity {
+            Some(arity) => match arity.bounds() {
+                Some(bounds) => format!("{}... {bounds}", option.value.label()),
+                None => format!("{}...", option.value.label()),
+            },
+            None => option.value.label(),
+        };
+        let names: Vec<String> = options
+            .iter()
+            .map(|option| match option.short {
+                Some(short) => format!("-{short}, {}", label(option)),
+                None if any_short => format!("    {}", label(option)),
+                None => label(option),
+            })
+            .collect();
+        let width = names.iter().map(|name| visible_width(name)).max().unwrap_or(0);
+        let lines: String = options
+            .iter()
+            .zip(names)
+            .map(|(option, name)| match option.value.annotations(true) {
+                text if text.is_empty() => format!("{name}\n"),
+                text => format!("{}  {}\n", pad(&name, width), text.join(" ")),
+            })
+            .collect();
+        Some(format!("{}:\n{}", command.titles.options, start_with(lines, &Self::indent(command))))
+    }
+
+    /// `Commands:` section, followed by one section per category in order of appearance
+    fn command_details(&self, command: &Command) -> Option<String> {
+        let commands = command.subcommands.as_ref()?;
+        let mut categories: Vec<Option<&str>> = vec![None];
+        for category in commands.iter().map(|cmd| cmd.category.as_deref()) {
+            if !categories.contains(&category) {
+                categories.push(category);
+            }
+        }
+        let sections: Vec<String> = categories
+            .into_iter()
+            .filter_map(|category| {
+                let mut listed: Vec<&Command> = commands.iter().filter(|cmd| cmd.category.as_deref() == category).collect();
+                if command.display_order == DisplayOrder::Alphabetical {
+                    listed.sort_by_key(|cmd| cmd.value.name().to_lowercase());
+                }
+                (!listed.is_empty() || commands.is_empty()).then(|| {
+                    format!("{}:\n{}", category.unwrap_or(&command.titles.commands), start_with(
+                        Command::get_details_formatter().fmt(listed.into_iter(), |cmd| Some(format!("{:#}\n", cmd.value))), &Self::indent(command)
+                    ))
+                })
+            })
+            .collect();
+        Some(sections.join("\n"))
+    }
+
+    fn examples_details(&self, command: &Command) -> Option<String> {
+        (!command.examples.is_empty())
+            .then(|| format!("{}:\n{}", command.titles.examples, start_with(command.examples.join("\n"), &Self::indent(command))))
+    }
+
+    fn notes_details(&self, command: &Command) -> Option<String> {
+        command.after_help.as_ref().map(|notes| format!("{}:\n{}", command.titles.notes, start_with(notes.to_string(), &Self::indent(command))))
+    }
+
+    /// Sections following the usage line
+    #[cfg(test)]
+    pub(crate) fn details(&self, command: &Command) -> String {
+        written(String::new(), |result| self.write_details(result, command))
+    }
+
+    /// Writes the sections following the usage line, separated by an empty line
+    fn write_details(&self, out: &mut impl std::fmt::Write, command: &Command) -> std::fmt::Result {
+        let sections = [
+            Self::arguments_details,
+            Self::global_options_details,
+            Self::command_details,
+            Self::examples_details,
+            Self::notes_details,
+        ];
+        let mut first = true;
+        for section in sections.into_iter().filter_map(|section| section(self, command)) {
+            if !first {
+                out.write_char('\n')?;
+            }
+            out.write_str(&section)?;
+            first = false;
+        }
+        Ok(())
+    }
+
+    /// Writes the help message of `command` section by section, without building it as a whole
+    pub fn write(&self, out: &mut impl std::fmt::Write, command: &Command, ancestors: &[&str]) -> std::fmt::Result {
+        if let Some(template) = &command.template {
+            return out.write_str(&self.fill_template(template, command, ancestors));
+        }
+        for paragraph in [self.header(command).as_deref(), command.before_help.as_deref(), self.about(command)]
+            .into_iter()
+            .flatten()
+        {
+            write!(out, "{paragraph}\n\n")?;
+        }
+        write!(out, "{}: ", command.titles.usage)?;
+        for name in ancestors {
+            write!(out, "{name} ")?;
+        }
+        write!(out, "{}\n\n", command.summarize())?;
+        self.write_details(out, command)
+    }
+
+    fn usage(&self, command: &Command, ancestors: &[&str]) -> String {
+        let mut usage = String::new();
+        for name in ancestors {
+            usage.push_str(name);
+            usage.push(' ');
+        }
+        usage.push_str(&command.summarize());
+        usage
+    }
+
+    /// `name version` line followed by the authors, when the command has a version or authors
+    fn header(&self, command: &Command) -> Option<String> {
+        let mut lines = Vec::new();
+        if let Some(version) = &command.version {
+            lines.push(format!("{} {version}", command.value.name()));
+        }
+        lines.extend(command.author.as_ref().map(|author| author.to_string()));
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    fn about<'a>(&self, command: &'a Command) -> Option<&'a str> {
+        if self.long {
+            command.value.long_description()
+        } else {
+            command.value.description()
+        }
+    }
+
+    /// Fills a help template, an empty string replacing a missing section
+    fn fill_template(&self, template: &str, command: &Command, ancestors: &[&str]) -> String {
+        fill(
+            template,
+            &[
+                ("version", command.version.as_deref().unwrap_or("")),
+                ("author", command.author.as_deref().unwrap_or("")),
+                ("before_help", command.before_help.as_deref().unwrap_or("")),
+                ("about", self.about(command).unwrap_or("")),
+                ("usage", &self.usage(command, ancestors)),
+                ("arguments", &self.arguments_details(command).unwrap_or_default()),
+                ("global_options", &self.global_options_details(command).unwrap_or_default()),
+                ("options", &self.global_options_details(command).unwrap_or_default()),
+                ("commands", &self.command_details(command).unwrap_or_default()),
+                ("examples", &self.examples_details(command).unwrap_or_default()),
+                ("after_help", &self.notes_details(command).unwrap_or_default()),
+            ],
+        )
+    }
+}
+
+impl Renderer for PlainText {
+    fn render(&self, command: &Command, ancestors: &[&str]) -> String {
+        written(String::new(), |result| self.write(result, command, ancestors))
+    }
+}
+
+/// Documentation page: one section per command, arguments and options as tables
+pub struct Markdown;
+
+impl Markdown {
+    /// Row of the arguments, options and commands tables
+    const ROW: &'static str = "| `{name}` | {description} |\n";
+
+    /// Escapes a table cell
+    fn cell(text: &str) -> String {
+        text.replace('|', "\\|").replace('\n', "<br>")
+    }
+
+    fn row(result: &mut String, arg: &Arg, extra: Option<String>) {
+        let mut description = Self::cell(arg.value.long_description().unwrap_or(""));
+        if let Some(extra) = extra {
+            if !description.is_empty() {
+                description.push(' ');
+            }
+            description.push_str(&extra);
+        }
+        result.push_str(&fill(Self::ROW, &[("name", arg.value.name()), ("description", &description)]));
+    }
+
+    /// Rows of the leaf arguments, choices being listed in their argument row
+    fn rows(result: &mut String, group: &ArgGroup) {
+        for arg in group.get_args() {
+            match &arg.r#type {
+                ArgType::Value => Self::row(result, arg, None),
+                ArgType::Choices(Choices(choices)) | ArgType::Commands(Commands(choices)) => {
+                    let names: Vec<String> = choices.iter().map(|choice| format!("`{}`", choice.value.name())).collect();
+                    Self::row(result, arg, Some(format!("(one of {})", names.join(", "))));
+                }
+                ArgType::Group(group) => Self::rows(result, group),
+            }
+        }
+    }
+
+    fn table(result: &mut String, level: usize, title: &str, rows: impl FnOnce(&mut String)) {
+        result.push_str(&format!("{} {title}\n\n| Name | Description |\n| --- | --- |\n", "#".repeat(level)));
+        rows(result);
+        result.push('\n');
+    }
+
+    fn section(&self, result: &mut String, command: &Command, path: &mut Vec<String>) {
+        path.push(command.value.name().to_string());
+        let level = path.len().min(5);
+        result.push_str(&format!("{} {}\n\n", "#".repeat(level), path.join(" ")));
+        for paragraph in [command.before_help.as_deref(), command.value.long_description()].into_iter().flatten() {
+            result.push_str(&format!("{paragraph}\n\n"));
+        }
+        let ancestors: String = path[..path.len() - 1].iter().map(|name| format!("{name} ")).collect();
+        result.push_str(&format!("```\n{ancestors}{}\n```\n\n", command.summarize()));
+        if !command.arguments.get_args().is_empty() {
+            Self::table(result, level + 1, &command.titles.arguments, |result| Self::rows(result, &command.arguments));
+        }
+        if !command.global_options.get_args().is_empty() {
+            Self::table(result, level + 1, &command.titles.options, |result| Self::rows(result, &command.global_options));
+        }
+        if let Some(commands) = &command.subcommands {
+            Self::table(result, level + 1, &command.titles.commands, |result| {
+                let rows = Formatter {
+                    template: Some(Self::ROW),
+                    ..Default::default()
+                };
+                // writing to a String cannot fail
+                let _ = rows.write_fields(result, commands.iter(), |subcommand| {
+                    Some(vec![
+                        ("name", subcommand.value.name().to_string()),
+                        ("description", Self::cell(subcommand.value.description().unwrap_or(""))),
+                    ])
+                });
+            });
+        }
+        if !command.examples.is_empty() {
+            result.push_str(&format!(
+                "{} {}\n\n```\n{}\n```\n\n",
+                "#".repeat(level + 1),
+                command.titles.examples,
+                command.examples.join("\n")
+            ));
+        }
+        if let Some(notes) = &command.after_help {
+            result.push_str(&format!("{} {}\n\n{notes}\n\n", "#".repeat(level + 1), command.titles.notes));
+        }
+        for subcommand in command.subcommands.iter().flatten() {
+            self.section(result, subcommand, path);
+        }
+        path.pop();
+    }
+}
+
+impl Renderer for Markdown {
+    fn render(&self, command: &Command, ancestors: &[&str]) -> String {
+        let mut result = String::new();
+        self.section(&mut result, command, &mut ancestors.iter().map(|name| name.to_string()).collect());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_render_the_long_description_on_demand() {
+        let mut app = Command::new("app", Some("an application"));
+        app.set_long_description("an application\n\nwhich does a lot");
+        assert_eq!(app.help(), "an application\n\nUsage: app\n\n");
+        assert_eq!(app.long_help(), "an application\n\nwhich does a lot\n\nUsage: app\n\n");
+    }
+
+    #[test]
+    fn it_should_render_extra_sections() {
+        let mut app = Command::new("app", Some("an application"));
+        app.set_before_help("app 1.0");
+        app.set_after_help("See https://example.com/app");
+        app.add_example("app build");
+        app.add_example("app build --release");
+        assert_eq!(
+            app.help(),
+            "app 1.0\n\nan application\n\nUsage: app\n\nExamples:\n  app build\n  app build --release\n\nNotes:\n  See https://example.com/app\n"
+        );
+    }
+
+    #[test]
+    fn it_should_render_the_version_and_authors() {
+        let mut app = Command::new("app", Some("an application"));
+        app.set_version("1.2.0");
+        app.set_author("Jane Doe");
+        assert_eq!(app.help(), "app 1.2.0\nJane Doe\n\nan application\n\nUsage: app\n\n");
+    }
+
+    #[test]
+    fn it_should_group_commands_by_category() {
+        let command = |name, category: Option<&'static str>| {
+            let mut command = Command::new(name, None);
+            if let Some(category) = category {
+                command.set_category(category);
+            }
+            command
+        };
+        let mut app = Command::new("app", None);
+        app.set_subcommands(vec![
+            command("build", Some("Build commands")),
+            command("help", None),
+            command("fetch", Some("Network commands")),
+            command("check", Some("Build commands")),
+        ]);
+        assert_eq!(
+            app.help(),
+            "Usage: app [COMMAND] ..\n\nCommands:\n  help\n\nBuild commands:\n  build\n  check\n\nNetwork commands:\n  fetch\n"
+        );
+    }
+
+    #[test]
+    fn it_should_indent_each_level() {
+        let mut app = Command::new("app", None);
+        app.set_indent(4);
+        app.set_arguments(vec![Arg::with_type(
+            "mode",
+            Some("how to run"),
+            ArgType::Choices(Choices(vec![
+                Arg::with_type("fast", Some("skips checks"), ArgType::Group(ArgGroup(vec![Arg::new("jobs", None)]))),
+                Arg::new("slow", Some("checks everything")),
+            ])),
+        )]);
+        app.add_example("app fast 4");
+        assert_eq!(
+            app.help(),
+            "Usage: app <mode>\n\nArguments:\n    mode    how to run\n        - fast    skips checks\n              jobs\n        - slow    checks everything\n\nExamples:\n    app fast 4\n"
+        );
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn it_should_align_wide_names() {
+        let mut app = Command::new("app", None);
+        app.set_arguments(vec![Arg::new("名前", Some("name"))]);
+        app.set_global_options(vec![
+            Arg::new("--出力", Some("output")).with_value_name("VALUE"),
+            Arg::new("--verbose", Some("log more")),
+        ]);
+        assert_eq!(
+            app.help(),
+            "Usage: app [OPTIONS] <名前>\n\nArguments:\n  名前    name\n\nOptions:\n  --出力 <VALUE>  output\n  --verbose       log more\n"
+        );
+    }
+
+    #[test]
+    fn it_should_fill_the_help_template() {
+        let mut app = Command::new("app", Some("an application"));
+        app.set_arguments(vec![Arg::new("file", Some("file to read"))]);
+        app.set_template("{usage}\n{about}\n\n{arguments}{commands}{unknown}");
+        assert_eq!(app.help(), "app <file>\nan application\n\nArguments:\n  file    file to read\n{unknown}");
+    }
+
+    #[test]
+    fn it_should_render_nested_sections() {
+        let mut add = Command::new("add", Some("adds a remote"));
+        add.set_arguments(vec![
+            Arg::new("name", Some("remote | alias")),
+            Arg::with_type(
+                "mode",
+                None,
+                ArgType::Choices(Choices(vec![Arg::new("fast", None), Arg::new("slow", None)])),
+            ),
+        ]);
+        let mut app = Command::new("app", Some("an application"));
+        app.set_global_options(vec![Arg::new("--verbose", Some("prints more"))]);
+        app.set_subcommands(vec![add]);
+        app.add_example("app add origin fast");
+        assert_eq!(
+            app.to_markdown(),
+            r#"# app
+
+an application
+
+```
+app [OPTIONS] [COMMAND] ..
+```
+
+## Options
+
+| Name | Description |
+| --- | --- |
+| `--verbose` | prints more |
+
+## Commands
+
+| Name | Description |
+| --- | --- |
+| `add` | adds a remote |
+
+## Examples
+
+```
+app add origin fast
+```
+
+## app add
+
+adds a remote
+
+```
+app add <name> <mode>
+```
+
+### Arguments
+
+| Name | Description |
+| --- | --- |
+| `name` | remote \| alias |
+| `mode` | (one of `fast`, `slow`) |
+
+"#
+        );
+    }
+}
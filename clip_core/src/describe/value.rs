@@ -16,18 +16,286 @@
 //You should have received a copy of the GNU General Public License along with this program. If
 //not, see <https://www.gnu.org/licenses/>.
 
-#[derive(Debug, PartialEq)]
+use super::arg::ValueHint;
+use super::formatter::{pad, visible_width};
+use super::locale::Localizer;
+use std::borrow::Cow;
+
+/// Description given to `Command::new` and `Arg::new`: `None`, `Some("..")`, or a text known at
+/// compile time or built at runtime
+pub trait IntoDescription {
+    fn into_description(self) -> Option<Cow<'static, str>>;
+}
+
+impl IntoDescription for Option<&'static str> {
+    fn into_description(self) -> Option<Cow<'static, str>> {
+        self.map(Cow::Borrowed)
+    }
+}
+
+impl IntoDescription for &'static str {
+    fn into_description(self) -> Option<Cow<'static, str>> {
+        Some(Cow::Borrowed(self))
+    }
+}
+
+impl IntoDescription for String {
+    fn into_description(self) -> Option<Cow<'static, str>> {
+        Some(Cow::Owned(self))
+    }
+}
+
+impl IntoDescription for Cow<'static, str> {
+    fn into_description(self) -> Option<Cow<'static, str>> {
+        Some(self)
+    }
+}
+
+/// Texts are borrowed from the binary when known at compile time, e.g. by the derives, and
+/// owned when built at runtime, e.g. from a configuration file
+#[derive(Debug, Clone, PartialEq)]
 pub struct Value<'a> {
-    pub(crate) name: &'a str,
-    pub(crate) description: Option<&'a str>,
+    pub(crate) name: Cow<'a, str>,
+    /// short description, the first paragraph of the documentation
+    pub(crate) description: Option<Cow<'a, str>>,
+    /// whole documentation, only set when longer than the short description
+    pub(crate) long_description: Option<Cow<'a, str>>,
+    /// note telling what to use instead, empty when there is none
+    pub(crate) deprecated: Option<Cow<'a, str>>,
+    /// placeholder of the value in help messages, the name by default
+    pub(crate) value_name: Option<Cow<'a, str>>,
+    /// value used when the argument is missing
+    pub(crate) default: Option<Cow<'a, str>>,
+    /// names of the choices, listed inline when they have no documentation of their own
+    pub(crate) possible_values: Vec<Cow<'a, str>>,
+    /// type of the value, displayed by typed usage lines
+    pub(crate) type_name: Option<Cow<'a, str>>,
+}
+
+impl<'a> Value<'a> {
+    pub(crate) fn new(name: impl Into<Cow<'a, str>>, description: Option<&'a str>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.map(Cow::Borrowed),
+            long_description: None,
+            deprecated: None,
+            value_name: None,
+            default: None,
+            possible_values: Vec::new(),
+            type_name: None,
+        }
+    }
+
+    /// Name of the argument or command, e.g. the keyword of a subcommand
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Short description, the first paragraph of the documentation
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Whole documentation, falling back to the short description
+    pub fn long_description(&self) -> Option<&str> {
+        self.long_description.as_deref().or(self.description.as_deref())
+    }
+
+    /// Replaces the description and the whole documentation by their translations, the messages
+    /// `id` and `id-long`
+    pub(crate) fn localize(&mut self, localizer: &impl Localizer, id: &str) {
+        if let Some(description) = localizer.message(id) {
+            self.description = Some(Cow::Owned(description));
+        }
+        if let Some(long_description) = localizer.message(&format!("{id}-long")) {
+            self.long_description = Some(Cow::Owned(long_description));
+        }
+    }
+
+    /// Placeholder of the value, e.g. `FILE` for a positional value or `--out <FILE>` for an option
+    pub(crate) fn label(&self) -> String {
+        match &self.value_name {
+            Some(value_name) if self.name.starts_with('-') => format!("{} <{value_name}>", self.name),
+            Some(value_name) => value_name.to_string(),
+            None => self.name.to_string(),
+        }
+    }
+}
+
+impl Value<'static> {
+    /// Value known at compile time, e.g. in the `PossibleValues::values` of a type
+    pub const fn keyword(name: &'static str, description: Option<&'static str>) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            description: match description {
+                Some(description) => Some(Cow::Borrowed(description)),
+                None => None,
+            },
+            long_description: None,
+            deprecated: None,
+            value_name: None,
+            default: None,
+            possible_values: Vec::new(),
+            type_name: None,
+        }
+    }
+}
+
+/// Values a type accepts, e.g. the variants of an enum deriving `FromStr`
+///
+/// The derived help messages list them as `[possible values: ..]` and the completions offer them
+/// for the arguments of this type.
+pub trait PossibleValues {
+    /// Required method
+    fn values() -> &'static [Value<'static>];
+
+    /// Whether `value` is one of the values, ignoring the ASCII case as the parsers do
+    fn is_possible(value: &str) -> bool {
+        Self::values().iter().any(|possible| possible.name.eq_ignore_ascii_case(value))
+    }
+}
+
+/// Name standing for the value of an option of a type, e.g. `DURATION` in `--timeout <DURATION>`
+///
+/// The derived help messages use it for the options of this type which have no `#[value_name]`,
+/// instead of `VALUE`.
+pub trait Placeholder {
+    fn placeholder() -> &'static str;
+}
+
+/// Value hint of the arguments of a type, e.g. `Hostname` for a URL
+///
+/// The derived completions use it for the arguments of this type which have no `#[value_hint]`.
+pub trait TypeHint {
+    fn value_hint() -> ValueHint;
+}
+
+/// Gives the `PossibleValues`, the `Placeholder` and the `TypeHint` of a type when it implements
+/// them, used by the derives
+///
+/// `(&Possible::<T>::new()).possible_values()` resolves to `PossibleImplemented` when
+/// `PossibleValues` is implemented, and to `PossibleMissing`, which gives no value, otherwise.
+/// `placeholder()` resolves the same way, giving `VALUE` when `Placeholder` is not implemented,
+/// and `value_hint()`, giving `ValueHint::Unknown` when `TypeHint` is not.
+#[doc(hidden)]
+pub struct Possible<T>(std::marker::PhantomData<T>);
+
+impl<T> Possible<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait PossibleImplemented {
+    fn possible_values(&self) -> &'static [Value<'static>];
+}
+
+impl<T: PossibleValues> PossibleImplemented for Possible<T> {
+    fn possible_values(&self) -> &'static [Value<'static>] {
+        T::values()
+    }
+}
+
+#[doc(hidden)]
+pub trait PossibleMissing {
+    fn possible_values(&self) -> &'static [Value<'static>];
+}
+
+impl<T> PossibleMissing for &Possible<T> {
+    fn possible_values(&self) -> &'static [Value<'static>] {
+        &[]
+    }
+}
+
+#[doc(hidden)]
+pub trait PlaceholderImplemented {
+    fn placeholder(&self) -> &'static str;
+}
+
+impl<T: Placeholder> PlaceholderImplemented for Possible<T> {
+    fn placeholder(&self) -> &'static str {
+        T::placeholder()
+    }
+}
+
+#[doc(hidden)]
+pub trait PlaceholderMissing {
+    fn placeholder(&self) -> &'static str;
+}
+
+impl<T> PlaceholderMissing for &Possible<T> {
+    fn placeholder(&self) -> &'static str {
+        "VALUE"
+    }
+}
+
+#[doc(hidden)]
+pub trait HintImplemented {
+    fn value_hint(&self) -> ValueHint;
+}
+
+impl<T: TypeHint> HintImplemented for Possible<T> {
+    fn value_hint(&self) -> ValueHint {
+        T::value_hint()
+    }
+}
+
+#[doc(hidden)]
+pub trait HintMissing {
+    fn value_hint(&self) -> ValueHint;
+}
+
+impl<T> HintMissing for &Possible<T> {
+    fn value_hint(&self) -> ValueHint {
+        ValueHint::Unknown
+    }
+}
+
+impl Value<'_> {
+    /// Label followed by the description and the annotations, e.g. `[default: ..]`
+    ///
+    /// Possible values can be left out when the choices are listed right after.
+    pub(crate) fn line(&self, possible_values: bool) -> String {
+        self.line_with_label(self.label(), possible_values)
+    }
+
+    /// Same as `line` with another label, e.g. the one of a variadic argument, kept apart from the
+    /// annotations by two spaces at least
+    pub(crate) fn line_with_label(&self, label: String, possible_values: bool) -> String {
+        let text = self.annotations(possible_values);
+        if text.is_empty() {
+            label
+        } else {
+            format!("{}{}", pad(&label, visible_width(&label).max(6) + 2), text.join(" "))
+        }
+    }
+
+    /// Description followed by the annotations, without the label
+    pub(crate) fn annotations(&self, possible_values: bool) -> Vec<String> {
+        let mut text: Vec<String> = self.description.iter().map(|description| description.to_string()).collect();
+        if possible_values && !self.possible_values.is_empty() {
+            text.push(format!("[possible values: {}]", self.possible_values.join(", ")));
+        }
+        if let Some(default) = &self.default {
+            text.push(format!("[default: {default}]"));
+        }
+        match self.deprecated.as_deref() {
+            Some("") => text.push(String::from("[deprecated]")),
+            Some(note) => text.push(format!("[deprecated: {note}]")),
+            None => (),
+        }
+        text
+    }
 }
 
 impl std::fmt::Display for Value<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if f.alternate() && self.description.is_some() {
-            write!(f, "{:8}{}", self.name, self.description.unwrap())
+        if f.alternate() {
+            f.write_str(&self.line(true))
         } else {
-            write!(f, "{}", self.name)
+            f.write_str(&self.label())
         }
     }
 }
@@ -41,44 +309,76 @@ mod tests {
         assert_eq!(
             format!(
                 "{}",
-                Value {
-                    name: "name",
-                    description: Some("description")
-                }
+                Value::new("name", Some("description"))
             ),
             "name"
         );
         assert_eq!(
             format!(
                 "{}",
-                Value {
-                    name: "name",
-                    description: None
-                }
+                Value::new("name", None)
             ),
             "name"
         );
     }
 
+    #[test]
+    fn display_value_name() {
+        let mut value = Value::new("cfg_path", Some("configuration"));
+        value.value_name = Some(Cow::Borrowed("FILE"));
+        assert_eq!(format!("{value:#}"), "FILE    configuration");
+        value.name = Cow::Borrowed("--config");
+        assert_eq!(value.label(), "--config <FILE>");
+    }
+
+    #[test]
+    fn display_possible_values_and_default() {
+        let mut value = Value::new("mode", Some("speed"));
+        value.possible_values = vec![Cow::Borrowed("one"), Cow::Borrowed("two"), Cow::Borrowed("three")];
+        value.default = Some(Cow::Borrowed("two"));
+        assert_eq!(format!("{value:#}"), "mode    speed [possible values: one, two, three] [default: two]");
+    }
+
+    enum Speed {}
+
+    impl PossibleValues for Speed {
+        fn values() -> &'static [Value<'static>] {
+            static VALUES: [Value<'static>; 2] = [Value::keyword("slow", None), Value::keyword("fast", Some("no checks"))];
+            &VALUES
+        }
+    }
+
+    #[test]
+    fn possible_values_of_a_type() {
+        assert!(Speed::is_possible("FAST") && !Speed::is_possible("medium"));
+        assert_eq!(Possible::<Speed>::new().possible_values()[1].description(), Some("no checks"));
+        assert!((&Possible::<u8>::new()).possible_values().is_empty());
+    }
+
+    #[test]
+    fn display_deprecated() {
+        let mut value = Value::new("name", Some("description"));
+        value.deprecated = Some(Cow::Borrowed("use other"));
+        assert_eq!(format!("{value:#}"), "name    description [deprecated: use other]");
+        assert_eq!(format!("{value}"), "name");
+        let mut value = Value::new("name", None);
+        value.deprecated = Some(Cow::Borrowed(""));
+        assert_eq!(format!("{value:#}"), "name    [deprecated]");
+    }
+
     #[test]
     fn display_alternate() {
         assert_eq!(
             format!(
                 "{:#}",
-                Value {
-                    name: "name",
-                    description: Some("description")
-                }
+                Value::new("name", Some("description"))
             ),
             "name    description"
         );
         assert_eq!(
             format!(
                 "{:#}",
-                Value {
-                    name: "name",
-                    description: None
-                }
+                Value::new("name", None)
             ),
             "name"
         );
@@ -0,0 +1,162 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Standard directories of an application, named after its command
+//!
+//! - Linux and other Unix systems follow the XDG base directories: `$XDG_CONFIG_HOME/app`,
+//!   `$XDG_CACHE_HOME/app` and `$XDG_DATA_HOME/app`, by default `~/.config/app`, `~/.cache/app`
+//!   and `~/.local/share/app`. A variable which is not an absolute path is ignored, as the
+//!   specification requires.
+//! - macOS: `~/Library/Application Support/app` for the configuration and data, and
+//!   `~/Library/Caches/app`
+//! - Windows: `%APPDATA%\app` for the configuration and data, and `%LOCALAPPDATA%\app\cache`
+//!
+//! The configuration file found there is read by the layered configuration:
+//!
+//! ```no_run
+//! use clip_core::describe::arg::Arg;
+//! use clip_core::describe::command::Command;
+//! use clip_core::dirs::Dirs;
+//! use clip_core::layers::Layers;
+//!
+//! let mut app = Command::new("app", None);
+//! app.set_global_options(vec![Arg::new("--jobs", None).with_value_name("N")]);
+//! let mut layers = Layers::new().with_env_prefix("APP");
+//! if let Some(dirs) = Dirs::of(&app) {
+//!     layers = layers.with_file(dirs.config_file("config.toml"));
+//! }
+//! let layered = layers.resolve(&app, &["build"]).unwrap();
+//! ```
+
+use crate::describe::command::{AsCommand, Command};
+use std::path::{Path, PathBuf};
+
+/// Platform conventions for the directories
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Platform {
+    Xdg,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    const CURRENT: Platform = if cfg!(target_os = "macos") {
+        Platform::MacOs
+    } else if cfg!(windows) {
+        Platform::Windows
+    } else {
+        Platform::Xdg
+    };
+}
+
+/// Configuration, cache and data directories of an application
+///
+/// They are only resolved, not created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dirs {
+    config: PathBuf,
+    cache: PathBuf,
+    data: PathBuf,
+}
+
+impl Dirs {
+    /// Directories of the command `command`, None when the home directory is unknown
+    pub fn of(command: &Command) -> Option<Self> {
+        Self::named(command.value.name())
+    }
+
+    /// Directories of the command of `C`
+    pub fn of_command<C: AsCommand>() -> Option<Self> {
+        Self::of(&C::command())
+    }
+
+    /// Directories of the application `name`
+    pub fn named(name: &str) -> Option<Self> {
+        Self::resolve_with(name, |variable| std::env::var_os(variable).map(PathBuf::from))
+    }
+
+    /// Same as `named` with the environment variables given by `env`
+    pub fn resolve_with(name: &str, env: impl Fn(&str) -> Option<PathBuf>) -> Option<Self> {
+        resolve(Platform::CURRENT, name, env)
+    }
+
+    pub fn config_dir(&self) -> &Path {
+        &self.config
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data
+    }
+
+    /// Path of the file `file_name` in the configuration directory, e.g. for `Layers::with_file`
+    pub fn config_file(&self, file_name: impl AsRef<Path>) -> PathBuf {
+        self.config.join(file_name)
+    }
+}
+
+fn resolve(platform: Platform, name: &str, env: impl Fn(&str) -> Option<PathBuf>) -> Option<Dirs> {
+    let env = |variable: &str| env(variable).filter(|path| !path.as_os_str().is_empty());
+    match platform {
+        Platform::Xdg => {
+            let home = env("HOME");
+            let base = |variable: &str, default: &str| {
+                env(variable)
+                    .filter(|path| path.is_absolute())
+                    .or_else(|| home.as_ref().map(|home| home.join(default)))
+            };
+            Some(Dirs {
+                config: base("XDG_CONFIG_HOME", ".config")?.join(name),
+                cache: base("XDG_CACHE_HOME", ".cache")?.join(name),
+                data: base("XDG_DATA_HOME", ".local/share")?.join(name),
+            })
+        }
+        Platform::MacOs => {
+            let library = env("HOME")?.join("Library");
+            let support = library.join("Application Support").join(name);
+            Some(Dirs { config: support.clone(), cache: library.join("Caches").join(name), data: support })
+        }
+        Platform::Windows => {
+            let roaming = env("APPDATA")?.join(name);
+            Some(Dirs { config: roaming.clone(), cache: env("LOCALAPPDATA")?.join(name).join("cache"), data: roaming })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(variables: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<PathBuf> + 'a {
+        move |name| variables.iter().find(|(variable, _)| *variable == name).map(|(_, value)| PathBuf::from(value))
+    }
+
+    #[test]
+    fn it_should_follow_the_xdg_variables() {
+        let dirs = resolve(Platform::Xdg, "app", env(&[("HOME", "/home/me"), ("XDG_CACHE_HOME", "/tmp/cache"), ("XDG_DATA_HOME", "relative")]))
+            .unwrap();
+        assert_eq!(dirs.config_file("config.toml"), Path::new("/home/me/.config/app/config.toml"));
+        assert_eq!(dirs.cache_dir(), Path::new("/tmp/cache/app"));
+        assert_eq!(dirs.data_dir(), Path::new("/home/me/.local/share/app"));
+        assert_eq!(resolve(Platform::Xdg, "app", env(&[("XDG_CONFIG_HOME", "/etc/xdg")])), None);
+    }
+
+    #[test]
+    fn it_should_follow_the_platform_conventions() {
+        let dirs = resolve(Platform::MacOs, "app", env(&[("HOME", "/Users/me")])).unwrap();
+        assert_eq!(dirs.config_dir(), Path::new("/Users/me/Library/Application Support/app"));
+        assert_eq!(dirs.cache_dir(), Path::new("/Users/me/Library/Caches/app"));
+        let dirs = resolve(Platform::Windows, "app", env(&[("APPDATA", "/roaming"), ("LOCALAPPDATA", "/local")])).unwrap();
+        assert_eq!((dirs.data_dir(), dirs.cache_dir()), (Path::new("/roaming/app"), Path::new("/local/app/cache")));
+    }
+}
@@ -0,0 +1,554 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Friendly reports of parsing errors
+//!
+//! A `ClipError` keeps the arguments which failed to parse along with the position of the
+//! offending one, so that it can be pointed at within the reconstructed command line:
+//!
+//! ```text
+//! error: invalid value: invalid digit found in string
+//!
+//!   app --jobs many
+//!              ^^^^
+//!
+//! Usage: app [OPTIONS]
+//!
+//! For more information, try '--help'.
+//! ```
+//!
+//! The same parts are given one by one, the way diagnostic reporters such as miette or ariadne
+//! expect them: `source_code` is the command line, `labels` the spans pointed at within it,
+//! `help` a suggestion and `ParsingError::code` a stable identifier of the error. `into_report`
+//! gathers them into an `ErrorReport`, which implements `miette::Diagnostic` with the `miette`
+//! feature:
+//!
+//! ```
+//! use clip_core::describe::command::Command;
+//! use clip_core::error::ClipError;
+//! use clip_core::parser::ParsingError;
+//!
+//! let err = ClipError::new(ParsingError::TooManyArguments, &["extra"], Some(0));
+//! let report = err.into_report(&Command::new("app", None));
+//! assert_eq!((report.source_code.as_str(), report.labels[0].span.clone()), ("app extra", 4..9));
+//! // printed by miette with `eprintln!("{:?}", miette::Report::new(report))`
+//! ```
+
+use crate::describe::arg::{Arg, ArgType, GetArgs};
+use crate::describe::command::{AsCommand, Command};
+use crate::describe::formatter::visible_width;
+use crate::parser::{try_parse_counted, Located, ParsingError, Parsed, TryParse};
+use crate::record::quote;
+use crate::secret::MASK;
+use std::borrow::Cow;
+use std::ffi::OsString;
+
+#[derive(Debug, PartialEq)]
+pub struct ClipError {
+    pub error: ParsingError,
+    pub args: Vec<String>,
+    /// index of the offending argument, `args.len()` when one is missing at the end of the line
+    /// and None when it is not known
+    pub position: Option<usize>,
+}
+
+impl ClipError {
+    pub fn new<S: AsRef<str>>(error: ParsingError, args: &[S], position: Option<usize>) -> Self {
+        Self {
+            error,
+            args: args.iter().map(|arg| arg.as_ref().to_string()).collect(),
+            position,
+        }
+    }
+
+    /// Error message, command line with the offending argument underlined, usage line of
+    /// `command` and a hint to ask for help
+    pub fn render(&self, command: &Command) -> String {
+        let mut result = format!("error: {}\n\n", self.error);
+        if let Some(position) = self.position {
+            let mut line = command.value.name().to_string();
+            let mut offset = visible_width(&line) + 1;
+            let mut marker = 1;
+            for (index, arg) in masked(&self.args, command).iter().enumerate() {
+                let arg = quote(arg);
+                if index < position {
+                    offset += visible_width(&arg) + 1;
+                } else if index == position {
+                    marker = visible_width(&arg).max(1);
+                }
+                line.push(' ');
+                line.push_str(&arg);
+            }
+            result.push_str(&format!("  {line}\n  {}{}\n\n", " ".repeat(offset), "^".repeat(marker)));
+        }
+        result.push_str(&format!(
+            "Usage: {}\n\nFor more information, try '--help'.",
+            command.summarize()
+        ));
+        result
+    }
+
+    /// Command line the labels point into: the name of `command` followed by the arguments,
+    /// quoted when needed and the secret values masked
+    pub fn source_code(&self, command: &Command) -> String {
+        let mut line = command.value.name().to_string();
+        for arg in masked(&self.args, command) {
+            line.push(' ');
+            line.push_str(&quote(&arg));
+        }
+        line
+    }
+
+    /// Span of the offending argument in `source_code`, in bytes, an empty one at the end of the
+    /// line when an argument is missing
+    pub fn labels(&self, command: &Command) -> Vec<Label> {
+        let Some(position) = self.position else {
+            return Vec::new();
+        };
+        let args: Vec<String> = masked(&self.args, command).iter().map(|arg| quote(arg)).collect();
+        let start = command.value.name().len() + args.iter().take(position).map(|arg| arg.len() + 1).sum::<usize>();
+        let span = match args.get(position) {
+            Some(arg) => start + 1..start + 1 + arg.len(),
+            None => start..start,
+        };
+        let text = match &self.error {
+            ParsingError::TooFewArguments | ParsingError::Arity { .. } => "expected more here",
+            ParsingError::BadType(_) => "invalid value",
+            ParsingError::VariantNotFound => "unknown command or value",
+            ParsingError::TooManyArguments => "unexpected argument",
+            ParsingError::Ambiguous { .. } => "ambiguous",
+            ParsingError::InvalidUtf8 => "not unicode",
+            _ => "given here",
+        };
+        vec![Label { span, text: String::from(text) }]
+    }
+
+    /// Suggestion fixing the error, e.g. the name of a command close to a mistyped one
+    pub fn help(&self, command: &Command) -> Option<String> {
+        match &self.error {
+            ParsingError::Requires { requires, .. } => Some(format!("provide '{requires}' as well")),
+            ParsingError::RequiredUnless { argument, unless } => Some(format!("provide '{argument}' or '{unless}'")),
+            ParsingError::Ambiguous { candidates, .. } => Some(format!("write one of {} in full", candidates.join(", "))),
+            ParsingError::VariantNotFound | ParsingError::TooManyArguments => {
+                let given = self.args.get(self.position?)?;
+                let given = given.split_once('=').map_or(given.as_str(), |(name, _)| name);
+                let mut names = Vec::new();
+                known_names(command, &mut names);
+                let closest = names
+                    .into_iter()
+                    .filter(|name| *name != given)
+                    .map(|name| (distance(given, name), name))
+                    .filter(|(distance, name)| *distance <= (name.chars().count() / 3).max(1))
+                    .min()?;
+                Some(format!("a similar name exists: '{}'", closest.1))
+            }
+            _ => None,
+        }
+    }
+
+    /// Parts of the diagnostic gathered for a reporter, see `ErrorReport`
+    pub fn into_report(self, command: &Command) -> ErrorReport {
+        ErrorReport {
+            source_code: self.source_code(command),
+            labels: self.labels(command),
+            help: self.help(command),
+            error: self.error,
+        }
+    }
+}
+
+/// Parsing error with the command line it points into, given to diagnostic reporters
+#[derive(Debug, PartialEq)]
+pub struct ErrorReport {
+    pub error: ParsingError,
+    /// see `ClipError::source_code`
+    pub source_code: String,
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
+}
+
+impl std::fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for ErrorReport {}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for ErrorReport {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(self.error.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.help.as_ref().map(|help| Box::new(help) as Box<dyn std::fmt::Display>)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.source_code)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        Some(Box::new(self.labels.iter().map(|label| {
+            miette::LabeledSpan::new_with_span(Some(label.text.clone()), label.span.clone())
+        })))
+    }
+}
+
+/// Parsing error along with the command it was raised by, see `WithUsage`
+///
+/// Being a plain `std::error::Error`, it converts with `?` into the error types boxing any error,
+/// e.g. `anyhow::Error` or `Box<dyn Error>`, its source being the parsing error.
+#[derive(Debug, PartialEq)]
+pub struct UsageError {
+    pub error: ParsingError,
+    /// names of the command and of the subcommands selected, e.g. `["git", "remote", "add"]`
+    pub path: Vec<String>,
+    /// usage line of the last command of the path
+    pub usage: String,
+}
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid arguments for '{}': {}\n\nUsage: {}", self.path.join(" "), self.error, self.usage)
+    }
+}
+
+impl std::error::Error for UsageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Adds the command path and usage line to a parsing error, instead of formatting it by hand
+///
+/// ```
+/// use clip_core::describe::arg::Arg;
+/// use clip_core::describe::command::{AsCommand, Command};
+/// use clip_core::error::WithUsage;
+/// use clip_core::parser::ParsingError;
+///
+/// struct App;
+///
+/// impl AsCommand for App {
+///     fn command() -> Command {
+///         let mut app = Command::new("app", None);
+///         app.set_arguments(vec![Arg::new("file", None)]);
+///         app
+///     }
+/// }
+///
+/// fn run() -> Result<(), Box<dyn std::error::Error>> {
+///     Err(ParsingError::TooFewArguments).with_usage::<App>()?
+/// }
+///
+/// assert_eq!(run().unwrap_err().to_string(), "invalid arguments for 'app': missing argument\n\nUsage: app <file>");
+/// ```
+pub trait WithUsage<T> {
+    /// Required method
+    fn with_usage<C: AsCommand + 'static>(self) -> Result<T, UsageError>;
+}
+
+impl<T> WithUsage<T> for Result<T, ParsingError> {
+    fn with_usage<C: AsCommand + 'static>(self) -> Result<T, UsageError> {
+        self.map_err(|error| ClipError::new(error, &[] as &[&str], None)).with_usage::<C>()
+    }
+}
+
+/// The subcommands of the path are the ones named by the arguments
+impl<T> WithUsage<T> for Result<T, ClipError> {
+    fn with_usage<C: AsCommand + 'static>(self) -> Result<T, UsageError> {
+        self.map_err(|err| {
+            let mut command = C::command_ref();
+            let mut path = vec![command.value.name().to_string()];
+            for arg in err.args.iter() {
+                let subcommand = command.subcommands.iter().flatten().find(|subcommand| subcommand.value.name().eq_ignore_ascii_case(arg));
+                if let Some(subcommand) = subcommand {
+                    command = subcommand;
+                    path.push(subcommand.value.name().to_string());
+                }
+            }
+            UsageError { error: err.error, path, usage: command.summarize() }
+        })
+    }
+}
+
+/// Part of the command line pointed at by a report, as a miette or ariadne label
+#[derive(Debug, PartialEq)]
+pub struct Label {
+    /// byte range in `ClipError::source_code`
+    pub span: std::ops::Range<usize>,
+    pub text: String,
+}
+
+/// Names of the options, subcommands and choices of the tree
+fn known_names<'a>(command: &'a Command, names: &mut Vec<&'a str>) {
+    fn arg_names<'a>(args: &'a [Arg], names: &mut Vec<&'a str>) {
+        for arg in args {
+            match &arg.r#type {
+                ArgType::Choices(choices) => {
+                    names.extend(choices.get_args().iter().map(|choice| choice.value.name()));
+                    arg_names(choices.get_args(), names);
+                }
+                ArgType::Commands(commands) => {
+                    names.extend(commands.get_args().iter().map(|command| command.value.name()));
+                    arg_names(commands.get_args(), names);
+                }
+                ArgType::Group(group) => arg_names(group.get_args(), names),
+                ArgType::Value => (),
+            }
+        }
+    }
+    names.extend(command.global_options.get_args().iter().map(|option| option.value.name()));
+    arg_names(command.arguments.get_args(), names);
+    for subcommand in command.subcommands.iter().flatten() {
+        names.push(subcommand.value.name());
+        known_names(subcommand, names);
+    }
+}
+
+/// Number of characters to insert, remove or replace to turn `a` into `b`
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a != *b);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Arguments with the values of the secret options of `command` masked
+fn masked<'a>(args: &'a [String], command: &Command) -> Vec<Cow<'a, str>> {
+    let secrets: Vec<&Arg> = command.global_options.get_args().iter().filter(|option| option.secret).collect();
+    let is_secret = |arg: &str| {
+        secrets.iter().any(|option| {
+            option.value.name() == arg || option.short.is_some_and(|short| arg == format!("-{short}"))
+        })
+    };
+    let mut result = Vec::with_capacity(args.len());
+    let mut mask_next = false;
+    for arg in args {
+        if std::mem::take(&mut mask_next) {
+            result.push(Cow::Borrowed(MASK));
+        } else if let Some((name, _)) = arg.split_once('=').filter(|(name, _)| is_secret(name)) {
+            result.push(Cow::Owned(format!("{name}={MASK}")));
+        } else {
+            mask_next = is_secret(arg);
+            result.push(Cow::Borrowed(arg.as_str()));
+        }
+    }
+    result
+}
+
+impl std::fmt::Display for ClipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for ClipError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Same as `parser::parse` but locates the offending argument of an error
+///
+/// The argument taken last by the parser is blamed, the end of the line when a missing one, and
+/// the first field given for a `ParsingError::Invalid` one.
+pub fn parse<'a, T, R>(args: &'a [&'a str], callback: impl FnOnce(T) -> R) -> Result<R, ClipError>
+where
+    T: TryParse<&'a &'a str, Error = ParsingError>,
+{
+    let (error, count) = match try_parse_counted::<_, T, _>(args.iter()) {
+        Ok(Parsed(parsed, mut rest)) => match rest.next() {
+            None => return Ok(callback(parsed)),
+            Some(_) => (ParsingError::TooManyArguments, rest.consumed()),
+        },
+        Err(Located { error, consumed }) => (error, consumed),
+    };
+    Err(locate(error, count, args))
+}
+
+/// Same as `parse` with the arguments of the operating system, the ones which are not unicode
+/// being shown lossily
+pub fn parse_os<'a, T, R>(args: &'a [OsString], callback: impl FnOnce(T) -> R) -> Result<R, ClipError>
+where
+    T: TryParse<&'a OsString, Error = ParsingError>,
+{
+    let (error, count) = match try_parse_counted::<_, T, _>(args.iter()) {
+        Ok(Parsed(parsed, mut rest)) => match rest.next() {
+            None => return Ok(callback(parsed)),
+            Some(_) => (ParsingError::TooManyArguments, rest.consumed()),
+        },
+        Err(Located { error, consumed }) => (error, consumed),
+    };
+    let args: Vec<Cow<'_, str>> = args.iter().map(|arg| arg.to_string_lossy()).collect();
+    Err(locate(error, count, &args))
+}
+
+/// Error located on the offending argument, `count` arguments having been taken
+fn locate<S: AsRef<str>>(error: ParsingError, count: usize, args: &[S]) -> ClipError {
+    let position = match &error {
+        ParsingError::TooFewArguments | ParsingError::Arity { .. } => args.len(),
+        // a broken constraint is blamed on the first of its fields given
+        ParsingError::Invalid { fields, .. } => args
+            .iter()
+            .map(AsRef::as_ref)
+            .position(|arg| fields.contains(&arg.split_once('=').map_or(arg, |(name, _)| name)))
+            .unwrap_or(count.saturating_sub(1)),
+        _ => count.saturating_sub(1),
+    };
+    ClipError::new(error, args, Some(position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Jobs(u8);
+
+    impl<'a> TryParse<&'a &'a str> for Jobs {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a &'a str>>(mut value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            if value.next() != Some(&"--jobs") {
+                return Err(ParsingError::VariantNotFound);
+            }
+            let jobs = value.next().ok_or(ParsingError::TooFewArguments)?;
+            Ok(Parsed(Jobs(jobs.parse().map_err(ParsingError::bad_type)?), value))
+        }
+    }
+
+    fn app() -> Command {
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![Arg::new("--jobs", None)]);
+        app
+    }
+
+    #[test]
+    fn it_should_underline_the_offending_argument() {
+        let err = parse(&["--jobs", "many"], |Jobs(jobs)| jobs).unwrap_err();
+        assert_eq!(err, ClipError::new(ParsingError::bad_type("invalid digit found in string"), &["--jobs", "many"], Some(1)));
+        assert_eq!(
+            err.render(&app()),
+            "error: invalid value: invalid digit found in string\n\n  app --jobs many\n             ^^^^\n\nUsage: app [OPTIONS]\n\nFor more information, try '--help'."
+        );
+    }
+
+    #[test]
+    fn it_should_point_after_the_line_when_an_argument_is_missing() {
+        let err = parse(&["--jobs"], |Jobs(jobs)| jobs).unwrap_err();
+        assert_eq!(err.position, Some(1));
+        assert!(err.render(&app()).contains("  app --jobs\n             ^\n"));
+    }
+
+    #[test]
+    fn it_should_point_at_the_first_leftover_argument() {
+        let err = parse(&["--jobs", "2", "extra arg"], |Jobs(jobs)| jobs).unwrap_err();
+        assert_eq!(err.error, ParsingError::TooManyArguments);
+        assert_eq!(err.position, Some(2));
+        assert!(err.render(&app()).contains("  app --jobs 2 'extra arg'\n               ^^^^^^^^^^^\n"));
+    }
+
+    #[test]
+    fn it_should_skip_the_line_when_the_position_is_unknown() {
+        let err = ClipError::new(ParsingError::VariantNotFound, &["help", "nope"], None);
+        assert_eq!(
+            err.render(&app()),
+            "error: unknown command or value\n\nUsage: app [OPTIONS]\n\nFor more information, try '--help'."
+        );
+        assert_eq!(parse(&["--jobs", "3"], |Jobs(jobs)| jobs), Ok(3));
+    }
+
+    #[test]
+    fn it_should_give_the_parts_of_a_diagnostic() {
+        let mut app = app();
+        app.set_subcommands(vec![Command::new("status", None), Command::new("stash", None)]);
+        let err = ClipError::new(ParsingError::VariantNotFound, &["--jobs", "2", "statsu"], Some(2));
+        assert_eq!(err.source_code(&app), "app --jobs 2 statsu");
+        assert_eq!(err.labels(&app), [Label { span: 13..19, text: String::from("unknown command or value") }]);
+        assert_eq!(&err.source_code(&app)[err.labels(&app)[0].span.clone()], "statsu");
+        assert_eq!(err.help(&app).as_deref(), Some("a similar name exists: 'status'"));
+        let err = ClipError::new(ParsingError::TooManyArguments, &["--job=2"], Some(0));
+        assert_eq!(err.help(&app).as_deref(), Some("a similar name exists: '--jobs'"));
+        let err = ClipError::new(ParsingError::TooFewArguments, &["--jobs"], Some(1));
+        assert_eq!(err.labels(&app)[0].span, 10..10);
+        assert_eq!(ClipError::new(ParsingError::VariantNotFound, &["zzz"], Some(0)).help(&app), None);
+    }
+
+    #[test]
+    fn it_should_gather_a_report() {
+        let mut app = app();
+        app.set_subcommands(vec![Command::new("status", None)]);
+        let report = ClipError::new(ParsingError::VariantNotFound, &["statsu"], Some(0)).into_report(&app);
+        assert_eq!(report.source_code, "app statsu");
+        assert_eq!(report.labels, [Label { span: 4..10, text: String::from("unknown command or value") }]);
+        assert_eq!(report.help.as_deref(), Some("a similar name exists: 'status'"));
+        assert_eq!(report.to_string(), ParsingError::VariantNotFound.to_string());
+    }
+
+    #[cfg(feature = "miette")]
+    #[test]
+    fn it_should_be_a_miette_diagnostic() {
+        use miette::Diagnostic;
+
+        let mut app = app();
+        app.set_subcommands(vec![Command::new("status", None)]);
+        let report = ClipError::new(ParsingError::VariantNotFound, &["--jobs", "2", "statsu"], Some(2)).into_report(&app);
+        assert_eq!(report.code().map(|code| code.to_string()).as_deref(), Some("clip::variant_not_found"));
+        assert_eq!(report.help().map(|help| help.to_string()).as_deref(), Some("a similar name exists: 'status'"));
+        let labels: Vec<_> = report.labels().unwrap().collect();
+        assert_eq!((labels[0].offset(), labels[0].len(), labels[0].label()), (13, 6, Some("unknown command or value")));
+        let span = report.source_code().unwrap().read_span(&labels[0].inner().clone(), 0, 0).unwrap();
+        assert_eq!(span.data(), b"statsu");
+    }
+
+    #[test]
+    fn it_should_add_the_command_path_and_usage() {
+        struct Git;
+
+        impl AsCommand for Git {
+            fn command() -> Command {
+                let mut add = Command::new("add", None);
+                add.set_arguments(vec![Arg::new("name", None)]);
+                let mut remote = Command::new("remote", None);
+                remote.set_subcommands(vec![add]);
+                let mut git = Command::new("git", None);
+                git.set_subcommands(vec![remote]);
+                git
+            }
+        }
+
+        let err = ClipError::new(ParsingError::TooFewArguments, &["remote", "add"], Some(2));
+        let err = Err::<(), _>(err).with_usage::<Git>().unwrap_err();
+        assert_eq!(err.path, ["git", "remote", "add"]);
+        assert_eq!(err.usage, "add <name>");
+        assert_eq!(err.to_string(), "invalid arguments for 'git remote add': missing argument\n\nUsage: add <name>");
+        let boxed: Box<dyn std::error::Error> = err.into();
+        assert_eq!(boxed.source().map(ToString::to_string), Some(ParsingError::TooFewArguments.to_string()));
+    }
+
+    #[test]
+    fn it_should_mask_secret_values() {
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![Arg::new("--password", None).with_short('p').secret()]);
+        let err = ClipError::new(ParsingError::TooManyArguments, &["--password", "hunter2", "-p", "x", "--password=y", "z"], Some(5));
+        let rendered = err.render(&app);
+        assert!(rendered.contains("  app --password *** -p *** --password=*** z\n"), "{rendered}");
+        assert!(!rendered.contains("hunter2"));
+    }
+}
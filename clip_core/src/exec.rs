@@ -0,0 +1,57 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Helpers to spawn other programs from parsed values
+//!
+//! Arguments are handed to the program one by one, there is no shell in between: no quoting,
+//! globbing or variable expansion can happen whatever the values contain.
+
+use crate::parser::ToArgs;
+use std::ffi::OsStr;
+use std::process::Command;
+
+/// Builds a command running `program` with the arguments of `parsed`
+pub fn command_from(program: impl AsRef<OsStr>, parsed: &impl ToArgs) -> Command {
+    let mut command = Command::new(program);
+    command.args(parsed.to_args());
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Copy {
+        from: String,
+        to: String,
+    }
+
+    impl ToArgs for Copy {
+        fn to_args(&self) -> Vec<String> {
+            vec![self.from.clone(), self.to.clone()]
+        }
+    }
+
+    #[test]
+    fn it_should_pass_each_argument_verbatim() {
+        let command = command_from(
+            "cp",
+            &Copy {
+                from: String::from("my file; rm -rf /"),
+                to: String::from("$HOME/*"),
+            },
+        );
+        assert_eq!(command.get_program(), "cp");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            vec!["my file; rm -rf /", "$HOME/*"]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fuzzing of parsers with arbitrary bytes
+//!
+//! A fuzzer input is read as a command line whose arguments are separated by NUL bytes, the bytes
+//! which are not valid UTF-8 being replaced. `fuzz_parse` feeds it to a parser, which is expected
+//! to fail with an error rather than panic; `corpus_entry` writes such an input from arguments to
+//! seed the corpus. The `fuzz` directory of the repository holds a `cargo fuzz` target.
+//!
+//! ```
+//! use clip_core::fuzz::{arguments, corpus_entry};
+//!
+//! let entry = corpus_entry(&["add", "--force", "file"]);
+//! assert_eq!(entry, b"add\0--force\0file");
+//! assert_eq!(arguments(&entry), ["add", "--force", "file"]);
+//! ```
+
+use crate::parser::{Parsed, TryParse};
+
+/// Arguments of a fuzzer input, separated by NUL bytes
+pub fn arguments(data: &[u8]) -> Vec<String> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+    data.split(|&byte| byte == 0).map(|argument| String::from_utf8_lossy(argument).into_owned()).collect()
+}
+
+/// Fuzzer input made of `args`, the inverse of `arguments`
+pub fn corpus_entry<S: AsRef<str>>(args: &[S]) -> Vec<u8> {
+    args.iter().map(|arg| arg.as_ref().as_bytes()).collect::<Vec<_>>().join(&0)
+}
+
+/// Parses the arguments of a fuzzer input, panicking only if the parser does
+///
+/// The error of a failed parsing is formatted as well, as it would be reported.
+pub fn fuzz_parse<T>(data: &[u8])
+where
+    T: for<'a> TryParse<&'a str>,
+    for<'a> <T as TryParse<&'a str>>::Error: std::fmt::Display,
+{
+    let args = arguments(data);
+    match T::try_parse(args.iter().map(String::as_str)) {
+        Ok(Parsed(_, rest)) => rest.for_each(drop),
+        Err(err) => drop(err.to_string()),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParsingError;
+
+    /// `--count N` followed by names
+    #[derive(Debug)]
+    struct Names(#[allow(dead_code)] u8, #[allow(dead_code)] Vec<String>);
+
+    impl<'a> TryParse<&'a str> for Names {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a str>>(mut value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            if value.next() != Some("--count") {
+                return Err(ParsingError::VariantNotFound);
+            }
+            let count = value.next().ok_or(ParsingError::TooFewArguments)?.parse().map_err(ParsingError::bad_type)?;
+            let names = value.by_ref().take(count as usize).map(String::from).collect();
+            Ok(Parsed(Names(count, names), value))
+        }
+    }
+
+    #[test]
+    fn it_should_split_the_input_on_nul_bytes() {
+        assert!(arguments(b"").is_empty());
+        assert_eq!(arguments(b"a\0\0b\xff"), ["a", "", "b\u{fffd}"]);
+        assert_eq!(corpus_entry(&["--count", "2", "a b"]), b"--count\x002\x00a b");
+        assert_eq!(arguments(&corpus_entry(&["", "x"])), ["", "x"]);
+    }
+
+    #[test]
+    fn it_should_survive_arbitrary_inputs() {
+        // xorshift, for reproducible inputs
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..1000 {
+            let length = (next() % 32) as usize;
+            let data: Vec<u8> = (0..length).map(|_| next() as u8 % 8).map(|byte| b"-c1\0\xffo n"[byte as usize]).collect();
+            fuzz_parse::<Names>(&data);
+        }
+    }
+}
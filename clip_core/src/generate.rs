@@ -0,0 +1,247 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Valid command lines generated from a command tree, for property tests
+//!
+//! `args` walks the `Command` of a type and builds a line it accepts: a subcommand is picked at
+//! random, optional options and trailing optional arguments are given or not, and each value is
+//! made after its type name (`u8`, `bool`, `String`..) or its possible values. Values of an
+//! unknown type are small numbers, which most types parse.
+//!
+//! The lines depend only on the seed of the `Rng`, so that a failing case can be replayed; a
+//! property-testing framework can drive them by generating the seed. With the `proptest` feature,
+//! `strategy` and `command_strategy` do so for proptest, e.g.
+//! `proptest!(|(line in command_strategy::<App>())| { .. })`.
+//!
+//! ```
+//! use clip_core::describe::arg::Arg;
+//! use clip_core::describe::command::Command;
+//! use clip_core::generate::{args, Rng};
+//!
+//! let mut app = Command::new("app", None);
+//! app.set_arguments(vec![Arg::new("count", None).with_type_name("u8")]);
+//! let line = args(&app, &mut Rng::new(7));
+//! assert!(line[0].parse::<u8>().is_ok());
+//! ```
+
+use crate::describe::arg::{Arg, ArgType, GetArgs};
+use crate::describe::command::Command;
+
+/// Words given to text values, none of them looking like an option
+const WORDS: [&str; 8] = ["alpha", "beta", "origin", "main", "file.txt", "with space", "Ünïcode", "42"];
+
+/// Pseudo-random generator (xorshift), reproducible from its seed
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift never leaves 0
+        Self(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Number in `0..bound`, `bound` being at least 1
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+}
+
+/// Arguments accepted by `command`, not including its own name
+pub fn args(command: &Command, rng: &mut Rng) -> Vec<String> {
+    let mut result = Vec::new();
+    push_command(command, rng, &mut result);
+    result
+}
+
+/// proptest strategy of the lines accepted by `command`, generated from a random seed
+#[cfg(feature = "proptest")]
+pub fn strategy(command: &'static Command) -> impl proptest::strategy::Strategy<Value = Vec<String>> {
+    use proptest::strategy::Strategy;
+
+    proptest::arbitrary::any::<u64>().prop_map(move |seed| args(command, &mut Rng::new(seed)))
+}
+
+/// Same as `strategy` with the command of `C`
+#[cfg(feature = "proptest")]
+pub fn command_strategy<C: crate::describe::command::AsCommand + 'static>(
+) -> impl proptest::strategy::Strategy<Value = Vec<String>> {
+    strategy(C::command_ref())
+}
+
+fn push_command(command: &Command, rng: &mut Rng, result: &mut Vec<String>) {
+    for option in command.global_options.get_args() {
+        if !option.is_required() && rng.coin() {
+            continue;
+        }
+        // a repeatable option is given within its bounds
+        let count = match option.arity {
+            Some(arity) => {
+                let min = arity.min.max(1);
+                min + rng.below(arity.max.unwrap_or(min + 2).saturating_sub(min) + 1)
+            }
+            None => 1,
+        };
+        for _ in 0..count {
+            result.push(option.value.name().to_string());
+            if option.value.value_name.is_some() {
+                result.push(value(option, rng));
+            }
+        }
+    }
+    push_arguments(command.arguments.get_args(), rng, result);
+    // the `help` subcommand is answered before parsing, not parsed
+    let subcommands: Vec<&Command> = command
+        .subcommands
+        .iter()
+        .flatten()
+        .filter(|subcommand| !subcommand.value.name().eq_ignore_ascii_case("help"))
+        .collect();
+    if !subcommands.is_empty() {
+        let subcommand = subcommands[rng.below(subcommands.len())];
+        result.push(subcommand.value.name().to_string());
+        push_command(subcommand, rng, result);
+    }
+}
+
+/// Values of positional arguments, the optional ones being left out only when no required one
+/// follows them
+fn push_arguments(arguments: &[Arg], rng: &mut Rng, result: &mut Vec<String>) {
+    for (index, argument) in arguments.iter().enumerate() {
+        let trailing = arguments[index..].iter().all(|argument| !argument.is_required());
+        if trailing && !matches!(argument.r#type, ArgType::Group(_)) && rng.coin() {
+            return;
+        }
+        match &argument.r#type {
+            ArgType::Value => match argument.arity {
+                Some(arity) => {
+                    let count = arity.min + rng.below(arity.max.unwrap_or(arity.min + 3).saturating_sub(arity.min) + 1);
+                    for _ in 0..count {
+                        result.push(value(argument, rng));
+                    }
+                }
+                None => result.push(value(argument, rng)),
+            },
+            ArgType::Group(group) => push_arguments(group.get_args(), rng, result),
+            ArgType::Choices(choices) => push_choice(choices.get_args(), rng, result),
+            ArgType::Commands(commands) => push_choice(commands.get_args(), rng, result),
+        }
+    }
+}
+
+fn push_choice(choices: &[Arg], rng: &mut Rng, result: &mut Vec<String>) {
+    if choices.is_empty() {
+        return;
+    }
+    let choice = &choices[rng.below(choices.len())];
+    result.push(choice.value.name().to_string());
+    if let ArgType::Group(group) = &choice.r#type {
+        push_arguments(group.get_args(), rng, result);
+    }
+}
+
+/// Value of an argument after its possible values or its type
+fn value(argument: &Arg, rng: &mut Rng) -> String {
+    let possible = &argument.value.possible_values;
+    if !possible.is_empty() {
+        return possible[rng.below(possible.len())].to_string();
+    }
+    match argument.value.type_name.as_deref() {
+        Some("bool") => rng.coin().to_string(),
+        Some("char") => char::from(b'a' + rng.below(26) as u8).to_string(),
+        Some("f32" | "f64") => format!("{}.5", rng.below(100)),
+        Some("String" | "PathBuf" | "OsString" | "&str") => WORDS[rng.below(WORDS.len())].to_string(),
+        Some(name) if name.starts_with("&'") => WORDS[rng.below(WORDS.len())].to_string(),
+        Some(name) if name.ends_with("HumanDuration") => format!("{}m{}s", rng.below(60), rng.below(60)),
+        Some(name) if name.ends_with("Timestamp") => format!("2024-{:02}-{:02}", 1 + rng.below(12), 1 + rng.below(28)),
+        _ => rng.below(100).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::arg::{ArgGroup, Choices};
+
+    fn app() -> Command {
+        let mut add = Command::new("add", None);
+        add.set_arguments(vec![
+            Arg::new("name", None).with_type_name("String"),
+            Arg::new("depth", None).with_type_name("u8").optional(),
+        ]);
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![
+            Arg::new("--verbose", None).optional(),
+            Arg::new("--jobs", None).with_value_name("N").with_type_name("u16"),
+        ]);
+        app.set_subcommands(vec![add, Command::new("list", None)]);
+        app
+    }
+
+    #[test]
+    fn it_should_generate_lines_accepted_by_the_tree() {
+        let app = app();
+        for seed in 0..200 {
+            let line = args(&app, &mut Rng::new(seed));
+            let matches = crate::matches::matches(&app, &line).unwrap();
+            assert!(matches.value("--jobs").unwrap().parse::<u16>().is_ok(), "{line:?}");
+            match matches.subcommands()[..] {
+                ["add"] => assert!(matches.contains("name")),
+                ["list"] => assert!(!matches.contains("name")),
+                _ => panic!("{line:?}"),
+            }
+        }
+        assert_eq!(args(&app, &mut Rng::new(3)), args(&app, &mut Rng::new(3)));
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn it_should_give_a_proptest_strategy() {
+        use proptest::test_runner::TestRunner;
+
+        let app: &'static Command = Box::leak(Box::new(app()));
+        TestRunner::deterministic()
+            .run(&strategy(app), |line| {
+                proptest::prop_assert!(crate::matches::matches(app, &line).is_ok(), "{line:?}");
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn it_should_follow_the_arguments_of_a_choice() {
+        let mut app = Command::new("app", None);
+        app.set_arguments(vec![Arg::with_type(
+            "shape",
+            None,
+            ArgType::Choices(Choices(vec![
+                Arg::with_type("circle", None, ArgType::Group(ArgGroup(vec![Arg::new("radius", None).with_type_name("f64")]))),
+                Arg::new("point", None),
+            ])),
+        )]);
+        for seed in 0..50 {
+            match &args(&app, &mut Rng::new(seed))[..] {
+                [circle, radius] if circle == "circle" => assert!(radius.parse::<f64>().is_ok()),
+                [point] => assert_eq!(point, "point"),
+                line => panic!("{line:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Paths matching a pattern given as an argument, e.g. `'src/**/*.rs'`
+//!
+//! Shells expand the patterns they are given unquoted, but not the quoted ones, and not on
+//! Windows: a `GlobPaths` field expands its pattern when parsed, the same way on every platform.
+//!
+//! - `*` matches any sequence of characters within a path component, `?` a single character
+//! - `[abc]`, `[a-z]` match one of the characters, `[!abc]` any other
+//! - `**` as a whole component matches any number of directories
+//! - hidden files, starting with `.`, only match a component which starts with `.` as well
+//!
+//! A pattern matching no path fails to parse, unless the field has `#[clip(allow_no_match)]`.
+//! The paths are sorted.
+//!
+//! ```no_run
+//! use clip_core::glob::GlobPaths;
+//!
+//! let sources: GlobPaths = "src/**/*.rs".parse().unwrap();
+//! for path in &sources {
+//!     println!("{}", path.display());
+//! }
+//! ```
+
+use crate::describe::value::Placeholder;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum GlobError {
+    /// the pattern is malformed, with the reason
+    Pattern(String, &'static str),
+    /// no path matches the pattern
+    NoMatch(String),
+    /// a directory could not be read
+    Io(PathBuf, std::io::Error),
+}
+
+impl std::fmt::Display for GlobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlobError::Pattern(pattern, reason) => write!(f, "invalid pattern '{pattern}': {reason}"),
+            GlobError::NoMatch(pattern) => write!(f, "no path matches '{pattern}'"),
+            GlobError::Io(path, err) => write!(f, "cannot read '{}': {err}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for GlobError {}
+
+/// Pattern with the paths it matched when it was parsed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GlobPaths {
+    pattern: String,
+    paths: Vec<PathBuf>,
+}
+
+impl GlobPaths {
+    /// Paths matching `pattern`, failing with `GlobError::NoMatch` when there are none unless
+    /// `allow_no_match`
+    pub fn expand(pattern: &str, allow_no_match: bool) -> Result<Self, GlobError> {
+        let components: Vec<&str> = pattern.split(is_separator).collect();
+        for component in &components {
+            check(component).map_err(|reason| GlobError::Pattern(pattern.to_string(), reason))?;
+        }
+        let (base, components) = match components.split_first() {
+            // an absolute pattern starts with an empty component
+            Some((&"", rest)) if components.len() > 1 => (PathBuf::from(&pattern[..1]), rest),
+            _ => (PathBuf::new(), &components[..]),
+        };
+        let mut paths = Vec::new();
+        walk(base, components, &mut paths)?;
+        paths.sort();
+        paths.dedup();
+        if paths.is_empty() && !allow_no_match {
+            return Err(GlobError::NoMatch(pattern.to_string()));
+        }
+        Ok(GlobPaths { pattern: pattern.to_string(), paths })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    pub fn into_paths(self) -> Vec<PathBuf> {
+        self.paths
+    }
+}
+
+impl std::str::FromStr for GlobPaths {
+    type Err = GlobError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::expand(s, false)
+    }
+}
+
+/// The pattern, which is expanded again when parsed back
+impl std::fmt::Display for GlobPaths {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl Placeholder for GlobPaths {
+    fn placeholder() -> &'static str {
+        "PATTERN"
+    }
+}
+
+impl IntoIterator for GlobPaths {
+    type Item = PathBuf;
+    type IntoIter = std::vec::IntoIter<PathBuf>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.paths.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a GlobPaths {
+    type Item = &'a PathBuf;
+    type IntoIter = std::slice::Iter<'a, PathBuf>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.paths.iter()
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || std::path::is_separator(c)
+}
+
+fn is_wildcard(component: &str) -> bool {
+    component.contains(['*', '?', '['])
+}
+
+/// Fails when a character class is not closed
+fn check(component: &str) -> Result<(), &'static str> {
+    let mut chars = component.chars();
+    while let Some(c) = chars.next() {
+        if c == '[' {
+            // a `]` right after the opening, negated or not, is part of the class
+            if chars.clone().next() == Some('!') {
+                chars.next();
+            }
+            chars.next();
+            chars.by_ref().find(|&c| c == ']').ok_or("unclosed character class")?;
+        }
+    }
+    Ok(())
+}
+
+/// Pushes the paths under `base` matching the `components` left
+fn walk(base: PathBuf, components: &[&str], paths: &mut Vec<PathBuf>) -> Result<(), GlobError> {
+    let Some((component, rest)) = components.split_first() else {
+        if !base.as_os_str().is_empty() && base.symlink_metadata().is_ok() {
+            paths.push(base);
+        }
+        return Ok(());
+    };
+    match *component {
+        "" | "." => walk(base, rest, paths),
+        "**" => {
+            walk(base.clone(), rest, paths)?;
+            for (name, path) in entries(&base)? {
+                if !name.starts_with('.') && path.is_dir() {
+                    walk(path, components, paths)?;
+                }
+            }
+            Ok(())
+        }
+        component if is_wildcard(component) => {
+            let pattern: Vec<char> = component.chars().collect();
+            for (name, path) in entries(&base)? {
+                if (!name.starts_with('.') || component.starts_with('.')) && matches(&pattern, &name.chars().collect::<Vec<_>>()) {
+                    walk(path, rest, paths)?;
+                }
+            }
+            Ok(())
+        }
+        literal => {
+            let path = base.join(literal);
+            if path.symlink_metadata().is_ok() {
+                walk(path, rest, paths)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Names and paths of the entries of a directory, none when it does not exist
+///
+/// The names are only used for matching: the paths are kept as given by the system, even when
+/// they are not unicode.
+fn entries(dir: &Path) -> Result<Vec<(String, PathBuf)>, GlobError> {
+    let read = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let entries = match std::fs::read_dir(read) {
+        Ok(entries) => entries,
+        Err(err) if matches!(err.kind(), std::io::ErrorKind::NotFound | std::io::ErrorKind::NotADirectory) => return Ok(Vec::new()),
+        Err(err) => return Err(GlobError::Io(read.to_path_buf(), err)),
+    };
+    let mut result = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|err| GlobError::Io(read.to_path_buf(), err))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        // `read_dir(".")` gives `./name`, the pattern did not start with `./`
+        let path = if dir.as_os_str().is_empty() { PathBuf::from(entry.file_name()) } else { entry.path() };
+        result.push((name, path));
+    }
+    Ok(result)
+}
+
+/// Whether `name` matches the wildcards of `pattern`
+///
+/// On a mismatch, only the last `*` seen takes one more character, the earlier ones keeping
+/// theirs: the time taken is at most the product of both lengths, whatever the number of stars.
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // position of the last star in the pattern, and of the first name character it did not take
+    let mut star = None;
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            star = Some((p, n));
+            p += 1;
+            continue;
+        }
+        if let Some(next) = (p < pattern.len()).then(|| matches_one(pattern, p, name[n])).flatten() {
+            p = next;
+            n += 1;
+            continue;
+        }
+        match star {
+            Some((star_p, star_n)) => {
+                star = Some((star_p, star_n + 1));
+                p = star_p + 1;
+                n = star_n + 1;
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Position after the wildcard, class or literal at `p` when it matches `c`
+fn matches_one(pattern: &[char], p: usize, c: char) -> Option<usize> {
+    match pattern[p] {
+        '?' => Some(p + 1),
+        '[' => {
+            let negated = pattern.get(p + 1) == Some(&'!');
+            let start = if negated { p + 2 } else { p + 1 };
+            // `check` made sure the class is closed, a `]` right after the opening being part of it
+            let end = start + 1 + pattern[start + 1..].iter().position(|&c| c == ']').unwrap_or(0);
+            let class = &pattern[start..end];
+            let mut found = false;
+            let mut index = 0;
+            while index < class.len() {
+                if index + 2 < class.len() && class[index + 1] == '-' {
+                    found |= (class[index]..=class[index + 2]).contains(&c);
+                    index += 3;
+                } else {
+                    found |= class[index] == c;
+                    index += 1;
+                }
+            }
+            (found != negated).then_some(end + 1)
+        }
+        literal => (literal == c).then_some(p + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(name: &str, files: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("clip-glob-{}-{name}", std::process::id()));
+        for file in files {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        root
+    }
+
+    fn names(root: &Path, pattern: &str) -> Vec<String> {
+        let paths = GlobPaths::expand(&format!("{}/{pattern}", root.display()), true).unwrap();
+        paths.into_iter().map(|path| path.strip_prefix(root).unwrap().display().to_string()).collect()
+    }
+
+    #[test]
+    fn it_should_match_wildcards() {
+        let chars = |text: &str| text.chars().collect::<Vec<_>>();
+        assert!(matches(&chars("*.rs"), &chars("main.rs")));
+        assert!(!matches(&chars("*.rs"), &chars("main.rs.bak")));
+        assert!(matches(&chars("a?c"), &chars("abc")));
+        assert!(matches(&chars("[a-c]x"), &chars("bx")) && !matches(&chars("[!a-c]x"), &chars("bx")));
+        assert!(matches(&chars("[]]"), &chars("]")));
+        assert!(matches(&chars("*a*b*"), &chars("xaxxbx")) && !matches(&chars("*a*b"), &chars("xaxxbx")));
+        assert!(matches(&chars("**"), &chars("")) && !matches(&chars("?"), &chars("")));
+        // a backtracking matcher tries every split of the name between the stars
+        assert!(!matches(&chars("*a*a*a*a*a*a*a*a*b"), &chars(&"a".repeat(40))));
+        assert!(matches!(GlobPaths::expand("src/[a", true), Err(GlobError::Pattern(_, "unclosed character class"))));
+        assert!(matches!(GlobPaths::expand("[!]", true), Err(GlobError::Pattern(..))));
+    }
+
+    #[test]
+    fn it_should_expand_a_pattern() {
+        let root = tree("expand", &["src/main.rs", "src/lib.rs", "src/parser/mod.rs", "src/.hidden.rs", "README.md"]);
+        assert_eq!(names(&root, "src/*.rs"), ["src/lib.rs", "src/main.rs"]);
+        assert_eq!(names(&root, "**/*.rs"), ["src/lib.rs", "src/main.rs", "src/parser/mod.rs"]);
+        assert_eq!(names(&root, "src/.*"), ["src/.hidden.rs"]);
+        assert_eq!(names(&root, "*/m?d.rs"), Vec::<String>::new());
+        assert_eq!(names(&root, "README.md"), ["README.md"]);
+        let pattern = format!("{}/*.txt", root.display());
+        assert_eq!(
+            pattern.parse::<GlobPaths>().unwrap_err().to_string(),
+            format!("no path matches '{pattern}'")
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn it_should_keep_the_paths_which_are_not_unicode() {
+        use std::os::unix::ffi::OsStrExt;
+        let root = tree("raw", &[]);
+        let raw = root.join(std::ffi::OsStr::from_bytes(b"caf\xe9.txt"));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(&raw, "").unwrap();
+        let paths = GlobPaths::expand(&format!("{}/*.txt", root.display()), false).unwrap();
+        assert_eq!(paths.paths(), [raw]);
+    }
+}
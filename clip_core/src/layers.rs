@@ -0,0 +1,394 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Layered configuration: command line over environment over configuration file
+//!
+//! The options of a command may be given by three layers, each one overriding the previous:
+//! - a configuration file, either TOML (`jobs = 4`) or, for a `.json` file, a JSON object
+//!   (`{"jobs": 4}`), holding top-level keys named after the options (`dry_run` or `dry-run` for
+//!   `--dry-run`)
+//! - environment variables named after the options under a prefix, e.g. `APP_DRY_RUN`
+//! - the command line
+//!
+//! Each option takes its value from the highest layer giving it, the others being dropped, so that
+//! an option given several times (a `Vec` field) does not accumulate the values of all the layers.
+//! The value of the file or the environment is turned into arguments put before the command line
+//! ones. Only the top-level options of the command are concerned; a flag is turned on by `true`,
+//! `yes` or `1` and off by any other value, e.g. `APP_DRY_RUN=false` over `dry_run = true`.
+//!
+//! ```
+//! use clip_core::describe::arg::Arg;
+//! use clip_core::describe::command::Command;
+//! use clip_core::layers::{Layers, Source};
+//!
+//! let mut app = Command::new("app", None);
+//! app.set_global_options(vec![Arg::new("--jobs", None).with_value_name("N")]);
+//! let layered = Layers::new().with_env_prefix("APP").resolve_with(&app, &["build"], |name| {
+//!     (name == "APP_JOBS").then(|| String::from("8"))
+//! }).unwrap();
+//! assert_eq!(layered.args(), ["--jobs", "8", "build"]);
+//! assert_eq!(layered.source("--jobs"), Some(&Source::Env(String::from("APP_JOBS"))));
+//! ```
+
+use crate::describe::arg::{Arg, GetArgs};
+use crate::describe::command::Command;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Layer which supplied the value of an option
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// the configuration file
+    File(PathBuf),
+    /// the environment variable
+    Env(String),
+    Cli,
+}
+
+#[derive(Debug)]
+pub enum LayerError {
+    /// the configuration file could not be read
+    Io(PathBuf, std::io::Error),
+    /// a line of the configuration file is not supported, with its number (starting from 1)
+    Syntax(PathBuf, usize, String),
+    /// a key of the configuration file matches no option
+    UnknownKey(PathBuf, String),
+}
+
+impl std::fmt::Display for LayerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayerError::Io(path, err) => write!(f, "cannot read '{}': {err}", path.display()),
+            LayerError::Syntax(path, line, message) => write!(f, "{}:{line}: {message}", path.display()),
+            LayerError::UnknownKey(path, key) => write!(f, "{}: unknown option '{key}'", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for LayerError {}
+
+/// Where the options are looked for before the command line
+#[derive(Debug, Default)]
+pub struct Layers {
+    file: Option<PathBuf>,
+    env_prefix: Option<String>,
+}
+
+/// Arguments gathered from all the layers, with the layer of each option
+#[derive(Debug, PartialEq)]
+pub struct Layered {
+    args: Vec<String>,
+    sources: HashMap<String, Source>,
+}
+
+impl Layered {
+    /// Arguments to parse, those of the configuration file and the environment coming first
+    pub fn args(&self) -> Vec<&str> {
+        self.args.iter().map(String::as_str).collect()
+    }
+
+    /// Layer which supplied `option` (e.g. `--jobs`), None when it has not been given
+    pub fn source(&self, option: &str) -> Option<&Source> {
+        self.sources.get(option)
+    }
+}
+
+impl Layers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads options from `path`, if the file exists
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// Reads options from the environment variables starting with `prefix` and an underscore
+    pub fn with_env_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.env_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Gathers the options of `command` from all the layers
+    pub fn resolve<S: AsRef<str>>(&self, command: &Command, cli: &[S]) -> Result<Layered, LayerError> {
+        self.resolve_with(command, cli, |name| std::env::var(name).ok())
+    }
+
+    /// Same as `resolve` with the environment variables given by `env`
+    pub fn resolve_with<S: AsRef<str>>(
+        &self,
+        command: &Command,
+        cli: &[S],
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Layered, LayerError> {
+        let options = command.global_options.get_args();
+        let mut sources = HashMap::new();
+        for arg in cli {
+            let arg = arg.as_ref();
+            let name = arg.split_once('=').map_or(arg, |(name, _)| name);
+            if let Some(option) = options
+                .iter()
+                .find(|option| option.value.name() == name || option.short.is_some_and(|short| name == format!("-{short}")))
+            {
+                sources.insert(option.value.name().to_string(), Source::Cli);
+            }
+        }
+        // winning value of each option missing from the command line, a later layer replacing it
+        let mut values: Vec<(&Arg, String, Source)> = Vec::new();
+        let on_cli = |option: &Arg| sources.contains_key(option.value.name());
+        if let Some(path) = &self.file {
+            for (key, value) in read_file(path)? {
+                let option = options
+                    .iter()
+                    .find(|option| option_key(option) == key.replace('-', "_"))
+                    .ok_or_else(|| LayerError::UnknownKey(path.clone(), key))?;
+                if !on_cli(option) {
+                    give(&mut values, option, value, Source::File(path.clone()));
+                }
+            }
+        }
+        if let Some(prefix) = &self.env_prefix {
+            for option in options {
+                let name = format!("{prefix}_{}", option_key(option).to_uppercase());
+                if let Some(value) = env(&name).filter(|_| !on_cli(option)) {
+                    give(&mut values, option, value, Source::Env(name));
+                }
+            }
+        }
+        let mut args = Vec::new();
+        for (option, value, source) in values {
+            let name = option.value.name();
+            if option.value.value_name.is_some() {
+                args.extend([name.to_string(), value]);
+            } else if matches!(value.to_lowercase().as_str(), "true" | "yes" | "1") {
+                args.push(name.to_string());
+            } else {
+                // a flag turned off is not given
+                continue;
+            }
+            sources.insert(name.to_string(), source);
+        }
+        args.extend(cli.iter().map(|arg| arg.as_ref().to_string()));
+        Ok(Layered { args, sources })
+    }
+}
+
+/// Gives `value` to `option`, replacing the value of a lower layer
+fn give<'a>(values: &mut Vec<(&'a Arg, String, Source)>, option: &'a Arg, value: String, source: Source) {
+    match values.iter_mut().find(|(given, ..)| std::ptr::eq(*given, option)) {
+        Some(given) => *given = (option, value, source),
+        None => values.push((option, value, source)),
+    }
+}
+
+/// Name of an option in the configuration file and the environment, e.g. `dry_run`
+fn option_key(option: &Arg) -> String {
+    option.value.name().trim_start_matches('-').replace('-', "_")
+}
+
+/// Top-level keys and values of a configuration file, none when it does not exist
+fn read_file(path: &Path) -> Result<Vec<(String, String)>, LayerError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(LayerError::Io(path.to_path_buf(), err)),
+    };
+    let syntax = |line: usize, message: &str| LayerError::Syntax(path.to_path_buf(), line, message.to_string());
+    if path.extension().is_some_and(|extension| extension == "json") {
+        parse_json(&content).map_err(|message| syntax(1, message))
+    } else {
+        parse_toml(&content).map_err(|(line, message)| syntax(line, message))
+    }
+}
+
+/// `key = value` lines, where a value is a string, a number or a boolean
+fn parse_toml(content: &str) -> Result<Vec<(String, String)>, (usize, &'static str)> {
+    let mut pairs = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            return Err((number + 1, "tables are not supported"));
+        }
+        let (key, value) = line.split_once('=').ok_or((number + 1, "expected `key = value`"))?;
+        let value = value.trim();
+        let value = match value.strip_prefix('"') {
+            Some(quoted) => unescape(quoted).map_err(|message| (number + 1, message))?,
+            None => {
+                // a comment may follow a bare value
+                let value = value.split('#').next().unwrap_or_default().trim();
+                if value.is_empty() || value.starts_with(['[', '{', '\'']) {
+                    return Err((number + 1, "expected a string, a number or a boolean"));
+                }
+                value.to_string()
+            }
+        };
+        pairs.push((key.trim().trim_matches('"').to_string(), value));
+    }
+    Ok(pairs)
+}
+
+/// Content of a double quoted string, `quoted` following its opening quote
+fn unescape(quoted: &str) -> Result<String, &'static str> {
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Ok(value),
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(c @ ('"' | '\\' | '/')) => value.push(c),
+                _ => return Err("unsupported escape sequence"),
+            },
+            c => value.push(c),
+        }
+    }
+    Err("unterminated string")
+}
+
+/// Object whose values are strings, numbers or booleans
+fn parse_json(content: &str) -> Result<Vec<(String, String)>, &'static str> {
+    let mut pairs = Vec::new();
+    let mut rest = content.trim().strip_prefix('{').ok_or("expected an object")?.trim_start();
+    if let Some(end) = rest.strip_prefix('}') {
+        return if end.trim().is_empty() { Ok(pairs) } else { Err("unexpected content after the object") };
+    }
+    loop {
+        let quoted_key = rest.strip_prefix('"').ok_or("expected a key")?;
+        let key = unescape(quoted_key)?;
+        rest = quoted_key[closing_quote(quoted_key)..].trim_start().strip_prefix(':').ok_or("expected `:`")?.trim_start();
+        let value = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let value = unescape(quoted)?;
+                rest = &quoted[closing_quote(quoted)..];
+                value
+            }
+            None => {
+                let end = rest.find([',', '}']).ok_or("unterminated object")?;
+                let value = rest[..end].trim();
+                if value.is_empty() || value.starts_with(['[', '{']) || value == "null" {
+                    return Err("expected a string, a number or a boolean");
+                }
+                rest = &rest[end..];
+                value.to_string()
+            }
+        };
+        pairs.push((key, value));
+        rest = rest.trim_start();
+        if let Some(next) = rest.strip_prefix(',') {
+            rest = next.trim_start();
+        } else if let Some(end) = rest.strip_prefix('}') {
+            return if end.trim().is_empty() { Ok(pairs) } else { Err("unexpected content after the object") };
+        } else {
+            return Err("expected `,` or `}`");
+        }
+    }
+}
+
+/// Position following the closing quote of a string, `quoted` following its opening quote
+fn closing_quote(quoted: &str) -> usize {
+    let mut escaped = false;
+    for (index, c) in quoted.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return index + 1,
+            _ => (),
+        }
+    }
+    quoted.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> Command {
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![
+            Arg::new("--jobs", None).with_value_name("N"),
+            Arg::new("--output", None).with_short('o').with_value_name("DIR"),
+            Arg::new("--dry-run", None),
+        ]);
+        app
+    }
+
+    fn config(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("clip-layers-{}-{name}", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn it_should_override_file_by_env_by_cli() {
+        let path = config("override.toml", "# defaults\njobs = 2\noutput = \"build dir\" \ndry_run = true # safer\n");
+        let env = |name: &str| (name == "APP_JOBS").then(|| String::from("4"));
+        let layered = Layers::new()
+            .with_file(&path)
+            .with_env_prefix("APP")
+            .resolve_with(&app(), &["-o", "out", "build"], env)
+            .unwrap();
+        assert_eq!(layered.args(), ["--jobs", "4", "--dry-run", "-o", "out", "build"]);
+        assert_eq!(layered.source("--jobs"), Some(&Source::Env(String::from("APP_JOBS"))));
+        assert_eq!(layered.source("--output"), Some(&Source::Cli));
+        assert_eq!(layered.source("--dry-run"), Some(&Source::File(path)));
+    }
+
+    #[test]
+    fn it_should_keep_the_value_of_the_highest_layer_only() {
+        let mut app = app();
+        app.set_global_options(vec![Arg::new("--include", None).with_value_name("DIR"), Arg::new("--dry-run", None)]);
+        let path = config("highest.toml", "include = \"lib\"\ndry_run = true\n");
+        let layers = Layers::new().with_file(&path).with_env_prefix("APP");
+        let env = |name: &str| match name {
+            "APP_INCLUDE" => Some(String::from("vendor")),
+            "APP_DRY_RUN" => Some(String::from("false")),
+            _ => None,
+        };
+        let layered = layers.resolve_with(&app, &["build"], env).unwrap();
+        assert_eq!(layered.args(), ["--include", "vendor", "build"]);
+        assert_eq!(layered.source("--dry-run"), None);
+        let layered = layers.resolve_with(&app, &["--include", "src", "--include=tests"], env).unwrap();
+        assert_eq!(layered.args(), ["--include", "src", "--include=tests"]);
+        assert_eq!(layered.source("--include"), Some(&Source::Cli));
+    }
+
+    #[test]
+    fn it_should_read_json_files() {
+        let path = config("read.json", r#"{ "jobs": 3, "dry-run": false, "output": "a \"b\"" }"#);
+        let layered = Layers::new().with_file(&path).resolve_with(&app(), &["build"], |_| None).unwrap();
+        assert_eq!(layered.args(), ["--jobs", "3", "--output", "a \"b\"", "build"]);
+        assert_eq!(layered.source("--dry-run"), None);
+    }
+
+    #[test]
+    fn it_should_ignore_a_missing_file() {
+        let layered = Layers::new()
+            .with_file("/nonexistent/clip/config.toml")
+            .resolve_with(&app(), &["--jobs=1"], |_| None)
+            .unwrap();
+        assert_eq!(layered.args(), ["--jobs=1"]);
+        assert_eq!(layered.source("--jobs"), Some(&Source::Cli));
+    }
+
+    #[test]
+    fn it_should_reject_unsupported_files() {
+        let resolve = |name: &str, content: &str| Layers::new().with_file(config(name, content)).resolve_with(&app(), &[""; 0], |_| None);
+        assert!(matches!(resolve("unknown.toml", "threads = 2"), Err(LayerError::UnknownKey(_, key)) if key == "threads"));
+        assert!(matches!(resolve("table.toml", "jobs = 2\n[build]"), Err(LayerError::Syntax(_, 2, _))));
+        assert!(matches!(resolve("array.toml", "output = [1]"), Err(LayerError::Syntax(_, 1, _))));
+        assert!(matches!(resolve("nested.json", r#"{"jobs": {"n": 1}}"#), Err(LayerError::Syntax(..))));
+        assert!(matches!(resolve("trailing.json", r#"{"jobs": 1"#), Err(LayerError::Syntax(..))));
+    }
+}
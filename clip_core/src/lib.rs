@@ -8,5 +8,34 @@
 //
 // You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod argfile;
+pub mod builder;
+pub mod completion;
+pub mod components;
 pub mod describe;
+pub mod dirs;
+pub mod error;
+pub mod exec;
+pub mod fuzz;
+pub mod generate;
+#[cfg(feature = "glob")]
+pub mod glob;
+pub mod layers;
+pub mod matches;
+pub mod migrate;
+pub mod pager;
 pub mod parser;
+pub mod plugin;
+pub mod record;
+pub mod repl;
+pub mod run;
+pub mod secret;
+pub mod shlex;
+pub mod stream;
+pub mod testing;
+pub mod time;
+pub mod trace;
+#[cfg(feature = "url")]
+pub mod url;
+pub mod winargs;
+pub mod wizard;
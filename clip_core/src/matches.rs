@@ -0,0 +1,279 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Untyped view of a command line, read by walking the `Command` tree
+//!
+//! `matches` tells which subcommands were selected and the raw values given to each argument,
+//! without building any typed value: e.g. to print every option provided, or to inspect the line
+//! before parsing it.
+//!
+//! - options are found by their name (`--jobs`) or short alias (`-j`), taking the next argument or
+//!   the text after `=` when they have a value name, and no value otherwise (flags)
+//! - the options of a command are accepted after its subcommands as well
+//! - positional values are given in order to the arguments of the selected command, a choice
+//!   keyword being followed by the arguments of that choice, then select one of its subcommands
+//! - everything after `--` is positional, as well as every value given to a trailing argument
+//!
+//! ```
+//! use clip_core::describe::arg::Arg;
+//! use clip_core::describe::command::Command;
+//! use clip_core::matches::matches;
+//!
+//! let mut add = Command::new("add", None);
+//! add.set_arguments(vec![Arg::new("name", None)]);
+//! let mut app = Command::new("app", None);
+//! app.set_global_options(vec![Arg::new("--jobs", None).with_value_name("N")]);
+//! app.set_subcommands(vec![add]);
+//!
+//! let matches = matches(&app, &["add", "origin", "--jobs", "4"]).unwrap();
+//! assert_eq!(matches.subcommands(), ["add"]);
+//! assert_eq!(matches.value("--jobs"), Some("4"));
+//! assert_eq!(matches.value("name"), Some("origin"));
+//! ```
+
+use crate::describe::arg::{Arg, ArgType, GetArgs};
+use crate::describe::command::Command;
+use crate::parser::ParsingError;
+use std::collections::VecDeque;
+
+/// Subcommands and raw values of a command line
+#[derive(Debug, Default, PartialEq)]
+pub struct Matches {
+    subcommands: Vec<String>,
+    /// names in the order they were first given, with their number of occurrences and all their values
+    values: Vec<(String, usize, Vec<String>)>,
+}
+
+impl Matches {
+    /// Names of the subcommands selected, from the outermost one
+    pub fn subcommands(&self) -> Vec<&str> {
+        self.subcommands.iter().map(String::as_str).collect()
+    }
+
+    /// Whether the argument or option `name` was given, e.g. a flag
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.iter().any(|(given, _, _)| given == name)
+    }
+
+    /// Every value given to `name`, empty for a flag, None when it was not given
+    pub fn values(&self, name: &str) -> Option<&[String]> {
+        self.values.iter().find(|(given, _, _)| given == name).map(|(_, _, values)| values.as_slice())
+    }
+
+    /// Number of times `name` was given, e.g. `-v -v` counting a flag twice
+    pub fn occurrences(&self, name: &str) -> usize {
+        self.values.iter().find(|(given, _, _)| given == name).map_or(0, |(_, count, _)| *count)
+    }
+
+    /// Last value given to `name`, the one kept by the parser
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values(name)?.last().map(String::as_str)
+    }
+
+    /// Names and values of the arguments and options given, in the order they appeared first
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.values.iter().map(|(name, _, values)| (name.as_str(), values.as_slice()))
+    }
+
+    fn push(&mut self, name: &str, value: Option<String>) {
+        let index = match self.values.iter().position(|(given, _, _)| given == name) {
+            Some(index) => index,
+            None => {
+                self.values.push((name.to_string(), 0, Vec::new()));
+                self.values.len() - 1
+            }
+        };
+        self.values[index].1 += 1;
+        self.values[index].2.extend(value);
+    }
+}
+
+/// Walks `command` along `args`
+///
+/// Fails with `TooManyArguments` on an unknown option or an extra value, `VariantNotFound` on an
+/// unknown subcommand or choice, and `TooFewArguments` on an option missing its value. Missing
+/// positional arguments are left to the typed parsing.
+pub fn matches<S: AsRef<str>>(command: &Command, args: &[S]) -> Result<Matches, ParsingError> {
+    let mut result = Matches::default();
+    let mut current = command;
+    let mut options: Vec<&Arg> = current.global_options.get_args().iter().collect();
+    let mut positionals: VecDeque<&Arg> = current.arguments.get_args().iter().collect();
+    let mut args = args.iter().map(AsRef::as_ref);
+    let mut only_positionals = false;
+    while let Some(arg) = args.next() {
+        only_positionals |= positionals.front().is_some_and(|positional| positional.trailing);
+        if !only_positionals && arg == "--" {
+            only_positionals = true;
+        } else if !only_positionals && arg.starts_with('-') && arg.len() > 1 {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (arg, None),
+            };
+            let option = options
+                .iter()
+                .find(|option| option.value.name() == name || option.short.is_some_and(|short| name == format!("-{short}")))
+                .ok_or(ParsingError::TooManyArguments)?;
+            let value = match (option.value.value_name.is_some(), inline) {
+                (true, Some(value)) => Some(value.to_string()),
+                (true, None) => Some(args.next().ok_or(ParsingError::TooFewArguments)?.to_string()),
+                (false, Some(_)) => return Err(ParsingError::TooManyArguments),
+                (false, None) => None,
+            };
+            result.push(option.value.name(), value);
+        } else if let Some(subcommands) = current.subcommands.as_ref().filter(|_| positionals.is_empty()) {
+            current = subcommands
+                .iter()
+                .find(|subcommand| subcommand.value.name().eq_ignore_ascii_case(arg))
+                .ok_or(ParsingError::VariantNotFound)?;
+            result.subcommands.push(current.value.name().to_string());
+            options.extend(current.global_options.get_args());
+            positionals = current.arguments.get_args().iter().collect();
+        } else {
+            let positional = next_positional(&mut positionals).ok_or(ParsingError::TooManyArguments)?;
+            match &positional.r#type {
+                ArgType::Choices(choices) => {
+                    let choice = find(choices.get_args(), arg)?;
+                    prepend(&mut positionals, choice);
+                }
+                ArgType::Commands(commands) => {
+                    let command = find(commands.get_args(), arg)?;
+                    prepend(&mut positionals, command);
+                }
+                _ => {}
+            }
+            result.push(positional.value.name(), Some(arg.to_string()));
+            // a variadic argument takes the next values as well
+            let taken = result.values(positional.value.name()).map_or(0, <[String]>::len);
+            if positional.arity.is_some_and(|arity| arity.takes_more(taken)) {
+                positionals.push_front(positional);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Next argument taking a value, the arguments of a group being taken in its place
+fn next_positional<'a>(positionals: &mut VecDeque<&'a Arg>) -> Option<&'a Arg> {
+    loop {
+        let positional = positionals.pop_front()?;
+        match &positional.r#type {
+            ArgType::Group(_) => prepend(positionals, positional),
+            _ => return Some(positional),
+        }
+    }
+}
+
+/// Puts the arguments of a group, e.g. the fields of a choice, first
+fn prepend<'a>(positionals: &mut VecDeque<&'a Arg>, arg: &'a Arg) {
+    if let ArgType::Group(group) = &arg.r#type {
+        for inner in group.get_args().iter().rev() {
+            positionals.push_front(inner);
+        }
+    }
+}
+
+/// Choice or command named `keyword`
+fn find<'a>(args: &'a [Arg], keyword: &str) -> Result<&'a Arg, ParsingError> {
+    args.iter()
+        .find(|arg| arg.value.name().eq_ignore_ascii_case(keyword))
+        .ok_or(ParsingError::VariantNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::arg::{ArgGroup, Choices};
+
+    fn app() -> Command {
+        let mut add = Command::new("add", None);
+        add.set_arguments(vec![Arg::new("name", None), Arg::new("url", None)]);
+        add.set_global_options(vec![Arg::new("--fetch", None).with_short('f')]);
+        let mut remote = Command::new("remote", None);
+        remote.set_subcommands(vec![add]);
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![
+            Arg::new("--verbose", None).with_short('v'),
+            Arg::new("--jobs", None).with_value_name("N"),
+        ]);
+        app.set_subcommands(vec![remote]);
+        app
+    }
+
+    #[test]
+    fn it_should_walk_the_subcommands() {
+        let matches = matches(&app(), &["-v", "remote", "add", "origin", "--jobs=2", "-f", "https://host", "--jobs", "4"]).unwrap();
+        assert_eq!(matches.subcommands(), ["remote", "add"]);
+        assert!(matches.contains("--verbose") && matches.contains("--fetch"));
+        assert_eq!(matches.values("--verbose"), Some(&[][..]));
+        assert_eq!(matches.values("--jobs"), Some(&[String::from("2"), String::from("4")][..]));
+        assert_eq!(matches.value("--jobs"), Some("4"));
+        assert_eq!((matches.occurrences("--jobs"), matches.occurrences("--verbose"), matches.occurrences("name")), (2, 1, 1));
+        assert_eq!(matches.value("url"), Some("https://host"));
+        assert_eq!(
+            matches.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["--verbose", "name", "--jobs", "--fetch", "url"]
+        );
+    }
+
+    #[test]
+    fn it_should_follow_the_arguments_of_a_choice() {
+        let mut app = Command::new("app", None);
+        app.set_arguments(vec![
+            Arg::with_type(
+                "shape",
+                None,
+                ArgType::Choices(Choices(vec![
+                    Arg::with_type("circle", None, ArgType::Group(ArgGroup(vec![Arg::new("radius", None)]))),
+                    Arg::new("point", None),
+                ])),
+            ),
+            Arg::new("color", None),
+        ]);
+        let matches = matches(&app, &["circle", "2", "--", "-red"]).unwrap();
+        assert_eq!(matches.value("shape"), Some("circle"));
+        assert_eq!(matches.value("radius"), Some("2"));
+        assert_eq!(matches.value("color"), Some("-red"));
+        let matches = super::matches(&app, &["point", "red"]).unwrap();
+        assert_eq!((matches.value("radius"), matches.value("color")), (None, Some("red")));
+    }
+
+    #[test]
+    fn it_should_give_several_values_to_a_variadic_argument() {
+        let mut app = Command::new("app", None);
+        app.set_arguments(vec![Arg::new("files", None).with_arity(1, Some(2)), Arg::new("output", None)]);
+        let matches = matches(&app, &["a", "b", "out"]).unwrap();
+        assert_eq!(matches.values("files"), Some(&[String::from("a"), String::from("b")][..]));
+        assert_eq!(matches.value("output"), Some("out"));
+    }
+
+    #[test]
+    fn it_should_give_the_remaining_values_to_a_trailing_argument() {
+        let mut app = Command::new("app", None);
+        app.set_global_options(vec![Arg::new("--verbose", None)]);
+        app.set_arguments(vec![Arg::new("program", None), Arg::new("args", None).with_arity(0, None).trailing()]);
+        let matches = matches(&app, &["--verbose", "ls", "--", "-l", "--verbose"]).unwrap();
+        assert_eq!(matches.value("program"), Some("ls"));
+        assert_eq!(matches.values("args"), Some(&[String::from("--"), String::from("-l"), String::from("--verbose")][..]));
+        assert_eq!(matches.values("--verbose"), Some(&[][..]));
+    }
+
+    #[test]
+    fn it_should_reject_unknown_arguments() {
+        assert_eq!(matches(&app(), &["--unknown"]), Err(ParsingError::TooManyArguments));
+        assert_eq!(matches(&app(), &["--fetch", "remote"]), Err(ParsingError::TooManyArguments));
+        assert_eq!(matches(&app(), &["remote", "rename"]), Err(ParsingError::VariantNotFound));
+        assert_eq!(matches(&app(), &["--jobs"]), Err(ParsingError::TooFewArguments));
+        assert_eq!(matches(&app(), &["--verbose=yes"]), Err(ParsingError::TooManyArguments));
+        assert_eq!(
+            matches(&app(), &["remote", "add", "a", "b", "c"]),
+            Err(ParsingError::TooManyArguments)
+        );
+    }
+}
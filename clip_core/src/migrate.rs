@@ -0,0 +1,175 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Compatibility layer between an old and a new grammar
+//!
+//! The old grammar is a second type converted into the new one with `From`. It is only tried when
+//! the new grammar fails, in which case a deprecation notice tells the user how the same
+//! invocation is written with the new syntax.
+
+use crate::parser::{parse, ParsingError, ToArgs, TryParse};
+use crate::record::command_line;
+
+/// An invocation accepted by the old grammar only
+#[derive(Debug, PartialEq)]
+pub struct Deprecation {
+    /// arguments as given by the user
+    pub old: Vec<String>,
+    /// the same invocation with the new syntax
+    pub new: Vec<String>,
+}
+
+impl std::fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "warning: `{}` uses a deprecated syntax, use `{}` instead",
+            command_line(&self.old),
+            command_line(&self.new)
+        )
+    }
+}
+
+/// Parses with the `New` grammar, falling back to the `Old` one
+///
+/// `warn` is called with the deprecation notice when the old grammar has been used. If both fail,
+/// the error of the new grammar is returned.
+pub fn parse_migrating<'a, New, Old, R>(
+    args: &'a [&'a str],
+    warn: impl FnOnce(Deprecation),
+    callback: impl FnOnce(New) -> R,
+) -> Result<R, ParsingError>
+where
+    New: TryParse<&'a &'a str, Error = ParsingError> + From<Old> + ToArgs,
+    Old: TryParse<&'a &'a str, Error = ParsingError>,
+{
+    let new = match parse(args.iter(), |new: New| new) {
+        Ok(new) => new,
+        Err(err) => {
+            let new = parse(args.iter(), |old: Old| New::from(old)).map_err(|_| err)?;
+            warn(Deprecation {
+                old: args.iter().map(|arg| arg.to_string()).collect(),
+                new: new.to_args(),
+            });
+            new
+        }
+    };
+    Ok(callback(new))
+}
+
+/// Same as `parse_migrating`, printing the deprecation notice on the standard error
+pub fn parse_migrating_or_warn<'a, New, Old, R>(
+    args: &'a [&'a str],
+    callback: impl FnOnce(New) -> R,
+) -> Result<R, ParsingError>
+where
+    New: TryParse<&'a &'a str, Error = ParsingError> + From<Old> + ToArgs,
+    Old: TryParse<&'a &'a str, Error = ParsingError>,
+{
+    parse_migrating::<New, Old, R>(args, |deprecation| eprintln!("{deprecation}"), callback)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parsed;
+
+    /// new syntax: `copy <from> <to>`
+    #[derive(Debug, PartialEq)]
+    struct Copy {
+        from: String,
+        to: String,
+    }
+
+    /// old syntax: `cp <to> <from>`
+    struct OldCopy {
+        to: String,
+        from: String,
+    }
+
+    impl From<OldCopy> for Copy {
+        fn from(OldCopy { to, from }: OldCopy) -> Self {
+            Copy { from, to }
+        }
+    }
+
+    impl ToArgs for Copy {
+        fn to_args(&self) -> Vec<String> {
+            vec![String::from("copy"), self.from.clone(), self.to.clone()]
+        }
+    }
+
+    fn keyword_then_two<'a, I: Iterator<Item = &'a &'a str>>(
+        keyword: &str,
+        mut values: I,
+    ) -> Result<(String, String, I), ParsingError> {
+        if *values.next().ok_or(ParsingError::TooFewArguments)? != keyword {
+            return Err(ParsingError::VariantNotFound);
+        }
+        let first = values.next().ok_or(ParsingError::TooFewArguments)?.to_string();
+        let second = values.next().ok_or(ParsingError::TooFewArguments)?.to_string();
+        Ok((first, second, values))
+    }
+
+    impl<'a> TryParse<&'a &'a str> for Copy {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a &'a str>>(value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            let (from, to, rest) = keyword_then_two("copy", value)?;
+            Ok(Parsed(Copy { from, to }, rest))
+        }
+    }
+
+    impl<'a> TryParse<&'a &'a str> for OldCopy {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a &'a str>>(value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            let (to, from, rest) = keyword_then_two("cp", value)?;
+            Ok(Parsed(OldCopy { to, from }, rest))
+        }
+    }
+
+    #[test]
+    fn it_should_not_warn_with_the_new_syntax() {
+        let mut warnings = Vec::new();
+        let result = parse_migrating::<Copy, OldCopy, _>(&["copy", "a", "b"], |d| warnings.push(d), |copy| copy);
+        assert_eq!(
+            result,
+            Ok(Copy {
+                from: String::from("a"),
+                to: String::from("b")
+            })
+        );
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn it_should_migrate_the_old_syntax() {
+        let mut warnings = Vec::new();
+        let result = parse_migrating::<Copy, OldCopy, _>(&["cp", "b", "a"], |d| warnings.push(d), |copy| copy);
+        assert_eq!(
+            result,
+            Ok(Copy {
+                from: String::from("a"),
+                to: String::from("b")
+            })
+        );
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<String>>(),
+            vec!["warning: `cp b a` uses a deprecated syntax, use `copy a b` instead"]
+        );
+    }
+
+    #[test]
+    fn it_should_return_the_new_grammar_error() {
+        let result = parse_migrating::<Copy, OldCopy, _>(&["move", "a", "b"], |_| (), |copy| copy);
+        assert_eq!(result, Err(ParsingError::VariantNotFound));
+    }
+}
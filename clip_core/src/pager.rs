@@ -0,0 +1,81 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Paging of long help messages, like `git --help`
+//!
+//! A text taller than the terminal is piped through `$PAGER` (`less -R` by default, which keeps
+//! the colors) when the standard output is a terminal. Otherwise, or when the pager cannot be
+//! started, it is printed as is.
+
+use crate::describe::formatter::terminal_height;
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Pager used when `PAGER` is not set
+pub const DEFAULT_PAGER: &str = "less -R";
+
+/// Program and arguments of the pager: `PAGER` split at whitespaces, `DEFAULT_PAGER` when unset
+/// or empty
+pub fn pager_command(pager: Option<&str>) -> Vec<String> {
+    let words: Vec<String> = pager.unwrap_or("").split_whitespace().map(String::from).collect();
+    if words.is_empty() {
+        DEFAULT_PAGER.split_whitespace().map(String::from).collect()
+    } else {
+        words
+    }
+}
+
+/// Whether a text should be paged: it does not fit in a terminal of the given height
+pub fn needs_paging(text: &str, height: Option<usize>) -> bool {
+    height.is_some_and(|height| text.lines().count() >= height)
+}
+
+/// Prints `text`, through the pager when the standard output is a terminal too small for it
+pub fn page(text: &str) {
+    if std::io::stdout().is_terminal() && needs_paging(text, terminal_height()) {
+        let pager = std::env::var("PAGER").ok();
+        if spawn(&pager_command(pager.as_deref()), text).is_ok() {
+            return;
+        }
+    }
+    print!("{text}");
+}
+
+fn spawn(command: &[String], text: &str) -> std::io::Result<()> {
+    let (program, args) = command.split_first().ok_or(std::io::ErrorKind::InvalidInput)?;
+    let mut child = Command::new(program).args(args).stdin(Stdio::piped()).spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the user may quit the pager before reading everything
+        match stdin.write_all(text.as_bytes()) {
+            Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => return Err(err),
+            _ => (),
+        }
+    }
+    child.wait().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_split_the_pager_command() {
+        assert_eq!(pager_command(None), vec!["less", "-R"]);
+        assert_eq!(pager_command(Some("  ")), vec!["less", "-R"]);
+        assert_eq!(pager_command(Some("more -d")), vec!["more", "-d"]);
+    }
+
+    #[test]
+    fn it_should_page_texts_taller_than_the_terminal() {
+        assert!(!needs_paging("a\nb\n", None));
+        assert!(!needs_paging("a\nb\n", Some(3)));
+        assert!(needs_paging("a\nb\nc\n", Some(3)));
+    }
+}
@@ -8,15 +8,132 @@
 //
 // You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use std::cell::Cell;
+use std::ffi::{OsStr, OsString};
+use std::rc::Rc;
+
 #[derive(Debug, PartialEq)]
 pub enum ParsingError {
     /// Try to parse an additional argument where there is no more
     TooFewArguments,
-    /// could not parse a value into the expected type
-    BadType,
+    /// could not parse a value into the expected type, with the message of the conversion error
+    BadType(String),
     /// For an enumeration, Error if no value matched the input
     VariantNotFound,
     TooManyArguments,
+    /// `argument` has been provided but `requires` is missing
+    Requires {
+        argument: &'static str,
+        requires: &'static str,
+    },
+    /// neither `argument` nor `unless` has been provided
+    RequiredUnless {
+        argument: &'static str,
+        unless: &'static str,
+    },
+    /// an argument is not valid unicode, which only `PathBuf` and `OsString` fields accept
+    InvalidUtf8,
+    /// the values of `fields` break a constraint checked by `Validate`
+    Invalid {
+        fields: Vec<&'static str>,
+        message: String,
+    },
+    /// the variadic `argument` or the repeated option `argument` was given `found` values, out of
+    /// its bounds
+    Arity {
+        argument: &'static str,
+        min: usize,
+        max: Option<usize>,
+        found: usize,
+    },
+    /// `value` was given several times to `argument`, a set marked `#[deny_duplicates]`
+    DuplicateValue {
+        argument: &'static str,
+        value: String,
+    },
+    /// `keyword` abbreviates several variants, named by `candidates`
+    Ambiguous {
+        keyword: String,
+        candidates: Vec<&'static str>,
+    },
+}
+
+impl std::fmt::Display for ParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParsingError::TooFewArguments => f.write_str("missing argument"),
+            ParsingError::BadType(message) if message.is_empty() => f.write_str("invalid value"),
+            ParsingError::BadType(message) => write!(f, "invalid value: {message}"),
+            ParsingError::VariantNotFound => f.write_str("unknown command or value"),
+            ParsingError::TooManyArguments => f.write_str("unexpected argument"),
+            ParsingError::Requires { argument, requires } => {
+                write!(f, "'{argument}' requires '{requires}'")
+            }
+            ParsingError::RequiredUnless { argument, unless } => {
+                write!(f, "'{argument}' is required unless '{unless}' is provided")
+            }
+            ParsingError::InvalidUtf8 => f.write_str("invalid UTF-8 argument"),
+            ParsingError::Invalid { fields, message } if fields.is_empty() => f.write_str(message),
+            ParsingError::Invalid { fields, message } => {
+                let fields: Vec<String> = fields.iter().map(|field| format!("'{field}'")).collect();
+                write!(f, "{}: {message}", fields.join(", "))
+            }
+            ParsingError::Arity { argument, min, max, found } => {
+                let expected = match max {
+                    Some(max) if max == min => format!("{min}"),
+                    Some(max) => format!("{min} to {max}"),
+                    None => format!("at least {min}"),
+                };
+                write!(f, "'{argument}' expects {expected} values, {found} given")
+            }
+            ParsingError::DuplicateValue { argument, value } => {
+                write!(f, "'{value}' is given more than once to '{argument}'")
+            }
+            ParsingError::Ambiguous { keyword, candidates } => {
+                write!(f, "'{keyword}' is ambiguous, it may be: {}", candidates.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParsingError {}
+
+impl ParsingError {
+    /// Stable identifier of the kind of error, e.g. for diagnostic reporters
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParsingError::TooFewArguments => "clip::too_few_arguments",
+            ParsingError::BadType(_) => "clip::bad_type",
+            ParsingError::VariantNotFound => "clip::variant_not_found",
+            ParsingError::TooManyArguments => "clip::too_many_arguments",
+            ParsingError::Requires { .. } => "clip::requires",
+            ParsingError::RequiredUnless { .. } => "clip::required_unless",
+            ParsingError::InvalidUtf8 => "clip::invalid_utf8",
+            ParsingError::Invalid { .. } => "clip::invalid",
+            ParsingError::Arity { .. } => "clip::arity",
+            ParsingError::DuplicateValue { .. } => "clip::duplicate_value",
+            ParsingError::Ambiguous { .. } => "clip::ambiguous",
+        }
+    }
+
+    /// `BadType` keeping the message of a conversion error, typically a `FromStr::Err`
+    pub fn bad_type(err: impl std::fmt::Display) -> Self {
+        ParsingError::BadType(err.to_string())
+    }
+
+    /// `Invalid` error of a constraint on `fields`, e.g. `&["--start", "--end"]`
+    pub fn invalid(fields: &[&'static str], message: impl std::fmt::Display) -> Self {
+        ParsingError::Invalid { fields: fields.to_vec(), message: message.to_string() }
+    }
+
+    /// Exit code of an invocation failing with this error: `run::DATA_ERROR` for a value which
+    /// could not be converted or is not unicode, `run::USAGE_ERROR` otherwise
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ParsingError::BadType(_) | ParsingError::InvalidUtf8 => crate::run::DATA_ERROR,
+            _ => crate::run::USAGE_ERROR,
+        }
+    }
 }
 
 /// Generic container. No constraint exists for this type expect for its field number.
@@ -24,6 +141,40 @@ pub enum ParsingError {
 #[derive(Debug, PartialEq)]
 pub struct Parsed<T, I>(pub T, pub I);
 
+impl<T, I> Parsed<T, I> {
+    /// Converts the parsed value, keeping the leftovers
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Parsed<U, I> {
+        Parsed(f(self.0), self.1)
+    }
+
+    /// Parses the leftovers after the value, e.g. a body whose type depends on a header
+    ///
+    /// ```
+    /// use clip_core::parser::{Parsed, ParsingError};
+    ///
+    /// let header = Parsed("add", ["1", "2"].into_iter());
+    /// let sum = header.and_then(|command, mut rest| match command {
+    ///     "add" => Ok(Parsed(rest.by_ref().map(|n| n.parse::<u8>().unwrap()).sum::<u8>(), rest)),
+    ///     _ => Err(ParsingError::VariantNotFound),
+    /// });
+    /// assert_eq!(sum.map(|Parsed(sum, _)| sum), Ok(3));
+    /// ```
+    pub fn and_then<U, J, E>(self, f: impl FnOnce(T, I) -> Result<Parsed<U, J>, E>) -> Result<Parsed<U, J>, E> {
+        f(self.0, self.1)
+    }
+
+    /// Calls `f` with a reference to the parsed value, e.g. to log it
+    pub fn inspect(self, f: impl FnOnce(&T)) -> Self {
+        f(&self.0);
+        self
+    }
+
+    /// The parsed value and the leftovers
+    pub fn into_parts(self) -> (T, I) {
+        (self.0, self.1)
+    }
+}
+
 /// Simple and safe type conversions that may fail in a controlled way under some circumstances.
 /// It takes an iterator and return what's left once all values have been parsed
 /// It's very similar to and inspired by TryFrom from the std::convert library. It just is adapted to
@@ -35,6 +186,251 @@ pub trait TryParse<Item, T = Self> {
     fn try_parse<I: Iterator<Item=Item>>(value: I) -> Result<Parsed<T, I>, Self::Error>;
 }
 
+/// An argument as iterated over by the derived parsers: `&str`, `&&str`, `&OsStr` or `&OsString`
+///
+/// The operating system arguments (`std::env::args_os`) may not be unicode: they can still be
+/// parsed into `PathBuf` or `OsString` fields, other fields failing with `InvalidUtf8`.
+pub trait Token<'a>: Copy {
+    /// Required method
+    /// The argument as text, `InvalidUtf8` when it is not unicode
+    fn text(self) -> Result<&'a str, ParsingError>;
+    /// Required method
+    fn os_str(self) -> &'a OsStr;
+}
+
+impl<'a> Token<'a> for &'a str {
+    fn text(self) -> Result<&'a str, ParsingError> {
+        Ok(self)
+    }
+
+    fn os_str(self) -> &'a OsStr {
+        OsStr::new(self)
+    }
+}
+
+impl<'a, 'b: 'a> Token<'a> for &'a &'b str {
+    fn text(self) -> Result<&'a str, ParsingError> {
+        Ok(self)
+    }
+
+    fn os_str(self) -> &'a OsStr {
+        OsStr::new(self)
+    }
+}
+
+impl<'a> Token<'a> for &'a OsStr {
+    fn text(self) -> Result<&'a str, ParsingError> {
+        self.to_str().ok_or(ParsingError::InvalidUtf8)
+    }
+
+    fn os_str(self) -> &'a OsStr {
+        self
+    }
+}
+
+impl<'a> Token<'a> for &'a OsString {
+    fn text(self) -> Result<&'a str, ParsingError> {
+        self.to_str().ok_or(ParsingError::InvalidUtf8)
+    }
+
+    fn os_str(self) -> &'a OsStr {
+        self
+    }
+}
+
+/// Description of a named option, as given to a struct it is flattened into
+#[derive(Debug, PartialEq)]
+pub struct OptionSpec {
+    /// name without the leading dashes nor any prefix
+    pub name: &'static str,
+    /// one letter alias, accepted when the set is flattened without a prefix, several flags being
+    /// bundled as in `-vq`
+    pub short: Option<char>,
+    /// false for a flag
+    pub takes_value: bool,
+    pub description: Option<&'static str>,
+}
+
+/// Parses a value, `-` standing for the content of the standard input as for `#[stdin]` fields
+///
+/// The trailing new lines of the content are removed, as a shell does for a command substitution.
+pub fn stdin_value<T>(value: &str) -> Result<T, ParsingError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display {
+    value_or_read(value, &mut std::io::stdin().lock())
+}
+
+fn value_or_read<T>(value: &str, input: &mut impl std::io::Read) -> Result<T, ParsingError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display {
+    if value != "-" {
+        return value.parse().map_err(ParsingError::bad_type);
+    }
+    let mut content = String::new();
+    input.read_to_string(&mut content).map_err(ParsingError::bad_type)?;
+    content.trim_end_matches(['\n', '\r']).parse().map_err(ParsingError::bad_type)
+}
+
+/// Fails with `BadType` unless `value` is one of `possible`, ignoring the ASCII case, as checked
+/// for the fields marked with `#[possible("a", "b")]`
+pub fn check_possible<'a>(value: impl Token<'a>, possible: &[&str]) -> Result<(), ParsingError> {
+    let value = value.text()?;
+    if possible.iter().any(|possible| possible.eq_ignore_ascii_case(value)) {
+        Ok(())
+    } else {
+        Err(ParsingError::BadType(format!("'{value}' is not one of {}", possible.join(", "))))
+    }
+}
+
+/// State of the filesystem a path argument is checked for, see `check_path`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCheck {
+    FileExists,
+    DirExists,
+    /// the path can be written, or created when it does not exist
+    Writable,
+}
+
+/// Fails with `Invalid` on `argument` unless the path `value` is in the state of `check`, as
+/// checked for the fields marked `#[file_exists]`, `#[dir_exists]` or `#[writable]`
+pub fn check_path<'a>(value: impl Token<'a>, argument: &'static str, check: PathCheck) -> Result<(), ParsingError> {
+    let path = std::path::Path::new(value.os_str());
+    let invalid = |message: String| Err(ParsingError::invalid(&[argument], message));
+    let metadata = std::fs::metadata(path);
+    match (check, metadata) {
+        (PathCheck::FileExists, Ok(metadata)) if metadata.is_dir() => invalid(format!("'{}' is a directory, not a file", path.display())),
+        (PathCheck::FileExists, Err(err)) => invalid(format!("cannot access file '{}': {}", path.display(), io_reason(&err))),
+        (PathCheck::DirExists, Ok(metadata)) if !metadata.is_dir() => invalid(format!("'{}' is not a directory", path.display())),
+        (PathCheck::DirExists, Err(err)) => invalid(format!("cannot access directory '{}': {}", path.display(), io_reason(&err))),
+        (PathCheck::Writable, Ok(metadata)) if metadata.is_dir() && metadata.permissions().readonly() => {
+            invalid(format!("directory '{}' is read-only", path.display()))
+        }
+        (PathCheck::Writable, Ok(metadata)) if !metadata.is_dir() => match std::fs::OpenOptions::new().append(true).open(path) {
+            Ok(_) => Ok(()),
+            Err(err) => invalid(format!("cannot write '{}': {}", path.display(), io_reason(&err))),
+        },
+        (PathCheck::Writable, Err(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+            // the file is to be created in its directory
+            let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+            match std::fs::metadata(parent) {
+                Ok(metadata) if metadata.is_dir() && !metadata.permissions().readonly() => Ok(()),
+                Ok(metadata) if metadata.is_dir() => {
+                    invalid(format!("cannot create '{}': directory '{}' is read-only", path.display(), parent.display()))
+                }
+                Ok(_) => invalid(format!("cannot create '{}': '{}' is not a directory", path.display(), parent.display())),
+                Err(err) => invalid(format!("cannot create '{}': {}", path.display(), io_reason(&err))),
+            }
+        }
+        (PathCheck::Writable, Err(err)) => invalid(format!("cannot write '{}': {}", path.display(), io_reason(&err))),
+        _ => Ok(()),
+    }
+}
+
+/// Reason of an I/O error without the OS error code, e.g. `no such file or directory`
+fn io_reason(err: &std::io::Error) -> String {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => String::from("no such file or directory"),
+        std::io::ErrorKind::PermissionDenied => String::from("permission denied"),
+        _ => err.to_string(),
+    }
+}
+
+/// Prints the warning of a deprecated argument or command used on the line
+///
+/// The generated parsers call it once per argument, however many times it is parsed.
+pub fn warn_deprecated(name: &str, note: &str) {
+    if note.is_empty() {
+        eprintln!("warning: '{name}' is deprecated");
+    } else {
+        eprintln!("warning: '{name}' is deprecated: {note}");
+    }
+}
+
+/// Variant abbreviated by `keyword`, for `#[clip(abbreviations)]`
+///
+/// `keywords` pairs the lowercase keywords of an enum with the index of their variant. None when
+/// `keyword` starts no keyword, `Ambiguous` when it starts the keywords of several variants, the
+/// first keyword of each being listed.
+pub fn resolve_abbreviation(keyword: &str, keywords: &[(&'static str, usize)]) -> Result<Option<usize>, ParsingError> {
+    if keyword.is_empty() {
+        return Ok(None);
+    }
+    let mut candidates: Vec<(&'static str, usize)> = Vec::new();
+    for &(candidate, variant) in keywords {
+        let starts = candidate.get(..keyword.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(keyword));
+        if starts && !candidates.iter().any(|&(_, found)| found == variant) {
+            candidates.push((candidate, variant));
+        }
+    }
+    match candidates[..] {
+        [] => Ok(None),
+        [(_, variant)] => Ok(Some(variant)),
+        _ => Err(ParsingError::Ambiguous {
+            keyword: keyword.to_string(),
+            candidates: candidates.into_iter().map(|(candidate, _)| candidate).collect(),
+        }),
+    }
+}
+
+/// A set of named options which can be flattened into another struct, possibly under a prefix
+/// avoiding name collisions (`--db-host` instead of `--host`)
+pub trait Flatten: Sized {
+    /// Required method
+    /// Options accepted by the set
+    fn options() -> &'static [OptionSpec];
+    /// Required method
+    /// Builds the value from the options found on the line, with their value if they take one
+    fn build(found: Vec<(&'static str, Option<String>)>) -> Result<Self, ParsingError>;
+}
+
+/// Constraints spanning several fields, checked once a value is parsed
+///
+/// The TryParse derive calls `validate` on the parsed value of any type implementing it, its error
+/// being the error of the parsing; `ParsingError::invalid` names the fields at fault.
+pub trait Validate {
+    /// Required method
+    fn validate(&self) -> Result<(), ParsingError>;
+}
+
+/// Calls `Validate::validate` when the type implements it, used by the TryParse derive
+///
+/// `(&Validation(&value)).validate_parsed()` resolves to `ValidateImplemented` when `Validate`
+/// is implemented, and to `ValidateMissing`, which accepts any value, otherwise.
+#[doc(hidden)]
+pub struct Validation<'v, T>(pub &'v T);
+
+#[doc(hidden)]
+pub trait ValidateImplemented {
+    fn validate_parsed(&self) -> Result<(), ParsingError>;
+}
+
+impl<T: Validate> ValidateImplemented for Validation<'_, T> {
+    fn validate_parsed(&self) -> Result<(), ParsingError> {
+        self.0.validate()
+    }
+}
+
+#[doc(hidden)]
+pub trait ValidateMissing {
+    fn validate_parsed(&self) -> Result<(), ParsingError>;
+}
+
+impl<T> ValidateMissing for &Validation<'_, T> {
+    fn validate_parsed(&self) -> Result<(), ParsingError> {
+        Ok(())
+    }
+}
+
+/// Inverse of `TryParse`: returns the canonical arguments representing a value
+///
+/// Parsing the returned arguments is expected to give back an equal value.
+pub trait ToArgs {
+    /// Required method
+    fn to_args(&self) -> Vec<String>;
+}
+
 pub fn parse<'a, T, R>(args: impl Iterator<Item = &'a &'a str>, callback: impl FnOnce(T) -> R) -> Result<R, ParsingError>
     where
         T: TryParse<&'a &'a str, Error = ParsingError> {
@@ -45,3 +441,194 @@ pub fn parse<'a, T, R>(args: impl Iterator<Item = &'a &'a str>, callback: impl F
         Err(err) => Err(err)
     }
 }
+
+/// Same as `parse` for a fallible callback, a parsing error being converted into its error type
+pub fn try_parse_and_run<'a, T, R, E>(
+    args: impl Iterator<Item = &'a &'a str>,
+    callback: impl FnOnce(T) -> Result<R, E>,
+) -> Result<R, E>
+    where
+        T: TryParse<&'a &'a str, Error = ParsingError>,
+        E: From<ParsingError> {
+    parse(args, callback)?
+}
+
+/// Same as `parse` for operating system arguments, which may not be unicode
+pub fn parse_os<'a, T, R>(args: &'a [OsString], callback: impl FnOnce(T) -> R) -> Result<R, ParsingError>
+    where
+        T: TryParse<&'a OsString, Error = ParsingError> {
+    let Parsed(parsed, mut rest) = T::try_parse(args.iter())?;
+    match rest.next() {
+        Some(_) => Err(ParsingError::TooManyArguments),
+        None => Ok(callback(parsed)),
+    }
+}
+
+/// Parses `T` from the front of the arguments and returns it with the ones left, which are not
+/// an error as with `parse`
+///
+/// Meant for tools forwarding the trailing arguments to a child process.
+pub fn parse_partial<Item, T>(args: impl Iterator<Item = Item>) -> Result<(T, Vec<Item>), T::Error>
+    where
+        T: TryParse<Item> {
+    let Parsed(parsed, rest) = T::try_parse(args)?;
+    Ok((parsed, rest.collect()))
+}
+
+/// Iterator counting the arguments taken from it, see `try_parse_counted`
+#[derive(Debug)]
+pub struct Counted<I> {
+    inner: I,
+    // shared with `try_parse_counted`, which reads it once the parser dropped the iterator
+    consumed: Rc<Cell<usize>>,
+}
+
+impl<I> Counted<I> {
+    pub fn new(inner: I) -> Self {
+        Self { inner, consumed: Rc::default() }
+    }
+
+    /// Number of arguments taken so far, i.e. the index of the next one in the original arguments
+    pub fn consumed(&self) -> usize {
+        self.consumed.get()
+    }
+}
+
+impl<I: Iterator> Iterator for Counted<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next();
+        if item.is_some() {
+            self.consumed.set(self.consumed.get() + 1);
+        }
+        item
+    }
+}
+
+/// Error of a parser along with the number of arguments it had taken
+#[derive(Debug, PartialEq)]
+pub struct Located<E> {
+    pub error: E,
+    /// arguments taken when the error was raised, the last one being usually the offending one
+    pub consumed: usize,
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for Located<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at argument {}", self.error, self.consumed.max(1))
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for Located<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Parses `T` while counting the arguments it takes
+///
+/// The leftovers tell their offset in the original arguments with `Counted::consumed`, and an
+/// error how many arguments were taken before it was raised.
+///
+/// ```
+/// use clip_core::parser::{try_parse_counted, Located, Parsed, ParsingError, TryParse};
+///
+/// struct Pair(u8, u8);
+///
+/// impl<'a> TryParse<&'a str> for Pair {
+///     type Error = ParsingError;
+///
+///     fn try_parse<I: Iterator<Item = &'a str>>(mut value: I) -> Result<Parsed<Self, I>, Self::Error> {
+///         let mut next = || value.next().ok_or(ParsingError::TooFewArguments)?.parse().map_err(ParsingError::bad_type);
+///         let pair = Pair(next()?, next()?);
+///         Ok(Parsed(pair, value))
+///     }
+/// }
+///
+/// let Parsed(_, rest) = try_parse_counted::<_, Pair, _>(["1", "2", "3"].into_iter()).unwrap();
+/// assert_eq!(rest.consumed(), 2);
+/// let err = try_parse_counted::<_, Pair, _>(["1", "x"].into_iter()).err().unwrap();
+/// assert_eq!(err.to_string(), "invalid value: invalid digit found in string at argument 2");
+/// ```
+pub fn try_parse_counted<Item, T, I>(args: I) -> Result<Parsed<T, Counted<I>>, Located<T::Error>>
+    where
+        T: TryParse<Item>,
+        I: Iterator<Item = Item> {
+    let counted = Counted::new(args);
+    let consumed = counted.consumed.clone();
+    T::try_parse(counted).map_err(|error| Located { error, consumed: consumed.get() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_chain_parsings() {
+        let mut seen = None;
+        let parsed = Parsed("2", ["a", "b", "c"].into_iter())
+            .map(|count| count.parse::<usize>().unwrap())
+            .inspect(|count| seen = Some(*count))
+            .and_then(|count, mut rest| Ok::<_, ParsingError>(Parsed(rest.by_ref().take(count).collect::<Vec<_>>(), rest)));
+        let (names, rest) = parsed.unwrap().into_parts();
+        assert_eq!((seen, names, rest.collect::<Vec<_>>()), (Some(2), vec!["a", "b"], vec!["c"]));
+    }
+
+    #[test]
+    fn it_should_read_a_dash_value_from_the_input() {
+        let mut input = "first line\nsecond line\n\n".as_bytes();
+        assert_eq!(value_or_read::<String>("-", &mut input), Ok(String::from("first line\nsecond line")));
+        assert_eq!(value_or_read::<u8>("-", &mut "12\n".as_bytes()), Ok(12));
+        assert_eq!(value_or_read::<String>("file", &mut "ignored".as_bytes()), Ok(String::from("file")));
+        assert!(matches!(value_or_read::<u8>("-", &mut "x".as_bytes()), Err(ParsingError::BadType(_))));
+    }
+
+    #[test]
+    fn it_should_check_possible_values() {
+        assert_eq!(check_possible("Fast", &["slow", "fast"]), Ok(()));
+        assert_eq!(
+            check_possible("medium", &["slow", "fast"]).unwrap_err().to_string(),
+            "invalid value: 'medium' is not one of slow, fast"
+        );
+    }
+
+    /// Directory of its own for a test, which removes it once done
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().subsec_nanos();
+        let dir = std::env::temp_dir().join(format!("clip-{name}-{}-{nanos}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn it_should_check_the_state_of_paths() {
+        let dir = scratch_dir("check-path");
+        let file = dir.join("config.toml");
+        std::fs::write(&file, "").unwrap();
+        let check = |path: &std::path::Path, check| check_path(path.to_str().unwrap(), "input", check).map_err(|err| err.to_string());
+        assert_eq!(check(&file, PathCheck::FileExists), Ok(()));
+        assert_eq!(check(&dir, PathCheck::DirExists), Ok(()));
+        assert_eq!(check(&file, PathCheck::Writable), Ok(()));
+        assert_eq!(check(&dir.join("new.log"), PathCheck::Writable), Ok(()));
+        assert_eq!(
+            check(&dir.join("missing"), PathCheck::FileExists),
+            Err(format!("'input': cannot access file '{}': no such file or directory", dir.join("missing").display()))
+        );
+        assert_eq!(check(&dir, PathCheck::FileExists), Err(format!("'input': '{}' is a directory, not a file", dir.display())));
+        assert_eq!(check(&file, PathCheck::DirExists), Err(format!("'input': '{}' is not a directory", file.display())));
+        assert!(check(&dir.join("missing/new.log"), PathCheck::Writable).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn it_should_expand_unambiguous_abbreviations() {
+        let keywords = [("status", 0), ("stash", 1), ("commit", 2), ("ci", 2)];
+        assert_eq!(resolve_abbreviation("STATU", &keywords), Ok(Some(0)));
+        assert_eq!(resolve_abbreviation("c", &keywords), Ok(Some(2)));
+        assert_eq!(resolve_abbreviation("x", &keywords), Ok(None));
+        assert_eq!(resolve_abbreviation("", &keywords), Ok(None));
+        let ambiguous = resolve_abbreviation("sta", &keywords).unwrap_err();
+        assert_eq!(ambiguous.to_string(), "'sta' is ambiguous, it may be: status, stash");
+    }
+}
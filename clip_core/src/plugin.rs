@@ -0,0 +1,125 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Commands registered at runtime, from plugins or behind feature flags
+//!
+//! Registered handlers are described alongside the static command tree, so that they appear in
+//! its help, and are dispatched by name before the typed parsing of the static commands.
+
+use crate::describe::command::Command;
+use crate::parser::ParsingError;
+
+/// A command known at runtime only
+pub trait CommandHandler<R = ()> {
+    /// Required method
+    /// Description of the command, its name being the keyword dispatching to it
+    fn command(&self) -> Command;
+    /// Required method
+    /// Runs the command with the arguments following its name
+    fn run(&self, args: &[&str]) -> Result<R, ParsingError>;
+}
+
+/// Registry of runtime commands
+pub struct CommandSet<R = ()> {
+    handlers: Vec<Box<dyn CommandHandler<R>>>,
+}
+
+impl<R> Default for CommandSet<R> {
+    fn default() -> Self {
+        Self { handlers: Vec::new() }
+    }
+}
+
+impl<R> CommandSet<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, handler: impl CommandHandler<R> + 'static) {
+        self.handlers.push(Box::new(handler));
+    }
+
+    /// Adds the registered commands to the subcommands of a static command tree
+    pub fn merge_into(&self, mut command: Command) -> Command {
+        let mut subcommands = command.subcommands.take().unwrap_or_default();
+        subcommands.extend(self.handlers.iter().map(|handler| handler.command()));
+        if !subcommands.is_empty() {
+            command.set_subcommands(subcommands);
+        }
+        command
+    }
+
+    /// Runs the registered command named by the first argument (case insensitive)
+    ///
+    /// Returns None when no registered command matches, the arguments then belong to the static
+    /// commands.
+    pub fn dispatch(&self, args: &[&str]) -> Option<Result<R, ParsingError>> {
+        let (name, rest) = args.split_first()?;
+        self.handlers
+            .iter()
+            .find(|handler| handler.command().value.name().eq_ignore_ascii_case(name))
+            .map(|handler| handler.run(rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Echo;
+
+    impl CommandHandler<String> for Echo {
+        fn command(&self) -> Command {
+            Command::new("echo", Some("prints its arguments"))
+        }
+
+        fn run(&self, args: &[&str]) -> Result<String, ParsingError> {
+            Ok(args.join(" "))
+        }
+    }
+
+    struct Fail;
+
+    impl CommandHandler<String> for Fail {
+        fn command(&self) -> Command {
+            Command::new("fail", None)
+        }
+
+        fn run(&self, _: &[&str]) -> Result<String, ParsingError> {
+            Err(ParsingError::BadType(String::new()))
+        }
+    }
+
+    fn commands() -> CommandSet<String> {
+        let mut commands = CommandSet::new();
+        commands.register(Echo);
+        commands.register(Fail);
+        commands
+    }
+
+    #[test]
+    fn it_should_merge_registered_commands_into_the_tree() {
+        let mut app = Command::new("app", None);
+        app.set_subcommands(vec![Command::new("build", Some("builds"))]);
+        let app = commands().merge_into(app);
+        let subcommands = app.subcommands.unwrap();
+        let names: Vec<&str> = subcommands.iter().map(|cmd| cmd.value.name()).collect();
+        assert_eq!(names, vec!["build", "echo", "fail"]);
+    }
+
+    #[test]
+    fn it_should_dispatch_by_name() {
+        let commands = commands();
+        assert_eq!(commands.dispatch(&["ECHO", "a", "b"]), Some(Ok(String::from("a b"))));
+        assert_eq!(commands.dispatch(&["fail"]), Some(Err(ParsingError::BadType(String::new()))));
+        assert_eq!(commands.dispatch(&["build"]), None);
+        assert_eq!(commands.dispatch(&[]), None);
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Records successful invocations into a session file
+//!
+//! Each line of the session file holds a timestamp (seconds since the UNIX epoch) followed by a
+//! tab and the quoted argument line, an argument spanning several lines being written
+//! `$'first\nsecond'`. Invocations are recorded normalized by `ToArgs::to_args`, whatever the
+//! way they were written. Entries can be read back to re-run the last command, as `!!` does in
+//! `Repl`, or to audit what has been executed.
+//!
+//! An invocation is recorded once the command succeeded, as told by its `Outcome`. `Recorder::call`
+//! wraps the main function given to the runners, as `#[clipv::main(record = path)]` does:
+//!
+//! ```no_run
+//! # use clip_core::describe::command::{AsCommand, Command};
+//! # use clip_core::parser::{Parsed, ParsingError, ToArgs, TryParse};
+//! # struct Cli;
+//! # impl<'a> TryParse<&'a &'a str> for Cli {
+//! #     type Error = ParsingError;
+//! #     fn try_parse<I: Iterator<Item = &'a &'a str>>(value: I) -> Result<Parsed<Self, I>, Self::Error> {
+//! #         Ok(Parsed(Cli, value))
+//! #     }
+//! # }
+//! # impl AsCommand for Cli {
+//! #     fn command() -> Command {
+//! #         Command::new("cli", None)
+//! #     }
+//! # }
+//! # impl ToArgs for Cli {
+//! #     fn to_args(&self) -> Vec<String> {
+//! #         Vec::new()
+//! #     }
+//! # }
+//! use clip_core::record::Recorder;
+//! use clip_core::run::run;
+//!
+//! let recorder = Recorder::new("session.log");
+//! run(&["build"], "1.0", |cli: Cli| recorder.call(cli, |_| Ok::<(), String>(())));
+//! ```
+
+use crate::parser::{ParsingError, Parsed, ToArgs, TryParse};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug)]
+pub enum RecordError {
+    /// the session file could not be read or written
+    Io(std::io::Error),
+    /// arguments could not be parsed, nothing has been recorded
+    Parsing(ParsingError),
+}
+
+impl From<std::io::Error> for RecordError {
+    fn from(err: std::io::Error) -> Self {
+        RecordError::Io(err)
+    }
+}
+
+impl From<ParsingError> for RecordError {
+    fn from(err: ParsingError) -> Self {
+        RecordError::Parsing(err)
+    }
+}
+
+/// What a command returned, telling whether it succeeded and has to be recorded
+pub trait Outcome {
+    /// Required method
+    fn succeeded(&self) -> bool;
+}
+
+impl Outcome for () {
+    fn succeeded(&self) -> bool {
+        true
+    }
+}
+
+impl Outcome for String {
+    fn succeeded(&self) -> bool {
+        true
+    }
+}
+
+impl<T, E> Outcome for Result<T, E> {
+    fn succeeded(&self) -> bool {
+        self.is_ok()
+    }
+}
+
+impl Outcome for ExitCode {
+    fn succeeded(&self) -> bool {
+        *self == ExitCode::SUCCESS
+    }
+}
+
+/// A recorded invocation
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    /// seconds since the UNIX epoch
+    pub timestamp: u64,
+    pub arguments: Vec<String>,
+}
+
+/// Quotes an argument only when needed so the line can be split back
+///
+/// Line breaks and tabs are escaped within `$'..'`, as understood by bash and zsh, so that the
+/// argument stays on a single line.
+pub(crate) fn quote(arg: &str) -> String {
+    if arg.contains(['\n', '\r', '\t']) {
+        let mut quoted = String::from("$'");
+        for c in arg.chars() {
+            match c {
+                '\n' => quoted.push_str("\\n"),
+                '\r' => quoted.push_str("\\r"),
+                '\t' => quoted.push_str("\\t"),
+                '\'' | '\\' => quoted.extend(['\\', c]),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('\'');
+        quoted
+    } else if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '\'' || c == '\\') {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Joins arguments into a single line, quoting the ones which need it
+pub fn command_line<S: AsRef<str>>(args: &[S]) -> String {
+    args.iter()
+        .map(|arg| quote(arg.as_ref()))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Splits back a line created by `command_line`
+fn split_line(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' if chars.clone().next() == Some('\'') => {
+                chars.next();
+                let arg = current.get_or_insert_with(String::new);
+                while let Some(quoted) = chars.next() {
+                    match quoted {
+                        '\'' => break,
+                        '\\' => match chars.next() {
+                            Some('n') => arg.push('\n'),
+                            Some('r') => arg.push('\r'),
+                            Some('t') => arg.push('\t'),
+                            Some(escaped) => arg.push(escaped),
+                            None => {}
+                        },
+                        quoted => arg.push(quoted),
+                    }
+                }
+            }
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                for quoted in chars.by_ref() {
+                    if quoted == '\'' {
+                        break;
+                    }
+                    arg.push(quoted);
+                }
+            }
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    current.get_or_insert_with(String::new).push(escaped);
+                }
+            }
+            c if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+    args
+}
+
+/// Appends invocations to a session file
+pub struct Recorder {
+    path: PathBuf,
+}
+
+impl Recorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Appends the arguments with the current timestamp
+    pub fn record<S: AsRef<str>>(&self, args: &[S]) -> std::io::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{timestamp}\t{}", command_line(args))
+    }
+
+    /// Reads every recorded invocation, oldest first
+    pub fn entries(&self) -> std::io::Result<Vec<Entry>> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut entries = Vec::new();
+        for line in std::io::BufReader::new(file).lines() {
+            let line = line?;
+            if let Some((timestamp, arguments)) = line.split_once('\t') {
+                if let Ok(timestamp) = timestamp.parse() {
+                    entries.push(Entry {
+                        timestamp,
+                        arguments: split_line(arguments),
+                    });
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Last recorded invocation, the one `!!` re-runs
+    pub fn last(&self) -> std::io::Result<Option<Entry>> {
+        Ok(self.entries()?.pop())
+    }
+
+    /// Calls `main` with the command and records it, as normalized by `ToArgs::to_args`, when
+    /// `main` succeeded
+    ///
+    /// The error of the session file is returned instead of the outcome, which a runner reports as
+    /// a failure.
+    pub fn call<T, R>(&self, command: T, main: impl FnOnce(T) -> R) -> std::io::Result<R>
+    where
+        T: ToArgs,
+        R: Outcome,
+    {
+        let normalized = command.to_args();
+        let outcome = main(command);
+        if outcome.succeeded() {
+            self.record(&normalized)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Same as `parser::parse` but records the invocation, as normalized by `ToArgs::to_args`,
+    /// when the callback succeeded
+    pub fn parse<'a, T, R>(
+        &self,
+        args: &'a [&'a str],
+        callback: impl FnOnce(T) -> R,
+    ) -> Result<R, RecordError>
+    where
+        T: TryParse<&'a &'a str, Error = ParsingError> + ToArgs,
+        R: Outcome,
+    {
+        let Parsed(parsed, mut rest) = T::try_parse(args.iter())?;
+        if rest.next().is_some() {
+            return Err(RecordError::Parsing(ParsingError::TooManyArguments));
+        }
+        Ok(self.call(parsed, callback)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Name(String);
+
+    impl ToArgs for Name {
+        fn to_args(&self) -> Vec<String> {
+            vec![self.0.trim().to_string()]
+        }
+    }
+
+    impl<'a> TryParse<&'a &'a str> for Name {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a &'a str>>(mut value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            let name = value.next().ok_or(ParsingError::TooFewArguments)?;
+            Ok(Parsed(Name(name.to_string()), value))
+        }
+    }
+
+    fn session_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("clip-record-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn it_should_quote_and_split_back() {
+        let args = ["simple", "with space", "it's", "", "back\\slash"];
+        let line = command_line(&args);
+        assert_eq!(line, r#"simple 'with space' 'it'\''s' '' 'back\slash'"#);
+        assert_eq!(split_line(&line), args);
+        let args = ["two\nlines", "tab\tand 'quote'\\", "\r\n"];
+        let line = command_line(&args);
+        assert_eq!(line, r#"$'two\nlines' $'tab\tand \'quote\'\\' $'\r\n'"#);
+        assert_eq!(split_line(&line), args);
+    }
+
+    #[test]
+    fn it_should_keep_multiline_arguments_on_one_line() {
+        let path = session_file("multiline");
+        let recorder = Recorder::new(&path);
+        recorder.record(&["commit", "-m", "title\n\nbody"]).unwrap();
+        recorder.record(&["status"]).unwrap();
+        let entries = recorder.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].arguments, ["commit", "-m", "title\n\nbody"]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn it_should_record_successful_invocations_only() {
+        let path = session_file("success");
+        let recorder = Recorder::new(&path);
+        assert_eq!(recorder.last().unwrap(), None);
+        assert_eq!(recorder.parse(&["first"], |Name(name)| name).unwrap(), "first");
+        assert!(matches!(
+            recorder.parse(&["second", "extra"], |Name(name)| name),
+            Err(RecordError::Parsing(ParsingError::TooManyArguments))
+        ));
+        recorder.parse(&[" third one "], |_: Name| ()).unwrap();
+        assert_eq!(recorder.parse(&["failing"], |_: Name| Err::<(), _>("failed")).unwrap(), Err("failed"));
+        assert_eq!(recorder.call(Name(String::from("exiting")), |_| ExitCode::FAILURE).unwrap(), ExitCode::FAILURE);
+
+        let entries = recorder.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].arguments, vec!["first"]);
+        assert!(entries[0].timestamp > 0);
+        assert_eq!(recorder.last().unwrap().unwrap().arguments, vec!["third one"]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,366 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Interactive console dispatching each line to a handler
+//!
+//! Lines are split with `shlex::split` and parsed into the command enumeration, which is then
+//! dispatched to the handler. Some commands are built in:
+//! - `help [COMMAND]..` prints the help of the console or of one of its commands
+//! - `history` lists the lines entered so far
+//! - `!!` runs the last command again: the last one recorded when the console has a `Recorder`,
+//!   otherwise the previous line
+//! - `exit` and `quit` leave the console, as does the end of the input
+//!
+//! With the `rustyline` feature, `run` reads the lines with `rustyline`: they can be edited, the
+//! previous ones are recalled with the arrow keys and `with_history_file` keeps them across
+//! sessions. Ctrl-C clears the line being typed.
+
+use crate::describe::command::AsCommand;
+use crate::parser::{parse, ParsingError, ToArgs, TryParse};
+use crate::record::{command_line, Outcome, Recorder};
+use crate::run::Dispatch;
+use crate::shlex::split;
+use std::borrow::Cow;
+use std::io::{BufRead, Write};
+use std::marker::PhantomData;
+
+/// What the console prints of the output of a handler
+pub trait Echo {
+    /// Required method
+    fn echo(self, out: &mut impl Write) -> std::io::Result<()>;
+}
+
+impl Echo for () {
+    fn echo(self, _: &mut impl Write) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Echo for String {
+    fn echo(self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "{self}")
+    }
+}
+
+impl<T: Echo, E: std::fmt::Display> Echo for Result<T, E> {
+    fn echo(self, out: &mut impl Write) -> std::io::Result<()> {
+        match self {
+            Ok(output) => output.echo(out),
+            Err(err) => writeln!(out, "error: {err}"),
+        }
+    }
+}
+
+/// Arguments a command is recorded as, see `ToArgs::to_args`
+type Normalize<T> = fn(&T) -> Vec<String>;
+
+/// Console reading commands of type `T`
+pub struct Repl<T> {
+    prompt: Cow<'static, str>,
+    history: Vec<String>,
+    /// session file of the commands run, with the way to normalize them
+    recorder: Option<(Recorder, Normalize<T>)>,
+    #[cfg(feature = "rustyline")]
+    history_file: Option<std::path::PathBuf>,
+    command: PhantomData<T>,
+}
+
+impl<T> Repl<T>
+where
+    T: for<'a> TryParse<&'a &'a str, Error = ParsingError> + AsCommand + 'static,
+{
+    pub fn new(prompt: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            history: Vec::new(),
+            recorder: None,
+            #[cfg(feature = "rustyline")]
+            history_file: None,
+            command: PhantomData,
+        }
+    }
+
+    /// Records every command run successfully into the session file of `recorder`, normalized by
+    /// `ToArgs::to_args`, `!!` running the last one recorded again
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self
+    where
+        T: ToArgs,
+    {
+        self.recorder = Some((recorder, T::to_args));
+        self
+    }
+
+    /// Loads the lines recalled by the arrow keys from `path` and saves them there when the console
+    /// is left, the `history` command only listing the ones of the session
+    #[cfg(feature = "rustyline")]
+    pub fn with_history_file(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.history_file = Some(path.into());
+        self
+    }
+
+    /// Lines entered so far, blank ones excepted
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Runs the console on the standard input and output until `exit` or the end of the input
+    #[cfg(not(feature = "rustyline"))]
+    pub fn run<H>(&mut self, handler: &mut H) -> std::io::Result<()>
+    where
+        T: Dispatch<H>,
+        T::Output: Echo + Outcome,
+    {
+        self.run_with(std::io::stdin().lock(), &mut std::io::stdout(), handler)
+    }
+
+    /// Runs the console on the terminal until `exit` or the end of the input, the lines being
+    /// edited with `rustyline`
+    #[cfg(feature = "rustyline")]
+    pub fn run<H>(&mut self, handler: &mut H) -> std::io::Result<()>
+    where
+        T: Dispatch<H>,
+        T::Output: Echo + Outcome,
+    {
+        use rustyline::error::ReadlineError;
+
+        let io_error = |err: ReadlineError| match err {
+            ReadlineError::Io(err) => err,
+            err => std::io::Error::other(err),
+        };
+        let mut editor = rustyline::DefaultEditor::new().map_err(io_error)?;
+        if let Some(path) = &self.history_file {
+            // there is no history yet on the first run
+            if path.exists() {
+                editor.load_history(path).map_err(io_error)?;
+            }
+        }
+        let mut output = std::io::stdout();
+        loop {
+            let line = match editor.readline(&self.prompt) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(err) => return Err(io_error(err)),
+            };
+            let line = line.trim();
+            if !line.is_empty() {
+                editor.add_history_entry(line).map_err(io_error)?;
+            }
+            if !self.execute(line, &mut output, handler)? {
+                break;
+            }
+        }
+        match &self.history_file {
+            Some(path) => editor.save_history(path).map_err(io_error),
+            None => Ok(()),
+        }
+    }
+
+    /// Same as `run` on any input and output
+    pub fn run_with<H>(&mut self, mut input: impl BufRead, output: &mut impl Write, handler: &mut H) -> std::io::Result<()>
+    where
+        T: Dispatch<H>,
+        T::Output: Echo + Outcome,
+    {
+        let mut line = String::new();
+        loop {
+            write!(output, "{}", self.prompt)?;
+            output.flush()?;
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            if !self.execute(line.trim(), output, handler)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Executes a line, false when the console has to be left
+    fn execute<H>(&mut self, line: &str, output: &mut impl Write, handler: &mut H) -> std::io::Result<bool>
+    where
+        T: Dispatch<H>,
+        T::Output: Echo + Outcome,
+    {
+        if line.is_empty() {
+            return Ok(true);
+        }
+        let tokens = if line == "!!" {
+            let last = match &self.recorder {
+                Some((recorder, _)) => recorder.last()?.map(|entry| entry.arguments),
+                None => self.history.last().and_then(|line| split(line).ok()),
+            };
+            let Some(tokens) = last else {
+                writeln!(output, "error: no command to run again")?;
+                return Ok(true);
+            };
+            // the command is shown and kept in the history as run
+            let line = command_line(&tokens);
+            writeln!(output, "{line}")?;
+            self.history.push(line);
+            tokens
+        } else {
+            self.history.push(line.to_string());
+            match split(line) {
+                Ok(tokens) => tokens,
+                Err(err) => {
+                    writeln!(output, "error: {err}")?;
+                    return Ok(true);
+                }
+            }
+        };
+        let args: Vec<&str> = tokens.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["exit" | "quit"] => return Ok(false),
+            ["history"] => {
+                for (index, entry) in self.history.iter().enumerate() {
+                    writeln!(output, "{:>4}  {entry}", index + 1)?;
+                }
+                return Ok(true);
+            }
+            _ => {}
+        }
+        match T::help_subcommand(&args) {
+            Some(Ok(help)) => write!(output, "{help}")?,
+            Some(Err(err)) => writeln!(output, "error: {err}")?,
+            None => {
+                let normalize = self.recorder.as_ref().map(|(_, normalize)| *normalize);
+                let run = |command: T| (normalize.map(|normalize| normalize(&command)), command.dispatch(handler));
+                match parse(args.iter(), run) {
+                    Ok((normalized, result)) => {
+                        if let (Some((recorder, _)), Some(normalized)) = (&self.recorder, normalized) {
+                            if result.succeeded() {
+                                recorder.record(&normalized)?;
+                            }
+                        }
+                        result.echo(output)?
+                    }
+                    Err(err) => writeln!(output, "error: {err}\nFor more information, try 'help'.")?,
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::command::Command;
+    use crate::parser::Parsed;
+
+    enum Todo {
+        Add(String),
+        List,
+    }
+
+    impl<'a> TryParse<&'a &'a str> for Todo {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a &'a str>>(mut value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            match value.next().copied() {
+                Some("add") => {
+                    let item = value.next().ok_or(ParsingError::TooFewArguments)?;
+                    Ok(Parsed(Todo::Add(item.to_string()), value))
+                }
+                Some("list") => Ok(Parsed(Todo::List, value)),
+                _ => Err(ParsingError::VariantNotFound),
+            }
+        }
+    }
+
+    impl AsCommand for Todo {
+        fn command() -> Command {
+            let mut todo = Command::new("todo", None);
+            todo.set_subcommands(vec![Command::new("add", Some("adds an item")), Command::new("list", None)]);
+            todo
+        }
+    }
+
+    impl Dispatch<Vec<String>> for Todo {
+        type Output = Result<String, String>;
+
+        fn dispatch(self, items: &mut Vec<String>) -> Self::Output {
+            match self {
+                Todo::Add(item) if items.contains(&item) => Err(format!("'{item}' already exists")),
+                Todo::Add(item) => {
+                    items.push(item);
+                    Ok(format!("{} items", items.len()))
+                }
+                Todo::List => Ok(items.join(", ")),
+            }
+        }
+    }
+
+    impl ToArgs for Todo {
+        fn to_args(&self) -> Vec<String> {
+            match self {
+                Todo::Add(item) => vec![String::from("add"), item.clone()],
+                Todo::List => vec![String::from("list")],
+            }
+        }
+    }
+
+    fn session(input: &str) -> (String, Vec<String>, Repl<Todo>) {
+        session_with(Repl::new("> "), input)
+    }
+
+    fn session_with(mut repl: Repl<Todo>, input: &str) -> (String, Vec<String>, Repl<Todo>) {
+        let mut output = Vec::new();
+        let mut items = Vec::new();
+        repl.run_with(input.as_bytes(), &mut output, &mut items).unwrap();
+        (String::from_utf8(output).unwrap(), items, repl)
+    }
+
+    #[test]
+    fn it_should_dispatch_each_line() {
+        let (output, items, repl) = session("add 'buy milk'\n\nadd 'buy milk'\nadd bread\nlist\n");
+        assert_eq!(
+            output,
+            "> 1 items\n> > error: 'buy milk' already exists\n> 2 items\n> buy milk, bread\n> "
+        );
+        assert_eq!(items, ["buy milk", "bread"]);
+        assert_eq!(repl.history().len(), 4);
+    }
+
+    #[test]
+    fn it_should_answer_built_in_commands() {
+        let (output, items, _) = session("help add\nhistory\nexit\nadd ignored\n");
+        assert_eq!(output, "> adds an item\n\nUsage: todo add\n\n>    1  help add\n   2  history\n> ");
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn it_should_report_errors_and_go_on() {
+        let (output, items, _) = session("add\nadd 'open\nadd done\n");
+        assert_eq!(
+            output,
+            "> error: missing argument\nFor more information, try 'help'.\n> error: unterminated ' quote\n> 1 items\n> "
+        );
+        assert_eq!(items, ["done"]);
+    }
+
+    #[test]
+    fn it_should_run_the_last_command_again() {
+        let (output, items, repl) = session("!!\nadd bread\n!!\n");
+        assert_eq!(output, "> error: no command to run again\n> 1 items\n> add bread\nerror: 'bread' already exists\n> ");
+        assert_eq!((items, repl.history()), (vec![String::from("bread")], &[String::from("add bread"), String::from("add bread")][..]));
+        let path = std::env::temp_dir().join(format!("clip-repl-{}-recorded", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let (output, items, _) = session_with(Repl::new("> ").with_recorder(Recorder::new(&path)), "add 'buy milk'\nadd 'buy milk'\nhelp\nlist\n");
+        assert_eq!(items, ["buy milk"]);
+        assert!(output.ends_with("> buy milk\n> "));
+        let (output, items, _) = session_with(Repl::new("> ").with_recorder(Recorder::new(&path)), "!!\n");
+        assert_eq!((output.as_str(), items), ("> list\n\n> ", Vec::<String>::new()));
+        // the failed `add` is not recorded
+        assert_eq!(Recorder::new(&path).entries().unwrap().len(), 3);
+        std::fs::remove_file(path).unwrap();
+    }
+}
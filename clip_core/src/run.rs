@@ -0,0 +1,429 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Execution of parsed commands
+
+use crate::completion::{complete, COMPLETE_KEYWORD};
+use crate::describe::command::{AsCommand, Command};
+use crate::describe::formatter::{terminal_width, wrap};
+use crate::error::{parse, parse_os, ClipError};
+use crate::pager::page;
+use crate::parser::{ParsingError, TryParse};
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+use std::process::{ExitCode, Termination};
+
+/// Exit code of an invocation which could not be parsed
+pub const USAGE_ERROR: u8 = 2;
+
+/// Exit code of an invocation with a value which could not be converted, `EX_DATAERR` of sysexits
+pub const DATA_ERROR: u8 = 65;
+
+/// Routes a parsed command to the handler of its variant
+///
+/// The derive macro generates a `<Enum>Handler` trait with one method per variant (snake cased)
+/// taking the variant fields, and implements `Dispatch` for any implementor of that trait.
+pub trait Dispatch<H> {
+    type Output;
+
+    /// Required method
+    fn dispatch(self, handler: &mut H) -> Self::Output;
+}
+
+/// Entry point of a CLI, used by the `#[clipv::main]` attribute
+///
+/// Answers `-h` (short help) and `--help` (long help) with the help of the subcommand they follow
+/// (see `Command::help_flag`), `help [COMMAND]..` and `-V`/`--version` requests
+/// (the version of the command taking precedence over `version`),
+/// as well as the hidden `__complete` ones of the shells, otherwise parses the arguments and calls
+/// `main`. A parsing error is printed on the standard
+/// error as rendered by `ClipError::render` and exits with `ParsingError::exit_code`; what `main` returns is reported like
+/// the return value of a regular `main` function.
+pub fn run<'a, T, R>(args: &'a [&'a str], version: &str, main: impl FnOnce(T) -> R) -> ExitCode
+where
+    T: TryParse<&'a &'a str, Error = ParsingError> + AsCommand + 'static,
+    R: Termination,
+{
+    run_with(args, version, ParsingError::exit_code, main)
+}
+
+/// Same as `run` with the exit code of each parsing error given by `exit_code`, used by
+/// `#[clipv::main(exit_code = function)]`
+pub fn run_with<'a, T, R>(
+    args: &'a [&'a str],
+    version: &str,
+    exit_code: impl FnOnce(&ParsingError) -> u8,
+    main: impl FnOnce(T) -> R,
+) -> ExitCode
+where
+    T: TryParse<&'a &'a str, Error = ParsingError> + AsCommand + 'static,
+    R: Termination,
+{
+    match answer::<T>(args, version) {
+        Some(Ok(code)) => code,
+        Some(Err(err)) => usage_error::<T>(err, exit_code),
+        None => match parse(args, main) {
+            Ok(result) => result.report(),
+            Err(err) => usage_error::<T>(err, exit_code),
+        },
+    }
+}
+
+/// Same as `run` with the arguments of the operating system, as given by `std::env::args_os`
+///
+/// The arguments which are not unicode are kept as they are for the `PathBuf` and `OsString`
+/// fields, other fields failing with `ParsingError::InvalidUtf8`.
+pub fn run_os<'a, T, R>(args: &'a [OsString], version: &str, main: impl FnOnce(T) -> R) -> ExitCode
+where
+    T: TryParse<&'a OsString, Error = ParsingError> + AsCommand + 'static,
+    R: Termination,
+{
+    run_os_with(args, version, ParsingError::exit_code, main)
+}
+
+/// Same as `run_os` with the exit code of each parsing error given by `exit_code`
+pub fn run_os_with<'a, T, R>(
+    args: &'a [OsString],
+    version: &str,
+    exit_code: impl FnOnce(&ParsingError) -> u8,
+    main: impl FnOnce(T) -> R,
+) -> ExitCode
+where
+    T: TryParse<&'a OsString, Error = ParsingError> + AsCommand + 'static,
+    R: Termination,
+{
+    // the requests answered before parsing are all unicode
+    let text: Vec<Cow<'_, str>> = args.iter().map(|arg| arg.to_string_lossy()).collect();
+    let text: Vec<&str> = text.iter().map(Cow::as_ref).collect();
+    match answer::<T>(&text, version) {
+        Some(Ok(code)) => code,
+        Some(Err(err)) => usage_error::<T>(err, exit_code),
+        None => match parse_os(args, main) {
+            Ok(result) => result.report(),
+            Err(err) => usage_error::<T>(err, exit_code),
+        },
+    }
+}
+
+/// Answers the completion, help and version requests, None for the invocations to parse
+fn answer<T: AsCommand + 'static>(args: &[&str], version: &str) -> Option<Result<ExitCode, ClipError>> {
+    if let Some((&COMPLETE_KEYWORD, words)) = args.split_first() {
+        for candidate in complete(T::command_ref(), words) {
+            println!("{candidate}");
+        }
+        return Some(Ok(ExitCode::SUCCESS));
+    }
+    let command = T::command_ref();
+    if let Some((path, long)) = command.help_flag(args) {
+        print_help(match (path.is_empty(), long) {
+            (true, true) => T::long_help(),
+            (true, false) => T::help(),
+            (false, true) => command.long_help_for_path(&path).unwrap_or_default(),
+            (false, false) => command.help_for_path(&path).unwrap_or_default(),
+        });
+        return Some(Ok(ExitCode::SUCCESS));
+    }
+    if let [arg] = args {
+        if *arg == "-V" || *arg == "--version" {
+            println!("{} {}", command.value, command.version.as_deref().unwrap_or(version));
+            return Some(Ok(ExitCode::SUCCESS));
+        }
+    }
+    match T::help_subcommand(args)? {
+        Ok(help) => {
+            print_help(help);
+            Some(Ok(ExitCode::SUCCESS))
+        }
+        Err(err) => Some(Err(ClipError::new(err, args, None))),
+    }
+}
+
+/// Prints a help message, wrapped to the terminal width when it is known and paged when taller
+/// than the terminal, see `pager::page`
+fn print_help(help: String) {
+    match terminal_width() {
+        Some(width) => page(&wrap(&help, width)),
+        None => page(&help),
+    }
+}
+
+fn usage_error<T: AsCommand + 'static>(err: ClipError, exit_code: impl FnOnce(&ParsingError) -> u8) -> ExitCode {
+    eprintln!("{}", err.render(T::command_ref()));
+    ExitCode::from(exit_code(&err.error))
+}
+
+/// Name a multi-call binary was called by: the file name of `argv0` without its extension
+pub fn applet_name(argv0: &str) -> &str {
+    Path::new(argv0).file_stem().and_then(OsStr::to_str).unwrap_or(argv0)
+}
+
+/// Help message of a multi-call binary, listing its subcommands as applets
+pub fn applets_help<T: AsCommand>() -> String {
+    let mut command = T::command();
+    command.titles.commands = Cow::Borrowed("Applets");
+    command.help()
+}
+
+/// Entry point of a multi-call binary (busybox style), used by `#[clipv::main(multicall)]`
+///
+/// `args` starts with the path the program was called by. Called through a link named after a
+/// subcommand of `T` (`ls` → `T::Ls`), it runs that subcommand with the remaining arguments,
+/// `-h`/`--help` printing its help. Called by any other name it behaves as `run`, the applet being
+/// the first argument (`box ls -l`), and prints the list of applets when given no argument.
+pub fn run_multicall<T, R>(args: &[&str], version: &str, main: impl FnOnce(T) -> R) -> ExitCode
+where
+    T: for<'a> TryParse<&'a &'a str, Error = ParsingError> + AsCommand + 'static,
+    R: Termination,
+{
+    run_multicall_with(args, version, ParsingError::exit_code, main)
+}
+
+/// Same as `run_multicall` with the exit code of each parsing error given by `exit_code`
+pub fn run_multicall_with<T, R>(
+    args: &[&str],
+    version: &str,
+    exit_code: impl FnOnce(&ParsingError) -> u8,
+    main: impl FnOnce(T) -> R,
+) -> ExitCode
+where
+    T: for<'a> TryParse<&'a &'a str, Error = ParsingError> + AsCommand + 'static,
+    R: Termination,
+{
+    let (applet, rest) = match args.split_first() {
+        Some((argv0, rest)) => (applet_name(argv0), rest),
+        None => ("", args),
+    };
+    let command = T::command_ref();
+    let linked = match command.find(&[applet]) {
+        Some(subcommand) if !std::ptr::eq(subcommand, command) => subcommand,
+        _ => {
+            if rest.is_empty() || rest == ["-h"] || rest == ["--help"] {
+                print_help(applets_help::<T>());
+                return ExitCode::SUCCESS;
+            }
+            return run_with(rest, version, exit_code, main);
+        }
+    };
+    if let Some(help) = linked_help(linked, rest) {
+        print_help(help);
+        return ExitCode::SUCCESS;
+    }
+    let line: Vec<&str> = std::iter::once(applet).chain(rest.iter().copied()).collect();
+    run_with(&line, version, exit_code, main)
+}
+
+/// Help asked by a `-h`/`--help` flag given to the applet `linked`
+fn linked_help<S: AsRef<str>>(linked: &Command, args: &[S]) -> Option<String> {
+    let (path, long) = linked.help_flag(args)?;
+    match long {
+        true => linked.long_help_for_path(&path),
+        false => linked.help_for_path(&path),
+    }
+}
+
+/// Same as `run_multicall` with the arguments of the operating system, see `run_os`
+pub fn run_multicall_os<T, R>(args: &[OsString], version: &str, main: impl FnOnce(T) -> R) -> ExitCode
+where
+    T: for<'a> TryParse<&'a OsString, Error = ParsingError> + AsCommand + 'static,
+    R: Termination,
+{
+    run_multicall_os_with(args, version, ParsingError::exit_code, main)
+}
+
+/// Same as `run_multicall_os` with the exit code of each parsing error given by `exit_code`
+pub fn run_multicall_os_with<T, R>(
+    args: &[OsString],
+    version: &str,
+    exit_code: impl FnOnce(&ParsingError) -> u8,
+    main: impl FnOnce(T) -> R,
+) -> ExitCode
+where
+    T: for<'a> TryParse<&'a OsString, Error = ParsingError> + AsCommand + 'static,
+    R: Termination,
+{
+    let (argv0, rest) = match args.split_first() {
+        Some((argv0, rest)) => (argv0.to_string_lossy(), rest),
+        None => (Cow::Borrowed(""), args),
+    };
+    let applet = applet_name(&argv0);
+    let command = T::command_ref();
+    let linked = match command.find(&[applet]) {
+        Some(subcommand) if !std::ptr::eq(subcommand, command) => subcommand,
+        _ => {
+            if rest.is_empty() || rest == ["-h"] || rest == ["--help"] {
+                print_help(applets_help::<T>());
+                return ExitCode::SUCCESS;
+            }
+            return run_os_with(rest, version, exit_code, main);
+        }
+    };
+    let text: Vec<Cow<'_, str>> = rest.iter().map(|arg| arg.to_string_lossy()).collect();
+    if let Some(help) = linked_help(linked, &text) {
+        print_help(help);
+        return ExitCode::SUCCESS;
+    }
+    let line: Vec<OsString> = std::iter::once(OsString::from(applet)).chain(rest.iter().cloned()).collect();
+    run_os_with(&line, version, exit_code, main)
+}
+
+/// Future returned by asynchronous handlers
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Same as `Dispatch` for asynchronous handlers
+///
+/// The derive macro generates a `<Enum>AsyncHandler` trait whose methods return a `BoxFuture`, so
+/// that subcommands can be routed to async handlers whatever the executor.
+#[cfg(feature = "async")]
+pub trait AsyncDispatch<H> {
+    type Output;
+
+    /// Required method
+    fn dispatch(self, handler: &mut H) -> BoxFuture<'_, Self::Output>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    enum Command {
+        Add(String),
+        List,
+    }
+
+    struct Store(Vec<String>);
+
+    impl Dispatch<Store> for Command {
+        type Output = usize;
+
+        fn dispatch(self, store: &mut Store) -> usize {
+            if let Command::Add(item) = self {
+                store.0.push(item);
+            }
+            store.0.len()
+        }
+    }
+
+    struct Greet(String);
+
+    impl<'a> TryParse<&'a &'a str> for Greet {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a &'a str>>(mut value: I) -> Result<crate::parser::Parsed<Self, I>, Self::Error> {
+            let name = value.next().ok_or(ParsingError::TooFewArguments)?;
+            Ok(crate::parser::Parsed(Greet(name.to_string()), value))
+        }
+    }
+
+    impl AsCommand for Greet {
+        fn command() -> crate::describe::command::Command {
+            let mut command = crate::describe::command::Command::new("greet", None);
+            command.set_arguments(vec![crate::describe::arg::Arg::new("name", None)]);
+            command
+        }
+    }
+
+    #[test]
+    fn it_should_run_main_with_parsed_arguments() {
+        let mut called = None;
+        assert_eq!(run(&["claire"], "1.0", |Greet(name)| called = Some(name)), ExitCode::SUCCESS);
+        assert_eq!(called.as_deref(), Some("claire"));
+        assert_eq!(run(&["claire"], "1.0", |_: Greet| Err::<(), _>("failed")), ExitCode::FAILURE);
+    }
+
+    #[test]
+    fn it_should_answer_help_and_version_without_running() {
+        for args in [&["--help"][..], &["claire", "-h"], &["--version"]] {
+            assert_eq!(run(args, "1.0", |_: Greet| panic!("should not run")), ExitCode::SUCCESS);
+        }
+        // `Greet` has no subcommands, `help` is a name
+        let mut called = None;
+        assert_eq!(run(&["help"], "1.0", |Greet(name)| called = Some(name)), ExitCode::SUCCESS);
+        assert_eq!(called.as_deref(), Some("help"));
+        // parsed as values, which `Greet` does not expect
+        assert_eq!(run(&["--", "-h"], "1.0", |_: Greet| ()), ExitCode::from(USAGE_ERROR));
+    }
+
+    #[test]
+    fn it_should_exit_with_usage_error() {
+        assert_eq!(run(&[], "1.0", |_: Greet| ()), ExitCode::from(USAGE_ERROR));
+        assert_eq!(run(&["a", "b"], "1.0", |_: Greet| ()), ExitCode::from(USAGE_ERROR));
+    }
+
+    #[test]
+    fn it_should_map_errors_to_exit_codes() {
+        assert_eq!(ParsingError::BadType(String::new()).exit_code(), DATA_ERROR);
+        assert_eq!(ParsingError::TooManyArguments.exit_code(), USAGE_ERROR);
+        let codes = |err: &ParsingError| match err {
+            ParsingError::TooManyArguments => 64,
+            _ => 1,
+        };
+        assert_eq!(run_with(&["a", "b"], "1.0", codes, |_: Greet| ()), ExitCode::from(64));
+        assert_eq!(run_with(&[], "1.0", codes, |_: Greet| ()), ExitCode::from(1));
+    }
+
+    /// `greet` and `wave` applets
+    enum Applet {
+        Greet(Greet),
+        Wave,
+    }
+
+    impl<'a> TryParse<&'a &'a str> for Applet {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a &'a str>>(mut value: I) -> Result<crate::parser::Parsed<Self, I>, Self::Error> {
+            match value.next().copied() {
+                Some("greet") => Greet::try_parse(value).map(|crate::parser::Parsed(greet, rest)| crate::parser::Parsed(Applet::Greet(greet), rest)),
+                Some("wave") => Ok(crate::parser::Parsed(Applet::Wave, value)),
+                Some(_) => Err(ParsingError::VariantNotFound),
+                None => Err(ParsingError::TooFewArguments),
+            }
+        }
+    }
+
+    impl AsCommand for Applet {
+        fn command() -> crate::describe::command::Command {
+            let mut command = crate::describe::command::Command::new("box", None);
+            command.set_subcommands(vec![Greet::command(), crate::describe::command::Command::new("wave", None)]);
+            command
+        }
+    }
+
+    #[test]
+    fn it_should_run_the_applet_named_by_the_binary() {
+        assert_eq!(applet_name("/usr/bin/greet"), "greet");
+        assert_eq!(applet_name("wave.exe"), "wave");
+        let mut called = None;
+        let greet = |applet: Applet| {
+            called = Some(match applet {
+                Applet::Greet(Greet(name)) => name,
+                Applet::Wave => String::from("wave"),
+            })
+        };
+        assert_eq!(run_multicall(&["/usr/bin/greet", "claire"], "1.0", greet), ExitCode::SUCCESS);
+        assert_eq!(called.as_deref(), Some("claire"));
+        let mut called = None;
+        assert_eq!(run_multicall(&["./box", "wave"], "1.0", |applet: Applet| called = Some(matches!(applet, Applet::Wave))), ExitCode::SUCCESS);
+        assert_eq!(called, Some(true));
+        assert_eq!(run_multicall(&["wave", "extra"], "1.0", |_: Applet| ()), ExitCode::from(USAGE_ERROR));
+        for args in [&["box"][..], &["box", "--help"], &["greet", "-h"]] {
+            assert_eq!(run_multicall(args, "1.0", |_: Applet| panic!("should not run")), ExitCode::SUCCESS);
+        }
+        assert!(applets_help::<Applet>().contains("Applets:\n  greet\n  wave\n"));
+    }
+
+    #[test]
+    fn it_should_dispatch_to_the_handler() {
+        let mut store = Store(Vec::new());
+        assert_eq!(Command::Add(String::from("a")).dispatch(&mut store), 1);
+        assert_eq!(Command::List.dispatch(&mut store), 1);
+    }
+}
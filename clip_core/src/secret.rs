@@ -0,0 +1,105 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Values which must never be echoed, such as passwords
+//!
+//! An argument marked with `Arg::secret` (`#[secret]` with the derive macros) is masked in error
+//! messages and typed without echo when the wizard prompts for it. Its field can be a `Secret`
+//! so that debugging or logging the parsed struct does not reveal it either.
+
+use std::io::{BufRead, IsTerminal, Write};
+
+/// What is displayed in place of a secret value
+pub const MASK: &str = "***";
+
+/// Value redacted from `Debug` and `Display`, parsed as its inner type
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    /// The secret value itself, to be handled with care
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret({MASK})")
+    }
+}
+
+impl<T> std::fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(MASK)
+    }
+}
+
+impl<T: std::str::FromStr> std::str::FromStr for Secret<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Secret)
+    }
+}
+
+/// Reads a line from `input`, turning the terminal echo off while it is typed
+///
+/// The echo is switched with `stty`, and only when the standard input is a terminal.
+pub fn read_hidden(input: &mut impl BufRead, output: &mut impl Write) -> std::io::Result<String> {
+    let hidden = std::io::stdin().is_terminal() && stty("-echo");
+    let mut line = String::new();
+    let read = input.read_line(&mut line);
+    if hidden {
+        stty("echo");
+        // the new line typed has not been echoed either
+        writeln!(output)?;
+    }
+    if read? == 0 {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(line.trim_end_matches(['\n', '\r']).to_string())
+}
+
+fn stty(setting: &str) -> bool {
+    std::process::Command::new("stty")
+        .arg(setting)
+        .stdin(std::process::Stdio::inherit())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_redact_the_value() {
+        let password: Secret<String> = "hunter2".parse().unwrap();
+        assert_eq!(format!("{password:?} {password}"), "Secret(***) ***");
+        assert_eq!(password.expose(), "hunter2");
+        assert!("x".parse::<Secret<u8>>().is_err());
+    }
+
+    #[test]
+    fn it_should_read_a_line_without_its_end() {
+        let mut output = Vec::new();
+        assert_eq!(read_hidden(&mut "hunter2\r\nnext".as_bytes(), &mut output).unwrap(), "hunter2");
+        assert!(read_hidden(&mut "".as_bytes(), &mut output).is_err());
+    }
+}
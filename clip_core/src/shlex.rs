@@ -0,0 +1,176 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Splitting of raw lines into arguments the way a POSIX shell does
+//!
+//! Tokens are separated by whitespace, single quotes keep their content as is, double quotes
+//! only let a backslash escape `"`, `\`, `$` and `` ` ``, and a backslash escapes any character
+//! outside of quotes. It makes it possible to parse interactive command lines, not just `argv`:
+//!
+//! ```
+//! use clip_core::shlex::split;
+//!
+//! let tokens = split(r#"add "my file" it\'s"#).unwrap();
+//! assert_eq!(tokens, ["add", "my file", "it's"]);
+//! ```
+
+use crate::parser::{parse, ParsingError, TryParse};
+
+#[derive(Debug, PartialEq)]
+pub enum SplitError {
+    /// a quote, `'` or `"`, is not closed at the end of the line
+    UnterminatedQuote(char),
+    /// the line ends with a backslash escaping nothing
+    TrailingEscape,
+}
+
+impl std::fmt::Display for SplitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SplitError::UnterminatedQuote(quote) => write!(f, "unterminated {quote} quote"),
+            SplitError::TrailingEscape => f.write_str("nothing to escape at the end of the line"),
+        }
+    }
+}
+
+impl std::error::Error for SplitError {}
+
+/// Error of a line which could not be split or parsed
+#[derive(Debug, PartialEq)]
+pub enum LineError {
+    Split(SplitError),
+    Parsing(ParsingError),
+}
+
+impl From<SplitError> for LineError {
+    fn from(err: SplitError) -> Self {
+        LineError::Split(err)
+    }
+}
+
+impl From<ParsingError> for LineError {
+    fn from(err: ParsingError) -> Self {
+        LineError::Parsing(err)
+    }
+}
+
+impl std::fmt::Display for LineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineError::Split(err) => err.fmt(f),
+            LineError::Parsing(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LineError {}
+
+/// Splits a line into its arguments
+pub fn split(line: &str) -> Result<Vec<String>, SplitError> {
+    let mut tokens = Vec::new();
+    // None between tokens, so that quotes can make an empty one
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let token = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => token.push(c),
+                        None => return Err(SplitError::UnterminatedQuote('\'')),
+                    }
+                }
+            }
+            '"' => {
+                let token = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => token.push(c),
+                            Some(c) => {
+                                token.push('\\');
+                                token.push(c);
+                            }
+                            None => return Err(SplitError::UnterminatedQuote('"')),
+                        },
+                        Some(c) => token.push(c),
+                        None => return Err(SplitError::UnterminatedQuote('"')),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => current.get_or_insert_with(String::new).push(c),
+                None => return Err(SplitError::TrailingEscape),
+            },
+            c if c.is_whitespace() => tokens.extend(current.take()),
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    tokens.extend(current);
+    Ok(tokens)
+}
+
+/// Same as `parser::parse` for a raw line, split with `split`
+pub fn parse_line<T, R>(line: &str, callback: impl FnOnce(T) -> R) -> Result<R, LineError>
+where
+    T: for<'a> TryParse<&'a &'a str, Error = ParsingError>,
+{
+    let tokens = split(line)?;
+    let args: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    Ok(parse(args.iter(), callback)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parsed;
+
+    #[test]
+    fn it_should_split_on_whitespace() {
+        assert_eq!(split("  add\tone   two \n").unwrap(), ["add", "one", "two"]);
+        assert!(split("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_should_honor_quotes_and_escapes() {
+        assert_eq!(split(r#"'a b' "c d" e\ f"#).unwrap(), ["a b", "c d", "e f"]);
+        assert_eq!(split(r#"'' "" x''y"#).unwrap(), ["", "", "xy"]);
+        assert_eq!(split(r#"'no \escape' "\"quoted\" \n \\""#).unwrap(), [r"no \escape", r#""quoted" \n \"#]);
+        assert_eq!(split(r#"it\'s "mixed"'quotes'"#).unwrap(), ["it's", "mixedquotes"]);
+    }
+
+    #[test]
+    fn it_should_reject_unterminated_lines() {
+        assert_eq!(split("'open"), Err(SplitError::UnterminatedQuote('\'')));
+        assert_eq!(split("\"open\\\""), Err(SplitError::UnterminatedQuote('"')));
+        assert_eq!(split("end\\"), Err(SplitError::TrailingEscape));
+    }
+
+    struct Words(Vec<String>);
+
+    impl<'a> TryParse<&'a &'a str> for Words {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a &'a str>>(mut value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            let words = value.by_ref().take(2).map(|word| word.to_string()).collect();
+            Ok(Parsed(Words(words), value))
+        }
+    }
+
+    #[test]
+    fn it_should_parse_a_line() {
+        assert_eq!(parse_line("one 'two three'", |Words(words)| words), Ok(vec![String::from("one"), String::from("two three")]));
+        assert_eq!(parse_line("a b c", |Words(words)| words), Err(LineError::Parsing(ParsingError::TooManyArguments)));
+        assert_eq!(parse_line("'a", |Words(words)| words), Err(LineError::Split(SplitError::UnterminatedQuote('\''))));
+    }
+}
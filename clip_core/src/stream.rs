@@ -0,0 +1,283 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Arguments parsed as they come, without collecting them first
+//!
+//! - `each` parses the values of a variadic argument one at a time, e.g. millions of file names
+//!   handed by `xargs`, where a `Vec` field would hold all of them
+//! - `Source` reads arguments from an `io::BufRead` one line at a time, each line being split as
+//!   a shell does (see `shlex::split`): e.g. commands sent to a control socket, parsed one after
+//!   the other with `Source::parse_next` while the connection stays open
+//! - `parse_lines` parses every line of a script independently, e.g. for a `--batch` mode reading
+//!   commands from the standard input
+//!
+//! ```
+//! use clip_core::stream::{each, Source};
+//!
+//! let sizes: Result<Vec<u32>, _> = each(["4", "16"].into_iter()).collect();
+//! assert_eq!(sizes, Ok(vec![4, 16]));
+//!
+//! let mut source = Source::new("add 'my file'\n\nremove it\n".as_bytes());
+//! assert_eq!(source.next().unwrap().unwrap(), ["add", "my file"]);
+//! assert_eq!(source.next().unwrap().unwrap(), ["remove", "it"]);
+//! assert_eq!(source.line_number(), 3);
+//! assert!(source.next().is_none());
+//! ```
+
+use crate::parser::{parse, ParsingError, Token, TryParse};
+use crate::shlex::{self, split, LineError};
+use std::io::BufRead;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// Iterator parsing each value into `T` when it is taken, see `each`
+#[derive(Debug)]
+pub struct Each<'a, T, I> {
+    values: I,
+    item: PhantomData<(&'a (), T)>,
+}
+
+/// Values parsed one at a time into `T`, failing with `ParsingError::BadType` as a field would
+pub fn each<'a, T, I>(values: I) -> Each<'a, T, I> {
+    Each { values, item: PhantomData }
+}
+
+impl<'a, T, I> Iterator for Each<'a, T, I>
+where
+    I: Iterator,
+    I::Item: Token<'a>,
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    type Item = Result<T, ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.values.next()?;
+        Some(value.text().and_then(|text| text.parse().map_err(ParsingError::bad_type)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+/// Error of a command read from a stream
+#[derive(Debug)]
+pub enum StreamError {
+    Io(std::io::Error),
+    Line(LineError),
+}
+
+impl From<std::io::Error> for StreamError {
+    fn from(err: std::io::Error) -> Self {
+        StreamError::Io(err)
+    }
+}
+
+impl From<LineError> for StreamError {
+    fn from(err: LineError) -> Self {
+        StreamError::Line(err)
+    }
+}
+
+impl std::fmt::Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Io(err) => err.fmt(f),
+            StreamError::Line(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+/// Arguments read line by line, blank lines being skipped
+///
+/// Only the current line is held in memory; as an iterator, it yields the arguments of each line.
+pub struct Source<R> {
+    reader: R,
+    line: String,
+    line_number: usize,
+}
+
+impl<R: BufRead> Source<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, line: String::new(), line_number: 0 }
+    }
+
+    /// Number of the line read last, from 1
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Reads the next line which is not blank, false at the end of the input
+    fn read_line(&mut self) -> std::io::Result<bool> {
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                return Ok(false);
+            }
+            self.line_number += 1;
+            if !self.line.trim().is_empty() {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Parses the next line into `T` and gives it to `callback`, None at the end of the input
+    ///
+    /// A line which fails to parse is reported without stopping the stream: the next call reads
+    /// the following line.
+    pub fn parse_next<T, Res>(&mut self, callback: impl FnOnce(T) -> Res) -> Option<Result<Res, StreamError>>
+    where
+        T: for<'a> TryParse<&'a &'a str, Error = ParsingError>,
+    {
+        match self.next()? {
+            Ok(tokens) => {
+                let args: Vec<&str> = tokens.iter().map(String::as_str).collect();
+                Some(parse(args.iter(), callback).map_err(|err| LineError::Parsing(err).into()))
+            }
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// Commands of a script, each line being parsed into `T` on its own, see `parse_lines`
+pub struct Lines<T, R> {
+    source: Source<R>,
+    command: PhantomData<T>,
+}
+
+/// Parses each line of `reader` into `T`
+///
+/// Blank lines and comments, starting with `#`, are skipped. A line which fails to parse yields
+/// an error and the next ones are still parsed; `Lines::line_number` tells where it was.
+///
+/// ```
+/// use clip_core::parser::{Parsed, ParsingError, TryParse};
+/// use clip_core::stream::parse_lines;
+///
+/// struct Sleep(u32);
+///
+/// impl<'a> TryParse<&'a &'a str> for Sleep {
+///     type Error = ParsingError;
+///
+///     fn try_parse<I: Iterator<Item = &'a &'a str>>(mut values: I) -> Result<Parsed<Self, I>, Self::Error> {
+///         let seconds = values.next().ok_or(ParsingError::TooFewArguments)?;
+///         Ok(Parsed(Sleep(seconds.parse().map_err(ParsingError::bad_type)?), values))
+///     }
+/// }
+///
+/// let script = "# waits\n1\n\n2 3\n4\n";
+/// let results: Vec<Result<u32, String>> = parse_lines::<Sleep, _>(script.as_bytes())
+///     .map(|sleep| sleep.map(|Sleep(seconds)| seconds).map_err(|err| err.to_string()))
+///     .collect();
+/// assert_eq!(results, [Ok(1), Err(String::from("unexpected argument")), Ok(4)]);
+/// ```
+pub fn parse_lines<T, R: BufRead>(reader: R) -> Lines<T, R> {
+    Lines { source: Source::new(reader), command: PhantomData }
+}
+
+impl<T, R> Lines<T, R> {
+    /// Number of the line parsed last, from 1
+    pub fn line_number(&self) -> usize {
+        self.source.line_number
+    }
+}
+
+impl<T, R> Iterator for Lines<T, R>
+where
+    R: BufRead,
+    T: for<'a> TryParse<&'a &'a str, Error = ParsingError>,
+{
+    type Item = Result<T, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.source.read_line() {
+                Ok(true) if self.source.line.trim_start().starts_with('#') => continue,
+                Ok(true) => return Some(shlex::parse_line(&self.source.line, |command| command).map_err(StreamError::Line)),
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err.into())),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Source<R> {
+    type Item = Result<Vec<String>, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_line() {
+            Ok(true) => Some(split(&self.line).map_err(|err| LineError::Split(err).into())),
+            Ok(false) => None,
+            Err(err) => Some(Err(err.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parsed;
+    use crate::shlex::SplitError;
+
+    /// `set <key> <value>`
+    struct Set(String, u8);
+
+    impl<'a> TryParse<&'a &'a str> for Set {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a &'a str>>(mut values: I) -> Result<Parsed<Self, I>, Self::Error> {
+            if values.next() != Some(&"set") {
+                return Err(ParsingError::VariantNotFound);
+            }
+            let key = values.next().ok_or(ParsingError::TooFewArguments)?.to_string();
+            let value = values.next().ok_or(ParsingError::TooFewArguments)?.parse().map_err(ParsingError::bad_type)?;
+            Ok(Parsed(Set(key, value), values))
+        }
+    }
+
+    #[test]
+    fn it_should_parse_values_one_at_a_time() {
+        let mut sizes = each::<u8, _>(["1", "x", "3"].into_iter());
+        assert_eq!(sizes.next(), Some(Ok(1)));
+        assert!(matches!(sizes.next(), Some(Err(ParsingError::BadType(_)))));
+        assert_eq!(sizes.next(), Some(Ok(3)));
+        assert_eq!(sizes.next(), None);
+    }
+
+    #[test]
+    fn it_should_parse_each_line_of_a_stream() {
+        let mut source = Source::new("set a 1\nset 'b c'\n  \nset d 'x\nset e 5".as_bytes());
+        assert_eq!(source.parse_next(|Set(key, value)| (key, value)).unwrap().unwrap(), (String::from("a"), 1));
+        assert!(matches!(
+            source.parse_next(|Set(key, _)| key),
+            Some(Err(StreamError::Line(LineError::Parsing(ParsingError::TooFewArguments))))
+        ));
+        assert!(matches!(
+            source.parse_next(|Set(key, _)| key),
+            Some(Err(StreamError::Line(LineError::Split(SplitError::UnterminatedQuote('\'')))))
+        ));
+        assert_eq!(source.line_number(), 4);
+        assert_eq!(source.parse_next(|Set(key, value)| (key, value)).unwrap().unwrap(), (String::from("e"), 5));
+        assert!(source.parse_next(|Set(key, _)| key).is_none());
+    }
+
+    #[test]
+    fn it_should_parse_the_lines_of_a_script_independently() {
+        let mut lines = parse_lines::<Set, _>("# settings\nset a 1\nset b x\n\n  # done\nset c 3\n".as_bytes());
+        assert_eq!(lines.next().unwrap().ok().map(|Set(key, value)| (key, value)), Some((String::from("a"), 1)));
+        assert!(matches!(lines.next(), Some(Err(StreamError::Line(LineError::Parsing(ParsingError::BadType(_)))))));
+        assert_eq!(lines.line_number(), 3);
+        assert_eq!(lines.next().unwrap().ok().map(|Set(key, _)| key), Some(String::from("c")));
+        assert!(lines.next().is_none());
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Helpers to regression-test a command line interface
+//!
+//! - `assert_parse_eq!(Type, ["a", "b"], expected)` parses the whole line into `Type` and compares
+//!   the result, `assert_parse_err!` comparing the error instead
+//! - `assert_help_snapshot!(Type)` compares the help message of `Type`, rendered without colors
+//!   and wrapped to `SNAPSHOT_WIDTH` columns whatever the terminal, with the snapshot stored in
+//!   `tests/snapshots/<Type>.help` of the calling crate. A missing snapshot is written, and all of
+//!   them are rewritten when the `CLIP_UPDATE_SNAPSHOTS` environment variable is set.
+//!
+//! ```
+//! use clip_core::assert_parse_eq;
+//! use clip_core::parser::{Parsed, ParsingError, TryParse};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Jobs(u8);
+//!
+//! impl<'a> TryParse<&'a &'a str> for Jobs {
+//!     type Error = ParsingError;
+//!
+//!     fn try_parse<I: Iterator<Item = &'a &'a str>>(mut value: I) -> Result<Parsed<Self, I>, Self::Error> {
+//!         let jobs = value.next().ok_or(ParsingError::TooFewArguments)?;
+//!         Ok(Parsed(Jobs(jobs.parse().map_err(ParsingError::bad_type)?), value))
+//!     }
+//! }
+//!
+//! assert_parse_eq!(Jobs, ["4"], Jobs(4));
+//! ```
+
+use crate::describe::command::AsCommand;
+use crate::parser::{ParsingError, TryParse};
+use std::path::Path;
+
+/// Width the help snapshots are wrapped to
+pub const SNAPSHOT_WIDTH: usize = 80;
+
+/// Environment variable asking to rewrite the help snapshots
+pub const UPDATE_SNAPSHOTS: &str = "CLIP_UPDATE_SNAPSHOTS";
+
+/// Value parsed from the whole of `args`, used by `assert_parse_eq!`
+pub fn parse<'a, T>(args: &'a [&'a str]) -> Result<T, ParsingError>
+where
+    T: TryParse<&'a &'a str, Error = ParsingError>,
+{
+    crate::parser::parse(args.iter(), |parsed| parsed)
+}
+
+/// Help message of `T` as stored in a snapshot: without colors and wrapped to `SNAPSHOT_WIDTH`
+pub fn help_snapshot<T: AsCommand + 'static>() -> String {
+    T::command_ref().help_with_width(SNAPSHOT_WIDTH)
+}
+
+/// Compares `help` with the snapshot `dir/name.help`, used by `assert_help_snapshot!`
+///
+/// The snapshot is written when it is missing or `UPDATE_SNAPSHOTS` is set.
+#[track_caller]
+pub fn assert_snapshot(dir: impl AsRef<Path>, name: &str, help: &str) {
+    let path = dir.as_ref().join(format!("{name}.help"));
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) if std::env::var_os(UPDATE_SNAPSHOTS).is_none() => expected,
+        _ => {
+            let written = std::fs::create_dir_all(dir.as_ref()).and_then(|()| std::fs::write(&path, help));
+            if let Err(err) = written {
+                panic!("cannot write the snapshot {}: {err}", path.display());
+            }
+            return;
+        }
+    };
+    if expected != help {
+        panic!(
+            "the help message differs from the snapshot {}, set {UPDATE_SNAPSHOTS} to update it\n\
+             --- snapshot\n{expected}\n--- help\n{help}",
+            path.display()
+        );
+    }
+}
+
+/// Asserts that the whole line parses into the expected value
+///
+/// `assert_parse_eq!(Type, ["a", "b"], expected)`
+#[macro_export]
+macro_rules! assert_parse_eq {
+    ($type:ty, [$($arg:expr),* $(,)?], $expected:expr $(,)?) => {
+        assert_eq!($crate::testing::parse::<$type>(&[$($arg),*]), Ok($expected))
+    };
+}
+
+/// Asserts that parsing the whole line fails with the expected error
+///
+/// `assert_parse_err!(Type, ["a", "b"], ParsingError::TooManyArguments)`
+#[macro_export]
+macro_rules! assert_parse_err {
+    ($type:ty, [$($arg:expr),* $(,)?], $expected:expr $(,)?) => {
+        assert_eq!($crate::testing::parse::<$type>(&[$($arg),*]).err(), Some($expected))
+    };
+}
+
+/// Asserts that the help message of a type matches its snapshot in `tests/snapshots`
+///
+/// `assert_help_snapshot!(Type)`, see the `testing` module.
+#[macro_export]
+macro_rules! assert_help_snapshot {
+    ($type:ty) => {
+        $crate::testing::assert_snapshot(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"),
+            stringify!($type),
+            &$crate::testing::help_snapshot::<$type>(),
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::arg::Arg;
+    use crate::describe::command::Command;
+
+    struct App;
+
+    impl AsCommand for App {
+        fn command() -> Command {
+            let mut app = Command::new("app", Some("an application"));
+            app.set_arguments(vec![Arg::new("file", Some("file to read"))]);
+            app
+        }
+    }
+
+    #[test]
+    fn it_should_compare_the_help_with_its_snapshot() {
+        let dir = std::env::temp_dir().join(format!("clip-snapshots-{}", std::process::id()));
+        let help = help_snapshot::<App>();
+        assert_snapshot(&dir, "App", &help);
+        assert_eq!(std::fs::read_to_string(dir.join("App.help")).unwrap(), help);
+        assert_snapshot(&dir, "App", &help);
+        let changed = std::panic::catch_unwind(|| assert_snapshot(&dir, "App", "other help"));
+        assert!(changed.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Durations and points in time written as people do
+//!
+//! - `HumanDuration`: numbers followed by a unit, e.g. `90s`, `1h30m`, `1.5d` or `250ms`
+//! - `Timestamp`: a date `2024-06-01` with an optional time `2024-06-01T12:30[:00]` or a number
+//!   of seconds since the epoch `@1717200000`. With the `time` feature, dates relative to now are
+//!   accepted as well: `now`, `today`, `yesterday`, `tomorrow`, or a duration before or after now,
+//!   `2h ago` or `in 3d`.
+//!
+//! Times are in UTC, a day starting at midnight UTC, from year 0 to year 9999, and are matched
+//! ignoring the case. An option of these types is displayed as `<DURATION>` or `<DATE>` in the
+//! help messages.
+//!
+//! ```
+//! use clip_core::time::{HumanDuration, Timestamp};
+//! use std::time::Duration;
+//!
+//! let timeout: HumanDuration = "1h30m".parse().unwrap();
+//! assert_eq!(timeout.0, Duration::from_secs(5400));
+//! let since: Timestamp = "2024-06-01".parse().unwrap();
+//! assert_eq!(since.unix_seconds(), 1717200000);
+//! assert_eq!(
+//!     "90x".parse::<HumanDuration>().unwrap_err().to_string(),
+//!     "invalid duration '90x': unknown unit 'x', expected ms, s, m, h, d or w"
+//! );
+//! ```
+
+use crate::describe::value::Placeholder;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// `0000-01-01T00:00:00Z` and `9999-12-31T23:59:59Z`, the range of the parsed timestamps
+const SECONDS_RANGE: std::ops::RangeInclusive<i64> = -62167219200..=253402300799;
+
+/// Units of a duration with their length in nanoseconds
+const UNITS: [(&[&str], u128); 8] = [
+    (&["ns", "nanosecond", "nanoseconds"], 1),
+    (&["us", "µs", "microsecond", "microseconds"], 1_000),
+    (&["ms", "millisecond", "milliseconds"], 1_000_000),
+    (&["s", "sec", "secs", "second", "seconds"], 1_000_000_000),
+    (&["m", "min", "mins", "minute", "minutes"], 60_000_000_000),
+    (&["h", "hr", "hrs", "hour", "hours"], 3_600_000_000_000),
+    (&["d", "day", "days"], 86_400_000_000_000),
+    (&["w", "week", "weeks"], 604_800_000_000_000),
+];
+
+/// Text which is not a duration or a timestamp, with the reason
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimeError {
+    Duration(String, String),
+    Timestamp(String, String),
+}
+
+impl std::fmt::Display for TimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeError::Duration(text, reason) => write!(f, "invalid duration '{text}': {reason}"),
+            TimeError::Timestamp(text, reason) => write!(f, "invalid date '{text}': {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for TimeError {}
+
+/// Duration written as numbers followed by units, e.g. `1h30m`
+///
+/// The units are `ns`, `us`, `ms`, `s`, `m`, `h`, `d` and `w`, or their names (`min`, `hours`..).
+/// Numbers may have a fractional part, and spaces may separate numbers from units and parts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanDuration(pub Duration);
+
+impl From<HumanDuration> for Duration {
+    fn from(duration: HumanDuration) -> Self {
+        duration.0
+    }
+}
+
+impl std::str::FromStr for HumanDuration {
+    type Err = TimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s).map(HumanDuration).map_err(|reason| TimeError::Duration(s.to_string(), reason))
+    }
+}
+
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return Err(String::from("expected a number followed by a unit, e.g. 90s or 1h30m"));
+    }
+    let mut nanos: u128 = 0;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+        let (number, after) = rest.split_at(number_end);
+        let after = after.trim_start();
+        let unit_end = after.find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace()).unwrap_or(after.len());
+        let (unit, after) = after.split_at(unit_end);
+        if number.is_empty() {
+            return Err(String::from("expected a number followed by a unit, e.g. 90s or 1h30m"));
+        }
+        if unit.is_empty() {
+            return Err(format!("missing unit after {number}, e.g. {number}s"));
+        }
+        let (_, length) = UNITS
+            .iter()
+            .find(|(names, _)| names.iter().any(|name| name.eq_ignore_ascii_case(unit)))
+            .ok_or_else(|| format!("unknown unit '{unit}', expected ms, s, m, h, d or w"))?;
+        // the fractional part is taken as a number of tenths, hundredths..
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() || fraction.contains('.') || fraction.len() > 9 {
+            return Err(format!("invalid number '{number}'"));
+        }
+        let scale = 10u128.pow(fraction.len() as u32);
+        let mantissa: u128 = format!("{whole}{fraction}").parse().map_err(|_| String::from("too long"))?;
+        nanos = mantissa
+            .checked_mul(*length)
+            .map(|part| part / scale)
+            .and_then(|part| nanos.checked_add(part))
+            .ok_or_else(|| String::from("too long"))?;
+        rest = after.trim_start();
+    }
+    let seconds = u64::try_from(nanos / 1_000_000_000).map_err(|_| String::from("too long"))?;
+    Ok(Duration::new(seconds, (nanos % 1_000_000_000) as u32))
+}
+
+/// Largest units first, e.g. `1d2h30m`, parsed back as the same duration
+impl std::fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut nanos = self.0.as_nanos();
+        if nanos == 0 {
+            return f.write_str("0s");
+        }
+        // weeks are left to days, which are more common
+        for (names, length) in UNITS[..7].iter().rev() {
+            if nanos >= *length {
+                write!(f, "{}{}", nanos / length, names[0])?;
+                nanos %= length;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Placeholder for HumanDuration {
+    fn placeholder() -> &'static str {
+        "DURATION"
+    }
+}
+
+/// Point in time, to the second, in UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    seconds: i64,
+}
+
+impl Timestamp {
+    /// Point `seconds` after the Unix epoch (before it when negative)
+    pub fn from_unix(seconds: i64) -> Self {
+        Timestamp { seconds }
+    }
+
+    /// Current time, the epoch when the clock is set before it
+    pub fn now() -> Self {
+        Self::try_from(SystemTime::now()).unwrap_or(Timestamp { seconds: 0 })
+    }
+
+    pub fn unix_seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        match u64::try_from(self.seconds) {
+            Ok(seconds) => UNIX_EPOCH + Duration::from_secs(seconds),
+            Err(_) => UNIX_EPOCH - Duration::from_secs(self.seconds.unsigned_abs()),
+        }
+    }
+
+    /// Parses `text`, the relative ones (`yesterday`, `2h ago`..) being relative to `now`
+    #[cfg(feature = "time")]
+    pub fn parse_relative_to(text: &str, now: Timestamp) -> Result<Self, TimeError> {
+        let lowercase = text.trim().to_lowercase();
+        parse_relative(&lowercase, now)
+            .unwrap_or_else(|| parse_timestamp(&lowercase))
+            .map_err(|reason| TimeError::Timestamp(text.to_string(), reason))
+    }
+}
+
+/// Fails for a time before the Unix epoch, which `SystemTime` cannot tell to the second
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = TimeError;
+
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        let after = time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| TimeError::Timestamp(format!("{time:?}"), String::from("before the Unix epoch")))?;
+        bounded(after.as_secs().try_into().unwrap_or(i64::MAX)).map_err(|reason| TimeError::Timestamp(format!("{time:?}"), reason))
+    }
+}
+
+impl std::str::FromStr for Timestamp {
+    type Err = TimeError;
+
+    #[cfg(feature = "time")]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_relative_to(s, Self::now())
+    }
+
+    #[cfg(not(feature = "time"))]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_timestamp(&s.trim().to_lowercase()).map_err(|reason| TimeError::Timestamp(s.to_string(), reason))
+    }
+}
+
+/// Date relative to `now` written in lowercase, None for any other text
+#[cfg(feature = "time")]
+fn parse_relative(text: &str, now: Timestamp) -> Option<Result<Timestamp, String>> {
+    let today = now.seconds - now.seconds.rem_euclid(SECONDS_PER_DAY);
+    let relative = |duration: &str, sign: i64| -> Result<Timestamp, String> {
+        let duration = parse_duration(duration)?;
+        let seconds = i64::try_from(duration.as_secs()).map_err(|_| String::from("too far"))?;
+        bounded(now.seconds.saturating_add(sign * seconds))
+    };
+    Some(match text {
+        "now" => Ok(now),
+        "today" => Ok(Timestamp::from_unix(today)),
+        "yesterday" => Ok(Timestamp::from_unix(today - SECONDS_PER_DAY)),
+        "tomorrow" => Ok(Timestamp::from_unix(today + SECONDS_PER_DAY)),
+        _ => match (text.strip_suffix(" ago"), text.strip_prefix("in ")) {
+            (Some(duration), _) => relative(duration, -1),
+            (None, Some(duration)) => relative(duration, 1),
+            (None, None) => return None,
+        },
+    })
+}
+
+/// Absolute date written in lowercase
+fn parse_timestamp(text: &str) -> Result<Timestamp, String> {
+    if let Some(seconds) = text.strip_prefix('@') {
+        return bounded(seconds.parse().map_err(|_| format!("expected a number of seconds after @, got '{seconds}'"))?);
+    }
+    let (date, time) = match text.split_once(['t', ' ']) {
+        Some((date, time)) => (date, Some(time.strip_suffix('z').unwrap_or(time))),
+        None => (text, None),
+    };
+    let fields: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = fields[..] else {
+        return Err(String::from(if cfg!(feature = "time") {
+            "expected YYYY-MM-DD with an optional time THH:MM[:SS], @SECONDS, now, today, yesterday, tomorrow, \
+             or a duration followed by ago"
+        } else {
+            "expected YYYY-MM-DD with an optional time THH:MM[:SS] or @SECONDS"
+        }));
+    };
+    let number = |field: &str, what: &str| field.parse::<i64>().map_err(|_| format!("invalid {what} '{field}'"));
+    let (year, month, day) = (number(year, "year")?, number(month, "month")?, number(day, "day")?);
+    if !(0..=9999).contains(&year) {
+        return Err(String::from("too far, expected a year from 0 to 9999"));
+    }
+    if !(1..=12).contains(&month) {
+        return Err(format!("invalid month {month}, expected 1 to 12"));
+    }
+    if !(1..=days_in_month(year, month)).contains(&day) {
+        return Err(format!("invalid day {day}, {year}-{month:02} has {} days", days_in_month(year, month)));
+    }
+    let mut seconds = days_from_civil(year, month, day) * SECONDS_PER_DAY;
+    if let Some(time) = time {
+        let fields: Vec<&str> = time.split(':').collect();
+        let (hour, minute, second) = match fields[..] {
+            [hour, minute] => (hour, minute, "0"),
+            [hour, minute, second] => (hour, minute, second),
+            _ => return Err(format!("invalid time '{time}', expected HH:MM or HH:MM:SS")),
+        };
+        let (hour, minute, second) = (number(hour, "hour")?, number(minute, "minute")?, number(second, "second")?);
+        if hour > 23 || minute > 59 || second > 59 || hour.min(minute).min(second) < 0 {
+            return Err(format!("invalid time '{time}'"));
+        }
+        seconds += hour * 3600 + minute * 60 + second;
+    }
+    Ok(Timestamp::from_unix(seconds))
+}
+
+/// Timestamp `seconds` after the epoch, failing outside of `SECONDS_RANGE`
+fn bounded(seconds: i64) -> Result<Timestamp, String> {
+    match SECONDS_RANGE.contains(&seconds) {
+        true => Ok(Timestamp::from_unix(seconds)),
+        false => Err(String::from("too far, expected a year from 0 to 9999")),
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since the Unix epoch of a date of the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Year, month and day of a number of days since the Unix epoch
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// `2024-06-01T12:30:00Z`, parsed back as the same timestamp
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = civil_from_days(self.seconds.div_euclid(SECONDS_PER_DAY));
+        let time = self.seconds.rem_euclid(SECONDS_PER_DAY);
+        write!(f, "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z", time / 3600, time / 60 % 60, time % 60)
+    }
+}
+
+impl Placeholder for Timestamp {
+    fn placeholder() -> &'static str {
+        "DATE"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duration(text: &str) -> Result<Duration, String> {
+        text.parse::<HumanDuration>().map(Duration::from).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn it_should_parse_durations() {
+        assert_eq!(duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(duration("1.5 Hours 250ms"), Ok(Duration::from_millis(5_400_250)));
+        assert_eq!(duration("2w"), Ok(Duration::from_secs(14 * 86400)));
+        assert_eq!(duration("90"), Err(String::from("invalid duration '90': missing unit after 90, e.g. 90s")));
+        assert_eq!(duration("h"), Err(String::from("invalid duration 'h': expected a number followed by a unit, e.g. 90s or 1h30m")));
+        assert_eq!(duration("1.2.3s"), Err(String::from("invalid duration '1.2.3s': invalid number '1.2.3'")));
+        assert!(duration("99999999999999999999999w").is_err());
+    }
+
+    #[test]
+    fn it_should_display_durations_as_parsed() {
+        for (text, displayed) in [("90s", "1m30s"), ("0ms", "0s"), ("26h", "1d2h"), ("1.5s", "1s500ms"), ("3w", "21d"), ("7ns", "7ns")] {
+            let parsed: HumanDuration = text.parse().unwrap();
+            assert_eq!(parsed.to_string(), displayed);
+            assert_eq!(displayed.parse(), Ok(parsed));
+        }
+    }
+
+    fn timestamp(text: &str) -> Result<String, String> {
+        text.parse::<Timestamp>().map(|time| time.to_string()).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn it_should_parse_timestamps() {
+        assert_eq!(timestamp("2024-06-01"), Ok(String::from("2024-06-01T00:00:00Z")));
+        assert_eq!(timestamp("2024-02-29T08:05Z"), Ok(String::from("2024-02-29T08:05:00Z")));
+        assert_eq!(timestamp("2024-02-29t08:05z"), Ok(String::from("2024-02-29T08:05:00Z")));
+        assert_eq!(timestamp("1969-12-31 23:59:59"), Ok(String::from("1969-12-31T23:59:59Z")));
+        assert_eq!(timestamp("@0"), Ok(String::from("1970-01-01T00:00:00Z")));
+        assert_eq!(timestamp("2023-02-29"), Err(String::from("invalid date '2023-02-29': invalid day 29, 2023-02 has 28 days")));
+        assert_eq!(timestamp("2024-13-01"), Err(String::from("invalid date '2024-13-01': invalid month 13, expected 1 to 12")));
+        assert_eq!(timestamp("2024-06-01T24:00"), Err(String::from("invalid date '2024-06-01T24:00': invalid time '24:00'")));
+        assert!(timestamp("last week").is_err());
+        let too_far = |text: &str| Err(format!("invalid date '{text}': too far, expected a year from 0 to 9999"));
+        assert_eq!(timestamp("99999999999999-01-01"), too_far("99999999999999-01-01"));
+        assert_eq!(timestamp("@-9223372036854775808"), too_far("@-9223372036854775808"));
+        assert_eq!(timestamp("9999-12-31T23:59:59"), Ok(String::from("9999-12-31T23:59:59Z")));
+        assert_eq!(Timestamp::try_from(Timestamp::from_unix(5).to_system_time()), Ok(Timestamp::from_unix(5)));
+        let before = Timestamp::try_from(Timestamp::from_unix(-5).to_system_time()).unwrap_err().to_string();
+        assert!(before.ends_with("before the Unix epoch"), "{before}");
+    }
+
+    #[cfg(not(feature = "time"))]
+    #[test]
+    fn it_should_reject_relative_dates_without_the_feature() {
+        assert_eq!(
+            timestamp("yesterday"),
+            Err(String::from("invalid date 'yesterday': expected YYYY-MM-DD with an optional time THH:MM[:SS] or @SECONDS"))
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn it_should_parse_relative_dates() {
+        let now = Timestamp::from_unix(1717245000); // 2024-06-01T12:30:00Z
+        let parse = |text: &str| Timestamp::parse_relative_to(text, now).map(|time| time.to_string()).map_err(|err| err.to_string());
+        assert_eq!(parse("now"), Ok(now.to_string()));
+        assert_eq!(parse("Yesterday"), Ok(String::from("2024-05-31T00:00:00Z")));
+        assert_eq!(parse("1h30m ago"), Ok(String::from("2024-06-01T11:00:00Z")));
+        assert_eq!(parse("1H30M AGO"), Ok(String::from("2024-06-01T11:00:00Z")));
+        assert_eq!(parse("In 1d"), Ok(String::from("2024-06-02T12:30:00Z")));
+        assert_eq!(parse("2024-06-01"), Ok(String::from("2024-06-01T00:00:00Z")));
+        assert_eq!(parse("-1-01-01"), Err(String::from("invalid date '-1-01-01': expected YYYY-MM-DD with an optional time THH:MM[:SS], @SECONDS, now, today, yesterday, tomorrow, or a duration followed by ago")));
+        assert_eq!(parse("in 15000000w"), Err(String::from("invalid date 'in 15000000w': too far, expected a year from 0 to 9999")));
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Decisions taken by the derived parsers, to debug why a line parsed the way it did
+//!
+//! With the `trace` feature, the parsers generated by the TryParse derive report each value given
+//! to an argument, each variant selected by a keyword and each fallback taken. Every decision is a
+//! `tracing` event of level TRACE and target `clip::parse`, with the fields of its `Event`, and is
+//! given to the tracer set by `with_tracer` on the current thread. Values of secret arguments are
+//! masked. Without the feature, nothing is reported and the calls compile to nothing.
+//!
+//! ```
+//! use clip_core::trace::traced;
+//!
+//! let (_, events) = traced(|| clip_core::trace::value("count", "3", false));
+//! # #[cfg(feature = "trace")]
+//! assert_eq!(events, ["'3' given to count"]);
+//! ```
+
+use crate::parser::Token;
+use crate::secret::MASK;
+use std::cell::RefCell;
+
+/// A parsing decision
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event<'e> {
+    /// `value` was given to the positional argument or the option `argument`
+    Value { argument: &'static str, value: &'e str },
+    /// the flag `option` was found
+    Flag { option: &'static str },
+    /// `keyword` selected `variant`
+    Variant { keyword: &'e str, variant: &'static str },
+    /// `keyword` matched no variant, the default command taking it as its first argument if any
+    Fallback { keyword: &'e str, variant: Option<&'static str> },
+}
+
+impl std::fmt::Display for Event<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::Value { argument, value } => write!(f, "'{value}' given to {argument}"),
+            Event::Flag { option } => write!(f, "flag {option} set"),
+            Event::Variant { keyword, variant } => write!(f, "'{keyword}' selects {variant}"),
+            Event::Fallback { keyword, variant: Some(variant) } => {
+                write!(f, "'{keyword}' matches no variant, falling back on {variant}")
+            }
+            Event::Fallback { keyword, variant: None } => write!(f, "'{keyword}' matches no variant"),
+        }
+    }
+}
+
+type Tracer = Box<dyn FnMut(&Event<'_>)>;
+
+thread_local! {
+    static TRACER: RefCell<Option<Tracer>> = const { RefCell::new(None) };
+}
+
+/// Calls `f`, the parsing decisions it takes on this thread being given to `tracer`
+pub fn with_tracer<R>(tracer: impl FnMut(&Event<'_>) + 'static, f: impl FnOnce() -> R) -> R {
+    let previous = TRACER.with(|cell| cell.replace(Some(Box::new(tracer))));
+    let result = f();
+    TRACER.with(|cell| *cell.borrow_mut() = previous);
+    result
+}
+
+/// Same as `with_tracer`, the decisions being returned as text
+pub fn traced<R>(f: impl FnOnce() -> R) -> (R, Vec<String>) {
+    let events = std::rc::Rc::new(RefCell::new(Vec::new()));
+    let sink = events.clone();
+    let result = with_tracer(move |event| sink.borrow_mut().push(event.to_string()), f);
+    let events = events.take();
+    (result, events)
+}
+
+/// Target of the `tracing` events
+pub const TARGET: &str = "clip::parse";
+
+fn emit(event: Event<'_>) {
+    #[cfg(feature = "trace")]
+    match event {
+        Event::Value { argument, value } => tracing::trace!(target: TARGET, argument, value, "{event}"),
+        Event::Flag { option } => tracing::trace!(target: TARGET, option, "{event}"),
+        Event::Variant { keyword, variant } => tracing::trace!(target: TARGET, keyword, variant, "{event}"),
+        Event::Fallback { keyword, variant } => tracing::trace!(target: TARGET, keyword, variant, "{event}"),
+    }
+    // taken out while called, so that a tracer may parse without reporting to itself
+    let Some(mut tracer) = TRACER.with(|cell| cell.borrow_mut().take()) else {
+        return;
+    };
+    tracer(&event);
+    TRACER.with(|cell| {
+        cell.borrow_mut().get_or_insert(tracer);
+    });
+}
+
+#[cfg(feature = "trace")]
+fn enabled() -> bool {
+    tracing::enabled!(target: TARGET, tracing::Level::TRACE) || TRACER.with(|cell| cell.borrow().is_some())
+}
+
+#[cfg(not(feature = "trace"))]
+fn enabled() -> bool {
+    false
+}
+
+/// Reports `value` given to `argument`, masked when `secret`
+pub fn value<'a>(argument: &'static str, value: impl Token<'a>, secret: bool) {
+    if enabled() {
+        let text = value.os_str().to_string_lossy();
+        emit(Event::Value { argument, value: if secret { MASK } else { &text } });
+    }
+}
+
+/// Reports the flag `option`
+pub fn flag(option: &'static str) {
+    if enabled() {
+        emit(Event::Flag { option });
+    }
+}
+
+/// Reports `keyword` selecting `variant`
+pub fn variant(keyword: &str, variant: &'static str) {
+    if enabled() {
+        emit(Event::Variant { keyword, variant });
+    }
+}
+
+/// Reports `keyword` matching no variant, `variant` being the default command if any
+pub fn fallback(keyword: &str, variant: Option<&'static str>) {
+    if enabled() {
+        emit(Event::Fallback { keyword, variant });
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_report_to_the_tracer_of_the_scope() {
+        let ((), events) = traced(|| {
+            variant("add", "Add");
+            value("--password", "hunter2", true);
+            let ((), inner) = traced(|| flag("--verbose"));
+            assert_eq!(inner, ["flag --verbose set"]);
+            fallback("x", None);
+        });
+        assert_eq!(events, ["'add' selects Add", "'***' given to --password", "'x' matches no variant"]);
+        value("count", "3", false);
+    }
+
+    /// Subscriber keeping the fields of the events
+    struct Fields(std::sync::Mutex<Vec<String>>);
+
+    struct Line(String);
+
+    impl tracing::field::Visit for Line {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push_str(&format!(" {}={value:?}", field.name()));
+        }
+    }
+
+    impl tracing::Subscriber for Fields {
+        fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+            metadata.target() == TARGET
+        }
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut line = Line(event.metadata().level().to_string());
+            event.record(&mut line);
+            self.0.lock().unwrap().push(line.0);
+        }
+        fn enter(&self, _: &tracing::span::Id) {}
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[test]
+    fn it_should_emit_tracing_events() {
+        let subscriber = std::sync::Arc::new(Fields(Default::default()));
+        tracing::subscriber::with_default(subscriber.clone(), || {
+            value("--password", "hunter2", true);
+            fallback("x", Some("Run"));
+        });
+        assert_eq!(
+            *subscriber.0.lock().unwrap(),
+            [
+                "TRACE message='***' given to --password argument=\"--password\" value=\"***\"",
+                "TRACE message='x' matches no variant, falling back on Run keyword=\"x\" variant=\"Run\"",
+            ]
+        );
+    }
+}
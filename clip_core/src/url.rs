@@ -0,0 +1,223 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! URL arguments, e.g. `https://user@host:8080/path?query#fragment`
+//!
+//! With the `url` feature, `url::Url` fields are displayed as `<URL>` in the help messages and
+//! completed with the known host names. A `UrlArg` field is a `url::Url` which also tells why a
+//! URL is rejected (`missing scheme, e.g. https://example.com`) instead of the terse errors of the
+//! `url` crate.
+//!
+//! The URL is normalized by the `url` crate, e.g. `HTTP://Example.com` becomes
+//! `http://example.com/`; `as_url` gives it to the code using `url::Url`.
+//!
+//! ```
+//! use clip_core::url::UrlArg;
+//!
+//! let url: UrlArg = "https://example.com:8443/api?v=2".parse().unwrap();
+//! assert_eq!((url.scheme(), url.host(), url.port()), ("https", Some("example.com"), Some(8443)));
+//! assert_eq!((url.path(), url.query()), ("/api", Some("v=2")));
+//! assert_eq!(
+//!     "example.com".parse::<UrlArg>().unwrap_err().to_string(),
+//!     "invalid URL 'example.com': missing scheme, e.g. https://example.com"
+//! );
+//! ```
+
+use crate::describe::arg::ValueHint;
+use crate::describe::value::{Placeholder, TypeHint};
+pub use ::url::Url;
+
+/// Text which is not a URL, with the reason
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlError {
+    pub url: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for UrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid URL '{}': {}", self.url, self.reason)
+    }
+}
+
+impl std::error::Error for UrlError {}
+
+/// `url::Url` telling why a URL is invalid when parsed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UrlArg(Url);
+
+impl UrlArg {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    pub fn as_url(&self) -> &Url {
+        &self.0
+    }
+
+    pub fn scheme(&self) -> &str {
+        self.0.scheme()
+    }
+
+    /// Host name or IP address, IPv6 ones keeping their brackets; None for a `scheme:path` URL or
+    /// an empty host, e.g. `file:///etc/hosts`
+    pub fn host(&self) -> Option<&str> {
+        self.0.host_str().filter(|host| !host.is_empty())
+    }
+
+    /// Port given explicitly, None when it is empty or the default one of the scheme, e.g.
+    /// `http://host:/` or `http://host:80/`
+    pub fn port(&self) -> Option<u16> {
+        self.0.port()
+    }
+
+    /// Port given, or the default one of the scheme
+    pub fn port_or_default(&self) -> Option<u16> {
+        self.0.port_or_known_default().or(match self.scheme() {
+            "ssh" => Some(22),
+            _ => None,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        self.0.path()
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.0.query()
+    }
+
+    pub fn fragment(&self) -> Option<&str> {
+        self.0.fragment()
+    }
+}
+
+impl From<UrlArg> for Url {
+    fn from(url: UrlArg) -> Self {
+        url.0
+    }
+}
+
+impl std::str::FromStr for UrlArg {
+    type Err = UrlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s).map(UrlArg).map_err(|reason| UrlError { url: s.to_string(), reason })
+    }
+}
+
+fn parse(text: &str) -> Result<Url, String> {
+    if text.is_empty() {
+        return Err(String::from("empty URL"));
+    }
+    if text.contains(char::is_whitespace) {
+        return Err(String::from("contains whitespace, which must be written %20"));
+    }
+    // `host:port` would be read as the scheme `host`
+    match text.find(':') {
+        Some(end) if end > 0 && !text[end + 1..].starts_with(|c: char| c.is_ascii_digit()) => (),
+        _ => return Err(format!("missing scheme, e.g. https://{text}")),
+    }
+    Url::parse(text).map_err(|err| {
+        let scheme = &text[..text.find(':').unwrap_or_default()];
+        let authority = text.split_once("://").map_or("", |(_, rest)| rest);
+        let authority = &authority[..authority.find(['/', '?', '#']).unwrap_or(authority.len())];
+        let host_and_port = &authority[authority.rfind('@').map_or(0, |at| at + 1)..];
+        match err {
+            ::url::ParseError::EmptyHost => format!("missing host, e.g. {scheme}://example.com"),
+            ::url::ParseError::InvalidPort => {
+                let port = host_and_port.rsplit_once(':').map_or("", |(_, port)| port);
+                format!("invalid port '{port}', expected a number up to 65535")
+            }
+            ::url::ParseError::InvalidIpv4Address
+            | ::url::ParseError::InvalidIpv6Address
+            | ::url::ParseError::InvalidDomainCharacter
+            | ::url::ParseError::IdnaError => {
+                // the colons of an IPv6 address are inside brackets
+                let host = match host_and_port.rfind(':') {
+                    Some(colon) if !host_and_port[colon..].contains(']') => &host_and_port[..colon],
+                    _ => host_and_port,
+                };
+                format!("invalid host '{host}'")
+            }
+            ::url::ParseError::RelativeUrlWithoutBase => format!("invalid scheme '{scheme}'"),
+            err => err.to_string(),
+        }
+    })
+}
+
+/// The URL as normalized
+impl std::fmt::Display for UrlArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Placeholder for Url {
+    fn placeholder() -> &'static str {
+        "URL"
+    }
+}
+
+impl TypeHint for Url {
+    fn value_hint() -> ValueHint {
+        ValueHint::Hostname
+    }
+}
+
+impl Placeholder for UrlArg {
+    fn placeholder() -> &'static str {
+        Url::placeholder()
+    }
+}
+
+impl TypeHint for UrlArg {
+    fn value_hint() -> ValueHint {
+        Url::value_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        text.parse::<UrlArg>().unwrap_err().reason
+    }
+
+    #[test]
+    fn it_should_split_a_url_into_its_parts() {
+        let url: UrlArg = "ssh://git@[::1]:2222/repo.git#main".parse().unwrap();
+        assert_eq!((url.scheme(), url.host(), url.port()), ("ssh", Some("[::1]"), Some(2222)));
+        assert_eq!((url.path(), url.query(), url.fragment()), ("/repo.git", None, Some("main")));
+        let url: UrlArg = "HTTP://Example.com".parse().unwrap();
+        assert_eq!((url.host(), url.path(), url.port_or_default()), (Some("example.com"), "/", Some(80)));
+        assert_eq!(url.to_string(), "http://example.com/");
+        let url: UrlArg = "mailto:me@example.com?subject=hi".parse().unwrap();
+        assert_eq!((url.host(), url.path(), url.query()), (None, "me@example.com", Some("subject=hi")));
+        let url: UrlArg = "file:///etc/hosts".parse().unwrap();
+        assert_eq!((url.host(), url.path()), (None, "/etc/hosts"));
+        assert_eq!(Url::from(url).as_str(), "file:///etc/hosts");
+        let url: UrlArg = "http://host:/index.html".parse().unwrap();
+        assert_eq!((url.host(), url.port(), url.port_or_default()), (Some("host"), None, Some(80)));
+        assert_eq!(url.path(), "/index.html");
+    }
+
+    #[test]
+    fn it_should_tell_why_a_url_is_invalid() {
+        assert_eq!(error("localhost:8080"), "missing scheme, e.g. https://localhost:8080");
+        assert_eq!(error("https://"), "missing host, e.g. https://example.com");
+        assert_eq!(error("https://example.com:99999"), "invalid port '99999', expected a number up to 65535");
+        assert_eq!(error("https://exa|mple.com"), "invalid host 'exa|mple.com'");
+        assert_eq!(error("https://[::g]/"), "invalid host '[::g]'");
+        assert_eq!(error("1http://host"), "invalid scheme '1http'");
+        assert_eq!(error("https://my host"), "contains whitespace, which must be written %20");
+    }
+}
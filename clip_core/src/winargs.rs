@@ -0,0 +1,110 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Splitting of raw command lines the way Windows does (`CommandLineToArgvW`)
+//!
+//! Windows programs receive their command line unsplit. Arguments are separated by spaces or tabs
+//! outside of double quotes, and backslashes are only special before a double quote:
+//! - `2n` backslashes followed by `"` give `n` backslashes, the quote opening or closing a quoted
+//!   part
+//! - `2n + 1` backslashes followed by `"` give `n` backslashes and a literal `"`
+//! - `""` within a quoted part gives a literal `"`
+//!
+//! The program name, first on the line, is only delimited by quotes or whitespace.
+//!
+//! ```
+//! use clip_core::winargs::split;
+//!
+//! let args = split(r#""C:\Program Files\app.exe" copy "my file.txt" C:\dir\ 2\"x"#);
+//! assert_eq!(args, [r"C:\Program Files\app.exe", "copy", "my file.txt", r"C:\dir\", r#"2"x"#]);
+//! ```
+
+/// Splits a whole command line, program name included
+pub fn split(line: &str) -> Vec<String> {
+    let line = line.trim_start_matches([' ', '\t']);
+    if line.is_empty() {
+        return Vec::new();
+    }
+    let (program, rest) = match line.strip_prefix('"') {
+        Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+        None => line.split_once([' ', '\t']).unwrap_or((line, "")),
+    };
+    let mut args = vec![program.to_string()];
+    args.extend(split_args(rest));
+    args
+}
+
+/// Splits the arguments of a command line, without a program name
+pub fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    // None between arguments, so that quotes can make an empty one
+    let mut current: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let mut backslashes = 1;
+                while chars.next_if_eq(&'\\').is_some() {
+                    backslashes += 1;
+                }
+                let arg = current.get_or_insert_with(String::new);
+                if chars.peek() == Some(&'"') {
+                    arg.extend(std::iter::repeat_n('\\', backslashes / 2));
+                    if backslashes % 2 == 1 {
+                        arg.push('"');
+                        chars.next();
+                    }
+                } else {
+                    arg.extend(std::iter::repeat_n('\\', backslashes));
+                }
+            }
+            '"' if quoted && chars.next_if_eq(&'"').is_some() => current.get_or_insert_with(String::new).push('"'),
+            '"' => {
+                current.get_or_insert_with(String::new);
+                quoted = !quoted;
+            }
+            ' ' | '\t' if !quoted => args.extend(current.take()),
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_split_on_spaces_and_tabs() {
+        assert_eq!(split_args("  a\tb   c "), ["a", "b", "c"]);
+        assert!(split_args("   ").is_empty());
+        assert_eq!(split_args("\"\" a\"\"\""), ["", "a\""]);
+    }
+
+    #[test]
+    fn it_should_apply_backslash_rules() {
+        assert_eq!(split_args(r#""a b c" d e"#), ["a b c", "d", "e"]);
+        assert_eq!(split_args(r#""ab\"c" "\\" d"#), [r#"ab"c"#, r"\", "d"]);
+        assert_eq!(split_args(r#"a\\\b d"e f"g h"#), [r"a\\\b", "de fg", "h"]);
+        assert_eq!(split_args(r#"a\\\"b c d"#), [r#"a\"b"#, "c", "d"]);
+        assert_eq!(split_args(r#"a\\\\"b c" d e"#), [r"a\\b c", "d", "e"]);
+        assert_eq!(split_args(r#""a ""b"" c""#), [r#"a "b" c"#]);
+    }
+
+    #[test]
+    fn it_should_take_the_program_name_as_is() {
+        assert_eq!(split(r#"C:\bin\app.exe "x y""#), [r"C:\bin\app.exe", "x y"]);
+        assert_eq!(split(r#""C:\a b\app\"  z"#), [r"C:\a b\app\", "z"]);
+        assert_eq!(split(r#"app"x y"#), [r#"app"x"#, "y"]);
+        assert!(split("").is_empty());
+    }
+}
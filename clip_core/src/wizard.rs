@@ -0,0 +1,430 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Guided mode: walks an argument tree and prompts for each value
+//!
+//! The wizard relies on the `AsArg` description of a type to know what to ask for. Each leaf is
+//! prompted with its name and description, choices are listed and validated, groups are walked
+//! recursively. An empty answer takes the default value of the argument, shown in the prompt.
+//! Collected answers form an argument line that is finally parsed with `TryParse`, so the wizard
+//! never builds a value the command line could not have produced, and a configuration file which
+//! can be saved for the next runs.
+
+use crate::describe::arg::{Arg, ArgType, AsArg, Choices, Commands, GetArgs};
+use crate::parser::{Parsed, ParsingError, TryParse};
+use crate::secret::read_hidden;
+use std::collections::VecDeque;
+use std::io::{BufRead, IsTerminal, Write};
+
+#[derive(Debug)]
+pub enum WizardError {
+    /// reading the answers or writing the questions failed
+    Io(std::io::Error),
+    /// collected answers could not be parsed into the expected type
+    Parsing(ParsingError),
+}
+
+impl From<std::io::Error> for WizardError {
+    fn from(err: std::io::Error) -> Self {
+        WizardError::Io(err)
+    }
+}
+
+impl From<ParsingError> for WizardError {
+    fn from(err: ParsingError) -> Self {
+        WizardError::Parsing(err)
+    }
+}
+
+/// Result of a guided session: the populated value, its equivalent argument line and the answers
+/// by argument name
+#[derive(Debug, PartialEq)]
+pub struct Guided<T>(pub T, pub Vec<String>, pub Vec<(String, String)>);
+
+impl<T> Guided<T> {
+    /// Joins the collected arguments into a single line, quoting the ones which need it
+    pub fn command_line(&self) -> String {
+        crate::record::command_line(&self.1)
+    }
+
+    /// Answers as `key = "value"` lines, the keys being named like the ones of the layered
+    /// configuration, e.g. `dry_run` for `dry-run`
+    ///
+    /// The choices made without an argument name, such as the variant of a top-level enum, are
+    /// only part of the command line.
+    pub fn config_file(&self) -> String {
+        let mut config = String::new();
+        for (name, answer) in &self.2 {
+            let key = name.trim_start_matches('-').replace('-', "_");
+            let mut value = String::new();
+            for c in answer.chars() {
+                match c {
+                    '\n' => value.push_str("\\n"),
+                    '\t' => value.push_str("\\t"),
+                    '"' | '\\' => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    c => value.push(c),
+                }
+            }
+            config.push_str(&format!("{key} = \"{value}\"\n"));
+        }
+        config
+    }
+}
+
+/// Argument line being collected, with the answers given to named arguments
+#[derive(Default)]
+struct Answers {
+    line: Vec<String>,
+    named: Vec<(String, String)>,
+}
+
+impl Answers {
+    fn push(&mut self, arg: Option<&Arg>, answer: String) {
+        if let Some(arg) = arg {
+            self.named.push((arg.value.name().to_string(), answer.clone()));
+        }
+        self.line.push(answer);
+    }
+}
+
+/// Prompts questions on `output` and reads answers from `input`
+pub struct Wizard<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    /// answers already given on the command line, used before prompting
+    given: VecDeque<String>,
+}
+
+impl<R: BufRead, W: Write> Wizard<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            given: VecDeque::new(),
+        }
+    }
+
+    /// Walks the `T` argument tree, prompting for each value, then parses the answers
+    pub fn run<T>(&mut self) -> Result<Guided<T>, WizardError>
+    where
+        T: AsArg + for<'a> TryParse<&'a str, Error = ParsingError>,
+    {
+        self.complete(&[])
+    }
+
+    /// Same as `run` where `args` answer the first questions, only the missing arguments being
+    /// prompted for
+    pub fn complete<T>(&mut self, args: &[&str]) -> Result<Guided<T>, WizardError>
+    where
+        T: AsArg + for<'a> TryParse<&'a str, Error = ParsingError>,
+    {
+        self.given = args.iter().map(|arg| arg.to_string()).collect();
+        let mut answers = Answers::default();
+        self.ask_type(&T::arguments(), &mut answers)?;
+        let Parsed(value, mut rest) = T::try_parse(answers.line.iter().map(String::as_str))?;
+        if rest.next().is_some() {
+            return Err(WizardError::Parsing(ParsingError::TooManyArguments));
+        }
+        Ok(Guided(value, answers.line, answers.named))
+    }
+
+    fn ask_type(&mut self, r#type: &ArgType, answers: &mut Answers) -> Result<(), WizardError> {
+        match r#type {
+            ArgType::Value => Ok(()),
+            ArgType::Group(group) => {
+                for arg in group.get_args() {
+                    self.ask(arg, answers)?;
+                }
+                Ok(())
+            }
+            ArgType::Choices(Choices(choices)) | ArgType::Commands(Commands(choices)) => {
+                let chosen = self.choose(None, choices)?;
+                answers.push(None, chosen.value.name().to_string());
+                self.ask_type(&chosen.r#type, answers)
+            }
+        }
+    }
+
+    fn ask(&mut self, arg: &Arg, answers: &mut Answers) -> Result<(), WizardError> {
+        match &arg.r#type {
+            ArgType::Value => {
+                let question = format!("{:#}", arg.value);
+                let answer = if arg.secret { self.prompt_hidden(&question)? } else { self.prompt(&question)? };
+                let answer = match &arg.value.default {
+                    Some(default) if answer.is_empty() => default.to_string(),
+                    _ => answer,
+                };
+                answers.push(Some(arg), answer);
+                Ok(())
+            }
+            ArgType::Group(_) => self.ask_type(&arg.r#type, answers),
+            ArgType::Choices(Choices(choices)) | ArgType::Commands(Commands(choices)) => {
+                let chosen = self.choose(Some(arg), choices)?;
+                answers.push(Some(arg), chosen.value.name().to_string());
+                self.ask_type(&chosen.r#type, answers)
+            }
+        }
+    }
+
+    /// Lists the choices and asks until one of them is selected, by name or by index, an empty
+    /// answer selecting the default choice of `parent` if any
+    fn choose<'c>(&mut self, parent: Option<&Arg>, choices: &'c [Arg]) -> Result<&'c Arg, WizardError> {
+        if let Some(given) = self.given.pop_front() {
+            return choices
+                .iter()
+                .find(|choice| choice.value.name().eq_ignore_ascii_case(&given))
+                .ok_or(WizardError::Parsing(ParsingError::VariantNotFound));
+        }
+        if let Some(parent) = parent {
+            writeln!(self.output, "{}", parent.value.line(false))?;
+        }
+        for (index, choice) in choices.iter().enumerate() {
+            writeln!(self.output, "  {}) {:#}", index + 1, choice.value)?;
+        }
+        loop {
+            let answer = self.prompt("choice")?;
+            let answer = match parent.and_then(|parent| parent.value.default.as_deref()) {
+                Some(default) if answer.is_empty() => default.to_string(),
+                _ => answer,
+            };
+            let found = answer
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| index.checked_sub(1))
+                .and_then(|index| choices.get(index))
+                .or_else(|| {
+                    choices
+                        .iter()
+                        .find(|choice| choice.value.name().eq_ignore_ascii_case(&answer))
+                });
+            if let Some(choice) = found {
+                return Ok(choice);
+            }
+            writeln!(self.output, "invalid choice '{answer}'")?;
+        }
+    }
+
+    fn prompt(&mut self, question: &str) -> Result<String, WizardError> {
+        if let Some(given) = self.given.pop_front() {
+            return Ok(given);
+        }
+        write!(self.output, "{question}: ")?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err(WizardError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(line.trim_end_matches(['\n', '\r']).to_string())
+    }
+
+    /// Same as `prompt` without echoing the answer, see `secret::read_hidden`
+    fn prompt_hidden(&mut self, question: &str) -> Result<String, WizardError> {
+        if let Some(given) = self.given.pop_front() {
+            return Ok(given);
+        }
+        write!(self.output, "{question}: ")?;
+        self.output.flush()?;
+        Ok(read_hidden(&mut self.input, &mut self.output)?)
+    }
+}
+
+/// Parses `T` from the arguments, prompting on the terminal for the missing ones
+///
+/// A `TooFewArguments` error is kept when the standard input is not a terminal.
+pub fn parse_interactive<T, R>(args: &[&str], callback: impl FnOnce(T) -> R) -> Result<R, WizardError>
+where
+    T: AsArg + for<'a> TryParse<&'a str, Error = ParsingError>,
+{
+    let parsed = match T::try_parse(args.iter().copied()) {
+        Ok(Parsed(parsed, mut rest)) => match rest.next() {
+            None => parsed,
+            Some(_) => return Err(WizardError::Parsing(ParsingError::TooManyArguments)),
+        },
+        Err(ParsingError::TooFewArguments) if std::io::stdin().is_terminal() => {
+            Wizard::new(std::io::stdin().lock(), std::io::stderr()).complete(args)?.0
+        }
+        Err(err) => return Err(WizardError::Parsing(err)),
+    };
+    Ok(callback(parsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::describe::arg::ArgGroup;
+
+    #[derive(Debug, PartialEq)]
+    enum Mode {
+        Fast,
+        Slow,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Setup {
+        name: String,
+        mode: Mode,
+    }
+
+    impl AsArg for Setup {
+        fn arguments() -> ArgType {
+            ArgType::Group(ArgGroup(vec![
+                Arg::new("name", Some("name of the project")),
+                Arg::with_type(
+                    "mode",
+                    None,
+                    ArgType::Choices(Choices(vec![Arg::new("Fast", None), Arg::new("Slow", None)])),
+                ),
+            ]))
+        }
+    }
+
+    impl<'a> TryParse<&'a str> for Setup {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a str>>(mut value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            let name = value.next().ok_or(ParsingError::TooFewArguments)?.to_string();
+            let mode = match value.next().ok_or(ParsingError::TooFewArguments)?.to_lowercase().as_str() {
+                "fast" => Mode::Fast,
+                "slow" => Mode::Slow,
+                _ => return Err(ParsingError::VariantNotFound),
+            };
+            Ok(Parsed(Setup { name, mode }, value))
+        }
+    }
+
+    #[test]
+    fn it_should_prompt_each_field_and_parse_answers() {
+        let mut output = Vec::new();
+        let result = Wizard::new("my project\nunknown\n2\n".as_bytes(), &mut output)
+            .run::<Setup>()
+            .unwrap();
+        assert_eq!(
+            result,
+            Guided(
+                Setup {
+                    name: String::from("my project"),
+                    mode: Mode::Slow
+                },
+                vec![String::from("my project"), String::from("Slow")],
+                vec![(String::from("name"), String::from("my project")), (String::from("mode"), String::from("Slow"))]
+            )
+        );
+        assert_eq!(result.command_line(), "'my project' Slow");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "name    name of the project: mode\n  1) Fast\n  2) Slow\nchoice: invalid choice 'unknown'\nchoice: "
+        );
+    }
+
+    struct Deploy {
+        target: String,
+        mode: Mode,
+    }
+
+    impl AsArg for Deploy {
+        fn arguments() -> ArgType {
+            ArgType::Group(ArgGroup(vec![
+                Arg::new("target", None).with_default("staging"),
+                Arg::with_type(
+                    "dry-run",
+                    None,
+                    ArgType::Choices(Choices(vec![Arg::new("Fast", None), Arg::new("Slow", None)])),
+                )
+                .with_default("Slow"),
+            ]))
+        }
+    }
+
+    impl<'a> TryParse<&'a str> for Deploy {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a str>>(value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            let Parsed(Setup { name, mode }, rest) = Setup::try_parse(value)?;
+            Ok(Parsed(Deploy { target: name, mode }, rest))
+        }
+    }
+
+    #[test]
+    fn it_should_take_the_default_of_empty_answers() {
+        let mut output = Vec::new();
+        let result = Wizard::new("\n\n".as_bytes(), &mut output).run::<Deploy>().unwrap();
+        assert_eq!((result.0.target.as_str(), &result.0.mode), ("staging", &Mode::Slow));
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "target  [default: staging]: dry-run  [default: Slow]\n  1) Fast\n  2) Slow\nchoice: "
+        );
+        assert_eq!(result.config_file(), "target = \"staging\"\ndry_run = \"Slow\"\n");
+    }
+
+    #[test]
+    fn it_should_write_the_answers_as_a_config_file() {
+        let result = Wizard::new("say \"hi\" \\o/\n1\n".as_bytes(), Vec::new()).run::<Setup>().unwrap();
+        assert_eq!(result.config_file(), "name = \"say \\\"hi\\\" \\\\o/\"\nmode = \"Fast\"\n");
+    }
+
+    #[test]
+    fn it_should_fail_when_input_ends() {
+        let result = Wizard::new("project\n".as_bytes(), Vec::new()).run::<Setup>();
+        assert!(matches!(result, Err(WizardError::Io(_))));
+    }
+
+    #[test]
+    fn it_should_only_prompt_for_missing_arguments() {
+        let mut output = Vec::new();
+        let result = Wizard::new("fast\n".as_bytes(), &mut output).complete::<Setup>(&["project"]).unwrap();
+        assert_eq!(result.0, Setup { name: String::from("project"), mode: Mode::Fast });
+        assert_eq!(String::from_utf8(output).unwrap(), "mode\n  1) Fast\n  2) Slow\nchoice: ");
+        let mut output = Vec::new();
+        let result = Wizard::new("".as_bytes(), &mut output).complete::<Setup>(&["project", "slow"]).unwrap();
+        assert_eq!(result.0.mode, Mode::Slow);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn it_should_parse_complete_arguments_without_prompting() {
+        assert_eq!(parse_interactive(&["p", "Fast"], |setup: Setup| setup.mode).unwrap(), Mode::Fast);
+        assert!(matches!(
+            parse_interactive(&["p", "Fast", "more"], |setup: Setup| setup.mode),
+            Err(WizardError::Parsing(ParsingError::TooManyArguments))
+        ));
+    }
+
+    struct Login {
+        user: String,
+        password: String,
+    }
+
+    impl AsArg for Login {
+        fn arguments() -> ArgType {
+            ArgType::Group(ArgGroup(vec![Arg::new("user", None), Arg::new("password", None).secret()]))
+        }
+    }
+
+    impl<'a> TryParse<&'a str> for Login {
+        type Error = ParsingError;
+
+        fn try_parse<I: Iterator<Item = &'a str>>(mut value: I) -> Result<Parsed<Self, I>, Self::Error> {
+            let user = value.next().ok_or(ParsingError::TooFewArguments)?.to_string();
+            let password = value.next().ok_or(ParsingError::TooFewArguments)?.to_string();
+            Ok(Parsed(Login { user, password }, value))
+        }
+    }
+
+    #[test]
+    fn it_should_prompt_for_secrets_without_echo() {
+        let mut output = Vec::new();
+        let result = Wizard::new("me\nhunter2\n".as_bytes(), &mut output).run::<Login>().unwrap();
+        assert_eq!((result.0.user.as_str(), result.0.password.as_str()), ("me", "hunter2"));
+        assert_eq!(String::from_utf8(output).unwrap(), "user: password: ");
+    }
+}
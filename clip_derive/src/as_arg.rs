@@ -20,14 +20,157 @@ use crate::attribute;
 use proc_macro::TokenStream;
 use quote::quote;
 
-pub(crate) fn impl_description(attrs: std::slice::Iter<'_, syn::Attribute>) -> proc_macro2::TokenStream {
+fn doc_lines(attrs: std::slice::Iter<'_, syn::Attribute>) -> Vec<String> {
     attrs
         .filter(attribute::is("doc"))
         .filter_map(attribute::extract_string)
+        .collect()
+}
+
+/// Short description: the first paragraph of the doc comment
+pub(crate) fn impl_description(attrs: std::slice::Iter<'_, syn::Attribute>) -> proc_macro2::TokenStream {
+    doc_lines(attrs)
+        .into_iter()
+        .skip_while(String::is_empty)
+        .take_while(|line| !line.is_empty())
         .reduce(|prev, nstr| format!("{}\n{}", prev, nstr))
         .map_or(quote! { None }, |val| quote! { Some(#val) })
 }
 
+/// Whole doc comment, only when it is longer than its first paragraph
+pub(crate) fn impl_long_description(attrs: std::slice::Iter<'_, syn::Attribute>) -> Option<String> {
+    let lines = doc_lines(attrs);
+    let start = lines.iter().position(|line| !line.is_empty())?;
+    let end = lines.iter().rposition(|line| !line.is_empty())?;
+    let lines = &lines[start..=end];
+    lines.iter().any(String::is_empty).then(|| lines.join("\n"))
+}
+
+/// `.with_long_description(..)` call for the arguments having a multi-paragraph doc comment
+fn impl_arg_long_description(attrs: &[syn::Attribute]) -> proc_macro2::TokenStream {
+    impl_long_description(attrs.iter())
+        .map(|long| quote! { .with_long_description(#long) })
+        .unwrap_or_default()
+}
+
+/// `.with_hint(..)` call setting the value hint given with `#[value_hint(FilePath)]`, or the
+/// `TypeHint` of the type when it implements it
+pub(crate) fn impl_value_hint(attrs: &[syn::Attribute], ty: &syn::Type) -> Result<proc_macro2::TokenStream, syn::Error> {
+    if let Some(attr) = attrs.iter().find(|attr| attribute::is("value_hint")(attr)) {
+        let hint: syn::Ident = attr.parse_args()?;
+        return Ok(quote! { .with_hint(clipv::describe::arg::ValueHint::#hint) });
+    }
+    let ty = crate::try_parse::option_inner(ty).or(crate::try_parse::collection_inner(ty)).unwrap_or(ty);
+    // a borrowed type names a lifetime which is not in scope
+    if let syn::Type::Reference(_) = ty {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    Ok(quote! {
+        .with_hint({
+            use clipv::describe::value::{HintImplemented as _, HintMissing as _};
+            (&clipv::describe::value::Possible::<#ty>::new()).value_hint()
+        })
+    })
+}
+
+/// `.secret()` call for the arguments marked with `#[secret]`
+pub(crate) fn impl_secret(attrs: &[syn::Attribute]) -> Option<proc_macro2::TokenStream> {
+    attrs.iter().any(|attr| attr.path().is_ident("secret")).then(|| quote! { .secret() })
+}
+
+/// `.deprecated(..)` call for the arguments marked with `#[deprecated_arg]`
+pub(crate) fn impl_deprecated(attrs: &[syn::Attribute]) -> Result<proc_macro2::TokenStream, syn::Error> {
+    Ok(attribute::deprecation(attrs)?
+        .map(|note| quote! { .deprecated(#note) })
+        .unwrap_or_default())
+}
+
+/// `.with_value_name(..)` call setting the placeholder given with `#[value_name = "FILE"]`
+pub(crate) fn impl_value_name(attrs: &[syn::Attribute]) -> proc_macro2::TokenStream {
+    attrs
+        .iter()
+        .find(attribute::is("value_name"))
+        .and_then(attribute::extract_string)
+        .map(|value_name| quote! { .with_value_name(#value_name) })
+        .unwrap_or_default()
+}
+
+/// `.with_default(..)` call for the fields having a `#[clip(default = "...")]`
+pub(crate) fn impl_default(attrs: &[syn::Attribute]) -> Result<proc_macro2::TokenStream, syn::Error> {
+    Ok(attribute::string_value(&attribute::clip_args(attrs)?, "default")?
+        .map(|default| quote! { .with_default(#default) })
+        .unwrap_or_default())
+}
+
+/// `.with_possible_values(..)` call listing the values of a `#[possible("a", "b")]` attribute, or
+/// the `PossibleValues` of the type when it implements it
+pub(crate) fn impl_possible_values(attrs: &[syn::Attribute], ty: &syn::Type) -> Result<proc_macro2::TokenStream, syn::Error> {
+    if let Some(possible) = attribute::possible_values(attrs)? {
+        let count = possible.len();
+        return Ok(quote! {
+            .with_possible_values({
+                static POSSIBLE: [clipv::describe::value::Value<'static>; #count] = [
+                    #(clipv::describe::value::Value::keyword(#possible, None)),*
+                ];
+                &POSSIBLE
+            })
+        });
+    }
+    let ty = crate::try_parse::option_inner(ty).or(crate::try_parse::collection_inner(ty)).unwrap_or(ty);
+    // a borrowed type names a lifetime which is not in scope
+    if let syn::Type::Reference(_) = ty {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    Ok(quote! {
+        .with_possible_values({
+            use clipv::describe::value::{PossibleImplemented as _, PossibleMissing as _};
+            (&clipv::describe::value::Possible::<#ty>::new()).possible_values()
+        })
+    })
+}
+
+/// `.with_value_name(..)` call naming the value of an option after the `Placeholder` of its
+/// type, `VALUE` when it has none
+pub(crate) fn impl_placeholder(ty: &syn::Type) -> proc_macro2::TokenStream {
+    let ty = crate::try_parse::option_inner(ty).or(crate::try_parse::collection_inner(ty)).unwrap_or(ty);
+    // a borrowed type names a lifetime which is not in scope
+    if let syn::Type::Reference(_) = ty {
+        return quote! { .with_value_name("VALUE") };
+    }
+    quote! {
+        .with_value_name({
+            use clipv::describe::value::{PlaceholderImplemented as _, PlaceholderMissing as _};
+            (&clipv::describe::value::Possible::<#ty>::new()).placeholder()
+        })
+    }
+}
+
+/// `.with_choices_summary(..)` call for the choices fields having an `#[inline_choices]`, a
+/// `#[clip(choices_limit = N)]` or a `#[clip(choices_as_name)]`
+fn impl_choices_summary(attrs: &[syn::Attribute]) -> Result<proc_macro2::TokenStream, syn::Error> {
+    if attrs.iter().any(|attr| attribute::is("inline_choices")(&attr)) {
+        return Ok(quote! { .with_choices_summary(clipv::describe::arg::ChoicesSummary::Inline) });
+    }
+    let clip = attribute::clip_args(attrs)?;
+    if attribute::has_flag(&clip, "choices_as_name") {
+        return Ok(quote! { .with_choices_summary(clipv::describe::arg::ChoicesSummary::Name) });
+    }
+    Ok(match attribute::expr_value(&clip, "choices_limit") {
+        Some(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(limit),
+            ..
+        })) => quote! { .with_choices_summary(clipv::describe::arg::ChoicesSummary::Limit(#limit)) },
+        Some(value) => return Err(syn::Error::new_spanned(value, "expected an integer literal")),
+        None => quote! {},
+    })
+}
+
+/// Name of the field type for typed usage lines, `Option<u8>` and `Vec<u8>` being displayed as `u8`,
+/// as are the sets of `u8`
+fn type_name(ty: &syn::Type) -> String {
+    let ty = crate::try_parse::option_inner(ty).or(crate::try_parse::collection_inner(ty)).unwrap_or(ty);
+    quote!(#ty).to_string().replace(' ', "")
+}
 
 fn is_subargument(attr: &syn::Attribute) -> bool {
     attribute::is("group")(&attr) || attribute::is("choices")(&attr)
@@ -49,17 +192,36 @@ fn impl_field_as_arg(
                 &(segments.last().unwrap().ident)
             };
             let description = impl_description(attrs.iter());
+            let hint = impl_value_hint(attrs, ty)?;
+            let long = impl_arg_long_description(attrs);
+            let deprecated = impl_deprecated(attrs)?;
+            let value_name = impl_value_name(attrs);
+            let default = impl_default(attrs)?;
+            let type_name = type_name(ty);
+            let optional = crate::try_parse::option_inner(ty).map(|_| quote! { .optional() });
+            let secret = impl_secret(attrs);
+            let possible = impl_possible_values(attrs, ty)?;
+            let arity = match crate::try_parse::collection_inner(ty) {
+                Some(_) => {
+                    let (min, max) = attribute::arity(attrs)?;
+                    let max = max.map_or(quote! { None }, |max| quote! { Some(#max) });
+                    Some(quote! { .with_arity(#min, #max) })
+                }
+                None => None,
+            };
+            let trailing = attrs.iter().find(attribute::is("trailing")).map(|_| quote! { .trailing() });
             Ok(if attrs.iter().any(is_subargument) {
+                let choices_summary = impl_choices_summary(attrs)?;
                 quote!{
                     clipv::describe::arg::Arg::with_type(
-                        stringify!(#name), #description, #ty::arguments()
-                    ),
+                        stringify!(#name), #description, <#ty as clipv::describe::arg::AsArg>::arguments()
+                    )#default #long #deprecated #choices_summary,
                 }
             } else {
                 quote!{
                     clipv::describe::arg::Arg::new(
                         stringify!(#name), #description
-                    ),
+                    )#hint #value_name #default #long #deprecated #optional #arity #trailing #secret #possible.with_type_name(#type_name),
                 }
             })
         }
@@ -67,17 +229,23 @@ fn impl_field_as_arg(
     }
 }
 
+/// Global and flattened options are not positional arguments, they are described by the command
+pub(crate) fn is_global(field: &syn::Field) -> bool {
+    field.attrs.iter().any(|attr| attribute::is("global")(&attr))
+        || attribute::clip_args(&field.attrs).is_ok_and(|clip| attribute::has_flag(&clip, "flatten"))
+}
+
 fn impl_fields_as_arg(
     fields: syn::punctuated::Iter<'_, syn::Field>,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
     let mut arguments = proc_macro2::TokenStream::new();
-    for field in fields {
+    for (_, field) in attribute::parse_order(fields)?.into_iter().filter(|(_, field)| !is_global(field)) {
         arguments.extend(impl_field_as_arg(field)?);
     }
     Ok(arguments)
 }
 
-fn impl_struct_field_as_arg(fields: &syn::Fields) -> Result<proc_macro2::TokenStream, syn::Error> {
+pub(crate) fn impl_struct_field_as_arg(fields: &syn::Fields) -> Result<proc_macro2::TokenStream, syn::Error> {
     match fields {
         // it has no arguments
         syn::Fields::Unit => Ok(proc_macro2::TokenStream::new()),
@@ -100,16 +268,20 @@ pub(crate) fn impl_enum_variant_as_arg(
     } in variants
     {
         let description = impl_description(attrs.iter());
+        let long = impl_arg_long_description(attrs);
+        let deprecated = impl_deprecated(attrs)?;
+        // the keyword the parser matches
+        let name = crate::as_command::impl_command_name(ident, attrs)?;
         if let syn::Fields::Unit = fields {
             arguments.extend(quote! {
-                clipv::describe::arg::Arg::new(stringify!(#ident), #description),
+                clipv::describe::arg::Arg::new(#name, #description)#long #deprecated,
             });
         } else {
             let sub_arguments = impl_struct_field_as_arg(fields)?;
             arguments.extend(quote! {
-                clipv::describe::arg::Arg::with_type(stringify!(#ident), #description, clipv::describe::arg::ArgType::Group(clipv::describe::arg::ArgGroup(vec![
+                clipv::describe::arg::Arg::with_type(#name, #description, clipv::describe::arg::ArgType::Group(clipv::describe::arg::ArgGroup(vec![
                     #sub_arguments
-                ]))),
+                ])))#long #deprecated,
             })
         }
     }
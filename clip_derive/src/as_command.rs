@@ -19,48 +19,314 @@
 use proc_macro::TokenStream;
 use quote::quote;
 
-fn impl_as_command_from_arg(syn::DeriveInput {
-    ident, attrs, ..
-}: &syn::DeriveInput, arguments: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-    let description = crate::as_arg::impl_description(attrs.iter());
-    quote! {
+/// Name of the command: the `#[command(name = ...)]` expression or the kebab-cased identifier
+pub(crate) fn impl_command_name(ident: &syn::Ident, attrs: &[syn::Attribute]) -> Result<proc_macro2::TokenStream, syn::Error> {
+    Ok(match crate::attribute::command_name(attrs)? {
+        Some(name) => quote! { #name },
+        None => {
+            let name = crate::attribute::kebab_case(ident);
+            quote! { #name }
+        }
+    })
+}
+
+/// Short description: the doc comment, falling back on the package description with
+/// `#[command(from_cargo)]`
+fn impl_command_description(attrs: &[syn::Attribute]) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let from_cargo = crate::attribute::has_flag(&crate::attribute::command_args(attrs)?, "from_cargo");
+    Ok(if from_cargo && !attrs.iter().any(|attr| crate::attribute::is("doc")(&attr)) {
+        quote! { Some(env!("CARGO_PKG_DESCRIPTION")).filter(|description| !description.is_empty()) }
+    } else {
+        crate::as_arg::impl_description(attrs.iter())
+    })
+}
+
+/// Statements configuring `cmd` from the `#[command(...)]` and `#[clip(...)]` options of the type
+fn impl_command_settings(attrs: &[syn::Attribute]) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let clip = crate::attribute::clip_args(attrs)?;
+    let command = crate::attribute::command_args(attrs)?;
+    let mut settings = proc_macro2::TokenStream::new();
+    if crate::attribute::has_flag(&command, "from_cargo") {
+        settings.extend(quote! {
+            cmd.set_version(env!("CARGO_PKG_VERSION"));
+            if !env!("CARGO_PKG_AUTHORS").is_empty() {
+                cmd.set_author(env!("CARGO_PKG_AUTHORS"));
+            }
+        });
+    }
+    if let Some(category) = crate::attribute::expr_value(&command, "category") {
+        settings.extend(quote! { cmd.set_category(#category); });
+    }
+    if let Some(version) = crate::attribute::expr_value(&command, "version") {
+        settings.extend(quote! { cmd.set_version(#version); });
+    }
+    if let Some(author) = crate::attribute::expr_value(&command, "author") {
+        settings.extend(quote! { cmd.set_author(#author); });
+    }
+    if let Some(long) = crate::attribute::expr_value(&command, "long_about") {
+        settings.extend(quote! { cmd.set_long_description(#long); });
+    } else if let Some(long) = crate::as_arg::impl_long_description(attrs.iter()) {
+        settings.extend(quote! { cmd.set_long_description(#long); });
+    }
+    if let Some(template) = crate::attribute::string_value(&clip, "template")? {
+        settings.extend(quote! { cmd.set_template(#template); });
+    }
+    if let Some(before_help) = crate::attribute::string_value(&clip, "before_help")? {
+        settings.extend(quote! { cmd.set_before_help(#before_help); });
+    }
+    if let Some(after_help) = crate::attribute::string_value(&clip, "after_help")? {
+        settings.extend(quote! { cmd.set_after_help(#after_help); });
+    }
+    if let Some(note) = crate::attribute::deprecation(attrs)? {
+        settings.extend(quote! { cmd.set_deprecated(#note); });
+    }
+    if crate::attribute::has_flag(&clip, "typed_usage") {
+        settings.extend(quote! { cmd.set_typed_usage(); });
+    }
+    if let Some(order) = crate::attribute::string_value(&clip, "display_order")? {
+        let order = match order.value().as_str() {
+            "declaration" => quote! { Declaration },
+            "alphabetical" => quote! { Alphabetical },
+            "required_first" => quote! { RequiredFirst },
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    order,
+                    "expected \"declaration\", \"alphabetical\" or \"required_first\"",
+                ))
+            }
+        };
+        settings.extend(quote! { cmd.set_display_order(clipv::describe::arg::DisplayOrder::#order); });
+    }
+    if crate::attribute::has_flag(&clip, "inline_subcommands") {
+        settings.extend(quote! { cmd.set_inline_subcommands(); });
+    }
+    for example in crate::attribute::string_values(&clip, "example")? {
+        settings.extend(quote! { cmd.add_example(#example); });
+    }
+    Ok(settings)
+}
+
+/// `help` and `long_help` built once and kept in a static, asked with `#[clip(static_help)]`
+fn impl_static_help(attrs: &[syn::Attribute]) -> Result<proc_macro2::TokenStream, syn::Error> {
+    if !crate::attribute::has_flag(&crate::attribute::clip_args(attrs)?, "static_help") {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    Ok(quote! {
+        fn help() -> String {
+            static HELP: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+            clipv::describe::command::cached_help(&HELP, || Self::command().help())
+        }
+        fn long_help() -> String {
+            static HELP: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+            clipv::describe::command::cached_help(&HELP, || Self::command().long_help())
+        }
+    })
+}
+
+/// Subcommand described by an enum variant, with the variant fields as its arguments
+///
+/// A variant marked with `#[command]` wraps another AsCommand type whose arguments and
+/// subcommands are taken, e.g. `Remote(RemoteCommand)`.
+fn impl_variant_as_command(
+    syn::Variant { ident, attrs, fields, .. }: &syn::Variant,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = impl_command_name(ident, attrs)?;
+    let description = impl_command_description(attrs)?;
+    let settings = impl_command_settings(attrs)?;
+    let wraps_command = attrs
+        .iter()
+        .any(|attr| crate::attribute::is("command")(&attr) && matches!(attr.meta, syn::Meta::Path(_)));
+    let content = match fields {
+        syn::Fields::Unnamed(syn::FieldsUnnamed { unnamed, .. }) if wraps_command && unnamed.len() == 1 =>
+        {
+            let ty = &unnamed[0].ty;
+            quote! {
+                let inner = <#ty as clipv::describe::command::AsCommand>::command();
+                cmd.arguments = inner.arguments;
+                cmd.global_options = inner.global_options;
+                cmd.subcommands = inner.subcommands;
+            }
+        }
+        fields => {
+            let arguments = crate::as_arg::impl_struct_field_as_arg(fields)?;
+            quote! { cmd.set_arguments(vec![#arguments]); }
+        }
+    };
+    Ok(quote! {
+        {
+            let mut cmd = clipv::describe::command::Command::new(#name, #description);
+            #content
+            #settings
+            cmd
+        }
+    })
+}
+
+fn impl_as_command_from_enum(
+    syn::DeriveInput { ident, attrs, .. }: &syn::DeriveInput,
+    variants: syn::punctuated::Iter<'_, syn::Variant>,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = impl_command_name(ident, attrs)?;
+    let description = impl_command_description(attrs)?;
+    let settings = impl_command_settings(attrs)?;
+    let subcommands = variants.map(impl_variant_as_command).collect::<Result<Vec<_>, _>>()?;
+    let static_help = impl_static_help(attrs)?;
+    Ok(quote! {
         impl clipv::describe::command::AsCommand for #ident {
             fn command() -> clipv::describe::command::Command {
                 let mut cmd = clipv::describe::command::Command::new(
-                    stringify!(#ident),
+                    #name,
                     #description
                 );
-                cmd.set_arguments(vec![
-                    clipv::describe::arg::Arg::with_type(
-                        stringify!(#ident), None,
-                        clipv::describe::arg::ArgType::Choices(
-                            clipv::describe::arg::Choices(vec![#arguments])
-                        )
-                    )
-                ]);
+                cmd.set_subcommands(vec![#(#subcommands),*]);
+                cmd.add_help_subcommand();
+                #settings
                 cmd
             }
+            #static_help
+        }
+    })
+}
+
+fn impl_as_command_from_struct(
+    syn::DeriveInput { ident, attrs, .. }: &syn::DeriveInput,
+    fields: &syn::Fields,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = impl_command_name(ident, attrs)?;
+    let description = impl_command_description(attrs)?;
+    let settings = impl_command_settings(attrs)?;
+    let arguments = crate::as_arg::impl_struct_field_as_arg(fields)?;
+    let static_help = impl_static_help(attrs)?;
+    let mut globals = proc_macro2::TokenStream::new();
+    let mut flattened = proc_macro2::TokenStream::new();
+    for field in fields.iter().filter(|field| crate::as_arg::is_global(field)) {
+        let clip = crate::attribute::clip_args(&field.attrs)?;
+        if crate::attribute::has_flag(&clip, "flatten") {
+            let ty = &field.ty;
+            let prefix = match crate::attribute::string_value(&clip, "prefix")? {
+                Some(prefix) => format!("--{}-", prefix.value()),
+                None => String::from("--"),
+            };
+            flattened.extend(quote! {
+                cmd.set_global_options(
+                    <#ty as clipv::parser::Flatten>::options().iter().map(|option| {
+                        let arg = clipv::describe::arg::Arg::new(format!("{}{}", #prefix, option.name), option.description);
+                        let arg = match option.short {
+                            Some(short) if #prefix == "--" => arg.with_short(short),
+                            _ => arg,
+                        };
+                        if option.takes_value { arg.with_value_name("VALUE") } else { arg }
+                    }).collect()
+                );
+            });
+        } else if let Some(name) = &field.ident {
+            let name = crate::attribute::long_name(name);
+            let description = crate::as_arg::impl_description(field.attrs.iter());
+            let hint = crate::as_arg::impl_value_hint(&field.attrs, &field.ty)?;
+            let long = crate::as_arg::impl_long_description(field.attrs.iter())
+                .map(|long| quote! { .with_long_description(#long) });
+            let deprecated = crate::as_arg::impl_deprecated(&field.attrs)?;
+            let is_flag = matches!(&field.ty, syn::Type::Path(syn::TypePath { path, .. }) if path.is_ident("bool"));
+            // an option taking a value is displayed as `--name <VALUE>` unless named otherwise,
+            // by the attribute or the type
+            let value_name = match crate::as_arg::impl_value_name(&field.attrs) {
+                value_name if value_name.is_empty() && !is_flag => crate::as_arg::impl_placeholder(&field.ty),
+                value_name => value_name,
+            };
+            let short = crate::attribute::char_value(&clip, "short")?.map(|short| quote! { .with_short(#short) });
+            let default = crate::as_arg::impl_default(&field.attrs)?;
+            let optional = is_flag || crate::try_parse::option_inner(&field.ty).is_some();
+            let optional = optional.then(|| quote! { .optional() });
+            let secret = crate::as_arg::impl_secret(&field.attrs);
+            // a `Vec` or set option may be repeated
+            let arity = match crate::try_parse::collection_inner(&field.ty) {
+                Some(_) => {
+                    let (min, max) = crate::attribute::arity(&field.attrs)?;
+                    let max = max.map_or(quote! { None }, |max| quote! { Some(#max) });
+                    Some(quote! { .with_arity(#min, #max) })
+                }
+                None => None,
+            };
+            let possible = match is_flag {
+                true => proc_macro2::TokenStream::new(),
+                false => crate::as_arg::impl_possible_values(&field.attrs, &field.ty)?,
+            };
+            globals.extend(quote! {
+                clipv::describe::arg::Arg::new(#name, #description)#short #hint #value_name #default #long #deprecated #optional #secret #possible #arity,
+            });
         }
     }
+    Ok(quote! {
+        impl clipv::describe::command::AsCommand for #ident {
+            fn command() -> clipv::describe::command::Command {
+                let mut cmd = clipv::describe::command::Command::new(
+                    #name,
+                    #description
+                );
+                cmd.set_arguments(vec![#arguments]);
+                cmd.set_global_options(vec![#globals]);
+                #flattened
+                #settings
+                cmd
+            }
+            #static_help
+        }
+    })
+}
+
+/// Generates a test checking the command tree with the linter, asked with `#[clip(lint)]`
+fn impl_lint_test(
+    syn::DeriveInput { ident, attrs, .. }: &syn::DeriveInput,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    if !crate::attribute::has_flag(&crate::attribute::clip_args(attrs)?, "lint") {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    let test = quote::format_ident!("__clip_lint_{}", ident);
+    Ok(quote! {
+        #[cfg(test)]
+        #[test]
+        #[allow(non_snake_case)]
+        fn #test() {
+            let command = <#ident as clipv::describe::command::AsCommand>::command();
+            let lints = clipv::describe::lint::lint(&command);
+            assert!(
+                lints.is_empty(),
+                "{}",
+                lints.iter().map(|lint| lint.to_string()).collect::<std::vec::Vec<String>>().join("\n")
+            );
+        }
+    })
 }
 
 fn impl_as_command(ast: &syn::DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
             // fn name() -> &'static str { stringify!(#ident) }
     match &ast.data {
         syn::Data::Enum(syn::DataEnum { variants, .. }) => {
-            let as_command = impl_as_command_from_arg(ast, crate::as_arg::impl_enum_variant_as_arg(variants.iter())?);
-            // let as_arg = crate::as_arg::impl_as_arg(ast)?;
-            Ok(quote!{ #as_command })
+            let as_command = impl_as_command_from_enum(ast, variants.iter())?;
+            let lint_test = impl_lint_test(ast)?;
+            Ok(quote!{ #as_command #lint_test })
+        },
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => {
+            let as_command = impl_as_command_from_struct(ast, fields)?;
+            let lint_test = impl_lint_test(ast)?;
+            Ok(quote!{ #as_command #lint_test })
         },
-        syn::Data::Struct(syn::DataStruct { struct_token, .. }) => Err(
-            syn::Error::new_spanned(struct_token, "only enum can be defined as command")
-        ),
         syn::Data::Union(syn::DataUnion { union_token, .. }) => Err(
             syn::Error::new_spanned(union_token, "Union aren't supported as commands")
         )
     }
 }
 
+/// Whether the Clip derive implements AsCommand: the type is marked `#[command(..)]` or is an
+/// enum with a `#[command]` variant
+pub(crate) fn is_command(ast: &syn::DeriveInput) -> bool {
+    let marked = |attrs: &[syn::Attribute]| attrs.iter().any(|attr| attr.path().is_ident("command"));
+    match &ast.data {
+        syn::Data::Enum(data) => marked(&ast.attrs) || data.variants.iter().any(|variant| marked(&variant.attrs)),
+        _ => marked(&ast.attrs),
+    }
+}
+
 pub(crate) fn impl_as_command_macro(ast: &syn::DeriveInput) -> TokenStream {
     impl_as_command(ast)
         .unwrap_or_else(|err| err.to_compile_error())
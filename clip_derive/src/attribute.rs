@@ -21,12 +21,12 @@
 /// The created closure only supports one path attribute
 /// For instance, `#[this::is::an::example]` won't work
 pub(crate) fn is(name: &'static str) -> Box<dyn Fn(&&syn::Attribute) -> bool> {
-    return Box::new(move |attr: &&syn::Attribute| {
+    Box::new(move |attr: &&syn::Attribute| {
         (*attr)
             .path()
             .get_ident()
             .is_some_and(|ident| *ident == name)
-    });
+    })
 }
 
 /// From a syn::Attribute TokenStream, try to retrieve a Literal String
@@ -52,5 +52,257 @@ pub(crate) fn extract_string(attr: &syn::Attribute) -> Option<String> {
     }
 }
 
+/// Name of a named option without its leading dashes: the kebab-cased field name
+pub(crate) fn option_name(ident: &syn::Ident) -> String {
+    ident.to_string().trim_start_matches("r#").replace('_', "-")
+}
+
+/// Converts a CamelCase identifier into snake_case, e.g. `RemoteAdd` into `remote_add`
+///
+/// An uppercase run is an acronym, split before its last capital when a lowercase letter follows,
+/// e.g. `HTTPGet` into `http_get`.
+pub(crate) fn snake_case(ident: &syn::Ident) -> String {
+    let ident = ident.to_string();
+    let chars: Vec<char> = ident.trim_start_matches("r#").chars().collect();
+    let mut result = String::new();
+    for (index, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let previous = index.checked_sub(1).map(|previous| chars[previous]);
+            let next = chars.get(index + 1);
+            let boundary = previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_ascii_digit()
+                    || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+            if boundary && !result.ends_with('_') {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Converts a CamelCase identifier into kebab-case, e.g. `RemoteAdd` into `remote-add`
+pub(crate) fn kebab_case(ident: &syn::Ident) -> String {
+    snake_case(ident).replace('_', "-")
+}
+
+/// Gathers the arguments of every `#[command(...)]` attribute, e.g. `#[command(name = "tool")]`
+///
+/// A bare `#[command]` has no arguments and is skipped.
+pub(crate) fn command_args(attrs: &[syn::Attribute]) -> Result<Vec<syn::Meta>, syn::Error> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter().filter(is("command")) {
+        if let syn::Meta::List(_) = attr.meta {
+            metas.extend(attr.parse_args_with(
+                syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+            )?);
+        }
+    }
+    Ok(metas)
+}
+
+/// Looks for a `name = <expression>` argument, e.g. `version = env!("CARGO_PKG_VERSION")`
+pub(crate) fn expr_value(metas: &[syn::Meta], name: &str) -> Option<syn::Expr> {
+    metas.iter().find_map(|meta| match meta {
+        syn::Meta::NameValue(syn::MetaNameValue { path, value, .. }) if path.is_ident(name) => {
+            Some(value.clone())
+        }
+        _ => None,
+    })
+}
+
+/// Looks for the `name = ...` argument of the `#[command(...)]` attributes
+pub(crate) fn command_name(attrs: &[syn::Attribute]) -> Result<Option<syn::Expr>, syn::Error> {
+    Ok(expr_value(&command_args(attrs)?, "name"))
+}
+
+/// Command line name of a named option: `--` followed by the kebab-cased field name
+pub(crate) fn long_name(ident: &syn::Ident) -> String {
+    format!("--{}", option_name(ident))
+}
+
+/// Gathers the arguments of every `#[clip(...)]` attribute, e.g. `#[clip(flatten, prefix = "db")]`
+pub(crate) fn clip_args(attrs: &[syn::Attribute]) -> Result<Vec<syn::Meta>, syn::Error> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter().filter(is("clip")) {
+        metas.extend(attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+        )?);
+    }
+    Ok(metas)
+}
+
+/// Whether a `#[clip(...)]` argument list contains the given flag, e.g. `flatten`
+pub(crate) fn has_flag(metas: &[syn::Meta], name: &str) -> bool {
+    metas
+        .iter()
+        .any(|meta| matches!(meta, syn::Meta::Path(path) if path.is_ident(name)))
+}
+
+/// Looks for a `name = "value"` argument in a `#[clip(...)]` argument list
+pub(crate) fn string_value(
+    metas: &[syn::Meta],
+    name: &str,
+) -> Result<Option<syn::LitStr>, syn::Error> {
+    Ok(string_values(metas, name)?.into_iter().next())
+}
+
+/// Looks for a `name = 'c'` argument in a `#[clip(...)]` argument list, e.g. `short = 'v'`
+pub(crate) fn char_value(metas: &[syn::Meta], name: &str) -> Result<Option<syn::LitChar>, syn::Error> {
+    match expr_value(metas, name) {
+        Some(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Char(lit_char),
+            ..
+        })) => Ok(Some(lit_char)),
+        Some(value) => Err(syn::Error::new_spanned(value, "expected a character literal")),
+        None => Ok(None),
+    }
+}
+
+/// Every `name = "value"` argument of a repeatable option, e.g. `example = "..."`
+pub(crate) fn string_values(
+    metas: &[syn::Meta],
+    name: &str,
+) -> Result<Vec<syn::LitStr>, syn::Error> {
+    let mut values = Vec::new();
+    for meta in metas {
+        if let syn::Meta::NameValue(syn::MetaNameValue { path, value, .. }) = meta {
+            if path.is_ident(name) {
+                match value {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit_str),
+                        ..
+                    }) => values.push(lit_str.clone()),
+                    _ => return Err(syn::Error::new_spanned(value, "expected a string literal")),
+                }
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Values listed by a `#[possible("a", "b")]` attribute, None when it is absent
+pub(crate) fn possible_values(attrs: &[syn::Attribute]) -> Result<Option<Vec<syn::LitStr>>, syn::Error> {
+    let Some(attr) = attrs.iter().find(is("possible")) else {
+        return Ok(None);
+    };
+    Ok(Some(
+        attr.parse_args_with(syn::punctuated::Punctuated::<syn::LitStr, syn::Token![,]>::parse_terminated)?
+            .into_iter()
+            .collect(),
+    ))
+}
+
+/// Variants of `clipv::parser::PathCheck` named by the `#[file_exists]`, `#[dir_exists]` and
+/// `#[writable]` attributes of a field
+pub(crate) fn path_checks(attrs: &[syn::Attribute]) -> Vec<syn::Ident> {
+    [("file_exists", "FileExists"), ("dir_exists", "DirExists"), ("writable", "Writable")]
+        .into_iter()
+        .filter(|(attribute, _)| attrs.iter().any(|attr| is(attribute)(&attr)))
+        .map(|(_, variant)| syn::Ident::new(variant, proc_macro2::Span::call_site()))
+        .collect()
+}
+
+/// Fields in the order their values are parsed, with their index in the declaration
+///
+/// A positional field marked `#[position = N]` is the Nth one parsed (from 0), the other ones
+/// filling the remaining places in the declaration order. Global options come last.
+pub(crate) fn parse_order<'f>(
+    fields: impl Iterator<Item = &'f syn::Field>,
+) -> Result<Vec<(usize, &'f syn::Field)>, syn::Error> {
+    let mut placed = Vec::new();
+    let mut unplaced = Vec::new();
+    let mut globals = Vec::new();
+    for (index, field) in fields.enumerate() {
+        match field.attrs.iter().find(is("position")).map(|attr| &attr.meta) {
+            Some(syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(position), .. }),
+                ..
+            })) => {
+                let position = position.base10_parse::<usize>()?;
+                if placed.iter().any(|&(other, _, _)| other == position) {
+                    return Err(syn::Error::new_spanned(field, "another field has the same position"));
+                }
+                placed.push((position, index, field));
+            }
+            Some(meta) => return Err(syn::Error::new_spanned(meta, "expected `#[position = N]`")),
+            None if crate::as_arg::is_global(field) => globals.push((index, field)),
+            None => unplaced.push((index, field)),
+        }
+    }
+    let mut position = 0;
+    for (index, field) in unplaced {
+        while placed.iter().any(|&(other, _, _)| other == position) {
+            position += 1;
+        }
+        placed.push((position, index, field));
+        position += 1;
+    }
+    placed.sort_by_key(|&(position, _, _)| position);
+    Ok(placed.into_iter().map(|(_, index, field)| (index, field)).chain(globals).collect())
+}
+
+/// Bounds of an `#[arity(min = 1, max = 4)]` attribute, any number of values by default
+pub(crate) fn arity(attrs: &[syn::Attribute]) -> Result<(usize, Option<usize>), syn::Error> {
+    let Some(attr) = attrs.iter().find(is("arity")) else {
+        return Ok((0, None));
+    };
+    let metas: Vec<syn::Meta> = attr
+        .parse_args_with(syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)?
+        .into_iter()
+        .collect();
+    let bound = |name| match expr_value(&metas, name) {
+        Some(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(bound), .. })) => bound.base10_parse::<usize>().map(Some),
+        Some(value) => Err(syn::Error::new_spanned(value, "expected an integer literal")),
+        None => Ok(None),
+    };
+    let (min, max) = (bound("min")?.unwrap_or_default(), bound("max")?);
+    if max.is_some_and(|max| max < min) {
+        return Err(syn::Error::new_spanned(attr, "the maximum is lower than the minimum"));
+    }
+    Ok((min, max))
+}
+
+/// Note of a `#[deprecated_arg(note = "...")]` attribute, empty for a bare `#[deprecated_arg]`
+///
+/// Returns None when the attribute is absent.
+pub(crate) fn deprecation(attrs: &[syn::Attribute]) -> Result<Option<syn::LitStr>, syn::Error> {
+    let Some(attr) = attrs.iter().find(is("deprecated_arg")) else {
+        return Ok(None);
+    };
+    let note = match &attr.meta {
+        syn::Meta::Path(_) => None,
+        _ => string_value(
+            &attr
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+                )?
+                .into_iter()
+                .collect::<Vec<syn::Meta>>(),
+            "note",
+        )?,
+    };
+    Ok(Some(note.unwrap_or_else(|| {
+        syn::LitStr::new("", proc_macro2::Span::call_site())
+    })))
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_split_identifiers_at_word_boundaries() {
+        let case = |ident: &str| kebab_case(&syn::Ident::new(ident, proc_macro2::Span::call_site()));
+        assert_eq!(case("RemoteAdd"), "remote-add");
+        assert_eq!(case("HTTPGet"), "http-get");
+        assert_eq!(case("GetHTTP"), "get-http");
+        assert_eq!(case("Ipv4Addr"), "ipv4-addr");
+        assert_eq!(case("Sha256Sum"), "sha256-sum");
+        assert_eq!(case("Remote_Add"), "remote-add");
+    }
+}
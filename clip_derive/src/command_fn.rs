@@ -0,0 +1,100 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+
+/// Converts a snake_case identifier into CamelCase, e.g. `remote_add` into `RemoteAdd`
+fn camel_case(ident: &syn::Ident) -> String {
+    ident
+        .to_string()
+        .trim_start_matches("r#")
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect()
+}
+
+/// Generates a command struct from the function parameters, and the ways to call the function
+fn impl_command(item: &syn::ItemFn) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let mut function = item.clone();
+    let name = &function.sig.ident;
+    let ty = format_ident!("{}Command", camel_case(name));
+    let vis = &function.vis;
+    let output = match &function.sig.output {
+        syn::ReturnType::Default => quote! { () },
+        syn::ReturnType::Type(_, ty) => quote! { #ty },
+    };
+    let docs: Vec<&syn::Attribute> = function
+        .attrs
+        .iter()
+        .filter(crate::attribute::is("doc"))
+        .collect();
+    let mut fields = proc_macro2::TokenStream::new();
+    let mut names = Vec::new();
+    for input in function.sig.inputs.iter() {
+        let syn::FnArg::Typed(syn::PatType { attrs, pat, ty, .. }) = input else {
+            return Err(syn::Error::new_spanned(input, "commands cannot take self"));
+        };
+        let syn::Pat::Ident(syn::PatIdent { ident, .. }) = pat.as_ref() else {
+            return Err(syn::Error::new_spanned(pat, "expected a named parameter"));
+        };
+        fields.extend(quote! {
+            #(#attrs)*
+            pub #ident: #ty,
+        });
+        names.push(ident.clone());
+    }
+    // parameter attributes (doc comments mostly) now belong to the generated fields
+    for input in function.sig.inputs.iter_mut() {
+        if let syn::FnArg::Typed(syn::PatType { attrs, .. }) = input {
+            attrs.clear();
+        }
+    }
+    // the doc comments of the function describe the command in the help
+    let command_name = name.to_string().trim_start_matches("r#").replace('_', "-");
+    Ok(quote! {
+        #function
+
+        #(#docs)*
+        #[derive(clipv::TryParse, clipv::AsArg, clipv::AsCommand)]
+        #[command(name = #command_name)]
+        #vis struct #ty {
+            #fields
+        }
+
+        impl #ty {
+            /// Calls the command function with the parsed arguments
+            #vis fn call(self) -> #output {
+                #name(#(self.#names),*)
+            }
+        }
+
+        impl<H> clipv::run::Dispatch<H> for #ty {
+            type Output = #output;
+
+            fn dispatch(self, _handler: &mut H) -> Self::Output {
+                self.call()
+            }
+        }
+    })
+}
+
+pub(crate) fn impl_command_macro(item: TokenStream) -> TokenStream {
+    match syn::parse::<syn::ItemFn>(item) {
+        Ok(item) => impl_command(&item).unwrap_or_else(|err| err.to_compile_error()),
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
@@ -0,0 +1,139 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::attribute;
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+
+/// A variant handler: its method name, parameters and the pattern destructuring the variant
+struct Handler {
+    method: syn::Ident,
+    parameters: proc_macro2::TokenStream,
+    pattern: proc_macro2::TokenStream,
+    arguments: Vec<syn::Ident>,
+}
+
+fn impl_handler(parent: &syn::Ident, variant: &syn::Variant) -> Handler {
+    let ident = &variant.ident;
+    let method = format_ident!("{}", attribute::snake_case(ident));
+    let mut parameters = proc_macro2::TokenStream::new();
+    let mut arguments = Vec::new();
+    let pattern = match &variant.fields {
+        syn::Fields::Unit => quote! { #parent::#ident },
+        syn::Fields::Named(syn::FieldsNamed { named, .. }) => {
+            for syn::Field { ident, ty, .. } in named.iter() {
+                parameters.extend(quote! { #ident: #ty, });
+                arguments.extend(ident.clone());
+            }
+            quote! { #parent::#ident { #(#arguments),* } }
+        }
+        syn::Fields::Unnamed(syn::FieldsUnnamed { unnamed, .. }) => {
+            for (index, syn::Field { ty, .. }) in unnamed.iter().enumerate() {
+                let argument = format_ident!("arg{}", index);
+                parameters.extend(quote! { #argument: #ty, });
+                arguments.push(argument);
+            }
+            quote! { #parent::#ident ( #(#arguments),* ) }
+        }
+    };
+    Handler {
+        method,
+        parameters,
+        pattern,
+        arguments,
+    }
+}
+
+/// Generation of a dispatcher, either synchronous or asynchronous
+struct DispatchMacro {
+    /// core trait implemented, from the `clipv::run` module
+    dispatch_trait: &'static str,
+    /// suffix of the generated handler trait
+    handler_suffix: &'static str,
+    /// return type of the handler methods and of the dispatch method
+    output: proc_macro2::TokenStream,
+}
+
+impl DispatchMacro {
+    fn impl_dispatch(&self, ast: &syn::DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
+        let syn::Data::Enum(syn::DataEnum { variants, .. }) = &ast.data else {
+            return Err(syn::Error::new_spanned(
+                &ast.ident,
+                "only enum can be dispatched",
+            ));
+        };
+        let ident = &ast.ident;
+        let vis = &ast.vis;
+        let handler_trait = format_ident!("{}{}", ident, self.handler_suffix);
+        let dispatch_trait = format_ident!("{}", self.dispatch_trait);
+        let output = &self.output;
+        let mut methods = proc_macro2::TokenStream::new();
+        let mut arms = proc_macro2::TokenStream::new();
+        for variant in variants.iter() {
+            let Handler {
+                method,
+                parameters,
+                pattern,
+                arguments,
+            } = impl_handler(ident, variant);
+            let doc = format!("Handles `{}::{}`", ident, variant.ident);
+            methods.extend(quote! {
+                #[doc = #doc]
+                fn #method(&mut self, #parameters) -> #output;
+            });
+            arms.extend(quote! {
+                #pattern => handler.#method(#(#arguments),*),
+            });
+        }
+        let doc = format!(
+            "Handlers of every `{}` variant, called by `{}::dispatch`",
+            ident, self.dispatch_trait
+        );
+        Ok(quote! {
+            #[doc = #doc]
+            #vis trait #handler_trait {
+                type Output;
+                #methods
+            }
+
+            impl<H: #handler_trait> clipv::run::#dispatch_trait<H> for #ident {
+                type Output = H::Output;
+
+                fn dispatch(self, handler: &mut H) -> #output {
+                    match self {
+                        #arms
+                    }
+                }
+            }
+        })
+    }
+}
+
+pub(crate) fn impl_dispatch_macro(ast: &syn::DeriveInput) -> TokenStream {
+    DispatchMacro {
+        dispatch_trait: "Dispatch",
+        handler_suffix: "Handler",
+        output: quote! { Self::Output },
+    }
+    .impl_dispatch(ast)
+    .unwrap_or_else(|err| err.to_compile_error())
+    .into()
+}
+
+pub(crate) fn impl_async_dispatch_macro(ast: &syn::DeriveInput) -> TokenStream {
+    DispatchMacro {
+        dispatch_trait: "AsyncDispatch",
+        handler_suffix: "AsyncHandler",
+        output: quote! { clipv::run::BoxFuture<'_, Self::Output> },
+    }
+    .impl_dispatch(ast)
+    .unwrap_or_else(|err| err.to_compile_error())
+    .into()
+}
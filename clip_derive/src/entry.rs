@@ -0,0 +1,108 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+
+/// Wraps a function taking the parsed CLI into an argument-less entry point
+///
+/// `exit_code = path` names a `fn(&ParsingError) -> u8` giving the exit codes of parsing errors,
+/// `arg_files` expands the `@file` arguments, see `argfile::expand`, `multicall` dispatches on
+/// the name the binary is called by, see `run::run_multicall`, and `record = path` appends the
+/// successful invocations to a session file, see `record::Recorder::call`
+fn impl_main(attr: TokenStream, item: &syn::ItemFn) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let mut exit_code: Option<syn::Path> = None;
+    let mut arg_files = false;
+    let mut multicall = false;
+    let mut record: Option<syn::Expr> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("exit_code") {
+            exit_code = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("arg_files") {
+            arg_files = true;
+            Ok(())
+        } else if meta.path.is_ident("multicall") {
+            multicall = true;
+            Ok(())
+        } else if meta.path.is_ident("record") {
+            record = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported main attribute, expected `exit_code`, `arg_files`, `multicall` or `record`"))
+        }
+    });
+    syn::parse::Parser::parse(parser, attr)?;
+    let syn::ItemFn { attrs, vis, sig, .. } = item;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "async main functions aren't supported",
+        ));
+    }
+    let ty = match sig.inputs.first() {
+        Some(syn::FnArg::Typed(syn::PatType { ty, .. })) if sig.inputs.len() == 1 => ty,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &sig.inputs,
+                "expected a single argument receiving the parsed command line",
+            ))
+        }
+    };
+    let name = &sig.ident;
+    let mut inner = item.clone();
+    inner.attrs.clear();
+    inner.vis = syn::Visibility::Inherited;
+    inner.sig.ident = format_ident!("__clip_main");
+    let (run, run_with) = match multicall {
+        true => (quote! { run_multicall_os }, quote! { run_multicall_os_with }),
+        false => (quote! { run_os }, quote! { run_os_with }),
+    };
+    let run = match exit_code {
+        Some(exit_code) => quote! {
+            clipv::run::#run_with::<#ty, _>(&args, env!("CARGO_PKG_VERSION"), #exit_code, __clip_main)
+        },
+        None => quote! { clipv::run::#run::<#ty, _>(&args, env!("CARGO_PKG_VERSION"), __clip_main) },
+    };
+    // a multi-call binary needs the name it is called by
+    let skipped = usize::from(!multicall);
+    let expand = arg_files.then(|| quote! {
+        let args = match clipv::argfile::expand_os(&args) {
+            Ok(args) => args,
+            Err(err) => {
+                eprintln!("error: {err}");
+                return std::process::ExitCode::from(clipv::run::USAGE_ERROR);
+            }
+        };
+    });
+    // successful invocations are appended to the session file
+    let record = record.map(|path| quote! {
+        let __clip_recorder = clipv::record::Recorder::new(#path);
+        let __clip_main = |command: #ty| __clip_recorder.call(command, __clip_main);
+    });
+    Ok(quote! {
+        #(#attrs)*
+        #vis fn #name() -> std::process::ExitCode {
+            #inner
+            #record
+            let args: std::vec::Vec<std::ffi::OsString> = std::env::args_os().skip(#skipped).collect();
+            #expand
+            #run
+        }
+    })
+}
+
+pub(crate) fn impl_main_macro(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match syn::parse::<syn::ItemFn>(item) {
+        Ok(item) => impl_main(attr, &item).unwrap_or_else(|err| err.to_compile_error()),
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
@@ -11,18 +11,22 @@
 use proc_macro::TokenStream;
 use quote::quote;
 
+/// Matches the value against each variant name, ignoring the ASCII case unless `unicode_case`
 fn impl_from_str_enum_fields(
     parent: &syn::Ident,
     variants: &syn::punctuated::Punctuated<syn::Variant, syn::token::Comma>,
+    unicode_case: bool,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
     let mut fields_gen = proc_macro2::TokenStream::new();
 
-    for syn::Variant { ident, fields, .. } in variants.iter() {
+    for variant @ syn::Variant { ident, fields, .. } in variants.iter() {
         if let syn::Fields::Unit = fields {
-            let lowercase_ident = ident.to_string().to_lowercase();
+            let keywords = crate::try_parse::variant_keywords(variant)?;
 
-            fields_gen.extend(quote! {
-                #lowercase_ident => Ok(#parent::#ident),
+            fields_gen.extend(if unicode_case {
+                quote! { #(#keywords)|* => Ok(#parent::#ident), }
+            } else {
+                quote! { if #(value.eq_ignore_ascii_case(#keywords))||* { Ok(#parent::#ident) } else }
             });
         } else {
             return Err(syn::Error::new_spanned(
@@ -37,27 +41,63 @@ fn impl_from_str_enum_fields(
 fn impl_from_str_trait_for_enum(
     name: &syn::Ident,
     fields: proc_macro2::TokenStream,
+    unicode_case: bool,
 ) -> proc_macro2::TokenStream {
+    let matching = if unicode_case {
+        quote! {
+            match value.to_lowercase().as_str() {
+                #fields
+                _ => Err(format!("Unexistant variant {}", value))
+            }
+        }
+    } else {
+        quote! { #fields { Err(format!("Unexistant variant {}", value)) } }
+    };
     quote! {
         impl std::str::FromStr for #name {
             type Err = String;
 
             fn from_str(value: &str) -> Result<Self, Self::Err> {
-                match value.to_lowercase().as_str() {
-                    #fields
-                    _ => Err(format!("Unexistant variant {}", value))
-                }
+                #matching
             }
         }
     }
 }
 
+/// Lists the variants, named as `FromStr` matches them, with their description
+fn impl_possible_values(
+    name: &syn::Ident,
+    variants: &syn::punctuated::Punctuated<syn::Variant, syn::token::Comma>,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let count = variants.len();
+    let values = variants.iter().map(|variant| {
+        let keyword = crate::try_parse::variant_keywords(variant)?.remove(0);
+        let description = crate::as_arg::impl_description(variant.attrs.iter());
+        Ok(quote! { clipv::describe::value::Value::keyword(#keyword, #description) })
+    }).collect::<Result<Vec<_>, syn::Error>>()?;
+    Ok(quote! {
+        impl clipv::describe::value::PossibleValues for #name {
+            fn values() -> &'static [clipv::describe::value::Value<'static>] {
+                static VALUES: [clipv::describe::value::Value<'static>; #count] = [#(#values),*];
+                &VALUES
+            }
+        }
+    })
+}
+
 pub(crate) fn impl_from_str_macro(ast: &syn::DeriveInput) -> TokenStream {
     if let syn::Data::Enum(syn::DataEnum { variants, .. }) = &ast.data {
-        match impl_from_str_enum_fields(&ast.ident, variants) {
-            Ok(fields) => impl_from_str_trait_for_enum(&ast.ident, fields),
-            Err(err) => err.to_compile_error(),
-        }
+        let unicode_case = match crate::attribute::clip_args(&ast.attrs) {
+            Ok(clip) => crate::attribute::has_flag(&clip, "unicode_case"),
+            Err(err) => return err.to_compile_error().into(),
+        };
+        impl_from_str_enum_fields(&ast.ident, variants, unicode_case)
+            .and_then(|fields| {
+                let mut gen = impl_from_str_trait_for_enum(&ast.ident, fields, unicode_case);
+                gen.extend(impl_possible_values(&ast.ident, variants)?);
+                Ok(gen)
+            })
+            .unwrap_or_else(|err| err.to_compile_error())
     } else {
         syn::Error::new_spanned(ast, "expected an enum").to_compile_error()
     }
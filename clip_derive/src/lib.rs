@@ -11,7 +11,11 @@
 mod as_arg;
 mod as_command;
 mod attribute;
+mod command_fn;
+mod dispatch;
+mod entry;
 mod from_str;
+mod to_args;
 mod try_parse;
 use proc_macro::TokenStream;
 
@@ -22,13 +26,26 @@ use proc_macro::TokenStream;
 /// Only for Unit enum, any other type is unsupported and will an error at compile time.
 ///
 /// This macro is just a conveniant way to parse a string into the derived enumeration. It is case
-/// insensitive. The behavior is actually the same as for the TryParse trait.
+/// insensitive. The behavior is actually the same as for the TryParse trait: a variant is matched
+/// by its kebab-cased name, e.g. `http-get` for `HTTPGet`, or written in one word, unless
+/// `#[command(name = "...")]` renames it.
+///
+/// Only the case of ASCII letters is ignored, which compares without allocating. With
+/// `#[clip(unicode_case)]` on the enum, the value is lowercased first, e.g. `ÉTÉ` matching `Été`.
+///
+/// `clipv::describe::value::PossibleValues` is implemented as well, so that help messages and
+/// completions list the variants for the fields of this type.
 ///
 /// # Examples
 ///
 /// ```
 /// # #[macro_use] extern crate clip_derive;
+/// # extern crate clip_core;
+/// # mod clipv {
+/// #    pub use clip_core::*;
+/// # }
 /// use clip_derive::FromStr;
+/// use clip_core::describe::value::PossibleValues;
 /// use std::str::FromStr;
 ///
 /// ##[derive(Debug, PartialEq, FromStr)]
@@ -38,37 +55,34 @@ use proc_macro::TokenStream;
 /// assert_eq!(Random::from_str("one"), Ok(Random::One));
 /// assert_eq!(Random::from_str("THREE"), Ok(Random::Three));
 /// assert!(Random::from_str("Four").is_err());
+/// assert_eq!(Random::values()[1].name(), "two");
 /// # }
 /// ```
-#[proc_macro_derive(FromStr)]
+#[proc_macro_derive(FromStr, attributes(clip, command))]
 pub fn from_str_macro_derive(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
     crate::from_str::impl_from_str_macro(&ast)
 }
 
-/// TryParse devive attribute
+/// TryParse derive attribute
 ///
 /// It is the Derive macro for the TryParse trait
 ///
 /// Supports Struct and Enum but not Union
 ///
-/// The try_parse helper attribute may be associated to a field to use the TryParse::try_parse
-/// method instead of the default str.parse one.
-///
 /// Since the FromStr and TryParse trait cannot be checked for a certain trait, generated errors
 /// can lead to crypted error messages. However since there is a few number of errors' sources:
 ///  - trying to derive an union, which should give an explicit error
 ///  - having an attributeless fields for which the type doesn't implement FromStr
 ///  - having a `#[try_parse]` attributed field for which the type doesn't implement TryParse
+///
 /// If the error seems hard to decrypt, chances are high that the problem is one of the last two.
 ///
 /// # Struct
-/// Only positional arguments are supported, meaning there is no concept of optional or flags for
-/// now. The position of a field within itself will determine the expected position within a parsed
-/// line.
-///
-/// For instance the following structure will expect `<titi> <tata> <toto>` in this order only.
+/// A struct takes positional arguments, optional arguments and global options. Its fields are
+/// positional unless marked `#[global]`, and are parsed in the declaration order: the following
+/// structure expects `<titi> <tata> <toto>` in this order only.
 /// ```
 /// # extern crate clip_core;
 /// # #[macro_use] extern crate clip_derive;
@@ -87,12 +101,120 @@ pub fn from_str_macro_derive(input: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// A field with an `Option` type consumes a value only if one is left, which makes sense for
+/// trailing fields, and a field with a `#[clip(default = "...")]` parses that value when none is
+/// left.
+///
+/// A positional `Vec` field takes every value left, or at most `max` of them with
+/// `#[arity(min = 1, max = 4)]`, fewer values than `min` failing with `ParsingError::Arity`; being
+/// greedy, it is usually the last positional field. The help shows it as `<name>...`.
+/// A `HashSet` or `BTreeSet` field is variadic as well and keeps each value once.
+///
+/// ```
+/// # extern crate clip_core;
+/// # #[macro_use] extern crate clip_derive;
+/// # mod clipv {
+/// #    pub use clip_derive::*;
+/// #    pub use clip_core::*;
+/// # }
+/// use clipv::{parser::{ParsingError, TryParse}, TryParse};
+///
+/// ##[derive(TryParse)]
+/// struct Login {
+///     ##[requires("password")]
+///     user: Option<String>,
+///     password: Option<String>,
+/// }
+///
+/// let result = Login::try_parse(["claire"].iter());
+/// assert_eq!(result.err(), Some(ParsingError::Requires { argument: "user", requires: "password" }));
+/// ```
+///
+/// # Global options
+/// A named field marked `#[global]` is not positional: it is given as `--field-name <value>` (or
+/// `--field-name=<value>`) anywhere on the line, before or after a subcommand keyword. A `bool`
+/// field is a flag taking no value and an `Option` field may be omitted. A `Vec`, `HashSet` or
+/// `BTreeSet` global option may be repeated (`-I src -I lib`), each occurrence adding a value; the
+/// help shows it as `--name <VALUE>...`.
+///
+/// Global options are extracted from every remaining value, hence a struct declaring some is
+/// expected to be the root of the command line: values left after its positional fields raise
+/// `ParsingError::TooManyArguments`.
 ///
 /// # Enum
 /// For an enumeration, the first positional parameter corresponds to the Variant (case insensitive
 /// match) that should be initialized and the following value are used if for the Variant
-/// initialisation.
+/// initialisation. A variant is matched by its kebab-cased name, e.g. `remote-add`, or by its name
+/// in one word, e.g. `remoteadd`.
+///
+/// # Attributes
+/// On a field:
+///  - `#[try_parse]`: parses the field with `TryParse::try_parse` instead of `str::parse`
+///  - `#[global]`: makes the field a global option, see above
+///  - `#[clip(short = 'v')]`: also accepts a global option as `-v <value>`
+///  - `#[clip(default = "...")]`: parses that value when the argument is missing
+///  - `#[clip(flatten)]`: accepts the global options of a struct made only of them as options of
+///    the parent; with `#[clip(flatten, prefix = "db")]` they are expected as `--db-<option>`,
+///    which avoids collisions between several flattened structs, and without a prefix the options
+///    having a short alias are accepted as `-v` as well, flags being bundled as in `-vv`. The
+///    options of a flattened struct take a single value and support `default`, `possible` and
+///    `stdin`
+///  - `#[requires("other")]`: if this field is provided, `other` must be too, or parsing fails
+///    with `ParsingError::Requires`
+///  - `#[required_unless("other")]`: this field may only be missing if `other` is provided, or
+///    parsing fails with `ParsingError::RequiredUnless`
+///  - `#[position = N]`: takes the Nth positional value (from 0) whatever the layout of the
+///    struct, the other fields filling the remaining places in order; AsArg, AsCommand and ToArgs
+///    follow that order
+///  - `#[arity(min = 1, max = 4)]`: bounds the number of values of a collection field, or of
+///    occurrences of a collection global option
+///  - `#[deny_duplicates]`: fails with `ParsingError::DuplicateValue` on a repeated value of a set
+///  - `#[trailing]`: on the last positional field, a `Vec` taking every remaining value verbatim,
+///    the ones looking like global options included, e.g. the command run by a wrapper
+///    (`mytool run <cmd> <args...>`)
+///  - `#[stdin]`: replaces a `-` value by the content of the standard input, following the
+///    convention of grep or jq (see `clipv::parser::stdin_value`)
+///  - `#[possible("fast", "slow")]`: only accepts those values, ignoring the ASCII case
+///  - `#[file_exists]`, `#[dir_exists]`: the path given must be an existing file or directory
+///  - `#[writable]`: the path given must be writable or, when it does not exist, be in a writable
+///    directory; the path checks fail with `ParsingError::Invalid` naming the field
+///  - `#[clip(allow_no_match)]`: accepts a `clipv::glob::GlobPaths` field (`glob` feature) whose
+///    pattern matches no path
+///  - `#[secret]`: masks the value in the traces
+///  - `#[deprecated_arg(note = "...")]`: warns on the standard error, once, when the field is given
+///
+/// On a variant:
+///  - `#[default_command]`: selects the variant when no value is left and, if it has fields, when
+///    the keyword matches no variant (the keyword then being its first argument)
+///  - `#[command(name = "...")]`: matches the variant by that keyword
 ///
+/// On the type:
+///  - `#[clip(before_parse = "check")]`: calls `fn check() -> Result<(), ParsingError>` before
+///    parsing
+///  - `#[clip(after_parse = "normalize")]`: calls `fn normalize(&mut Self) -> Result<(), ParsingError>`
+///    on the parsed value, e.g. to normalize or cross-validate fields; an error of either is the
+///    error of the parsing
+///  - `#[clip(unicode_case)]`: ignores the case of any letter in the enum keywords, at the cost of
+///    a lowercase copy of each keyword, instead of only the ASCII ones
+///  - `#[clip(fast_match)]`: dispatches the keywords on their length before comparing them, which
+///    saves most of the comparisons for enums with hundreds of variants
+///  - `#[clip(abbreviations)]`: accepts any unambiguous prefix of a keyword (`stat` for `status`),
+///    a prefix of several variants failing with `ParsingError::Ambiguous` which lists them
+///
+/// The parsed value is then checked by `clipv::parser::Validate::validate` when its type
+/// implements that trait, whose `ParsingError::invalid` errors name the fields at fault.
+///
+/// # Arguments
+/// TryParse is implemented over `&str`, `&&str`, `&OsStr` and `&OsString` items, the last two
+/// keeping the arguments which are not unicode for `PathBuf` and `OsString` fields (see
+/// `clipv::parser::parse_os`), other fields failing with `ParsingError::InvalidUtf8`.
+///
+/// A struct with a lifetime parameter may borrow `&'a str` fields from the arguments, which are
+/// then expected to live as long: such a struct is parsed without allocating.
+///
+/// With the `trace` feature, the generated parser reports each value given to a field, each
+/// variant selected and each fallback taken to the tracer of `clipv::trace::with_tracer`, the
+/// values of `#[secret]` fields being masked.
 ///
 /// # Examples
 ///
@@ -123,23 +245,303 @@ pub fn from_str_macro_derive(input: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
-#[proc_macro_derive(TryParse, attributes(try_parse))]
+#[proc_macro_derive(TryParse, attributes(try_parse, requires, required_unless, default_command, global, clip, command, deprecated_arg, secret, stdin, file_exists, dir_exists, writable, possible, position, arity, deny_duplicates, trailing))]
 pub fn try_parse_macro_derive(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
     crate::try_parse::impl_try_parse_macro(&ast)
 }
 
-#[proc_macro_derive(AsArg, attributes(choices, group))]
+/// ToArgs derive attribute
+///
+/// Implements `clipv::parser::ToArgs`, the inverse of the TryParse derive: global options come
+/// first (`--name value`, a flag only when set, an `Option` only when `Some`), followed by the
+/// positional fields in declaration order, converted with `to_string`. `#[try_parse]` fields
+/// give their own arguments and an enum value starts with the keyword of its variant.
+/// Flattened options are not supported.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate clip_derive;
+/// # extern crate clip_core;
+/// # mod clipv {
+/// #    pub use clip_derive::*;
+/// #    pub use clip_core::*;
+/// # }
+/// use clipv::{parser::ToArgs, ToArgs, TryParse};
+///
+/// ##[derive(TryParse, ToArgs)]
+/// struct Copy {
+///     ##[global]
+///     force: bool,
+///     from: String,
+///     to: Option<String>,
+/// }
+///
+/// fn main() {
+///     let copy = Copy { force: true, from: String::from("a"), to: None };
+///     assert_eq!(copy.to_args(), ["--force", "a"]);
+/// }
+/// ```
+#[proc_macro_derive(ToArgs, attributes(try_parse, global, clip, command, position, arity, deny_duplicates, trailing))]
+pub fn to_args_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    crate::to_args::impl_to_args_macro(&ast)
+}
+
+#[proc_macro_derive(AsArg, attributes(choices, group, global, clip, value_hint, value_name, deprecated_arg, secret, possible, inline_choices, position, arity, deny_duplicates, trailing))]
 pub fn as_arg_macro_derive(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
     crate::as_arg::impl_as_arg_macro(&ast)
 }
 
-#[proc_macro_derive(AsCommand, attributes(commands, command, choices, group))]
+/// AsCommand derive attribute
+///
+/// The variants of an enum are its subcommands, their fields being the subcommand arguments. A
+/// variant marked with `#[command]` wraps another AsCommand type and takes its subcommands.
+///
+/// Commands are named after the kebab-cased type or variant, e.g. `remote-add`, unless renamed
+/// with `#[command(name = "my-tool")]`; `#[command(name = env!("CARGO_BIN_NAME"))]` names the root
+/// command after the binary.
+///
+/// `#[command(version = "...", author = "...", long_about = "...")]` set the metadata displayed
+/// by the help message and `--version`, `#[command(from_cargo)]` taking the version, authors and
+/// description (when there is no doc comment) of the package. `#[command(category = "...")]` on
+/// a variant lists it under its own title in the help of the enum.
+///
+/// With `#[clip(lint)]` on the type, a test checking the command tree with
+/// `clipv::describe::lint::lint` is generated as well, failing on duplicated or unreachable names,
+/// missing descriptions and overlong help lines.
+///
+/// `#[clip(template = "...")]` overrides the layout of the help message, see
+/// `Command::set_template`. `#[clip(before_help = "...")]`, `#[clip(after_help = "...")]` and
+/// the repeatable `#[clip(example = "...")]` add free text and example invocations to it.
+/// `#[clip(typed_usage)]` shows the type of each value in the usage line, e.g. `<count: u8>`, and
+/// `#[clip(inline_subcommands)]` the subcommand names, e.g. `{add|remove}`.
+/// `#[clip(static_help)]` builds the help messages once, `help` and `long_help` then copying them
+/// instead of describing and formatting the whole command tree on each call.
+/// `#[clip(display_order = "alphabetical")]` (or `"required_first"`, `"declaration"` by default)
+/// sorts the arguments, options and subcommands listed by the help.
+///
+/// `#[value_name = "FILE"]` on a field replaces its name in the usage line and the details.
+/// On a `#[choices]` field, `#[clip(choices_limit = 3)]` shows at most 3 choices in the usage
+/// line, e.g. `<a|b|c|…>`, `#[clip(choices_as_name)]` always shows the field name and
+/// `#[inline_choices]` always shows every choice, e.g. `<Slow|Fast>` rather than `<speed>`.
+/// `#[clip(default = "...")]` shows `[default: ..]` in the details, the TryParse derive parsing
+/// that value when the argument is missing.
+/// Global options are listed in an `Options:` section as `-s, --long <VALUE>`, with the short
+/// alias of `#[clip(short = 'c')]`.
+/// The values of a `#[possible("fast", "slow")]` field, or of a field whose type implements
+/// `clipv::describe::value::PossibleValues`, are listed as `[possible values: ..]` and completed.
+///
+/// `#[deprecated_arg(note = "use --output instead")]` on a field or a variant marks it as
+/// deprecated in the help; the TryParse derive warns on the standard error, once, when it is used.
+/// `#[secret]` marks the value of a field as secret: masked in error messages and typed without
+/// echo when prompted for. A `clipv::secret::Secret` field also keeps it out of debug output.
+#[proc_macro_derive(AsCommand, attributes(commands, command, choices, group, global, clip, value_hint, value_name, deprecated_arg, secret, possible, inline_choices, position, arity, deny_duplicates, trailing))]
 pub fn as_command_macro_derive(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
     crate::as_command::impl_as_command_macro(&ast)
 }
+
+/// Clip derive attribute
+///
+/// Derives TryParse and AsArg together, as well as AsCommand for a type marked `#[command(..)]` or
+/// an enum with a `#[command]` variant, all of them reading the same attributes: see the
+/// documentation of each derive.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate clip_derive;
+/// # extern crate clip_core;
+/// # mod clipv {
+/// #    pub use clip_derive::*;
+/// #    pub use clip_core::*;
+/// # }
+/// use clipv::describe::command::AsCommand;
+/// use clipv::parser::{Parsed, TryParse};
+/// use clipv::Clip;
+///
+/// ##[derive(Clip)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// ##[derive(Clip)]
+/// ##[command(name = "shapes")]
+/// enum Shape {
+///     /// draws a point
+///     Point(
+///         ##[try_parse]
+///         Point,
+///     ),
+///     Clear,
+/// }
+///
+/// fn main() {
+///     let Parsed(shape, _) = Shape::try_parse(["point", "1", "2"].iter()).unwrap();
+///     assert!(matches!(shape, Shape::Point(Point { x: 1, y: 2 })));
+///     assert_eq!(Shape::command().summarize(), "shapes [COMMAND] ..");
+/// }
+/// ```
+#[proc_macro_derive(Clip, attributes(try_parse, requires, required_unless, default_command, commands, command, choices, group, global, clip, value_hint, value_name, deprecated_arg, secret, stdin, file_exists, dir_exists, writable, possible, inline_choices, position, arity, deny_duplicates, trailing))]
+pub fn clip_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    let mut output = crate::try_parse::impl_try_parse_macro(&ast);
+    output.extend(crate::as_arg::impl_as_arg_macro(&ast));
+    if crate::as_command::is_command(&ast) {
+        output.extend(crate::as_command::impl_as_command_macro(&ast));
+    }
+    output
+}
+
+/// Dispatch derive attribute
+///
+/// Only for enumerations. Generates a `<Enum>Handler` trait with one method per variant, named
+/// after the snake cased variant and taking its fields, then implements `clipv::run::Dispatch`
+/// so that a parsed command is routed to its handler without writing the `match` by hand.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate clip_core;
+/// # #[macro_use] extern crate clip_derive;
+/// # mod clipv {
+/// #    pub use clip_derive::*;
+/// #    pub use clip_core::*;
+/// # }
+/// use clipv::{run::Dispatch, Dispatch};
+///
+/// ##[derive(Dispatch)]
+/// enum Remote {
+///     Add { name: String },
+///     RemoveAll,
+/// }
+///
+/// struct Printer;
+///
+/// impl RemoteHandler for Printer {
+///     type Output = String;
+///
+///     fn add(&mut self, name: String) -> String { format!("adding {name}") }
+///     fn remove_all(&mut self) -> String { String::from("removing all") }
+/// }
+///
+/// assert_eq!(Remote::Add { name: String::from("origin") }.dispatch(&mut Printer), "adding origin");
+/// ```
+#[proc_macro_derive(Dispatch)]
+pub fn dispatch_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    crate::dispatch::impl_dispatch_macro(&ast)
+}
+
+/// AsyncDispatch derive attribute
+///
+/// Same as the Dispatch derive for asynchronous handlers: the generated `<Enum>AsyncHandler`
+/// methods return a `clipv::run::BoxFuture`, and `clipv::run::AsyncDispatch` is implemented. The
+/// `async` feature of clipv must be enabled.
+#[proc_macro_derive(AsyncDispatch)]
+pub fn async_dispatch_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    crate::dispatch::impl_async_dispatch_macro(&ast)
+}
+
+/// Main attribute
+///
+/// Turns `fn main(cli: Cli) -> R` into the program entry point: command line arguments are
+/// collected with `std::env::args_os` and parsed into `Cli` before calling the function, see
+/// `clipv::run::run_os`. `Cli` must implement both `TryParse` and `AsCommand`, and `R` anything a
+/// regular main function may return. Arguments which are not unicode are kept as they are for the
+/// `PathBuf` and `OsString` fields, other fields failing with `ParsingError::InvalidUtf8`.
+///
+/// `-h`/`--help`, `help [COMMAND]..` and `-V`/`--version` (using the crate version) are answered
+/// directly. A parsing error is printed with the usage line and the program exits with
+/// `ParsingError::exit_code`, or with the code returned by the function given as
+/// `#[clipv::main(exit_code = function)]`, a `fn(&ParsingError) -> u8`.
+/// `#[clipv::main(arg_files)]` replaces the `@file` arguments by the content of the file
+/// before parsing, see `clipv::argfile::expand_os`.
+/// `#[clipv::main(multicall)]` makes a multi-call binary: called through a link named after a
+/// subcommand (`ls` → `Cli::Ls`), it runs that subcommand, see `clipv::run::run_multicall_os`.
+/// `#[clipv::main(record = path)]` appends the invocations for which the function succeeded to
+/// the session file `path`, see `clipv::record::Recorder::call`: `Cli` must implement `ToArgs` and
+/// `R` `clipv::record::Outcome`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # extern crate clip_core;
+/// # #[macro_use] extern crate clip_derive;
+/// # mod clipv {
+/// #    pub use clip_derive::*;
+/// #    pub use clip_core::*;
+/// # }
+/// use clipv::{AsCommand, TryParse};
+///
+/// ##[derive(AsCommand, TryParse)]
+/// /// Greets someone
+/// struct Cli {
+///     /// who to greet
+///     name: String,
+/// }
+///
+/// ##[clipv::main]
+/// fn main(cli: Cli) -> Result<(), String> {
+///     println!("Hello {}", cli.name);
+///     Ok(())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
+    crate::entry::impl_main_macro(attr, item)
+}
+
+/// Command attribute
+///
+/// Defines a command from a free function: its parameters, with their doc comments and
+/// attributes, become the fields of a generated `<Function>Command` struct deriving `TryParse`,
+/// `AsArg` and `AsCommand`. The struct `call` method then calls the function with the parsed
+/// arguments.
+///
+/// The command is named after the kebab-cased function and described by its doc comments. It is
+/// run as any other command, e.g. `#[clipv::main] fn main(add: AddCommand) { .. }`, and
+/// implements `Dispatch` for any handler by calling the function, so that it can be given to a
+/// `Repl`, or be a `#[command]` variant of an enum whose handler method dispatches it.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate clip_core;
+/// # #[macro_use] extern crate clip_derive;
+/// # mod clipv {
+/// #    pub use clip_derive::*;
+/// #    pub use clip_core::*;
+/// # }
+/// use clipv::{describe::command::AsCommand, parser::parse};
+///
+/// /// Adds two numbers
+/// ##[clipv::command]
+/// fn add(
+///     /// first operand
+///     a: u8,
+///     /// second operand
+///     b: u8,
+/// ) -> u8 {
+///     a + b
+/// }
+///
+/// assert_eq!(parse(["1", "2"].iter(), AddCommand::call), Ok(3));
+/// assert!(AddCommand::command().help().starts_with("Adds two numbers\n\nUsage: add <a> <b>"));
+/// ```
+#[proc_macro_attribute]
+pub fn command(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    crate::command_fn::impl_command_macro(item)
+}
@@ -0,0 +1,114 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::attribute;
+use crate::try_parse::{is_bool, option_inner, variant_keywords, collection_inner};
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+
+/// Statements pushing the arguments of the fields, bound by reference to their name (or
+/// `__field_<index>`), global options first
+fn impl_fields(fields: &syn::Fields) -> Result<(Vec<syn::Ident>, proc_macro2::TokenStream), syn::Error> {
+    let mut bindings = Vec::new();
+    let mut globals = proc_macro2::TokenStream::new();
+    let mut positionals = proc_macro2::TokenStream::new();
+    for (index, syn::Field { ident, ty, attrs, .. }) in attribute::parse_order(fields.iter())? {
+        let binding = ident.clone().unwrap_or_else(|| format_ident!("__field_{}", index));
+        let clip = attribute::clip_args(attrs)?;
+        if attribute::has_flag(&clip, "flatten") {
+            return Err(syn::Error::new_spanned(ty, "ToArgs does not support flattened options"));
+        }
+        if attrs.iter().any(|attr| attr.path().is_ident("global")) {
+            let flag = attribute::long_name(&binding);
+            globals.extend(if is_bool(ty) {
+                quote! { if *#binding { args.push(String::from(#flag)); } }
+            } else if option_inner(ty).is_some() {
+                quote! { if let Some(value) = #binding { args.extend([String::from(#flag), value.to_string()]); } }
+            } else if collection_inner(ty).is_some() {
+                quote! { for value in #binding { args.extend([String::from(#flag), value.to_string()]); } }
+            } else {
+                quote! { args.extend([String::from(#flag), #binding.to_string()]); }
+            });
+        } else if attrs.iter().any(|attr| attr.path().is_ident("try_parse")) {
+            positionals.extend(quote! { args.extend(clipv::parser::ToArgs::to_args(#binding)); });
+        } else if option_inner(ty).is_some() {
+            positionals.extend(quote! { if let Some(value) = #binding { args.push(value.to_string()); } });
+        } else if collection_inner(ty).is_some() {
+            positionals.extend(quote! { args.extend(#binding.iter().map(ToString::to_string)); });
+        } else {
+            positionals.extend(quote! { args.push(#binding.to_string()); });
+        }
+        bindings.push((index, binding));
+    }
+    bindings.sort_by_key(|(index, _)| *index);
+    let bindings = bindings.into_iter().map(|(_, binding)| binding).collect();
+    globals.extend(positionals);
+    Ok((bindings, globals))
+}
+
+/// Pattern destructuring an object whose fields are bound by `impl_fields`
+fn pattern(path: proc_macro2::TokenStream, fields: &syn::Fields, bindings: &[syn::Ident]) -> proc_macro2::TokenStream {
+    match fields {
+        syn::Fields::Unit => path,
+        syn::Fields::Named(_) => quote! { #path { #(#bindings),* } },
+        syn::Fields::Unnamed(_) => quote! { #path ( #(#bindings),* ) },
+    }
+}
+
+fn impl_to_args(ast: &syn::DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &ast.ident;
+    let body = match &ast.data {
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => {
+            let (bindings, pushes) = impl_fields(fields)?;
+            let pattern = pattern(quote! { #name }, fields, &bindings);
+            quote! {
+                let #pattern = self;
+                #pushes
+            }
+        }
+        syn::Data::Enum(syn::DataEnum { variants, .. }) => {
+            let mut arms = proc_macro2::TokenStream::new();
+            for variant in variants {
+                let ident = &variant.ident;
+                let keyword = &variant_keywords(variant)?[0];
+                let (bindings, pushes) = impl_fields(&variant.fields)?;
+                let pattern = pattern(quote! { #name::#ident }, &variant.fields, &bindings);
+                arms.extend(quote! {
+                    #pattern => {
+                        args.push(String::from(#keyword));
+                        #pushes
+                    }
+                });
+            }
+            quote! {
+                match self {
+                    #arms
+                }
+            }
+        }
+        syn::Data::Union(union) => {
+            return Err(syn::Error::new_spanned(union.union_token, "unions are not supported"))
+        }
+    };
+    Ok(quote! {
+        impl clipv::parser::ToArgs for #name {
+            fn to_args(&self) -> std::vec::Vec<String> {
+                let mut args = std::vec::Vec::new();
+                #body
+                args
+            }
+        }
+    })
+}
+
+/// Implements ToArgs, the inverse of the TryParse derive
+pub(crate) fn impl_to_args_macro(ast: &syn::DeriveInput) -> TokenStream {
+    impl_to_args(ast).unwrap_or_else(|err| err.to_compile_error()).into()
+}
@@ -14,6 +14,392 @@ use quote::quote;
 
 struct ParsingMacro {
     recursion_attr: &'static str,
+    /// `#[clip(unicode_case)]`: keywords are matched ignoring the case of any letter rather than
+    /// of the ASCII ones only, which allocates a lowercase copy of each keyword
+    unicode_case: bool,
+    /// `#[clip(fast_match)]`: keywords are first dispatched on their length, which saves most of
+    /// the comparisons for enums with many variants
+    fast_match: bool,
+    /// `#[clip(abbreviations)]`: a keyword matching no variant selects the only variant it is a
+    /// prefix of
+    abbreviations: bool,
+}
+
+/// A `#[global]` field
+struct GlobalOption {
+    ident: syn::Ident,
+    ty: syn::Type,
+    /// `#[clip(default = "...")]`
+    default: Option<syn::LitStr>,
+    /// `#[clip(short = 'c')]`
+    short: Option<syn::LitChar>,
+    /// `#[stdin]`, reading a `-` value from the standard input
+    stdin: bool,
+    /// `#[clip(allow_no_match)]`, on a `GlobPaths` option
+    allow_no_match: bool,
+    /// `#[secret]`, masked in traces
+    secret: bool,
+    /// checks of a `#[possible("a", "b")]` attribute and of the path attributes (`#[file_exists]`..),
+    /// empty without any
+    checks: proc_macro2::TokenStream,
+    /// `#[arity(min = 1, max = 3)]` bounding the occurrences of a `Vec` or set option
+    arity: (usize, Option<usize>),
+    /// `#[deny_duplicates]`, on a set option
+    deny_duplicates: bool,
+}
+
+/// Statements parsing each field into a local binding, and the bindings used to build the object
+#[derive(Default)]
+struct FieldsParser {
+    gen: proc_macro2::TokenStream,
+    bindings: Vec<(Option<syn::Ident>, syn::Ident)>,
+    /// `#[global]` fields, extracted from the values before any positional field is parsed
+    globals: Vec<GlobalOption>,
+    /// `#[clip(flatten)]` fields with the dashes and prefix their options are expected with
+    flattened: Vec<(syn::Ident, syn::Type, String)>,
+    /// number of positional values before a `#[trailing]` field, after which no option is extracted
+    trailing: Option<usize>,
+}
+
+impl FieldsParser {
+    /// Extracts global options wherever they are in the values, leaving only positional ones
+    ///
+    /// Since global options may come after the last positional value, every value is consumed:
+    /// any value left once the positional fields have been parsed raises `TooManyArguments`.
+    fn impl_globals(&self, object: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if self.globals.is_empty() && self.flattened.is_empty() {
+            return object;
+        }
+        let mut declarations = proc_macro2::TokenStream::new();
+        let mut matches = proc_macro2::TokenStream::new();
+        let mut finalizations = proc_macro2::TokenStream::new();
+        for GlobalOption { ident, ty, default, short, stdin, allow_no_match, secret, checks, arity, deny_duplicates } in self.globals.iter() {
+            let flag = attribute::long_name(ident);
+            // the long name is matched again when there is no short one
+            let short = short.as_ref().map_or(flag.clone(), |short| format!("-{}", short.value()));
+            let present = presence(ident);
+            if is_bool(ty) {
+                declarations.extend(quote! { let mut #ident = false; });
+                finalizations.extend(quote! { let #present = #ident; });
+                matches.extend(quote! {
+                    if token == #flag || token == #short {
+                        clipv::trace::flag(#flag);
+                        #ident = true;
+                        continue;
+                    }
+                });
+                continue;
+            }
+            let prefix = format!("{flag}=");
+            // a `Vec` or set option is repeated, each occurrence adding a value
+            if let Some(inner) = collection_inner(ty) {
+                let parse_value = parse_value(inner, *stdin, *allow_no_match);
+                let count = quote::format_ident!("__{}_count", ident);
+                let insert = match (is_set(ty), deny_duplicates) {
+                    (false, _) => quote! { #ident.push(parsed); },
+                    (true, false) => quote! { #ident.insert(parsed); },
+                    (true, true) => quote! {
+                        if !#ident.insert(parsed) {
+                            return Err(clipv::parser::ParsingError::DuplicateValue {
+                                argument: #flag,
+                                value: clipv::parser::Token::os_str(value).to_string_lossy().into_owned(),
+                            });
+                        }
+                    },
+                };
+                let add = quote! {
+                    clipv::trace::value(#flag, value, #secret);
+                    #checks
+                    let parsed: #inner = #parse_value?;
+                    #count += 1;
+                    #insert
+                    continue;
+                };
+                declarations.extend(quote! {
+                    let mut #ident = <#ty>::new();
+                    let mut #count: usize = 0;
+                });
+                finalizations.extend(quote! { let #present = #count > 0; });
+                matches.extend(quote! {
+                    if token == #flag || token == #short {
+                        let value = values.next().ok_or(clipv::parser::ParsingError::TooFewArguments)?;
+                        #add
+                    }
+                    if let Some(value) = token.strip_prefix(#prefix) {
+                        #add
+                    }
+                });
+                let (min, max) = *arity;
+                if min > 0 || max.is_some() {
+                    let outside = match max {
+                        Some(max) => quote! { !(#min..=#max).contains(&#count) },
+                        None => quote! { #count < #min },
+                    };
+                    let max = max.map_or(quote! { None }, |max| quote! { Some(#max) });
+                    finalizations.extend(quote! {
+                        if #outside {
+                            return Err(clipv::parser::ParsingError::Arity {
+                                argument: #flag,
+                                min: #min,
+                                max: #max,
+                                found: #count,
+                            });
+                        }
+                    });
+                }
+                continue;
+            }
+            let inner = option_inner(ty).unwrap_or(ty);
+            let parse_value = parse_value(inner, *stdin, *allow_no_match);
+            declarations.extend(quote! { let mut #ident: Option<#inner> = None; });
+            matches.extend(quote! {
+                if token == #flag || token == #short {
+                    #ident = Some(values.next().map_or(Err(clipv::parser::ParsingError::TooFewArguments), |value| {
+                        clipv::trace::value(#flag, value, #secret);
+                        #checks
+                        #parse_value
+                    })?);
+                    continue;
+                }
+                if let Some(value) = token.strip_prefix(#prefix) {
+                    clipv::trace::value(#flag, value, #secret);
+                    #checks
+                    #ident = Some(#parse_value?);
+                    continue;
+                }
+            });
+            // whether the option was given, before its default is taken
+            finalizations.extend(quote! { let #present = #ident.is_some(); });
+            match default {
+                Some(default) => {
+                    let parse_default = parse_default(inner, default);
+                    finalizations.extend(quote! {
+                        let #ident = match #ident {
+                            Some(value) => value,
+                            None => #parse_default?,
+                        };
+                    });
+                }
+                None if option_inner(ty).is_none() => finalizations.extend(quote! {
+                    let #ident = #ident.ok_or(clipv::parser::ParsingError::TooFewArguments)?;
+                }),
+                None => (),
+            }
+        }
+        for (ident, ty, prefix) in self.flattened.iter() {
+            declarations.extend(quote! { let mut #ident = std::vec::Vec::new(); });
+            // short names are only given to the options flattened without a prefix
+            if prefix == "--" {
+                matches.extend(quote! {
+                    if let Some(shorts) = token.strip_prefix('-').filter(|shorts| !shorts.is_empty() && !shorts.starts_with('-')) {
+                        let options = <#ty as clipv::parser::Flatten>::options();
+                        let find = |short: char| options.iter().find(|option| option.short == Some(short));
+                        let flags: Option<std::vec::Vec<_>> = shorts.chars().map(|short| find(short).filter(|option| !option.takes_value)).collect();
+                        if let Some(flags) = flags {
+                            #ident.extend(flags.into_iter().map(|option| (option.name, None)));
+                            continue;
+                        }
+                        if let Some(option) = find(shorts.chars().next().unwrap_or_default()).filter(|_| shorts.chars().count() == 1) {
+                            let value = values.next().ok_or(clipv::parser::ParsingError::TooFewArguments)?;
+                            #ident.push((option.name, Some(clipv::parser::Token::text(value)?.to_string())));
+                            continue;
+                        }
+                    }
+                });
+            }
+            matches.extend(quote! {
+                if let Some(name) = token.strip_prefix(#prefix) {
+                    let (name, inline) = match name.split_once('=') {
+                        Some((name, value)) => (name, Some(value)),
+                        None => (name, None),
+                    };
+                    if let Some(option) = <#ty as clipv::parser::Flatten>::options().iter().find(|option| option.name == name) {
+                        let value = if option.takes_value {
+                            Some(match inline {
+                                Some(value) => value.to_string(),
+                                None => {
+                                    let value = values.next().ok_or(clipv::parser::ParsingError::TooFewArguments)?;
+                                    clipv::parser::Token::text(value)?.to_string()
+                                }
+                            })
+                        } else {
+                            None
+                        };
+                        #ident.push((option.name, value));
+                        continue;
+                    }
+                }
+            });
+            finalizations.extend(quote! {
+                let #ident = <#ty as clipv::parser::Flatten>::build(#ident)?;
+            });
+        }
+        // the values of a trailing field are kept verbatim, even when they look like options
+        let trailing = self.trailing.map(|preceding| quote! {
+            if positionals.len() >= #preceding {
+                positionals.push(value);
+                continue;
+            }
+        });
+        quote! {
+            {
+                #declarations
+                let mut positionals = std::vec::Vec::new();
+                while let Some(value) = values.next() {
+                    #trailing
+                    // an argument which is not unicode can only be positional
+                    let token: &str = clipv::parser::Token::text(value).unwrap_or_default();
+                    #matches
+                    positionals.push(value);
+                }
+                #finalizations
+                let mut values = positionals.into_iter();
+                let object = #object;
+                if values.next().is_some() {
+                    return Err(clipv::parser::ParsingError::TooManyArguments);
+                }
+                object
+            }
+        }
+    }
+}
+
+/// Binding telling whether the field `ident` was given on the line, its default being taken
+/// otherwise
+fn presence(ident: &syn::Ident) -> syn::Ident {
+    quote::format_ident!("__{}_present", ident)
+}
+
+pub(crate) fn is_bool(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(syn::TypePath { qself: None, path }) if path.is_ident("bool"))
+}
+
+/// Returns the inner type if the given type is an `Option`
+pub(crate) fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    generic_inner(ty, "Option")
+}
+
+/// Returns the item type if the given type is a `Vec`, a `HashSet` or a `BTreeSet`, the types of
+/// variadic fields
+pub(crate) fn collection_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    ["Vec", "HashSet", "BTreeSet"].into_iter().find_map(|name| generic_inner(ty, name))
+}
+
+/// Whether the given type is a `HashSet` or a `BTreeSet`, keeping each value once
+pub(crate) fn is_set(ty: &syn::Type) -> bool {
+    ["HashSet", "BTreeSet"].into_iter().any(|name| generic_inner(ty, name).is_some())
+}
+
+fn generic_inner<'t>(ty: &'t syn::Type, name: &str) -> Option<&'t syn::Type> {
+    if let syn::Type::Path(syn::TypePath {
+        qself: None,
+        path: syn::Path { segments, .. },
+    }) = ty
+    {
+        let last = segments.last()?;
+        if last.ident != name {
+            return None;
+        }
+        if let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
+            args, ..
+        }) = &last.arguments
+        {
+            if let Some(syn::GenericArgument::Type(inner)) = args.first() {
+                return Some(inner);
+            }
+        }
+    }
+    None
+}
+
+/// Warns on the standard error, once per program run, when `present` holds at runtime
+fn impl_deprecation_warning(
+    present: proc_macro2::TokenStream,
+    name: &str,
+    note: &syn::LitStr,
+) -> proc_macro2::TokenStream {
+    quote! {
+        if #present {
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            WARNED.call_once(|| clipv::parser::warn_deprecated(#name, #note));
+        }
+    }
+}
+
+/// Lowercased keywords selecting a variant
+///
+/// Either the `#[command(name = "...")]` literal, or the kebab-cased identifier, the identifier
+/// written in one word being accepted as well.
+pub(crate) fn variant_keywords(variant: &syn::Variant) -> Result<Vec<String>, syn::Error> {
+    match attribute::command_name(&variant.attrs)? {
+        Some(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(name),
+            ..
+        })) => Ok(vec![name.value().to_lowercase()]),
+        Some(name) => Err(syn::Error::new_spanned(name, "expected a string literal")),
+        None => {
+            let kebab = attribute::kebab_case(&variant.ident);
+            let lowercase = variant.ident.to_string().to_lowercase();
+            Ok(if kebab == lowercase { vec![kebab] } else { vec![kebab, lowercase] })
+        }
+    }
+}
+
+/// Whether the type is a `&str` borrowed from the arguments
+fn is_str_ref(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Reference(syn::TypeReference { elem, mutability: None, .. })
+        if matches!(&**elem, syn::Type::Path(syn::TypePath { qself: None, path }) if path.is_ident("str")))
+}
+
+/// Expression parsing the `#[clip(default = "...")]` value into `ty`
+fn parse_default(ty: &syn::Type, default: &syn::LitStr) -> proc_macro2::TokenStream {
+    if is_str_ref(ty) {
+        quote! { Ok::<#ty, clipv::parser::ParsingError>(#default) }
+    } else {
+        quote! { #default.parse::<#ty>().map_err(clipv::parser::ParsingError::bad_type) }
+    }
+}
+
+/// Whether the type keeps any argument, unicode or not: `PathBuf` or `OsString`
+fn is_os_type(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(syn::TypePath { qself: None, path })
+        if path.segments.last().is_some_and(|last| last.ident == "PathBuf" || last.ident == "OsString"))
+}
+
+/// Expression parsing the `value` token into `ty`, a `Result` with a `ParsingError`
+///
+/// With `stdin`, a `-` value is replaced by the content of the standard input. With
+/// `allow_no_match`, a `GlobPaths` pattern matching no path gives no path instead of failing. A
+/// `&str` borrows the token, and a `PathBuf` or an `OsString` is built from it as is, which may
+/// not be unicode.
+fn parse_value(ty: &syn::Type, stdin: bool, allow_no_match: bool) -> proc_macro2::TokenStream {
+    if stdin {
+        quote! { clipv::parser::Token::text(value).and_then(clipv::parser::stdin_value::<#ty>) }
+    } else if allow_no_match {
+        quote! {
+            clipv::parser::Token::text(value)
+                .and_then(|value| <#ty>::expand(value, true).map_err(clipv::parser::ParsingError::bad_type))
+        }
+    } else if is_str_ref(ty) {
+        quote! { clipv::parser::Token::text(value) }
+    } else if is_os_type(ty) {
+        quote! { Ok::<#ty, clipv::parser::ParsingError>(<#ty>::from(clipv::parser::Token::os_str(value))) }
+    } else {
+        quote! {
+            clipv::parser::Token::text(value)
+                .and_then(|value| value.parse::<#ty>().map_err(clipv::parser::ParsingError::bad_type))
+        }
+    }
+}
+
+/// Reads the field names listed in a relationship attribute, e.g. `#[requires("a", "b")]`
+fn related_fields(attr: &syn::Attribute) -> Result<Vec<syn::LitStr>, syn::Error> {
+    Ok(attr
+        .parse_args_with(
+            syn::punctuated::Punctuated::<syn::LitStr, syn::Token![,]>::parse_terminated,
+        )?
+        .into_iter()
+        .collect())
 }
 
 impl ParsingMacro {
@@ -21,6 +407,10 @@ impl ParsingMacro {
     ///
     /// if a #[try_parse] attribute is associated with the field, it will uses the TryParse::try_parse
     /// method for the field, otherwise and by default, str.parse::<ty> method is used.
+    /// An `Option` field consumes a value only if there is one left.
+    ///
+    /// Each field is bound to a local variable so that relationships between fields
+    /// (`#[requires]`, `#[required_unless]`) can be validated once every field has been parsed.
     ///
     /// Since there no way to know if a certain trait has been implemented (TryStr or TryParse mainly),
     /// the generated error is hard to read. However, it is the only source of error of this macro.
@@ -29,28 +419,253 @@ impl ParsingMacro {
     fn impl_fields(
         &self,
         fields: syn::punctuated::Iter<'_, syn::Field>,
-    ) -> Result<proc_macro2::TokenStream, syn::Error> {
-        let mut gen = proc_macro2::TokenStream::new();
-        for syn::Field {
+    ) -> Result<FieldsParser, syn::Error> {
+        let mut parser = FieldsParser::default();
+        let mut presences = std::collections::HashMap::new();
+        let mut relationships = Vec::new();
+        let mut deprecations = proc_macro2::TokenStream::new();
+        let mut bindings = Vec::new();
+        // single positional values before the trailing field, None once one takes an unknown number
+        let mut preceding = Some(0);
+        let mut trailing: Option<&syn::Attribute> = None;
+        for (index, syn::Field {
             ty, ident, attrs, ..
-        } in fields
+        }) in attribute::parse_order(fields)?
         {
-            if let Some(name) = ident {
-                gen.extend(quote! { #name: });
+            let binding = ident
+                .clone()
+                .unwrap_or_else(|| quote::format_ident!("__field_{}", index));
+            let option = option_inner(ty);
+            let global = attrs.iter().any(|attr| attribute::is("global")(&attr));
+            let clip = attribute::clip_args(attrs)?;
+            let default = attribute::string_value(&clip, "default")?;
+            let has_default = default.is_some();
+            let stdin = attrs.iter().any(|attr| attr.path().is_ident("stdin"));
+            let allow_no_match = attribute::has_flag(&clip, "allow_no_match");
+            let secret = attrs.iter().any(|attr| attr.path().is_ident("secret"));
+            let name = ident.as_ref().map_or(index.to_string(), ToString::to_string);
+            let possible = match attribute::possible_values(attrs)? {
+                Some(possible) => quote! { clipv::parser::check_possible(value, &[#(#possible),*])?; },
+                None => proc_macro2::TokenStream::new(),
+            };
+            let argument = match (global, ident) {
+                (true, Some(ident)) => attribute::long_name(ident),
+                _ => name.clone(),
+            };
+            let path_checks = attribute::path_checks(attrs);
+            let checks = quote! { #possible #(clipv::parser::check_path(value, #argument, clipv::parser::PathCheck::#path_checks)?;)* };
+            let traced = |parse_value| quote! { { clipv::trace::value(#name, value, #secret); #checks #parse_value } };
+            if let (Some(default), true) = (&default, option.is_some() || (global && is_bool(ty))) {
+                return Err(syn::Error::new_spanned(
+                    default,
+                    "a default value needs a field which is neither optional nor a flag",
+                ));
             }
-            if let Some(&_) = attrs.iter().find(attribute::is(self.recursion_attr)) {
-                gen.extend(quote! {
-                    {
+            if let (Some(attr), None) = (attrs.iter().find(attribute::is("arity")), collection_inner(ty)) {
+                return Err(syn::Error::new_spanned(attr, "an arity needs a `Vec` or set field"));
+            }
+            let deny_duplicates = attrs.iter().find(attribute::is("deny_duplicates"));
+            if let (Some(attr), false) = (deny_duplicates, is_set(ty)) {
+                return Err(syn::Error::new_spanned(attr, "duplicates can only be denied for a `HashSet` or `BTreeSet` field"));
+            }
+            let positional = !global && !attribute::has_flag(&clip, "flatten");
+            if let (Some(attr), true) = (trailing, positional) {
+                return Err(syn::Error::new_spanned(attr, "a trailing field must be the last positional one"));
+            }
+            if let Some(attr) = attrs.iter().find(attribute::is("trailing")) {
+                if !positional || collection_inner(ty).is_none() || is_set(ty) {
+                    return Err(syn::Error::new_spanned(attr, "a trailing field needs to be a positional `Vec`"));
+                }
+                trailing = Some(attr);
+                parser.trailing = preceding;
+            } else if positional && (collection_inner(ty).is_some() || attrs.iter().any(|attr| attribute::is(self.recursion_attr)(&attr))) {
+                preceding = None;
+            } else if positional {
+                preceding = preceding.map(|preceding| preceding + 1);
+            }
+            if attribute::has_flag(&clip, "flatten") {
+                let (Some(ident), None) = (ident, option) else {
+                    return Err(syn::Error::new_spanned(
+                        ty,
+                        "flattened options must be named and not optional",
+                    ));
+                };
+                let prefix = match attribute::string_value(&clip, "prefix")? {
+                    Some(prefix) => format!("--{}-", prefix.value()),
+                    None => String::from("--"),
+                };
+                parser.flattened.push((ident.clone(), ty.clone(), prefix));
+            } else if global {
+                match ident {
+                    Some(ident) => parser.globals.push(GlobalOption {
+                        ident: ident.clone(),
+                        ty: ty.clone(),
+                        default,
+                        short: attribute::char_value(&clip, "short")?,
+                        stdin,
+                        allow_no_match,
+                        secret,
+                        checks: checks.clone(),
+                        arity: attribute::arity(attrs)?,
+                        deny_duplicates: deny_duplicates.is_some(),
+                    }),
+                    None => {
+                        return Err(syn::Error::new_spanned(
+                            ty,
+                            "global options must be named fields",
+                        ))
+                    }
+                }
+            } else if let Some(&_) = attrs.iter().find(attribute::is(self.recursion_attr)) {
+                parser.gen.extend(quote! {
+                    let #binding = {
                         let clipv::parser::Parsed ( value, rest ) = #ty::try_parse(values)?;
                         values = rest;
                         value
+                    };
+                });
+            } else if let Some(inner) = collection_inner(ty) {
+                let (min, max) = attribute::arity(attrs)?;
+                let parse_value = traced(parse_value(inner, stdin, allow_no_match));
+                let take = max.map_or(quote! { usize::MAX }, |max| quote! { #max });
+                let max = max.map_or(quote! { None }, |max| quote! { Some(#max) });
+                let insert = match (is_set(ty), deny_duplicates) {
+                    (false, _) => quote! { collection.push(parsed); },
+                    (true, None) => quote! { collection.insert(parsed); },
+                    (true, Some(_)) => quote! {
+                        if !collection.insert(parsed) {
+                            return Err(clipv::parser::ParsingError::DuplicateValue {
+                                argument: #name,
+                                value: clipv::parser::Token::os_str(value).to_string_lossy().into_owned(),
+                            });
+                        }
                     },
+                };
+                parser.gen.extend(quote! {
+                    let #binding = {
+                        let mut collection = <#ty>::new();
+                        let mut found = 0;
+                        for value in values.by_ref().take(#take) {
+                            found += 1;
+                            let parsed: #inner = #parse_value?;
+                            #insert
+                        }
+                        if found < #min {
+                            return Err(clipv::parser::ParsingError::Arity {
+                                argument: #name,
+                                min: #min,
+                                max: #max,
+                                found,
+                            });
+                        }
+                        collection
+                    };
+                });
+            } else if let Some(inner) = option {
+                let parse_value = traced(parse_value(inner, stdin, allow_no_match));
+                parser.gen.extend(quote! {
+                    let #binding = values.next().map(|value| #parse_value).transpose()?;
+                });
+            } else if let Some(default) = default {
+                let parse_value = traced(parse_value(ty, stdin, allow_no_match));
+                let parse_default = parse_default(ty, &default);
+                let present = presence(&binding);
+                parser.gen.extend(quote! {
+                    let (#binding, #present) = match values.next() {
+                        Some(value) => (#parse_value?, true),
+                        None => (#parse_default?, false),
+                    };
                 });
             } else {
-                gen.extend(quote! { values.next().map_or(Err(clipv::parser::ParsingError::TooFewArguments), |value| value.parse::<#ty>().or(Err(clipv::parser::ParsingError::BadType)))?, });
+                let parse_value = traced(parse_value(ty, stdin, allow_no_match));
+                parser.gen.extend(quote! {
+                    let #binding = values.next().map_or(Err(clipv::parser::ParsingError::TooFewArguments), |value| #parse_value)?;
+                });
+            }
+            if let Some(name) = ident {
+                presences.insert(
+                    name.to_string(),
+                    if option.is_some() {
+                        quote! { #name.is_some() }
+                    } else if global && is_bool(ty) {
+                        quote! { #name }
+                    } else if global && collection_inner(ty).is_some() {
+                        quote! { !#name.is_empty() }
+                    } else {
+                        quote! { true }
+                    },
+                );
+                for attr in attrs.iter() {
+                    if attribute::is("requires")(&attr) || attribute::is("required_unless")(&attr) {
+                        relationships.push((name, attr));
+                    }
+                }
+            }
+            if let Some(note) = attribute::deprecation(attrs)? {
+                // only warns when the argument is on the line, not filled from its default
+                let (name, present) = match ident {
+                    Some(ident) if global => {
+                        let present = presence(ident);
+                        (attribute::long_name(ident), quote! { #present })
+                    }
+                    _ => (
+                        ident.as_ref().map_or(index.to_string(), ToString::to_string),
+                        if option.is_some() {
+                            quote! { #binding.is_some() }
+                        } else if collection_inner(ty).is_some() {
+                            quote! { !#binding.is_empty() }
+                        } else if has_default {
+                            let present = presence(&binding);
+                            quote! { #present }
+                        } else {
+                            quote! { true }
+                        },
+                    ),
+                };
+                deprecations.extend(impl_deprecation_warning(present, &name, &note));
             }
+            bindings.push((index, (ident.clone(), binding)));
         }
-        Ok(gen)
+        // the object is built in the declaration order
+        bindings.sort_by_key(|&(index, _)| index);
+        parser.bindings = bindings.into_iter().map(|(_, binding)| binding).collect();
+        for (name, attr) in relationships {
+            let present = &presences[&name.to_string()];
+            for related in related_fields(attr)? {
+                let other = presences
+                    .get(&related.value())
+                    .ok_or_else(|| syn::Error::new_spanned(&related, "unknown field"))?;
+                let argument = name.to_string();
+                let related_name = related.value();
+                parser.gen.extend(if attribute::is("requires")(&attr) {
+                    quote! {
+                        if #present && !(#other) {
+                            return Err(clipv::parser::ParsingError::Requires {
+                                argument: #argument,
+                                requires: #related_name,
+                            });
+                        }
+                    }
+                } else {
+                    quote! {
+                        if !(#present) && !(#other) {
+                            return Err(clipv::parser::ParsingError::RequiredUnless {
+                                argument: #argument,
+                                unless: #related_name,
+                            });
+                        }
+                    }
+                });
+            }
+        }
+        if let (Some(attr), None, false) = (trailing, parser.trailing, parser.globals.is_empty() && parser.flattened.is_empty()) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "with global options, a trailing field can only follow fields taking a single value",
+            ));
+        }
+        parser.gen.extend(deprecations);
+        Ok(parser)
     }
 
     /// Implements the initialisation of an object (Tuple/Struct/Unit).
@@ -58,18 +673,21 @@ impl ParsingMacro {
     /// Depending of the fields type, chooses the correct initialisation pattern
     fn impl_object_initialisation(
         &self,
-        ident: &syn::Ident,
+        path: proc_macro2::TokenStream,
         fields: &syn::Fields,
     ) -> Result<proc_macro2::TokenStream, syn::Error> {
         match fields {
-            syn::Fields::Unit => Ok(quote! { #ident }),
+            syn::Fields::Unit => Ok(path),
             syn::Fields::Named(syn::FieldsNamed { named, .. }) => {
-                let fields = self.impl_fields(named.iter())?;
-                Ok(quote! { #ident { #fields } })
+                let parser = self.impl_fields(named.iter())?;
+                let gen = &parser.gen;
+                let names = parser.bindings.iter().map(|(name, _)| name);
+                Ok(parser.impl_globals(quote! { { #gen #path { #(#names),* } } }))
             }
             syn::Fields::Unnamed(syn::FieldsUnnamed { unnamed, .. }) => {
-                let fields = self.impl_fields(unnamed.iter())?;
-                Ok(quote! { #ident ( #fields ) })
+                let FieldsParser { gen, bindings, .. } = self.impl_fields(unnamed.iter())?;
+                let bindings = bindings.iter().map(|(_, binding)| binding);
+                Ok(quote! { { #gen #path ( #(#bindings),* ) } })
             }
         }
     }
@@ -78,25 +696,147 @@ impl ParsingMacro {
     ///
     /// Consumes the next iterator value and tries to match to one of the enumeration variants
     /// It is case insensitive.
+    ///
+    /// A variant marked with `#[default_command]` is selected when there is no value left, or when
+    /// the keyword matches no variant: the keyword is then used as the variant first argument.
     fn impl_enum_initialization(
         &self,
         parent: &syn::Ident,
         variants: syn::punctuated::Iter<'_, syn::Variant>,
     ) -> Result<proc_macro2::TokenStream, syn::Error> {
-        let mut gen = proc_macro2::TokenStream::new();
-        for syn::Variant { ident, fields, .. } in variants {
-            let lowercase = ident.to_string().to_lowercase();
-            let value = self.impl_object_initialisation(ident, fields)?;
-            gen.extend(quote! {
-                #lowercase => Ok(#parent::#value),
-            });
+        let mut arms = Vec::new();
+        let mut default: Option<&syn::Variant> = None;
+        for variant in variants {
+            let syn::Variant { ident, fields, attrs, .. } = variant;
+            if let Some(attr) = attrs.iter().find(attribute::is("default_command")) {
+                if default.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "only one variant can be the default command",
+                    ));
+                }
+                default = Some(variant);
+            }
+            let keywords = variant_keywords(variant)?;
+            let value = self.impl_object_initialisation(quote! { #parent::#ident }, fields)?;
+            let warning = match attribute::deprecation(attrs)? {
+                Some(note) => impl_deprecation_warning(quote! { true }, &ident.to_string(), &note),
+                None => proc_macro2::TokenStream::new(),
+            };
+            let variant_name = ident.to_string();
+            arms.push((keywords, quote! {
+                clipv::trace::variant(keyword_text, #variant_name);
+                #warning
+                Ok(#value)
+            }));
         }
+        let (missing, fallback) = match default {
+            None => (
+                quote! { Err(clipv::parser::ParsingError::TooFewArguments) },
+                quote! {
+                    {
+                        clipv::trace::fallback(keyword_text, None);
+                        Err(clipv::parser::ParsingError::VariantNotFound)
+                    }
+                },
+            ),
+            Some(syn::Variant { ident, fields, .. }) => {
+                let value = self.impl_object_initialisation(quote! { #parent::#ident }, fields)?;
+                let fallback = if let syn::Fields::Unit = fields {
+                    quote! {
+                        {
+                            clipv::trace::fallback(keyword_text, None);
+                            Err(clipv::parser::ParsingError::VariantNotFound)
+                        }
+                    }
+                } else {
+                    let variant_name = ident.to_string();
+                    quote! {
+                        {
+                            clipv::trace::fallback(keyword_text, Some(#variant_name));
+                            let mut values = std::iter::once(keyword).chain(values.by_ref());
+                            Ok(#value)
+                        }
+                    }
+                };
+                (quote! { Ok(#value) }, fallback)
+            }
+        };
+        let fallback = if self.abbreviations {
+            let keywords = arms.iter().enumerate().flat_map(|(index, (keywords, _))| {
+                keywords.iter().map(move |keyword| quote! { (#keyword, #index) })
+            });
+            let abbreviated = arms.iter().enumerate().map(|(index, (_, body))| quote! { Ok(Some(#index)) => { #body } });
+            let keyword_text = match self.unicode_case {
+                true => quote! { &keyword_text.to_lowercase() },
+                false => quote! { keyword_text },
+            };
+            quote! {
+                match clipv::parser::resolve_abbreviation(#keyword_text, &[#(#keywords),*]) {
+                    Err(err) => Err(err),
+                    #(#abbreviated)*
+                    _ => #fallback
+                }
+            }
+        } else {
+            fallback
+        };
+        let matching = if self.fast_match {
+            // keywords are lowercase and ignoring the ASCII case keeps the length: a keyword may
+            // only match the ones of its length
+            let mut buckets = std::collections::BTreeMap::<usize, Vec<_>>::new();
+            for (keywords, body) in &arms {
+                for keyword in keywords {
+                    buckets.entry(keyword.len()).or_default().push((keyword, body));
+                }
+            }
+            let buckets = buckets.into_iter().map(|(length, arms)| {
+                let arms = arms.into_iter().map(|(keyword, body)| {
+                    let test = if self.unicode_case {
+                        quote! { lowercase == #keyword }
+                    } else {
+                        quote! { keyword_text.eq_ignore_ascii_case(#keyword) }
+                    };
+                    quote! { if #test { Some({ #body }) } else }
+                });
+                quote! { #length => #(#arms)* { None } }
+            });
+            let (lowercase, length) = match self.unicode_case {
+                true => (quote! { let lowercase = keyword_text.to_lowercase(); }, quote! { lowercase.len() }),
+                false => (proc_macro2::TokenStream::new(), quote! { keyword_text.len() }),
+            };
+            // every bucket falls through to the same fallback, generated once
+            quote! {
+                #lowercase
+                let matched = match #length {
+                    #(#buckets,)*
+                    _ => None,
+                };
+                match matched {
+                    Some(result) => result,
+                    None => #fallback,
+                }
+            }
+        } else if self.unicode_case {
+            let arms = arms.iter().map(|(keywords, body)| quote! { #(#keywords)|* => { #body } });
+            quote! {
+                match keyword_text.to_lowercase().as_str() {
+                    #(#arms)*
+                    _ => #fallback
+                }
+            }
+        } else {
+            let arms = arms.iter().map(|(keywords, body)| {
+                quote! { if #(keyword_text.eq_ignore_ascii_case(#keywords))||* { #body } else }
+            });
+            quote! { #(#arms)* { #fallback } }
+        };
         Ok(quote! {
-            {
-                let keyword = values.next().ok_or(clipv::parser::ParsingError::TooFewArguments)?;
-                match keyword.to_lowercase().as_str() {
-                    #gen
-                    _ => Err(clipv::parser::ParsingError::VariantNotFound)
+            match values.next() {
+                None => #missing,
+                Some(keyword) => {
+                    let keyword_text: &str = clipv::parser::Token::text(keyword).unwrap_or_default();
+                    #matching
                 }
             }?
         })
@@ -105,7 +845,7 @@ impl ParsingMacro {
     fn impl_parser(&self, ident: &syn::Ident, data: &syn::Data) -> proc_macro2::TokenStream {
         match data {
             syn::Data::Struct(syn::DataStruct { fields, .. }) => {
-                self.impl_object_initialisation(ident, fields)
+                self.impl_object_initialisation(quote! { #ident }, fields)
             }
             syn::Data::Enum(syn::DataEnum { variants, .. }) => {
                 self.impl_enum_initialization(ident, variants.iter())
@@ -119,29 +859,213 @@ impl ParsingMacro {
     }
 }
 
+/// Implements the Flatten trait for a struct made only of global options
+///
+/// Returns nothing for any other object since they cannot be flattened into another struct.
+fn impl_flatten(ident: &syn::Ident, data: &syn::Data) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let syn::Data::Struct(syn::DataStruct {
+        fields: syn::Fields::Named(syn::FieldsNamed { named, .. }),
+        ..
+    }) = data
+    else {
+        return Ok(proc_macro2::TokenStream::new());
+    };
+    if named.is_empty() || !named.iter().all(|field| field.attrs.iter().any(|attr| attribute::is("global")(&attr))) {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    let mut options = proc_macro2::TokenStream::new();
+    let mut declarations = proc_macro2::TokenStream::new();
+    let mut matches = proc_macro2::TokenStream::new();
+    let mut finalizations = proc_macro2::TokenStream::new();
+    let mut names = Vec::new();
+    for syn::Field { ident, ty, attrs, .. } in named.iter() {
+        let Some(ident) = ident else { continue };
+        let name = attribute::option_name(ident);
+        let description = crate::as_arg::impl_description(attrs.iter());
+        let takes_value = !is_bool(ty);
+        let clip = attribute::clip_args(attrs)?;
+        // `build` only sees the values, not the prefixed names the path checks and secrets report
+        let unsupported = ["secret", "file_exists", "dir_exists", "writable", "arity", "deny_duplicates"];
+        if let Some(attr) = attrs.iter().find(|attr| unsupported.iter().any(|name| attr.path().is_ident(name))) {
+            return Err(syn::Error::new_spanned(attr, "not supported on the options of a flattened struct"));
+        }
+        if attribute::has_flag(&clip, "allow_no_match") || collection_inner(ty).is_some() {
+            return Err(syn::Error::new_spanned(ty, "the options of a flattened struct take a single value"));
+        }
+        let short = match attribute::char_value(&clip, "short")? {
+            Some(short) => quote! { Some(#short) },
+            None => quote! { None },
+        };
+        options.extend(quote! {
+            clipv::parser::OptionSpec { name: #name, short: #short, takes_value: #takes_value, description: #description },
+        });
+        if is_bool(ty) {
+            declarations.extend(quote! { let mut #ident = false; });
+            matches.extend(quote! { #name => #ident = true, });
+        } else {
+            let inner = option_inner(ty).unwrap_or(ty);
+            let possible = attribute::possible_values(attrs)?
+                .map(|possible| quote! { clipv::parser::check_possible(value.as_str(), &[#(#possible),*])?; });
+            let parse_value = match attrs.iter().any(|attr| attr.path().is_ident("stdin")) {
+                true => quote! { clipv::parser::stdin_value::<#inner>(&value) },
+                false => quote! { value.parse::<#inner>().map_err(clipv::parser::ParsingError::bad_type) },
+            };
+            declarations.extend(quote! { let mut #ident: Option<#inner> = None; });
+            matches.extend(quote! {
+                #name => {
+                    let value = value.ok_or(clipv::parser::ParsingError::TooFewArguments)?;
+                    #possible
+                    #ident = Some(#parse_value?);
+                }
+            });
+            match attribute::string_value(&clip, "default")? {
+                Some(default) => {
+                    let parse_default = parse_default(inner, &default);
+                    finalizations.extend(quote! {
+                        let #ident = match #ident {
+                            Some(value) => value,
+                            None => #parse_default?,
+                        };
+                    });
+                }
+                None if option_inner(ty).is_none() => finalizations.extend(quote! {
+                    let #ident = #ident.ok_or(clipv::parser::ParsingError::TooFewArguments)?;
+                }),
+                None => (),
+            }
+        }
+        names.push(ident);
+    }
+    Ok(quote! {
+        impl clipv::parser::Flatten for #ident {
+            fn options() -> &'static [clipv::parser::OptionSpec] {
+                &[#options]
+            }
+
+            fn build(found: std::vec::Vec<(&'static str, Option<String>)>) -> Result<Self, clipv::parser::ParsingError> {
+                #declarations
+                for (name, value) in found {
+                    match name {
+                        #matches
+                        _ => {}
+                    }
+                }
+                #finalizations
+                Ok(Self { #(#names),* })
+            }
+        }
+    })
+}
+
+/// Types of the `#[try_parse]` fields, parsed with their own TryParse implementation
+fn nested_types(data: &syn::Data) -> Vec<&syn::Type> {
+    let fields: Vec<&syn::Field> = match data {
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => fields.iter().collect(),
+        syn::Data::Enum(syn::DataEnum { variants, .. }) => variants.iter().flat_map(|variant| variant.fields.iter()).collect(),
+        syn::Data::Union(_) => Vec::new(),
+    };
+    fields
+        .into_iter()
+        .filter(|field| field.attrs.iter().any(|attr| attr.path().is_ident("try_parse")))
+        .map(|field| &field.ty)
+        .collect()
+}
+
+/// Body of `try_parse` calling the `#[clip(before_parse = "fn")]` and
+/// `#[clip(after_parse = "fn")]` hooks around the parser
+///
+/// The parsed value is then checked by `clipv::parser::Validate` when its type implements it.
+fn impl_hooks(clip: &[syn::Meta], parser: proc_macro2::TokenStream) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let before = attribute::string_value(clip, "before_parse")?
+        .map(|hook| hook.parse::<syn::ExprPath>())
+        .transpose()?
+        .map(|hook| quote! { #hook()?; });
+    let (parsed, after) = match attribute::string_value(clip, "after_parse")? {
+        Some(hook) => {
+            let hook = hook.parse::<syn::ExprPath>()?;
+            (quote! { let mut parsed = #parser; }, quote! { #hook(&mut parsed)?; })
+        }
+        None => (quote! { let parsed = #parser; }, proc_macro2::TokenStream::new()),
+    };
+    Ok(quote! {
+        #before
+        #parsed
+        #after
+        {
+            use clipv::parser::{ValidateImplemented as _, ValidateMissing as _};
+            (&clipv::parser::Validation(&parsed)).validate_parsed()?;
+        }
+        Ok(clipv::parser::Parsed(parsed, values))
+    })
+}
+
 /// Implements TryParse trait for any rust object with the input values being an iterator of &str
 ///
 /// Supports Struct and Enum but not Union
 pub(crate) fn impl_try_parse_macro(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    let clip = match attribute::clip_args(&ast.attrs) {
+        Ok(clip) => clip,
+        Err(err) => return err.to_compile_error().into(),
+    };
     let parser = ParsingMacro {
         recursion_attr: "try_parse",
+        unicode_case: attribute::has_flag(&clip, "unicode_case"),
+        fast_match: attribute::has_flag(&clip, "fast_match"),
+        abbreviations: attribute::has_flag(&clip, "abbreviations"),
     }
     .impl_parser(name, &ast.data);
+    let body = match impl_hooks(&clip, parser) {
+        Ok(body) => body,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    // a struct with a lifetime borrows its `&str` fields from the arguments, which then live as
+    // long, and cannot be flattened from owned values
+    let lifetime = ast.generics.lifetimes().next().map(|param| param.lifetime.clone());
+    let flatten = match lifetime {
+        Some(_) => proc_macro2::TokenStream::new(),
+        None => impl_flatten(name, &ast.data).unwrap_or_else(|err| err.to_compile_error()),
+    };
+    let (lifetime, name) = match lifetime {
+        Some(lifetime) => (lifetime.clone(), quote! { #name<#lifetime> }),
+        None => (syn::Lifetime::new("'a", proc_macro2::Span::call_site()), quote! { #name }),
+    };
+    // types parsed by hand may only support unicode arguments
+    let nested = nested_types(&ast.data);
     quote! {
-        impl<'a> clipv::parser::TryParse<&'a str> for #name {
+        #flatten
+
+        impl<#lifetime> clipv::parser::TryParse<&#lifetime str> for #name {
+            type Error = clipv::parser::ParsingError;
+
+            fn try_parse<I: std::iter::Iterator<Item = &#lifetime str>>(mut values: I) -> Result<clipv::parser::Parsed<Self, I>, Self::Error> {
+                #body
+            }
+        }
+
+        impl<#lifetime> clipv::parser::TryParse<&#lifetime &#lifetime str> for #name {
+            type Error = clipv::parser::ParsingError;
+
+            fn try_parse<I: std::iter::Iterator<Item = &#lifetime &#lifetime str>>(mut values: I) -> Result<clipv::parser::Parsed<Self, I>, Self::Error> {
+                #body
+            }
+        }
+
+        impl<#lifetime> clipv::parser::TryParse<&#lifetime std::ffi::OsStr> for #name
+        where #(#nested: clipv::parser::TryParse<&#lifetime std::ffi::OsStr, Error = clipv::parser::ParsingError>,)* {
             type Error = clipv::parser::ParsingError;
 
-            fn try_parse<I: std::iter::Iterator<Item = &'a str>>(mut values: I) -> Result<clipv::parser::Parsed<Self, I>, Self::Error> {
-                Ok(clipv::parser::Parsed((#parser), values))
+            fn try_parse<I: std::iter::Iterator<Item = &#lifetime std::ffi::OsStr>>(mut values: I) -> Result<clipv::parser::Parsed<Self, I>, Self::Error> {
+                #body
             }
         }
 
-        impl<'a> clipv::parser::TryParse<&'a &'a str> for #name {
+        impl<#lifetime> clipv::parser::TryParse<&#lifetime std::ffi::OsString> for #name
+        where #(#nested: clipv::parser::TryParse<&#lifetime std::ffi::OsString, Error = clipv::parser::ParsingError>,)* {
             type Error = clipv::parser::ParsingError;
 
-            fn try_parse<I: std::iter::Iterator<Item = &'a &'a str>>(mut values: I) -> Result<clipv::parser::Parsed<Self, I>, Self::Error> {
-                Ok(clipv::parser::Parsed((#parser), values))
+            fn try_parse<I: std::iter::Iterator<Item = &#lifetime std::ffi::OsString>>(mut values: I) -> Result<clipv::parser::Parsed<Self, I>, Self::Error> {
+                #body
             }
         }
     }
@@ -0,0 +1,40 @@
+//SPDX-FileCopyrightText: 2024 Claire Bts <claxxx.bts@gmail.com>
+//SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Parses arbitrary command lines into a derived command tree, run with `cargo fuzz run parse`
+//!
+//! The arguments of an input are separated by NUL bytes, see `clipv::fuzz`.
+
+#![no_main]
+
+use clipv::TryParse;
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[derive(TryParse)]
+enum Remote {
+    Add {
+        name: String,
+        url: String,
+    },
+    Remove(String),
+    #[default_command]
+    List(Option<u8>),
+}
+
+#[allow(dead_code)]
+#[derive(TryParse)]
+struct App {
+    #[global]
+    verbose: bool,
+    #[global]
+    #[clip(short = 'j')]
+    jobs: Option<u16>,
+    #[try_parse]
+    remote: Remote,
+    depth: Option<i32>,
+}
+
+fuzz_target!(|data: &[u8]| {
+    clipv::fuzz::fuzz_parse::<App>(data);
+});
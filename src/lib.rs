@@ -11,3 +11,21 @@
 pub use clip_core::*;
 #[cfg(feature = "derive")]
 pub use clip_derive::*;
+
+/// Traits and derives needed by most command line interfaces, e.g. `use clipv::prelude::*;`
+///
+/// The `main` and `command` attribute macros are left out, as `command` is also an attribute of
+/// the derives: they are used as `#[clipv::main]` and `#[clipv::command]`.
+pub mod prelude {
+    pub use clip_core::describe::arg::AsArg;
+    pub use clip_core::describe::command::AsCommand;
+    pub use clip_core::describe::value::PossibleValues;
+    pub use clip_core::parser::{Parsed, ParsingError, ToArgs, TryParse, Validate};
+    pub use clip_core::run::Dispatch;
+    #[cfg(feature = "async")]
+    pub use clip_core::run::AsyncDispatch;
+    #[cfg(feature = "derive")]
+    pub use clip_derive::{AsArg, AsCommand, Clip, Dispatch, FromStr, ToArgs, TryParse};
+    #[cfg(all(feature = "derive", feature = "async"))]
+    pub use clip_derive::AsyncDispatch;
+}
@@ -38,9 +38,9 @@ fn it_should_support_struct_with_named_and_single_depth_fields() {
     assert_eq!(
         SimpleNamed::arguments(),
         ArgType::Group(ArgGroup(vec![
-            Arg::new("var1", Some("first structure field")),
-            Arg::new("var2", None),
-            Arg::new("var3", Some("This is a more\ncomplexe documentation"))
+            Arg::new("var1", Some("first structure field")).with_type_name("u8"),
+            Arg::new("var2", None).with_type_name("String"),
+            Arg::new("var3", Some("This is a more\ncomplexe documentation")).with_type_name("usize")
         ]))
     );
 }
@@ -55,8 +55,8 @@ enum SimpleEnum {
 
 fn simple_enum_arguments() -> ArgType {
     ArgType::Choices(Choices(vec![
-        Arg::new("Variant1", None),
-        Arg::new("Variant2", Some("provides some documentation")),
+        Arg::new("variant1", None),
+        Arg::new("variant2", Some("provides some documentation")),
     ]))
 }
 
@@ -81,9 +81,9 @@ fn it_should_support_struct_with_unnamed_and_single_depth_fields() {
     assert_eq!(
         SimpleUnnamed::arguments(),
         ArgType::Group(ArgGroup(vec![
-            Arg::new("u8", Some("first structure field")),
-            Arg::new("String", None),
-            Arg::new("usize", Some("This is a more\ncomplexe documentation"))
+            Arg::new("u8", Some("first structure field")).with_type_name("u8"),
+            Arg::new("String", None).with_type_name("std::string::String"),
+            Arg::new("usize", Some("This is a more\ncomplexe documentation")).with_type_name("usize")
         ]))
     );
 }
@@ -104,19 +104,22 @@ enum NestedStruct {
 fn nested_struct_arguments() -> ArgType {
     ArgType::Choices(Choices(vec![
         Arg::with_type(
-            "Struct",
+            "struct",
             Some("this is a nested structure variable"),
             ArgType::Group(ArgGroup(vec![
-                Arg::new("a", None),
-                Arg::new("b", Some("b is a subvariable of a struct")),
+                Arg::new("a", None).with_type_name("u8"),
+                Arg::new("b", Some("b is a subvariable of a struct")).with_type_name("u8"),
             ])),
         ),
         Arg::with_type(
-            "Tuple",
+            "tuple",
             None,
-            ArgType::Group(ArgGroup(vec![Arg::new("u8", None), Arg::new("u8", None)])),
+            ArgType::Group(ArgGroup(vec![
+                Arg::new("u8", None).with_type_name("u8"),
+                Arg::new("u8", None).with_type_name("u8"),
+            ])),
         ),
-        Arg::new("Unit", None),
+        Arg::new("unit", None),
     ]))
 }
 
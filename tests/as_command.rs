@@ -18,7 +18,7 @@
 #[cfg(feature = "derive")]
 mod derive_test {
 use clipv::describe::command::AsCommand;
-use clipv::{AsCommand, AsArg};
+use clipv::{AsCommand, AsArg, FromStr, TryParse};
 
 #[allow(dead_code)]
 #[derive(AsCommand)]
@@ -32,15 +32,22 @@ enum SimpleEnum {
 fn it_should_display_the_usage() {
     assert_eq!(SimpleEnum::help(), r#"description
 
-Usage: SimpleEnum <SimpleEnum>
+Usage: simple-enum [COMMAND] ..
 
-Arguments:
-  SimpleEnum
-    - Variant1
-    - Variant2provides some documentation
+Commands:
+  variant1
+  variant2  provides some documentation
+  help    prints this message or the help of the given subcommand
 "#);
 }
 
+#[test]
+fn it_should_accept_the_help_subcommand() {
+    assert_eq!(SimpleEnum::help_subcommand(&["help"]), Some(Ok(SimpleEnum::help())));
+    assert_eq!(SimpleEnum::help_subcommand(&["variant1"]), None);
+    assert_eq!(SimpleEnum::help_subcommand(&["help", "help"]).unwrap().unwrap(), "prints this message or the help of the given subcommand\n\nUsage: simple-enum help <COMMAND>...\n\nArguments:\n  <COMMAND>...\n");
+}
+
 #[derive(Debug,AsArg)]
 struct EmptyArg;
 
@@ -56,17 +63,41 @@ enum NestedEnum {
 
 #[test]
 fn it_should_support_nested_arguments() {
-    assert_eq!(NestedEnum::help(), r#"Usage: NestedEnum <NestedEnum>
+    assert_eq!(NestedEnum::help(), r#"Usage: nested-enum [COMMAND] ..
 
-Arguments:
-  NestedEnum
-    - u8
-      String
-    - a
-      b
-    - EmptyArg
-    - Unit
+Commands:
+  tuple
+  struct
+  sub-arg
+  unit
+  help    prints this message or the help of the given subcommand
 "#);
+    assert_eq!(
+        NestedEnum::command().help_for_path(&["struct"]),
+        Some(String::from("Usage: nested-enum struct <a> <b>\n\nArguments:\n  a\n  b\n"))
+    );
+    assert_eq!(
+        NestedEnum::command().help_for_path(&["Tuple"]),
+        Some(String::from("Usage: nested-enum tuple <u8> <String>\n\nArguments:\n  u8\n  String\n"))
+    );
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+/// manages the tool
+enum Tool {
+    /// manages remotes
+    #[command]
+    Remote(NestedEnum),
+    Status,
+}
+
+#[test]
+fn it_should_nest_commands_of_variants() {
+    assert_eq!(
+        Tool::command().help_for_path(&["remote"]),
+        Some(String::from("manages remotes\n\nUsage: tool remote [COMMAND] ..\n\nCommands:\n  tuple\n  struct\n  sub-arg\n  unit\n  help    prints this message or the help of the given subcommand\n"))
+    );
 }
 
 #[allow(dead_code)]
@@ -78,4 +109,524 @@ enum SimpleCommad {
     /// provides some documentation
     Variant2,
 }
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+/// with global options
+struct Global {
+    /// log more
+    #[global]
+    verbose: bool,
+    name: String,
+}
+
+#[test]
+fn it_should_display_global_options() {
+    assert_eq!(Global::help(), r#"with global options
+
+Usage: global [OPTIONS] <name>
+
+Arguments:
+  name
+
+Options:
+  --verbose  log more
+"#);
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+struct LongNames {
+    /// where to copy
+    destination_directory: String,
+    /// log more
+    #[global]
+    verbose_logging: bool,
+}
+
+#[test]
+fn it_should_keep_long_names_apart_from_their_description() {
+    assert_eq!(LongNames::help(), r#"Usage: long-names [OPTIONS] <destination_directory>
+
+Arguments:
+  destination_directory  where to copy
+
+Options:
+  --verbose-logging  log more
+"#);
+}
+
+#[allow(dead_code)]
+#[derive(TryParse)]
+struct Connection {
+    /// host
+    #[global]
+    host: String,
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+struct Flattened {
+    #[clip(flatten, prefix = "db")]
+    database: Connection,
+}
+
+#[test]
+fn it_should_display_prefixed_flattened_options() {
+    assert_eq!(Flattened::help(), r#"Usage: flattened [OPTIONS]
+
+Options:
+  --db-host <VALUE>  host
+"#);
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+#[clip(lint)]
+/// fully documented command, checked by a generated test
+struct Linted {
+    /// file to read
+    file: String,
+    /// log more
+    #[global]
+    verbose: bool,
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+struct Hinted {
+    #[value_hint(DirPath)]
+    directory: String,
+    /// configuration file
+    #[global]
+    #[value_hint(FilePath)]
+    config: Option<String>,
+}
+
+#[test]
+fn it_should_describe_value_hints() {
+    use clipv::describe::arg::{GetArgs, ValueHint};
+    let command = Hinted::command();
+    assert_eq!(command.arguments.get_args()[0].value_hint, ValueHint::DirPath);
+    assert_eq!(command.global_options.get_args()[0].value_hint, ValueHint::FilePath);
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+#[clip(template = "{usage}\n{arguments}")]
+/// not displayed by the template
+struct Templated {
+    /// file to read
+    file: String,
+}
+
+#[test]
+fn it_should_use_the_help_template() {
+    assert_eq!(Templated::help(), "templated <file>\nArguments:\n  file    file to read\n");
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+#[clip(after_help = "See the manual", example = "Documented a.txt", example = "Documented b.txt")]
+struct Documented {
+    file: String,
+}
+
+#[test]
+fn it_should_display_examples_and_notes() {
+    assert_eq!(Documented::help(), r#"Usage: documented <file>
+
+Arguments:
+  file
+
+Examples:
+  Documented a.txt
+  Documented b.txt
+
+Notes:
+  See the manual
+"#);
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+/// Copies files
+///
+/// Directories are copied recursively.
+struct Copy {
+    source: String,
+}
+
+#[test]
+fn it_should_split_short_and_long_descriptions() {
+    assert_eq!(Copy::help(), "Copies files\n\nUsage: copy <source>\n\nArguments:\n  source\n");
+    assert_eq!(
+        Copy::long_help(),
+        "Copies files\n\nDirectories are copied recursively.\n\nUsage: copy <source>\n\nArguments:\n  source\n"
+    );
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand, TryParse)]
+struct Export {
+    /// file to write
+    file: String,
+    /// legacy output file
+    #[deprecated_arg(note = "use --output instead")]
+    #[global]
+    out: Option<String>,
+}
+
+#[test]
+fn it_should_mark_deprecated_arguments() {
+    use clipv::parser::{Parsed, TryParse};
+    assert_eq!(
+        Export::help(),
+        "Usage: export [OPTIONS] <file>\n\nArguments:\n  file    file to write\n\nOptions:\n  --out <VALUE>  legacy output file [deprecated: use --output instead]\n"
+    );
+    let Parsed(export, _) = Export::try_parse(["a.txt", "--out", "b.txt"].iter()).unwrap();
+    assert_eq!(export.out.as_deref(), Some("b.txt"));
+}
+
+#[derive(TryParse)]
+struct LegacyExport {
+    #[deprecated_arg]
+    #[clip(default = "csv")]
+    format: String,
+    #[deprecated_arg]
+    #[global]
+    level: u8,
+    #[deprecated_arg]
+    #[global]
+    #[clip(default = "4")]
+    jobs: u8,
+    #[deprecated_arg]
+    #[global]
+    tag: Vec<String>,
+    #[deprecated_arg]
+    #[global]
+    force: bool,
+}
+
+#[test]
+fn it_should_parse_deprecated_options_which_are_not_optional() {
+    use clipv::parser::{Parsed, TryParse};
+    let Parsed(export, _) = LegacyExport::try_parse(["--level", "2"].iter()).unwrap();
+    assert_eq!((export.format.as_str(), export.level, export.jobs, export.tag.len(), export.force), ("csv", 2, 4, 0, false));
+    let Parsed(export, _) = LegacyExport::try_parse(["json", "--level=1", "--jobs", "8", "--tag", "a", "--force"].iter()).unwrap();
+    assert_eq!((export.format.as_str(), export.level, export.jobs, export.tag, export.force), ("json", 1, 8, vec![String::from("a")], true));
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+struct Load {
+    /// configuration to load
+    #[value_name = "FILE"]
+    cfg_path: String,
+    /// load recursively
+    #[value_name = "DEPTH"]
+    #[global]
+    depth: Option<u8>,
+}
+
+#[test]
+fn it_should_display_value_names() {
+    assert_eq!(
+        Load::help(),
+        "Usage: load [OPTIONS] <FILE>\n\nArguments:\n  FILE    configuration to load\n\nOptions:\n  --depth <DEPTH>  load recursively\n"
+    );
+}
+
+#[derive(Debug, PartialEq, FromStr, AsArg)]
+enum Speed {
+    Slow,
+    Fast,
+}
+
+#[derive(Debug, AsCommand, TryParse)]
+struct Drive {
+    /// how fast
+    #[choices]
+    #[clip(default = "slow")]
+    speed: Speed,
+    /// distance in km
+    #[global]
+    #[clip(default = "10")]
+    distance: u32,
+}
+
+#[test]
+fn it_should_display_possible_values_and_defaults() {
+    use clipv::parser::{Parsed, TryParse};
+    assert_eq!(
+        Drive::help(),
+        "Usage: drive [OPTIONS] <speed>\n\nArguments:\n  speed   how fast [possible values: slow, fast] [default: slow]\n\nOptions:\n  --distance <VALUE>  distance in km [default: 10]\n"
+    );
+    let Parsed(drive, _) = Drive::try_parse(std::iter::empty::<&str>()).unwrap();
+    assert_eq!((drive.speed, drive.distance), (Speed::Slow, 10));
+    let Parsed(drive, _) = Drive::try_parse(["fast", "--distance=3"].iter()).unwrap();
+    assert_eq!((drive.speed, drive.distance), (Speed::Fast, 3));
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+#[clip(typed_usage)]
+struct Resize {
+    #[value_name = "PATH"]
+    path: std::path::PathBuf,
+    count: Option<u8>,
+}
+
+#[test]
+fn it_should_display_typed_usage() {
+    assert_eq!(Resize::command().summarize(), "resize <PATH: std::path::PathBuf> <count: u8>");
+}
+
+#[derive(Debug, PartialEq, AsCommand, TryParse)]
+#[command(name = "my-tool")]
+enum Renamed {
+    RemoteAdd,
+    #[command(name = "rm")]
+    Remove,
+}
+
+#[test]
+fn it_should_rename_commands() {
+    use clipv::parser::{Parsed, TryParse};
+    assert_eq!(Renamed::command().summarize(), "my-tool [COMMAND] ..");
+    assert_eq!(Renamed::help_subcommand(&["help", "remote-add"]).unwrap().unwrap(), "Usage: my-tool remote-add\n\n");
+    for (keyword, expected) in [("remote-add", Renamed::RemoteAdd), ("RemoteAdd", Renamed::RemoteAdd), ("rm", Renamed::Remove)] {
+        let Parsed(parsed, _) = Renamed::try_parse([keyword].iter()).unwrap();
+        assert_eq!(parsed, expected);
+    }
+    assert!(Renamed::try_parse(["remove"].iter()).is_err());
+}
+
+#[derive(Debug, PartialEq, AsCommand, AsArg, FromStr, TryParse)]
+enum Request {
+    HTTPGet,
+    #[command(name = "put")]
+    HTTPPut,
+}
+
+#[test]
+fn it_should_keep_acronyms_in_one_word() {
+    use clipv::describe::arg::{Arg, ArgType, AsArg, Choices};
+    use clipv::parser::{Parsed, TryParse};
+    assert_eq!(Request::help(), "Usage: request [COMMAND] ..\n\nCommands:\n  http-get\n  put\n  help    prints this message or the help of the given subcommand\n");
+    assert_eq!(Request::arguments(), ArgType::Choices(Choices(vec![Arg::new("http-get", None), Arg::new("put", None)])));
+    let Parsed(parsed, _) = Request::try_parse(["http-get"].iter()).unwrap();
+    assert_eq!(parsed, Request::HTTPGet);
+    assert_eq!(("http-get".parse(), "HttpGet".parse(), "PUT".parse()), (Ok(Request::HTTPGet), Ok(Request::HTTPGet), Ok(Request::HTTPPut)));
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+#[command(from_cargo, author = "Jane Doe", long_about = "manages packages\n\nin depth")]
+struct Packaged;
+
+#[test]
+fn it_should_fill_metadata_from_cargo() {
+    let command = Packaged::command();
+    assert_eq!(command.version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+    assert_eq!(command.author.as_deref(), Some("Jane Doe"));
+    assert_eq!(
+        Packaged::long_help(),
+        format!("packaged {}\nJane Doe\n\nmanages packages\n\nin depth\n\nUsage: packaged\n\n", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+enum Cargo {
+    #[command(category = "Build commands")]
+    Build,
+    #[command(category = "Manifest commands")]
+    Add,
+    #[command(category = "Build commands")]
+    Check,
+}
+
+#[test]
+fn it_should_group_subcommands_by_category() {
+    assert_eq!(
+        Cargo::help(),
+        "Usage: cargo [COMMAND] ..\n\nCommands:\n  help    prints this message or the help of the given subcommand\n\nBuild commands:\n  build\n  check\n\nManifest commands:\n  add\n"
+    );
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+#[clip(display_order = "required_first")]
+struct Sorted {
+    label: Option<String>,
+    #[clip(default = "1")]
+    level: u8,
+    path: String,
+    /// log more
+    #[global]
+    verbose: bool,
+    /// output directory
+    #[global]
+    output: String,
+}
+
+#[test]
+fn it_should_sort_the_help_listings() {
+    assert_eq!(
+        Sorted::help(),
+        "Usage: sorted [OPTIONS] <label> <level> <path>\n\nArguments:\n  path\n  label\n  level   [default: 1]\n\nOptions:\n  --output <VALUE>  output directory\n  --verbose         log more\n"
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse, AsCommand)]
+struct Shorts {
+    /// log more
+    #[global]
+    #[clip(short = 'v')]
+    verbose: bool,
+    /// output directory
+    #[global]
+    #[clip(short = 'o')]
+    output: Option<String>,
+    /// dry run
+    #[global]
+    dry_run: bool,
+}
+
+#[test]
+fn it_should_align_short_and_long_options() {
+    use clipv::parser::{Parsed, TryParse};
+    assert_eq!(
+        Shorts::help(),
+        "Usage: shorts [OPTIONS]\n\nOptions:\n  -v, --verbose         log more\n  -o, --output <VALUE>  output directory\n      --dry-run         dry run\n"
+    );
+    let Parsed(shorts, _) = Shorts::try_parse(["-v", "-o", "out"].iter()).unwrap();
+    assert_eq!(
+        shorts,
+        Shorts {
+            verbose: true,
+            output: Some(String::from("out")),
+            dry_run: false
+        }
+    );
+}
+
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, AsArg, FromStr)]
+enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+struct Logs {
+    #[choices]
+    #[clip(choices_limit = 2)]
+    level: Level,
+    #[choices]
+    #[clip(choices_as_name)]
+    backup: Level,
+}
+
+#[test]
+fn it_should_truncate_long_choice_lists() {
+    assert_eq!(
+        Logs::help(),
+        "Usage: logs <trace|debug|…> <backup>\n\nArguments:\n  level   [possible values: trace, debug, info, warn, error]\n  backup  [possible values: trace, debug, info, warn, error]\n"
+    );
+}
+
+#[derive(Debug, TryParse, AsCommand)]
+struct Login {
+    /// account password
+    #[global]
+    #[secret]
+    password: clipv::secret::Secret<String>,
+    user: String,
+}
+
+#[test]
+fn it_should_keep_secrets_out_of_errors_and_debug() {
+    use clipv::error::parse;
+    use clipv::parser::{Parsed, TryParse};
+    let command = Login::command();
+    assert!(clipv::describe::arg::GetArgs::get_args(&command.global_options)[0].secret);
+    let Parsed(login, _) = Login::try_parse(["--password", "hunter2", "me"].iter()).unwrap();
+    assert_eq!((login.password.expose().as_str(), login.user.as_str()), ("hunter2", "me"));
+    assert_eq!(format!("{login:?}"), "Login { password: Secret(***), user: \"me\" }");
+    let err = parse(&["--password", "hunter2", "me", "extra"], |_: Login| ()).unwrap_err();
+    assert!(err.render(&command).contains("  login --password *** me extra\n"));
+}
+
+/// a command whose help is built once
+///
+/// with a longer documentation
+#[allow(dead_code)]
+#[derive(AsCommand)]
+#[clip(static_help)]
+enum Cached {
+    /// first command
+    First { count: u8 },
+    Second,
+}
+
+#[test]
+fn it_should_build_the_static_help_once() {
+    assert_eq!(Cached::help(), Cached::command().help());
+    assert_eq!(Cached::help(), Cached::command().help());
+    assert_eq!(Cached::long_help(), Cached::command().long_help());
+    assert_ne!(Cached::help(), Cached::long_help());
+}
+
+#[test]
+fn it_should_match_the_help_snapshots() {
+    clipv::assert_help_snapshot!(Cached);
+    clipv::assert_help_snapshot!(Logs);
+}
+
+#[derive(Debug, AsCommand, TryParse)]
+struct Tune {
+    /// how fast
+    speed: Speed,
+    /// how loud
+    #[global]
+    #[possible("low", "high")]
+    volume: Option<String>,
+}
+
+#[test]
+fn it_should_list_the_possible_values_of_a_field() {
+    use clipv::parser::{Parsed, ParsingError, TryParse};
+    assert_eq!(
+        Tune::help(),
+        "Usage: tune [OPTIONS] <speed>\n\nArguments:\n  speed   how fast [possible values: slow, fast]\n\nOptions:\n  --volume <VALUE>  how loud [possible values: low, high]\n"
+    );
+    assert_eq!(clipv::completion::complete(&Tune::command(), &["f"]), ["fast"]);
+    let Parsed(tune, _) = Tune::try_parse(["fast", "--volume", "HIGH"].iter()).unwrap();
+    assert_eq!((tune.speed, tune.volume.as_deref()), (Speed::Fast, Some("HIGH")));
+    assert_eq!(
+        Tune::try_parse(["fast", "--volume=medium"].iter()).err(),
+        Some(ParsingError::BadType(String::from("'medium' is not one of low, high")))
+    );
+}
+
+#[allow(dead_code)]
+#[derive(AsCommand)]
+struct Cruise {
+    #[choices]
+    #[inline_choices]
+    speed: Speed,
+    #[choices]
+    level: Level,
+}
+
+#[test]
+fn it_should_inline_choices_on_request() {
+    assert_eq!(Cruise::command().summarize(), "cruise <slow|fast> <level>");
+}
 }
@@ -0,0 +1,57 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(feature = "derive")]
+mod derive_test {
+use clipv::components::{ColorChoice, Level, Verbosity};
+use clipv::describe::command::AsCommand;
+use clipv::parser::parse;
+use clipv::{AsCommand, TryParse};
+
+#[derive(Debug, TryParse, AsCommand)]
+struct Fetch {
+    #[clip(flatten)]
+    verbosity: Verbosity,
+    url: String,
+}
+
+#[test]
+fn it_should_set_the_verbosity() {
+    let level = |args: &[&str]| parse(args.iter(), |fetch: Fetch| fetch.verbosity.level());
+    assert_eq!(parse(["-v", "host"].iter(), |fetch: Fetch| fetch.url), Ok(String::from("host")));
+    assert_eq!(level(&["host"]), Ok(Level::Warn));
+    assert_eq!(level(&["-vv", "host"]), Ok(Level::Debug));
+    assert_eq!(level(&["host", "-v", "--verbose", "-v"]), Ok(Level::Trace));
+    assert_eq!(level(&["-qvq", "host"]), Ok(Level::Error));
+    assert_eq!(level(&["--quiet", "-q", "host"]), Ok(Level::Off));
+    assert!(Fetch::help().contains("-v, --verbose  more output, repeated for even more\n"), "{}", Fetch::help());
+}
+
+#[derive(Debug, TryParse, AsCommand)]
+struct Show {
+    /// when to color the output
+    #[global]
+    #[clip(default = "auto")]
+    color: ColorChoice,
+}
+
+#[test]
+fn it_should_take_a_color_choice() {
+    assert_eq!(parse(std::iter::empty::<&&str>(), |show: Show| show.color), Ok(ColorChoice::Auto));
+    assert_eq!(parse(["--color", "never"].iter(), |show: Show| show.color), Ok(ColorChoice::Never));
+    assert_eq!(parse(["--color=Always"].iter(), |show: Show| show.color), Ok(ColorChoice::Always));
+    assert!(parse(["--color", "sometimes"].iter(), |show: Show| show.color).is_err());
+    assert!(
+        Show::help().contains("when to color the output [possible values: auto, always, never] [default: auto]"),
+        "{}",
+        Show::help()
+    );
+}
+}
@@ -0,0 +1,228 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(feature = "derive")]
+mod derive_test {
+use clipv::parser::parse;
+use clipv::run::Dispatch;
+use clipv::{Dispatch, TryParse};
+
+#[derive(Debug, PartialEq, TryParse, Dispatch, clipv::AsCommand, clipv::ToArgs)]
+enum Todo {
+    Add(String),
+    Done { index: usize },
+    ClearAll,
+}
+
+#[derive(Default)]
+struct List(Vec<String>);
+
+impl TodoHandler for List {
+    type Output = Result<usize, String>;
+
+    fn add(&mut self, arg0: String) -> Self::Output {
+        self.0.push(arg0);
+        Ok(self.0.len())
+    }
+
+    fn done(&mut self, index: usize) -> Self::Output {
+        if index < self.0.len() {
+            self.0.remove(index);
+            Ok(self.0.len())
+        } else {
+            Err(format!("no item {index}"))
+        }
+    }
+
+    fn clear_all(&mut self) -> Self::Output {
+        self.0.clear();
+        Ok(0)
+    }
+}
+
+#[derive(TryParse, clipv::AsCommand)]
+struct Cli {
+    name: String,
+}
+
+#[clipv::main]
+fn entry(cli: Cli) -> Result<(), String> {
+    println!("Hello {}", cli.name);
+    Ok(())
+}
+
+fn exit_code(_: &clipv::parser::ParsingError) -> u8 {
+    64
+}
+
+#[clipv::main(exit_code = exit_code, arg_files)]
+fn custom_entry(cli: Cli) {
+    println!("Hello {}", cli.name);
+}
+
+/// answers as `add`, `done` or `clear-all` when linked under those names
+#[clipv::main(multicall)]
+fn applets(todo: Todo) -> Result<(), String> {
+    todo.dispatch(&mut List::default()).map(drop)
+}
+
+/// appends the commands which succeeded to a session file
+#[clipv::main(record = std::env::temp_dir().join("todo-session"))]
+fn recorded(todo: Todo) -> Result<(), String> {
+    todo.dispatch(&mut List::default()).map(drop)
+}
+
+/// Renames a remote
+#[clipv::command]
+fn rename(
+    /// current name
+    from: String,
+    /// new name
+    to: Option<String>,
+) -> String {
+    format!("{from} -> {}", to.unwrap_or_default())
+}
+
+#[test]
+fn it_should_turn_a_function_into_a_command() {
+    use clipv::describe::arg::{ArgDetails, ArgSummarize, ArgType, AsArg};
+    assert_eq!(parse(["origin", "upstream"].iter(), RenameCommand::call), Ok(String::from("origin -> upstream")));
+    assert_eq!(parse(["origin"].iter(), RenameCommand::call), Ok(String::from("origin -> ")));
+    let ArgType::Group(group) = RenameCommand::arguments() else {
+        panic!("a command function is a group of arguments");
+    };
+    assert_eq!(group.summarize(), "<from> <to>");
+    assert_eq!(group.details(), "from    current name\nto      new name\n");
+}
+
+/// manages remotes
+#[derive(TryParse, Dispatch, clipv::AsCommand)]
+enum Remote {
+    #[command]
+    Rename(#[try_parse] RenameCommand),
+    List,
+}
+
+struct Remotes;
+
+impl RemoteHandler for Remotes {
+    type Output = String;
+
+    fn rename(&mut self, arg0: RenameCommand) -> Self::Output {
+        arg0.dispatch(self)
+    }
+
+    fn list(&mut self) -> Self::Output {
+        String::from("origin")
+    }
+}
+
+#[test]
+fn it_should_run_and_list_a_function_command() {
+    use clipv::describe::command::AsCommand;
+    assert_eq!(RenameCommand::command().help(), "Renames a remote\n\nUsage: rename <from> <to>\n\nArguments:\n  from    current name\n  to      new name\n");
+    assert_eq!(Remote::command().help(), "manages remotes\n\nUsage: remote [COMMAND] ..\n\nCommands:\n  rename\n  list\n  help    prints this message or the help of the given subcommand\n");
+    assert_eq!(Remote::command().help_for_path(&["rename"]), Some(String::from("Usage: remote rename <from> <to>\n\nArguments:\n  from    current name\n  to      new name\n")));
+    assert_eq!(parse(["rename", "origin", "upstream"].iter(), |remote: Remote| remote.dispatch(&mut Remotes)), Ok(String::from("origin -> upstream")));
+    assert_eq!(clipv::run::run(&["origin"], "1.0", |rename: RenameCommand| drop(rename.call())), std::process::ExitCode::SUCCESS);
+}
+
+#[test]
+fn it_should_generate_an_entry_point() {
+    let _: fn() -> std::process::ExitCode = entry;
+    let _: fn() -> std::process::ExitCode = custom_entry;
+    let _: fn() -> std::process::ExitCode = applets;
+    let _: fn() -> std::process::ExitCode = recorded;
+}
+
+#[derive(TryParse, clipv::AsCommand)]
+struct Copy {
+    source: std::path::PathBuf,
+    label: Option<String>,
+}
+
+#[test]
+#[cfg(unix)]
+fn it_should_run_with_arguments_which_are_not_unicode() {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+    use std::process::ExitCode;
+    let raw = OsString::from_vec(b"caf\xe9.txt".to_vec());
+    let mut copied = None;
+    assert_eq!(clipv::run::run_os(std::slice::from_ref(&raw), "1.0", |copy: Copy| copied = Some((copy.source, copy.label))), ExitCode::SUCCESS);
+    assert_eq!(copied, Some((std::path::PathBuf::from(raw.clone()), None)));
+    let args = [OsString::from("a.txt"), raw];
+    let code = clipv::run::run_os(&args, "1.0", |_: Copy| panic!("should not run"));
+    assert_eq!(code, ExitCode::from(clipv::run::DATA_ERROR));
+}
+
+#[test]
+fn it_should_dispatch_parsed_commands() {
+    let mut list = List::default();
+    assert_eq!(parse(["add", "milk"].iter(), |todo: Todo| todo.dispatch(&mut list)), Ok(Ok(1)));
+    assert_eq!(parse(["add", "eggs"].iter(), |todo: Todo| todo.dispatch(&mut list)), Ok(Ok(2)));
+    assert_eq!(parse(["done", "0"].iter(), |todo: Todo| todo.dispatch(&mut list)), Ok(Ok(1)));
+    assert_eq!(list.0, vec!["eggs"]);
+    assert_eq!(Todo::Done { index: 4 }.dispatch(&mut list), Err(String::from("no item 4")));
+    assert_eq!(Todo::ClearAll.dispatch(&mut list), Ok(0));
+}
+}
+
+#[cfg(all(feature = "derive", feature = "async"))]
+mod async_test {
+use clipv::run::{AsyncDispatch, BoxFuture};
+use clipv::AsyncDispatch;
+use std::future::Future;
+
+#[derive(AsyncDispatch)]
+enum Counter {
+    Add(usize),
+    Reset,
+}
+
+struct Total(usize);
+
+impl CounterAsyncHandler for Total {
+    type Output = usize;
+
+    fn add(&mut self, arg0: usize) -> BoxFuture<'_, usize> {
+        Box::pin(async move {
+            self.0 += arg0;
+            self.0
+        })
+    }
+
+    fn reset(&mut self) -> BoxFuture<'_, usize> {
+        Box::pin(async move {
+            self.0 = 0;
+            self.0
+        })
+    }
+}
+
+/// Polls a future which never waits, there is no need for an executor
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn it_should_dispatch_to_async_handlers() {
+    let mut total = Total(0);
+    assert_eq!(block_on(Counter::Add(3).dispatch(&mut total)), 3);
+    assert_eq!(block_on(Counter::Add(4).dispatch(&mut total)), 7);
+    assert_eq!(block_on(Counter::Reset.dispatch(&mut total)), 0);
+}
+}
@@ -33,4 +33,26 @@ fn it_should_raise_variant_not_found() {
     );
     assert_eq!("".parse::<Unit>(), Err(String::from("Unexistant variant ")));
 }
+
+#[derive(Debug, PartialEq, FromStr)]
+enum Season {
+    Été,
+    Hiver,
+}
+
+#[derive(Debug, PartialEq, FromStr)]
+#[clip(unicode_case)]
+enum UnicodeSeason {
+    Été,
+    Hiver,
+}
+
+#[test]
+fn it_should_ignore_the_case_of_ascii_letters_only_by_default() {
+    assert_eq!("HIVER".parse::<Season>(), Ok(Season::Hiver));
+    assert_eq!("été".parse::<Season>(), Ok(Season::Été));
+    assert!("ÉTÉ".parse::<Season>().is_err());
+    assert_eq!("ÉTÉ".parse::<UnicodeSeason>(), Ok(UnicodeSeason::Été));
+    assert_eq!("hiver".parse::<UnicodeSeason>(), Ok(UnicodeSeason::Hiver));
+}
 }
@@ -0,0 +1,45 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(all(feature = "derive", feature = "glob"))]
+mod derive_test {
+use clipv::describe::command::AsCommand;
+use clipv::glob::GlobPaths;
+use clipv::parser::parse;
+use clipv::{AsCommand, TryParse};
+
+#[derive(Debug, TryParse, AsCommand)]
+struct Lint {
+    sources: GlobPaths,
+    /// files left alone
+    #[global]
+    #[clip(allow_no_match)]
+    exclude: Option<GlobPaths>,
+}
+
+#[test]
+fn it_should_expand_the_patterns() {
+    let root = std::env::temp_dir().join(format!("clip-glob-derive-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    for file in ["a.rs", "b.rs", "c.txt"] {
+        std::fs::write(root.join(file), "").unwrap();
+    }
+    let rs = format!("{}/*.rs", root.display());
+    let md = format!("{}/*.md", root.display());
+    let lint = |args: &[&str]| {
+        parse(args.iter(), |lint: Lint| (lint.sources.paths().len(), lint.exclude.map(|exclude| exclude.paths().len())))
+    };
+    assert_eq!(lint(&[&rs]), Ok((2, None)));
+    assert_eq!(lint(&[&rs, "--exclude", &md]), Ok((2, Some(0))));
+    let err = lint(&[&md]).unwrap_err().to_string();
+    assert!(err.contains(&format!("no path matches '{md}'")), "{err}");
+    assert!(Lint::help().contains("--exclude <PATTERN>  files left alone"), "{}", Lint::help());
+}
+}
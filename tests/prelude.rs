@@ -0,0 +1,35 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(feature = "derive")]
+mod derive_test {
+use clipv::prelude::*;
+
+#[derive(Debug, PartialEq, FromStr)]
+enum Level {
+    Low,
+    High,
+}
+
+/// set the volume
+#[derive(Debug, PartialEq, TryParse, AsCommand)]
+struct Volume {
+    level: Level,
+    percent: Option<u8>,
+}
+
+#[test]
+fn it_should_need_only_the_prelude() {
+    let Parsed(volume, _) = Volume::try_parse(["high"].iter()).unwrap();
+    assert_eq!(volume, Volume { level: Level::High, percent: None });
+    assert!(matches!(Volume::try_parse(["loud"].iter()), Err(ParsingError::BadType(_))));
+    assert_eq!(Volume::command().summarize(), "volume <level> <percent>");
+}
+}
@@ -0,0 +1,37 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(feature = "derive")]
+mod derive_test {
+use clipv::describe::command::AsCommand;
+use clipv::parser::parse;
+use clipv::time::{HumanDuration, Timestamp};
+use clipv::{AsCommand, TryParse};
+use std::time::Duration;
+
+#[derive(Debug, TryParse, AsCommand)]
+struct Logs {
+    /// first day shown
+    since: Timestamp,
+    /// stop after
+    #[global]
+    timeout: Option<HumanDuration>,
+}
+
+#[test]
+fn it_should_parse_durations_and_timestamps() {
+    let logs = |args: &[&str]| parse(args.iter(), |logs: Logs| (logs.since.unix_seconds(), logs.timeout.map(Duration::from)));
+    assert_eq!(logs(&["2024-06-01", "--timeout", "1m30s"]), Ok((1717200000, Some(Duration::from_secs(90)))));
+    assert_eq!(logs(&["@60"]), Ok((60, None)));
+    let err = logs(&["@60", "--timeout=10"]).unwrap_err().to_string();
+    assert!(err.contains("invalid duration '10': missing unit after 10, e.g. 10s"), "{err}");
+    assert!(Logs::help().contains("--timeout <DURATION>  stop after"), "{}", Logs::help());
+}
+}
@@ -0,0 +1,142 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(feature = "derive")]
+mod derive_test {
+use clipv::parser::{parse, ToArgs};
+use clipv::{AsArg, AsCommand, ToArgs, TryParse};
+
+#[derive(Debug, PartialEq, TryParse, ToArgs, AsArg)]
+struct Target {
+    host: String,
+    port: Option<u16>,
+}
+
+#[derive(Debug, PartialEq, TryParse, ToArgs, AsCommand)]
+enum Remote {
+    Add {
+        #[global]
+        verbose: bool,
+        #[global]
+        name: Option<String>,
+        #[try_parse]
+        #[group]
+        target: Target,
+    },
+    #[command(name = "rm")]
+    Remove(String),
+    List,
+}
+
+fn round_trip(remote: Remote) {
+    let args = remote.to_args();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    assert_eq!(parse(args.iter(), |parsed: Remote| parsed), Ok(remote));
+}
+
+#[test]
+fn it_should_unparse_a_value() {
+    let add = Remote::Add {
+        verbose: true,
+        name: None,
+        target: Target {
+            host: String::from("example.com"),
+            port: Some(22),
+        },
+    };
+    assert_eq!(add.to_args(), ["add", "--verbose", "example.com", "22"]);
+    assert_eq!(Remote::Remove(String::from("origin")).to_args(), ["rm", "origin"]);
+    assert_eq!(Remote::List.to_args(), ["list"]);
+}
+
+#[test]
+fn it_should_parse_back_what_it_unparses() {
+    round_trip(Remote::Add {
+        verbose: false,
+        name: Some(String::from("with space")),
+        target: Target {
+            host: String::from("localhost"),
+            port: None,
+        },
+    });
+    round_trip(Remote::Remove(String::from("--")));
+    round_trip(Remote::List);
+}
+
+#[test]
+fn it_should_parse_generated_lines_back_and_forth() {
+    use clipv::describe::command::AsCommand;
+    use clipv::generate::{args, Rng};
+    for seed in 0..200 {
+        let line = args(Remote::command_ref(), &mut Rng::new(seed));
+        let line: Vec<&str> = line.iter().map(String::as_str).collect();
+        let remote = parse(line.iter(), |parsed: Remote| parsed).unwrap_or_else(|err| panic!("{line:?}: {err}"));
+        round_trip(remote);
+    }
+}
+
+/// `copy <from> <to> <mode>` whatever the layout of the tuple
+#[derive(Debug, PartialEq, TryParse, ToArgs, AsCommand)]
+struct Copy(
+    #[position = 2] String,
+    #[position = 0] String,
+    String,
+);
+
+#[test]
+fn it_should_parse_fields_at_their_position() {
+    use clipv::describe::command::AsCommand;
+    let copy = parse(["a", "b", "fast"].iter(), |copy: Copy| copy).unwrap();
+    assert_eq!(copy, Copy(String::from("fast"), String::from("a"), String::from("b")));
+    assert_eq!(copy.to_args(), ["a", "b", "fast"]);
+    assert_eq!(Copy::command().summarize(), "copy <String> <String> <String>");
+}
+
+#[derive(Debug, PartialEq, TryParse, ToArgs, AsCommand)]
+struct Compile {
+    /// directory searched for headers
+    #[global]
+    #[clip(short = 'I')]
+    include: Vec<String>,
+    /// feature enabled
+    #[global]
+    #[arity(max = 2)]
+    #[deny_duplicates]
+    feature: std::collections::BTreeSet<String>,
+    file: String,
+}
+
+#[test]
+fn it_should_accumulate_repeated_options() {
+    use clipv::describe::command::AsCommand;
+    use clipv::parser::ParsingError;
+    let compile = parse(["-I", "src", "main.c", "--include=lib", "--feature", "tls"].iter(), |compile: Compile| compile).unwrap();
+    assert_eq!(compile.include, ["src", "lib"]);
+    assert_eq!(compile.to_args(), ["--include", "src", "--include", "lib", "--feature", "tls", "main.c"]);
+    assert_eq!(parse(["main.c"].iter(), |compile: Compile| compile.include.len()), Ok(0));
+    assert_eq!(
+        parse(["--feature", "a", "--feature=a", "main.c"].iter(), |compile: Compile| compile).err(),
+        Some(ParsingError::DuplicateValue { argument: "--feature", value: String::from("a") })
+    );
+    assert_eq!(
+        parse(["--feature=a", "--feature=b", "--feature=c", "main.c"].iter(), |compile: Compile| compile).err(),
+        Some(ParsingError::Arity { argument: "--feature", min: 0, max: Some(2), found: 3 })
+    );
+    assert_eq!(
+        Compile::help(),
+        "Usage: compile [OPTIONS] <file>\n\nArguments:\n  file\n\nOptions:\n  -I, --include <VALUE>...        directory searched for headers\n      --feature <VALUE>... (0-2)  feature enabled\n"
+    );
+    for seed in 0..50 {
+        let line = clipv::generate::args(Compile::command_ref(), &mut clipv::generate::Rng::new(seed));
+        let line: Vec<&str> = line.iter().map(String::as_str).collect();
+        assert!(matches!(parse(line.iter(), |compile: Compile| compile), Ok(_) | Err(ParsingError::DuplicateValue { .. })), "{line:?}");
+    }
+}
+}
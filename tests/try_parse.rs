@@ -13,6 +13,7 @@ mod test {
 use clipv::parser::{Parsed, ParsingError, TryParse};
 use clipv::{FromStr, TryParse};
 
+#[allow(dead_code)]
 #[derive(Debug, PartialEq, TryParse)]
 struct Empty;
 
@@ -68,7 +69,9 @@ fn it_should_raise_too_few_argument() {
 fn it_should_raise_bad_argument_type() {
     let arguments = ["", "Hello, world"];
     let result = Leaf::try_parse(arguments.iter());
-    assert_eq!(result.err(), Some(ParsingError::BadType));
+    let err = result.err().unwrap();
+    assert_eq!(err, ParsingError::BadType(String::from("cannot parse integer from empty string")));
+    assert_eq!(err.to_string(), "invalid value: cannot parse integer from empty string");
 }
 
 #[test]
@@ -138,7 +141,7 @@ fn it_should_raise_bad_argument_type_command() {
     let arguments = ["tuple", "test", "43", "Hello"];
     assert_eq!(
         Command::try_parse(arguments.iter()).err(),
-        Some(ParsingError::BadType)
+        Some(ParsingError::BadType(String::from("invalid digit found in string")))
     );
 }
 
@@ -166,4 +169,659 @@ fn it_should_parse_the_parent() {
     );
     assert_eq!(rest.next(), Some("end").as_ref());
 }
+
+#[derive(Debug, PartialEq, TryParse)]
+struct Login {
+    #[requires("password")]
+    user: Option<String>,
+    #[required_unless("token")]
+    password: Option<String>,
+    token: Option<String>,
+}
+
+#[test]
+fn it_should_parse_optional_trailing_fields() {
+    let arguments = ["claire", "secret"];
+    let Parsed(parsed, _) = Login::try_parse(arguments.iter()).unwrap();
+    assert_eq!(
+        parsed,
+        Login {
+            user: Some(String::from("claire")),
+            password: Some(String::from("secret")),
+            token: None
+        }
+    );
+}
+
+#[test]
+fn it_should_raise_requires() {
+    let arguments = ["claire"];
+    assert_eq!(
+        Login::try_parse(arguments.iter()).err(),
+        Some(ParsingError::Requires {
+            argument: "user",
+            requires: "password"
+        })
+    );
+}
+
+#[test]
+fn it_should_raise_required_unless() {
+    let arguments: [&'static str; 0] = [];
+    assert_eq!(
+        Login::try_parse(arguments.iter()).err(),
+        Some(ParsingError::RequiredUnless {
+            argument: "password",
+            unless: "token"
+        })
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+enum Tool {
+    Add(String),
+    #[default_command]
+    Status(Option<String>),
+}
+
+#[test]
+fn it_should_select_the_default_command() {
+    {
+        let arguments: [&'static str; 0] = [];
+        let Parsed(parsed, _) = Tool::try_parse(arguments.iter()).unwrap();
+        assert_eq!(parsed, Tool::Status(None));
+    }
+    {
+        let arguments = ["file", "next"];
+        let Parsed(parsed, mut rest) = Tool::try_parse(arguments.iter()).unwrap();
+        assert_eq!(parsed, Tool::Status(Some(String::from("file"))));
+        assert_eq!(rest.next(), Some("next").as_ref());
+    }
+    {
+        let arguments = ["add", "file"];
+        let Parsed(parsed, _) = Tool::try_parse(arguments.iter()).unwrap();
+        assert_eq!(parsed, Tool::Add(String::from("file")));
+    }
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+struct Cli {
+    #[global]
+    verbose: bool,
+    #[global]
+    config_file: Option<String>,
+    #[try_parse]
+    command: Command,
+}
+
+#[test]
+fn it_should_parse_global_options_anywhere() {
+    let expected = Cli {
+        verbose: true,
+        config_file: Some(String::from("clip.toml")),
+        command: Command::Struct {
+            unit: Unit::One,
+            other: 3,
+        },
+    };
+    for arguments in [
+        ["--verbose", "--config-file", "clip.toml", "struct", "one", "3"],
+        ["struct", "--config-file=clip.toml", "one", "3", "--verbose", ""],
+    ] {
+        let arguments: Vec<&str> = arguments.into_iter().filter(|arg| !arg.is_empty()).collect();
+        let Parsed(parsed, _) = Cli::try_parse(arguments.iter()).unwrap();
+        assert_eq!(parsed, expected);
+    }
+}
+
+#[test]
+fn it_should_raise_too_many_arguments_with_global_options() {
+    let arguments = ["unit", "extra"];
+    assert_eq!(
+        Cli::try_parse(arguments.iter()).err(),
+        Some(ParsingError::TooManyArguments)
+    );
+    let arguments = ["unit"];
+    let Parsed(parsed, _) = Cli::try_parse(arguments.iter()).unwrap();
+    assert_eq!(
+        parsed,
+        Cli {
+            verbose: false,
+            config_file: None,
+            command: Command::Unit
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+struct Connection {
+    #[global]
+    host: String,
+    #[global]
+    port: Option<u16>,
+    #[global]
+    tls: bool,
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+struct Replicate {
+    #[clip(flatten, prefix = "from")]
+    source: Connection,
+    #[clip(flatten, prefix = "to")]
+    target: Connection,
+    table: String,
+}
+
+#[test]
+fn it_should_parse_prefixed_flattened_options() {
+    let arguments = ["--from-host", "a", "users", "--to-host=b", "--to-tls", "--from-port", "42"];
+    let Parsed(parsed, _) = Replicate::try_parse(arguments.iter()).unwrap();
+    assert_eq!(
+        parsed,
+        Replicate {
+            source: Connection {
+                host: String::from("a"),
+                port: Some(42),
+                tls: false
+            },
+            target: Connection {
+                host: String::from("b"),
+                port: None,
+                tls: true
+            },
+            table: String::from("users")
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+struct Conn {
+    #[global]
+    host: String,
+    #[global]
+    #[clip(default = "5432")]
+    port: u16,
+    #[global]
+    #[possible("disable", "require")]
+    #[clip(default = "require")]
+    ssl_mode: String,
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+struct Migrate {
+    #[clip(flatten, prefix = "db")]
+    db: Conn,
+}
+
+#[test]
+fn it_should_apply_the_defaults_and_checks_of_flattened_options() {
+    let Parsed(parsed, _) = Migrate::try_parse(["--db-host", "localhost"].iter()).unwrap();
+    assert_eq!((parsed.db.host.as_str(), parsed.db.port, parsed.db.ssl_mode.as_str()), ("localhost", 5432, "require"));
+    let Parsed(parsed, _) = Migrate::try_parse(["--db-host", "h", "--db-port=6432", "--db-ssl-mode", "disable"].iter()).unwrap();
+    assert_eq!((parsed.db.port, parsed.db.ssl_mode.as_str()), (6432, "disable"));
+    assert!(Migrate::try_parse(["--db-host", "h", "--db-ssl-mode", "prefer"].iter()).is_err());
+}
+
+#[test]
+fn it_should_not_accept_unprefixed_flattened_options() {
+    let arguments = ["--from-host", "a", "--to-host", "b", "--host", "c"];
+    assert_eq!(
+        Replicate::try_parse(arguments.iter()).err(),
+        Some(ParsingError::TooManyArguments)
+    );
+    let arguments = ["--from-host", "a", "users"];
+    assert_eq!(
+        Replicate::try_parse(arguments.iter()).err(),
+        Some(ParsingError::TooFewArguments)
+    );
+}
+
+#[test]
+fn it_should_return_the_leftovers_of_a_partial_parsing() {
+    let arguments = ["tuple", "1", "2", "Hello", "--", "child", "--verbose"];
+    let (parsed, rest) = clipv::parser::parse_partial::<_, Command>(arguments.iter()).unwrap();
+    assert_eq!(parsed, Command::Tuple(1, Leaf { a: 2, b: String::from("Hello") }));
+    assert_eq!(rest, [&"--", &"child", &"--verbose"]);
+    let arguments = ["tuple", "1"];
+    assert_eq!(
+        clipv::parser::parse_partial::<_, Command>(arguments.iter()).err(),
+        Some(ParsingError::TooFewArguments)
+    );
+}
+
+#[test]
+fn it_should_run_a_fallible_callback() {
+    #[derive(Debug, PartialEq)]
+    enum Error {
+        Parsing(ParsingError),
+        Empty,
+    }
+
+    impl From<ParsingError> for Error {
+        fn from(err: ParsingError) -> Self {
+            Error::Parsing(err)
+        }
+    }
+
+    let check = |leaf: Leaf| if leaf.b.is_empty() { Err(Error::Empty) } else { Ok(leaf.a) };
+    assert_eq!(clipv::parser::try_parse_and_run(["1", "a"].iter(), check), Ok(1));
+    assert_eq!(clipv::parser::try_parse_and_run(["1", ""].iter(), check), Err(Error::Empty));
+    assert_eq!(
+        clipv::parser::try_parse_and_run(["1", "a", "b"].iter(), check),
+        Err(Error::Parsing(ParsingError::TooManyArguments))
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+struct Filter {
+    #[stdin]
+    query: String,
+    #[global]
+    #[stdin]
+    input: Option<String>,
+}
+
+#[test]
+fn it_should_parse_stdin_fields_given_on_the_line() {
+    let arguments = [".name", "--input", "data.json"];
+    let Parsed(filter, _) = Filter::try_parse(arguments.iter()).unwrap();
+    assert_eq!(
+        filter,
+        Filter {
+            query: String::from(".name"),
+            input: Some(String::from("data.json"))
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+struct Archive {
+    #[global]
+    output: Option<std::path::PathBuf>,
+    name: String,
+    file: std::path::PathBuf,
+}
+
+#[cfg(unix)]
+#[test]
+fn it_should_keep_non_unicode_paths() {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+    let invalid = OsString::from_vec(vec![b'f', 0xff]);
+    let arguments = [OsString::from("--output"), invalid.clone(), OsString::from("backup"), invalid.clone()];
+    let archive = clipv::parser::parse_os(&arguments, |archive: Archive| archive).unwrap();
+    assert_eq!(archive.output.as_deref(), Some(std::path::Path::new(&invalid)));
+    assert_eq!(archive.name, "backup");
+    assert_eq!(archive.file.as_os_str(), invalid);
+    let arguments = [invalid.clone(), invalid];
+    assert_eq!(
+        clipv::parser::parse_os(&arguments, |archive: Archive| archive),
+        Err(ParsingError::InvalidUtf8)
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+struct Borrowed<'a> {
+    #[global]
+    #[clip(default = "origin")]
+    remote: &'a str,
+    branch: &'a str,
+    depth: u8,
+    message: Option<&'a str>,
+}
+
+#[test]
+fn it_should_borrow_str_fields_from_the_arguments() {
+    let arguments = ["main", "--remote", "upstream", "3", "fix"];
+    let Parsed(parsed, _) = Borrowed::try_parse(arguments.iter()).unwrap();
+    assert_eq!(
+        parsed,
+        Borrowed {
+            remote: "upstream",
+            branch: "main",
+            depth: 3,
+            message: Some("fix")
+        }
+    );
+    assert!(std::ptr::eq(parsed.branch, arguments[0]));
+    let arguments = ["main", "1"];
+    let Parsed(parsed, _) = Borrowed::try_parse(arguments.iter().copied()).unwrap();
+    assert_eq!((parsed.remote, parsed.message), ("origin", None));
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+enum Season {
+    Été,
+    Hiver,
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+#[clip(unicode_case)]
+enum UnicodeSeason {
+    Été,
+    Hiver,
+}
+
+#[test]
+fn it_should_match_keywords_ignoring_the_ascii_case() {
+    let Parsed(parsed, _) = Season::try_parse(["HIVER"].iter()).unwrap();
+    assert_eq!(parsed, Season::Hiver);
+    assert!(Season::try_parse(["ÉTÉ"].iter()).is_err());
+    let Parsed(parsed, _) = UnicodeSeason::try_parse(["ÉTÉ"].iter()).unwrap();
+    assert_eq!(parsed, UnicodeSeason::Été);
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+#[clip(fast_match)]
+enum Protocol {
+    Get,
+    Put,
+    Post,
+    Delete,
+    ListAll,
+    Fetch(u8),
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+#[clip(fast_match, unicode_case)]
+enum FastSeason {
+    Été,
+    Hiver,
+}
+
+#[test]
+fn it_should_dispatch_keywords_on_their_length() {
+    for (keyword, expected) in [
+        ("get", Protocol::Get),
+        ("PUT", Protocol::Put),
+        ("post", Protocol::Post),
+        ("Delete", Protocol::Delete),
+        ("list-all", Protocol::ListAll),
+        ("listall", Protocol::ListAll),
+    ] {
+        let Parsed(parsed, _) = Protocol::try_parse([keyword].iter()).unwrap();
+        assert_eq!(parsed, expected);
+    }
+    let Parsed(parsed, _) = Protocol::try_parse(["fetch", "3"].iter()).unwrap();
+    assert_eq!(parsed, Protocol::Fetch(3));
+    assert_eq!(
+        Protocol::try_parse(["pot"].iter()).err(),
+        Some(ParsingError::VariantNotFound)
+    );
+    assert_eq!(
+        Protocol::try_parse(["head"].iter()).err(),
+        Some(ParsingError::VariantNotFound)
+    );
+    let Parsed(parsed, _) = FastSeason::try_parse(["ÉTÉ"].iter()).unwrap();
+    assert_eq!(parsed, FastSeason::Été);
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+#[clip(fast_match, abbreviations)]
+enum Verb {
+    Status,
+    Stash,
+    #[default_command]
+    Run(String),
+}
+
+#[test]
+fn it_should_fall_back_from_any_length_bucket() {
+    for (keyword, expected) in [
+        ("stat", Verb::Status),
+        ("stas", Verb::Stash),
+        ("run", Verb::Run(String::from("script"))),
+        ("job", Verb::Run(String::from("job"))),
+    ] {
+        let Parsed(parsed, _) = Verb::try_parse([keyword, "script"].iter()).unwrap();
+        assert_eq!(parsed, expected);
+    }
+    assert!(matches!(Verb::try_parse(["sta"].iter()), Err(ParsingError::Ambiguous { .. })));
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+#[clip(before_parse = "count_parse", after_parse = "Range::normalize")]
+struct Range {
+    start: u8,
+    end: u8,
+}
+
+static PARSED_RANGES: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+fn count_parse() -> Result<(), ParsingError> {
+    PARSED_RANGES.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    Ok(())
+}
+
+impl Range {
+    fn normalize(&mut self) -> Result<(), ParsingError> {
+        if self.start == self.end {
+            return Err(ParsingError::bad_type("empty range"));
+        }
+        if self.start > self.end {
+            std::mem::swap(&mut self.start, &mut self.end);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn it_should_call_the_parse_hooks() {
+    let Parsed(parsed, _) = Range::try_parse(["9", "2"].iter()).unwrap();
+    assert_eq!(parsed, Range { start: 2, end: 9 });
+    assert_eq!(
+        Range::try_parse(["3", "3"].iter()).err(),
+        Some(ParsingError::BadType(String::from("empty range")))
+    );
+    assert_eq!(PARSED_RANGES.load(std::sync::atomic::Ordering::Relaxed), 2);
+}
+
+#[derive(Debug, TryParse)]
+struct Window {
+    #[global]
+    start: u8,
+    #[global]
+    end: u8,
+}
+
+impl clipv::parser::Validate for Window {
+    fn validate(&self) -> Result<(), ParsingError> {
+        if self.start >= self.end {
+            return Err(ParsingError::invalid(&["--start", "--end"], "--start must be before --end"));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn it_should_validate_the_parsed_value() {
+    let Parsed(window, _) = Window::try_parse(["--start", "1", "--end", "3"].iter()).unwrap();
+    assert_eq!((window.start, window.end), (1, 3));
+    let err = Window::try_parse(["--end", "1", "--start", "3"].iter()).err().unwrap();
+    assert_eq!(err.to_string(), "'--start', '--end': --start must be before --end");
+    let err = clipv::error::parse(&["--end", "1", "--start=3"], |_: Window| ()).unwrap_err();
+    assert_eq!(err.position, Some(0));
+}
+
+#[cfg(feature = "trace")]
+#[derive(Debug, TryParse)]
+struct Session {
+    #[global]
+    verbose: bool,
+    #[global]
+    #[secret]
+    key: String,
+    #[try_parse]
+    tool: Tool,
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn it_should_trace_the_parsing_decisions() {
+    let (parsed, events) = clipv::trace::traced(|| Session::try_parse(["--verbose", "--key=abc", "file"].iter()).map(|_| ()));
+    assert_eq!(parsed, Ok(()));
+    assert_eq!(
+        events,
+        [
+            "flag --verbose set",
+            "'***' given to --key",
+            "'file' matches no variant, falling back on Status",
+            "'file' given to 0",
+        ]
+    );
+    let Parsed(session, _) = Session::try_parse(["add", "x", "--key", "abc"].iter()).unwrap();
+    assert!(!session.verbose && session.key == "abc" && session.tool == Tool::Add(String::from("x")));
+}
+
+#[test]
+fn it_should_not_panic_on_malformed_lines() {
+    use clipv::fuzz::{corpus_entry, fuzz_parse};
+    for args in [
+        &["--verbose", "--config-file"][..],
+        &["--config-file=", "struct", "\u{fffd}", "300"],
+        &["tuple", "-1", "", "--", "--verbose="],
+        &["--window", "--start", "--end"],
+        &[],
+    ] {
+        let entry = corpus_entry(args);
+        fuzz_parse::<Cli>(&entry);
+        fuzz_parse::<Tool>(&entry);
+        fuzz_parse::<Window>(&entry);
+        fuzz_parse::<Protocol>(&entry[..entry.len() / 2]);
+    }
+}
+
+#[test]
+fn it_should_assert_with_the_testing_macros() {
+    clipv::assert_parse_eq!(Protocol, ["fetch", "3"], Protocol::Fetch(3));
+    clipv::assert_parse_eq!(Tool, ["add", "file"], Tool::Add(String::from("file")));
+    clipv::assert_parse_err!(Tool, ["add", "file", "extra"], ParsingError::TooManyArguments);
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+#[clip(abbreviations)]
+enum Git {
+    Status,
+    Stash,
+    Commit(String),
+    #[command(name = "rm")]
+    Remove,
+}
+
+#[test]
+fn it_should_match_unambiguous_abbreviations() {
+    clipv::assert_parse_eq!(Git, ["stat"], Git::Status);
+    clipv::assert_parse_eq!(Git, ["STAS"], Git::Stash);
+    clipv::assert_parse_eq!(Git, ["c", "fix"], Git::Commit(String::from("fix")));
+    clipv::assert_parse_eq!(Git, ["rm"], Git::Remove);
+    clipv::assert_parse_err!(Git, ["remove"], ParsingError::VariantNotFound);
+    clipv::assert_parse_err!(
+        Git,
+        ["st"],
+        ParsingError::Ambiguous { keyword: String::from("st"), candidates: vec!["status", "stash"] },
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse, clipv::AsCommand)]
+struct Bundle {
+    output: String,
+    /// files to bundle
+    #[arity(min = 1, max = 3)]
+    files: Vec<String>,
+}
+
+#[test]
+fn it_should_parse_variadic_fields_within_their_arity() {
+    use clipv::describe::command::AsCommand;
+    let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+    clipv::assert_parse_eq!(Bundle, ["out", "a"], Bundle { files: strings(&["a"]), output: String::from("out") });
+    clipv::assert_parse_eq!(Bundle, ["out", "a", "b", "c"], Bundle { files: strings(&["a", "b", "c"]), output: String::from("out") });
+    clipv::assert_parse_err!(Bundle, ["out", "a", "b", "c", "d"], ParsingError::TooManyArguments);
+    clipv::assert_parse_err!(Bundle, ["out"], ParsingError::Arity { argument: "files", min: 1, max: Some(3), found: 0 });
+    assert_eq!(
+        ParsingError::Arity { argument: "files", min: 1, max: Some(3), found: 0 }.to_string(),
+        "'files' expects 1 to 3 values, 0 given"
+    );
+    assert_eq!(
+        Bundle::help(),
+        "Usage: bundle <output> <files>...\n\nArguments:\n  output\n  <files>... (1-3)  files to bundle\n"
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse)]
+enum Build {
+    With(std::collections::BTreeSet<String>),
+    Only(#[deny_duplicates] std::collections::HashSet<u8>),
+}
+
+#[test]
+fn it_should_collect_sets_of_values() {
+    let features = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    clipv::assert_parse_eq!(Build, ["with", "tls", "json", "tls"], Build::With(features(&["json", "tls"])));
+    clipv::assert_parse_eq!(Build, ["only", "2", "1"], Build::Only([1, 2].into_iter().collect()));
+    clipv::assert_parse_err!(
+        Build,
+        ["only", "1", "2", "1"],
+        ParsingError::DuplicateValue { argument: "0", value: String::from("1") },
+    );
+    assert_eq!(
+        ParsingError::DuplicateValue { argument: "--feature", value: String::from("tls") }.to_string(),
+        "'tls' is given more than once to '--feature'"
+    );
+}
+
+#[derive(Debug, PartialEq, TryParse, clipv::AsCommand)]
+struct Exec {
+    #[global]
+    verbose: bool,
+    program: String,
+    #[trailing]
+    args: Vec<String>,
+}
+
+#[test]
+fn it_should_keep_the_trailing_values_verbatim() {
+    use clipv::describe::command::AsCommand;
+    let args = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    clipv::assert_parse_eq!(
+        Exec,
+        ["--verbose", "ls", "-l", "--", "--verbose"],
+        Exec { verbose: true, program: String::from("ls"), args: args(&["-l", "--", "--verbose"]) },
+    );
+    clipv::assert_parse_eq!(Exec, ["true"], Exec { verbose: false, program: String::from("true"), args: Vec::new() });
+    let matches = clipv::matches::matches(Exec::command_ref(), &["ls", "--verbose"]).unwrap();
+    assert_eq!(matches.values("args"), Some(&[String::from("--verbose")][..]));
+    assert_eq!(Exec::command().summarize(), "exec [OPTIONS] <program> <args>...");
+}
+
+#[derive(Debug, TryParse)]
+struct Convert {
+    #[file_exists]
+    input: std::path::PathBuf,
+    #[global]
+    #[writable]
+    output: Option<std::path::PathBuf>,
+}
+
+#[test]
+fn it_should_check_the_paths_before_running() {
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().subsec_nanos();
+    let dir = std::env::temp_dir().join(format!("clip-paths-derive-{}-{nanos}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("in.txt");
+    std::fs::write(&input, "").unwrap();
+    let (input, missing) = (input.to_str().unwrap(), dir.join("missing.txt"));
+    let output = dir.join("out/new.txt");
+    let convert = |args: &[&str]| clipv::parser::parse(args.iter(), |convert: Convert| convert.input.is_file() && convert.output.is_some()).map_err(|err| err.to_string());
+    assert_eq!(convert(&[input, "--output", dir.join("new.txt").to_str().unwrap()]), Ok(true));
+    assert_eq!(
+        convert(&[missing.to_str().unwrap()]),
+        Err(format!("'input': cannot access file '{}': no such file or directory", missing.display()))
+    );
+    assert_eq!(
+        convert(&[input, "--output", output.to_str().unwrap()]),
+        Err(format!("'--output': cannot create '{}': no such file or directory", output.display()))
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
 }
@@ -0,0 +1,55 @@
+// Copyright © 2024 Claire Bts
+//
+// This file is part of CLIP
+//
+// CLIP is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// CLIP is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(all(feature = "derive", feature = "url"))]
+mod derive_test {
+use clipv::completion::Shell;
+use clipv::describe::command::AsCommand;
+use clipv::parser::parse;
+use clipv::url::{Url, UrlArg};
+use clipv::{AsCommand, TryParse};
+
+#[derive(Debug, TryParse, AsCommand)]
+struct Download {
+    /// where to download from
+    #[global]
+    mirror: Option<UrlArg>,
+    url: UrlArg,
+}
+
+#[test]
+fn it_should_take_urls() {
+    let download = |args: &[&str]| parse(args.iter(), |download: Download| download.url.host().map(String::from)).map_err(|err| err.to_string());
+    assert_eq!(download(&["https://example.com/file.tar.gz"]), Ok(Some(String::from("example.com"))));
+    let err = download(&["example.com/file"]).unwrap_err();
+    assert!(err.contains("invalid URL 'example.com/file': missing scheme, e.g. https://example.com/file"), "{err}");
+    let mirror = parse(["--mirror=http://[::1]:8080", "ftp://host"].iter(), |download: Download| download.mirror.and_then(|mirror| mirror.port()));
+    assert_eq!(mirror, Ok(Some(8080)));
+    assert!(Download::help().contains("--mirror <URL>  where to download from"), "{}", Download::help());
+    assert!(Shell::Zsh.generate(&Download::command(), "download").contains("_hosts"));
+}
+
+#[derive(Debug, TryParse, AsCommand)]
+struct Open {
+    /// page to open
+    #[global]
+    page: Url,
+}
+
+#[test]
+fn it_should_take_url_crate_urls() {
+    let page = parse(["--page", "HTTPS://Example.com/a/../b"].iter(), |open: Open| open.page.to_string());
+    assert_eq!(page, Ok(String::from("https://example.com/b")));
+    let err = parse(["--page", "https://"].iter(), |open: Open| open.page).unwrap_err().to_string();
+    assert!(err.contains("empty host"), "{err}");
+    assert!(Open::help().contains("--page <URL>  page to open"), "{}", Open::help());
+    assert!(Shell::Zsh.generate(&Open::command(), "open").contains("_hosts"));
+}
+}